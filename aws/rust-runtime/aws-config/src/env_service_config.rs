@@ -4,7 +4,7 @@
  */
 
 use aws_runtime::env_config::section::EnvConfigSections;
-use aws_runtime::env_config::EnvConfigValue;
+use aws_runtime::env_config::{load_operation_config, EnvConfigValue};
 use aws_types::os_shim_internal::Env;
 use aws_types::service_config::{LoadServiceConfig, ServiceConfigKey};
 
@@ -24,4 +24,13 @@ impl LoadServiceConfig for EnvServiceConfig {
 
         Some(value.to_string())
     }
+
+    fn load_operation_config(&self, service_id: &str) -> Vec<(String, String, String)> {
+        load_operation_config(&self.env_config_sections, service_id)
+            .into_iter()
+            .map(|(operation_name, key, value)| {
+                (operation_name.to_owned(), key.to_owned(), value.to_owned())
+            })
+            .collect()
+    }
 }
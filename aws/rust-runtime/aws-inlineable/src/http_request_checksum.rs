@@ -12,6 +12,7 @@ use aws_runtime::content_encoding::header_value::AWS_CHUNKED;
 use aws_runtime::content_encoding::{AwsChunkedBody, AwsChunkedBodyOptions};
 use aws_smithy_checksums::ChecksumAlgorithm;
 use aws_smithy_checksums::{body::calculate, http::HttpChecksum};
+use aws_smithy_compression::http::PendingRequestCompression;
 use aws_smithy_runtime::client::sdk_feature::SmithySdkFeature;
 use aws_smithy_runtime_api::box_error::BoxError;
 use aws_smithy_runtime_api::client::interceptors::context::{
@@ -297,6 +298,18 @@ fn add_checksum_for_request_body(
     checksum_algorithm: ChecksumAlgorithm,
     cfg: &mut ConfigBag,
 ) -> Result<(), BoxError> {
+    // If the request is to be compressed, compress it here so that the checksum is calculated
+    // from the compressed body, whether or not the request compression interceptor has run yet.
+    if let Some(compression) = PendingRequestCompression::take(cfg) {
+        if request.body().bytes().is_some() {
+            compression.apply(request, cfg)?;
+        } else {
+            // A streaming body would have to be compressed as it's sent, leaving its length
+            // unknown. aws-chunked encoding needs that length, so the body is sent uncompressed.
+            tracing::debug!("not compressing streaming request body with a checksum trailer");
+        }
+    }
+
     match request.body().bytes() {
         // Body is in-memory: read it and insert the checksum as a header.
         Some(data) => {
@@ -391,12 +404,16 @@ fn wrap_streaming_request_body_in_checksum_calculating_body(
 
 #[cfg(test)]
 mod tests {
-    use crate::http_request_checksum::wrap_streaming_request_body_in_checksum_calculating_body;
+    use crate::http_request_checksum::{
+        add_checksum_for_request_body, wrap_streaming_request_body_in_checksum_calculating_body,
+    };
     use aws_smithy_checksums::ChecksumAlgorithm;
+    use aws_smithy_compression::http::PendingRequestCompression;
     use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
     use aws_smithy_types::base64;
     use aws_smithy_types::body::SdkBody;
     use aws_smithy_types::byte_stream::ByteStream;
+    use aws_smithy_types::config_bag::{ConfigBag, Layer};
     use bytes::BytesMut;
     use http_body::Body;
     use tempfile::NamedTempFile;
@@ -479,4 +496,50 @@ mod tests {
             "expected {body} to end with '{expected}'"
         );
     }
+
+    fn cfg_with_pending_compression() -> ConfigBag {
+        let mut layer = Layer::new("test");
+        layer.store_put(PendingRequestCompression::new(|request, _cfg| {
+            *request.body_mut() = SdkBody::from("compressed");
+            request.headers_mut().insert("content-encoding", "gzip");
+            Ok(())
+        }));
+        ConfigBag::of_layers(vec![layer])
+    }
+
+    #[test]
+    fn test_checksum_is_calculated_from_compressed_body() {
+        let mut request = HttpRequest::new(SdkBody::from("Hello world"));
+        let mut cfg = cfg_with_pending_compression();
+
+        let checksum_algorithm: ChecksumAlgorithm = "crc32".parse().unwrap();
+        add_checksum_for_request_body(&mut request, checksum_algorithm, &mut cfg).unwrap();
+
+        assert_eq!(Some(&b"compressed"[..]), request.body().bytes());
+        assert_eq!(Some("gzip"), request.headers().get("content-encoding"));
+        let mut checksum = checksum_algorithm.into_impl();
+        checksum.update(b"compressed");
+        assert_eq!(
+            Some(base64::encode(checksum.finalize()).as_str()),
+            request.headers().get("x-amz-checksum-crc32")
+        );
+        // The request compression interceptor won't compress the body again
+        assert!(PendingRequestCompression::take(&mut cfg).is_none());
+    }
+
+    #[test]
+    fn test_streaming_body_with_checksum_trailer_is_not_compressed() {
+        let mut request = HttpRequest::new(SdkBody::from_body_0_4(SdkBody::from("Hello world")));
+        let mut cfg = cfg_with_pending_compression();
+
+        let checksum_algorithm: ChecksumAlgorithm = "crc32".parse().unwrap();
+        add_checksum_for_request_body(&mut request, checksum_algorithm, &mut cfg).unwrap();
+
+        let headers = request.headers();
+        assert_eq!(Some("aws-chunked"), headers.get("content-encoding"));
+        assert_eq!(Some("11"), headers.get("x-amz-decoded-content-length"));
+        assert_eq!(Some("x-amz-checksum-crc32"), headers.get("x-amz-trailer"));
+        // The request compression interceptor won't compress the body either
+        assert!(PendingRequestCompression::take(&mut cfg).is_none());
+    }
 }
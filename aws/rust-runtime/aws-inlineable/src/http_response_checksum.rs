@@ -182,8 +182,9 @@ pub(crate) fn check_headers_for_precalculated_checksum(
     response_algorithms: &[&str],
 ) -> Option<(ChecksumAlgorithm, bytes::Bytes)> {
     let checksum_algorithms_to_check =
-        aws_smithy_checksums::http::CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER
-            .into_iter()
+        aws_smithy_checksums::http::ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER
+            .iter()
+            .copied()
             // Process list of algorithms, from fastest to slowest, that may have been used to checksum
             // the response body, ignoring any that aren't marked as supported algorithms by the model.
             .flat_map(|algo| {
@@ -199,7 +200,7 @@ pub(crate) fn check_headers_for_precalculated_checksum(
 
     for checksum_algorithm in checksum_algorithms_to_check {
         let checksum_algorithm: ChecksumAlgorithm = checksum_algorithm.parse().expect(
-            "CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER only contains valid checksum algorithm names",
+            "ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER only contains valid checksum algorithm names",
         );
         if let Some(base64_encoded_precalculated_checksum) =
             headers.get(checksum_algorithm.into_impl().header_name())
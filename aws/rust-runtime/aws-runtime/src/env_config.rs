@@ -348,6 +348,29 @@ fn get_service_config_from_profile<'a>(
     Some((Cow::Owned(value.to_owned()), source))
 }
 
+/// Load every operation-specific value set in the service's section of the profile file.
+///
+/// Operation-specific values are set as `<operation_name>.<key> = <value>` sub-properties of the
+/// service, and are returned as `(operation_name, key, value)` tuples. Environment variables and
+/// global or service-wide profile keys are never operation-specific.
+pub fn load_operation_config<'a>(
+    profiles: &'a EnvConfigSections,
+    service_id: &str,
+) -> Vec<(&'a str, &'a str, &'a str)> {
+    let Some(services_section_name) = profiles.get(SERVICES) else {
+        return Vec::new();
+    };
+    let profile_case_service_id = format_service_id_for_profile(service_id);
+    profiles
+        .other_sections()
+        .sub_properties(SERVICES, services_section_name, &profile_case_service_id)
+        .filter_map(|(sub_property_name, value)| {
+            let (operation_name, key) = sub_property_name.split_once('.')?;
+            Some((operation_name, key, value))
+        })
+        .collect()
+}
+
 fn format_service_id_for_env(service_id: impl AsRef<str>) -> String {
     service_id.as_ref().to_uppercase().replace(' ', "_")
 }
@@ -365,7 +388,7 @@ mod test {
     use std::collections::HashMap;
     use std::num::ParseIntError;
 
-    use super::EnvConfigValue;
+    use super::{load_operation_config, EnvConfigValue};
 
     fn validate_some_key(s: &str) -> Result<i32, ParseIntError> {
         s.parse()
@@ -516,6 +539,54 @@ mod test {
         assert_eq!(Some(4), service_from_profile);
     }
 
+    #[test]
+    fn test_load_operation_config() {
+        let profiles = EnvConfigSections::new(
+            HashMap::from([(
+                "default".to_owned(),
+                HashMap::from([
+                    ("some_key".to_owned(), "1".to_owned()),
+                    ("services".to_owned(), "dev".to_owned()),
+                ]),
+            )]),
+            Cow::Borrowed("default"),
+            HashMap::new(),
+            Properties::new_from_slice(&[
+                (
+                    new_prop_key("services", "dev", "s3", Some("some_key")),
+                    "2".to_string(),
+                ),
+                (
+                    new_prop_key("services", "dev", "s3", Some("get_object.some_key")),
+                    "3".to_string(),
+                ),
+                (
+                    new_prop_key("services", "dev", "s3", Some("put_object.other_key")),
+                    "4".to_string(),
+                ),
+                (
+                    new_prop_key("services", "dev", "dynamodb", Some("get_item.some_key")),
+                    "5".to_string(),
+                ),
+                (
+                    new_prop_key("services", "prod", "s3", Some("get_object.some_key")),
+                    "6".to_string(),
+                ),
+            ]),
+        );
+
+        let mut operation_config = load_operation_config(&profiles, "s3");
+        operation_config.sort();
+        assert_eq!(
+            vec![
+                ("get_object", "some_key", "3"),
+                ("put_object", "other_key", "4"),
+            ],
+            operation_config
+        );
+        assert!(load_operation_config(&profiles, "sqs").is_empty());
+    }
+
     #[tokio::test]
     async fn test_multiple_services() {
         let env = Env::from_slice(&[
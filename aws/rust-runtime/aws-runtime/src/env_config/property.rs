@@ -175,4 +175,20 @@ impl Properties {
     pub fn get(&self, properties_key: &PropertiesKey) -> Option<&PropertyValue> {
         self.inner.get(properties_key)
     }
+
+    /// Return the sub-properties of the given property, as `(sub-property name, value)` pairs.
+    pub(crate) fn sub_properties<'a: 'b, 'b>(
+        &'a self,
+        section_key: &'b str,
+        section_name: &'b str,
+        property_name: &'b str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'b {
+        self.inner.iter().filter_map(move |(key, value)| {
+            let sub_property_name = key.sub_property_name.as_deref()?;
+            (key.section_key == section_key
+                && key.section_name == section_name
+                && key.property_name == property_name)
+                .then_some((sub_property_name, value.as_str()))
+        })
+    }
 }
@@ -51,3 +51,6 @@ pub mod fs_util;
 /// Supporting code for parsing AWS config values set in a user's environment or
 /// in a shared config file.
 pub mod env_config;
+
+/// Supporting code for retry and timeout config set for individual operations.
+pub mod operation_config;
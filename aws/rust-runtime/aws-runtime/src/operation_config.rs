@@ -0,0 +1,349 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Retry and timeout config for individual operations, set in the shared config file.
+//!
+//! Operation config lives in the same `services` section as service-specific config. Each key is
+//! prefixed with the snake_case name of the operation it applies to:
+//!
+//! ```ini
+//! [profile default]
+//! services = tuned
+//!
+//! [services tuned]
+//! dynamodb =
+//!   get_item.max_attempts = 5
+//!   get_item.operation_attempt_timeout = 2
+//! ```
+//!
+//! The supported keys are:
+//! - `max_attempts`: the maximum number of attempts, including the initial request
+//! - `retry_mode`: `standard` or `adaptive`
+//! - `operation_timeout`: the timeout for the operation in seconds, including all retries
+//! - `operation_attempt_timeout`: the timeout for each attempt in seconds
+//!
+//! Timeouts may be fractional, e.g. `0.5`. Invalid values are logged and ignored.
+
+use aws_smithy_runtime_api::client::runtime_plugin::RuntimePlugin;
+use aws_smithy_types::config_bag::{FrozenLayer, Layer, Storable, StoreReplace};
+use aws_smithy_types::retry::{RetryConfig, RetryMode};
+use aws_smithy_types::timeout::TimeoutConfig;
+use aws_types::service_config::LoadServiceConfig;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+mod keys {
+    pub(super) const MAX_ATTEMPTS: &str = "max_attempts";
+    pub(super) const RETRY_MODE: &str = "retry_mode";
+    pub(super) const OPERATION_TIMEOUT: &str = "operation_timeout";
+    pub(super) const OPERATION_ATTEMPT_TIMEOUT: &str = "operation_attempt_timeout";
+}
+
+/// Retry and timeout overrides for the operations of a single service.
+///
+/// This is loaded once when a client is created, and stored in the client's config. When an
+/// operation is invoked, an [`OperationConfigRuntimePlugin`] applies that operation's overrides
+/// on top of the client's config. Config overrides set on the operation itself take precedence.
+#[derive(Clone, Debug, Default)]
+pub struct OperationConfig {
+    operations: HashMap<String, Overrides>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Overrides {
+    max_attempts: Option<u32>,
+    retry_mode: Option<RetryMode>,
+    operation_timeout: Option<Duration>,
+    operation_attempt_timeout: Option<Duration>,
+}
+
+impl Overrides {
+    fn is_empty(&self) -> bool {
+        self.max_attempts.is_none()
+            && self.retry_mode.is_none()
+            && self.operation_timeout.is_none()
+            && self.operation_attempt_timeout.is_none()
+    }
+}
+
+impl Storable for OperationConfig {
+    type Storer = StoreReplace<Self>;
+}
+
+impl OperationConfig {
+    /// Load overrides for the given operations of the service identified by `service_id`.
+    pub fn load(
+        service_config: &dyn LoadServiceConfig,
+        service_id: &str,
+        operation_names: &[&str],
+    ) -> Self {
+        let mut operations: HashMap<String, Overrides> = HashMap::new();
+        for (operation_name, key, value) in service_config.load_operation_config(service_id) {
+            if !operation_names.contains(&operation_name.as_str()) {
+                tracing::warn!(
+                    "ignoring `{operation_name}.{key}`: `{service_id}` has no operation `{operation_name}`"
+                );
+                continue;
+            }
+            let overrides = operations.entry(operation_name.clone()).or_default();
+            let (operation_name, value) = (operation_name.as_str(), value.as_str());
+            match key.as_str() {
+                keys::MAX_ATTEMPTS => {
+                    overrides.max_attempts = parse(operation_name, &key, value, |value| {
+                        u32::from_str(value).ok().filter(|attempts| *attempts > 0)
+                    })
+                }
+                keys::RETRY_MODE => {
+                    overrides.retry_mode = parse(operation_name, &key, value, |value| {
+                        RetryMode::from_str(value).ok()
+                    })
+                }
+                keys::OPERATION_TIMEOUT => {
+                    overrides.operation_timeout = parse(operation_name, &key, value, parse_seconds)
+                }
+                keys::OPERATION_ATTEMPT_TIMEOUT => {
+                    overrides.operation_attempt_timeout =
+                        parse(operation_name, &key, value, parse_seconds)
+                }
+                _ => {
+                    tracing::warn!("ignoring unsupported operation config `{operation_name}.{key}`")
+                }
+            }
+        }
+        operations.retain(|_, overrides| !overrides.is_empty());
+        Self { operations }
+    }
+
+    /// Returns true if no operation has any overrides.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+fn parse<T>(
+    operation_name: &str,
+    key: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Option<T> {
+    let parsed = parse(value);
+    if parsed.is_none() {
+        tracing::warn!("ignoring invalid value `{value}` for `{operation_name}.{key}`");
+    }
+    parsed
+}
+
+fn parse_seconds(value: &str) -> Option<Duration> {
+    f64::from_str(value)
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+}
+
+/// Runtime plugin that applies an operation's [`OperationConfig`] overrides.
+#[derive(Debug)]
+pub struct OperationConfigRuntimePlugin {
+    config: Option<FrozenLayer>,
+}
+
+impl OperationConfigRuntimePlugin {
+    /// Creates a new `OperationConfigRuntimePlugin` for the given operation.
+    ///
+    /// The overrides and the retry config they are applied to are read from `client_config`. If
+    /// it contains no overrides for this operation, the plugin does nothing.
+    pub fn new(operation_name: &str, client_config: &FrozenLayer) -> Self {
+        let config = client_config
+            .load::<OperationConfig>()
+            .and_then(|config| config.operations.get(operation_name))
+            .map(|overrides| {
+                let mut layer = Layer::new("OperationConfig");
+                if overrides.max_attempts.is_some() || overrides.retry_mode.is_some() {
+                    let mut retry_config = client_config
+                        .load::<RetryConfig>()
+                        .cloned()
+                        .unwrap_or_else(RetryConfig::standard);
+                    if let Some(max_attempts) = overrides.max_attempts {
+                        retry_config = retry_config.with_max_attempts(max_attempts);
+                    }
+                    if let Some(retry_mode) = overrides.retry_mode {
+                        retry_config = retry_config.with_retry_mode(retry_mode);
+                    }
+                    layer.store_put(retry_config);
+                }
+                if overrides.operation_timeout.is_some()
+                    || overrides.operation_attempt_timeout.is_some()
+                {
+                    let mut builder = TimeoutConfig::builder();
+                    if let Some(operation_timeout) = overrides.operation_timeout {
+                        builder = builder.operation_timeout(operation_timeout);
+                    }
+                    if let Some(operation_attempt_timeout) = overrides.operation_attempt_timeout {
+                        builder = builder.operation_attempt_timeout(operation_attempt_timeout);
+                    }
+                    // Timeout configs are merged across layers when the operation is invoked,
+                    // so the client's timeouts fill in the gaps below these, and timeouts set in
+                    // a config override take precedence over them.
+                    layer.store_put(builder.build());
+                }
+                layer.freeze()
+            });
+        Self { config }
+    }
+}
+
+impl RuntimePlugin for OperationConfigRuntimePlugin {
+    fn config(&self) -> Option<FrozenLayer> {
+        self.config.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_types::config_bag::ConfigBag;
+    use aws_smithy_types::timeout::MergeTimeoutConfig;
+    use aws_types::service_config::ServiceConfigKey;
+
+    #[derive(Debug)]
+    struct StaticServiceConfig(Vec<(&'static str, &'static str, &'static str)>);
+
+    impl LoadServiceConfig for StaticServiceConfig {
+        fn load_config(&self, _key: ServiceConfigKey<'_>) -> Option<String> {
+            unreachable!("operation config is loaded in one pass")
+        }
+
+        fn load_operation_config(&self, service_id: &str) -> Vec<(String, String, String)> {
+            assert_eq!("dynamodb", service_id);
+            self.0
+                .iter()
+                .map(|(operation_name, key, value)| {
+                    (
+                        operation_name.to_string(),
+                        key.to_string(),
+                        value.to_string(),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    fn client_config(operation_config: OperationConfig) -> FrozenLayer {
+        let mut layer = Layer::new("client");
+        layer.store_put(RetryConfig::standard().with_max_attempts(3));
+        layer.store_put(
+            TimeoutConfig::builder()
+                .operation_timeout(Duration::from_secs(30))
+                .operation_attempt_timeout(Duration::from_secs(10))
+                .build(),
+        );
+        layer.store_put(operation_config);
+        layer.freeze()
+    }
+
+    fn merged_timeout_config(layers: impl IntoIterator<Item = FrozenLayer>) -> TimeoutConfig {
+        let mut cfg = ConfigBag::base();
+        for layer in layers {
+            cfg.push_shared_layer(layer);
+        }
+        cfg.load::<MergeTimeoutConfig>()
+    }
+
+    #[test]
+    fn operation_overrides_are_applied() {
+        let service_config = StaticServiceConfig(vec![
+            ("get_item", "max_attempts", "5"),
+            ("get_item", "operation_attempt_timeout", "2"),
+            ("put_item", "retry_mode", "adaptive"),
+            ("put_item", "operation_timeout", "0.5"),
+        ]);
+        let operation_config = OperationConfig::load(
+            &service_config,
+            "dynamodb",
+            &["get_item", "put_item", "query"],
+        );
+        let client_config = client_config(operation_config);
+
+        let get_item = OperationConfigRuntimePlugin::new("get_item", &client_config)
+            .config()
+            .unwrap();
+        let retry_config = get_item.load::<RetryConfig>().unwrap();
+        assert_eq!(5, retry_config.max_attempts());
+        assert_eq!(RetryMode::Standard, retry_config.mode());
+        let timeout_config = merged_timeout_config([client_config.clone(), get_item]);
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            timeout_config.operation_timeout()
+        );
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            timeout_config.operation_attempt_timeout()
+        );
+
+        let put_item = OperationConfigRuntimePlugin::new("put_item", &client_config)
+            .config()
+            .unwrap();
+        let retry_config = put_item.load::<RetryConfig>().unwrap();
+        assert_eq!(3, retry_config.max_attempts());
+        assert_eq!(RetryMode::Adaptive, retry_config.mode());
+        let timeout_config = merged_timeout_config([client_config.clone(), put_item]);
+        assert_eq!(
+            Some(Duration::from_millis(500)),
+            timeout_config.operation_timeout()
+        );
+        assert_eq!(
+            Some(Duration::from_secs(10)),
+            timeout_config.operation_attempt_timeout()
+        );
+
+        assert!(OperationConfigRuntimePlugin::new("query", &client_config)
+            .config()
+            .is_none());
+    }
+
+    #[test]
+    fn config_override_timeouts_take_precedence_over_operation_timeouts() {
+        let service_config = StaticServiceConfig(vec![
+            ("get_item", "operation_timeout", "20"),
+            ("get_item", "operation_attempt_timeout", "2"),
+        ]);
+        let operation_config = OperationConfig::load(&service_config, "dynamodb", &["get_item"]);
+        let client_config = client_config(operation_config);
+        let get_item = OperationConfigRuntimePlugin::new("get_item", &client_config)
+            .config()
+            .unwrap();
+
+        let mut config_override = Layer::new("config_override");
+        config_override.store_put(
+            TimeoutConfig::builder()
+                .operation_attempt_timeout(Duration::from_secs(1))
+                .build(),
+        );
+        let timeout_config =
+            merged_timeout_config([client_config, get_item, config_override.freeze()]);
+        // set by the config override
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            timeout_config.operation_attempt_timeout()
+        );
+        // set by the operation config, not the client config
+        assert_eq!(
+            Some(Duration::from_secs(20)),
+            timeout_config.operation_timeout()
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_ignored() {
+        let service_config = StaticServiceConfig(vec![
+            ("get_item", "max_attempts", "0"),
+            ("get_item", "retry_mode", "sometimes"),
+            ("get_item", "operation_timeout", "-1"),
+            ("get_item", "unknown_key", "1"),
+            ("no_such_operation", "max_attempts", "5"),
+        ]);
+        let operation_config = OperationConfig::load(&service_config, "dynamodb", &["get_item"]);
+        assert!(operation_config.is_empty());
+    }
+}
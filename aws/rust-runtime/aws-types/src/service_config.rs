@@ -143,4 +143,15 @@ pub mod builder {
 pub trait LoadServiceConfig: fmt::Debug + Send + Sync {
     /// Given a [`ServiceConfigKey`], return the value associated with it.
     fn load_config(&self, key: ServiceConfigKey<'_>) -> Option<String>;
+
+    /// Return every operation-specific value set for the service identified by `service_id`.
+    ///
+    /// Each entry is an `(operation name, key, value)` tuple. In the shared config file, these
+    /// are set in the service's section as `<operation name>.<key> = <value>`.
+    ///
+    /// The default implementation returns no values.
+    fn load_operation_config(&self, service_id: &str) -> Vec<(String, String, String)> {
+        let _ = service_id;
+        Vec::new()
+    }
 }
@@ -65,6 +65,7 @@ val DECORATORS: List<ClientCodegenDecorator> =
             RemoveDefaultsDecorator(),
             TokenProvidersDecorator(),
             ServiceEnvConfigDecorator(),
+            OperationConfigDecorator(),
             HttpRequestCompressionDecorator(),
             DisablePayloadSigningDecorator(),
             // TODO(https://github.com/smithy-lang/smithy-rs/issues/3863): Comment in once the issue has been resolved
@@ -0,0 +1,85 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+package software.amazon.smithy.rustsdk
+
+import software.amazon.smithy.model.knowledge.TopDownIndex
+import software.amazon.smithy.model.shapes.OperationShape
+import software.amazon.smithy.rust.codegen.client.smithy.ClientCodegenContext
+import software.amazon.smithy.rust.codegen.client.smithy.customize.ClientCodegenDecorator
+import software.amazon.smithy.rust.codegen.client.smithy.generators.OperationCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.generators.OperationSection
+import software.amazon.smithy.rust.codegen.core.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.core.rustlang.writable
+import software.amazon.smithy.rust.codegen.core.smithy.customize.AdHocCustomization
+import software.amazon.smithy.rust.codegen.core.smithy.customize.adhocCustomization
+import software.amazon.smithy.rust.codegen.core.util.dq
+import software.amazon.smithy.rust.codegen.core.util.sdkId
+import software.amazon.smithy.rust.codegen.core.util.toSnakeCase
+
+/**
+ * Loads retry and timeout config for individual operations from the `services` section of the shared
+ * config file when a client is created from an `SdkConfig`, and applies it when those operations are invoked.
+ */
+class OperationConfigDecorator : ClientCodegenDecorator {
+    override val name: String = "OperationConfig"
+    override val order: Byte = 0
+
+    override fun extraSections(codegenContext: ClientCodegenContext): List<AdHocCustomization> {
+        val serviceId = codegenContext.serviceShape.sdkId().toSnakeCase().dq()
+        val operationNames =
+            TopDownIndex.of(codegenContext.model)
+                .getContainedOperations(codegenContext.serviceShape)
+                .joinToString(", ") { it.id.name.toSnakeCase().dq() }
+
+        return listOf(
+            adhocCustomization<SdkConfigSection.CopySdkConfigToClientConfig> { section ->
+                rustTemplate(
+                    """
+                    if let Some(service_config) = ${section.sdkConfig}.service_config() {
+                        let operation_config = #{OperationConfig}::load(service_config, $serviceId, &[$operationNames]);
+                        if !operation_config.is_empty() {
+                            ${section.serviceConfigBuilder}.config.store_put(operation_config);
+                        }
+                    }
+                    """,
+                    "OperationConfig" to
+                        AwsRuntimeType.awsRuntime(codegenContext.runtimeConfig)
+                            .resolve("operation_config::OperationConfig"),
+                )
+            },
+        )
+    }
+
+    override fun operationCustomizations(
+        codegenContext: ClientCodegenContext,
+        operation: OperationShape,
+        baseCustomizations: List<OperationCustomization>,
+    ): List<OperationCustomization> = baseCustomizations + OperationConfigCustomization(codegenContext, operation)
+}
+
+private class OperationConfigCustomization(
+    codegenContext: ClientCodegenContext,
+    private val operation: OperationShape,
+) : OperationCustomization() {
+    private val runtimeConfig = codegenContext.runtimeConfig
+
+    override fun section(section: OperationSection) =
+        when (section) {
+            is OperationSection.AdditionalRuntimePlugins ->
+                writable {
+                    section.addOperationRuntimePlugin(this) {
+                        rustTemplate(
+                            "#{OperationConfigRuntimePlugin}::new(${operation.id.name.toSnakeCase().dq()}, &client_config.config)",
+                            "OperationConfigRuntimePlugin" to
+                                AwsRuntimeType.awsRuntime(runtimeConfig)
+                                    .resolve("operation_config::OperationConfigRuntimePlugin"),
+                        )
+                    }
+                }
+
+            else -> emptySection
+        }
+}
@@ -13,6 +13,7 @@ import software.amazon.smithy.rust.codegen.client.smithy.customizations.ClientCu
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.HttpAuthDecorator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.HttpConnectorConfigDecorator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.IdempotencyTokenDecorator
+import software.amazon.smithy.rust.codegen.client.smithy.customizations.IdempotentOperationDecorator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.NoAuthDecorator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.SensitiveOutputDecorator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.StaticSdkFeatureTrackerDecorator
@@ -70,6 +71,7 @@ class RustClientCodegenPlugin : ClientDecoratableBuildPlugin() {
                 HttpConnectorConfigDecorator(),
                 SensitiveOutputDecorator(),
                 IdempotencyTokenDecorator(),
+                IdempotentOperationDecorator(),
                 StalledStreamProtectionDecorator(),
                 StaticSdkFeatureTrackerDecorator(),
                 *decorator,
@@ -0,0 +1,83 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+package software.amazon.smithy.rust.codegen.client.smithy.customizations
+
+import software.amazon.smithy.rust.codegen.client.smithy.ClientCodegenContext
+import software.amazon.smithy.rust.codegen.client.smithy.configReexport
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ConfigCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ServiceConfig
+import software.amazon.smithy.rust.codegen.core.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.core.rustlang.writable
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType.Companion.preludeScope
+
+/**
+ * Add an `http_version` field to Service config.
+ */
+class HttpVersionConfigCustomization(codegenContext: ClientCodegenContext) : ConfigCustomization() {
+    private val rc = codegenContext.runtimeConfig
+    private val codegenScope =
+        arrayOf(
+            *preludeScope,
+            "HttpVersion" to configReexport(RuntimeType.smithyRuntimeApiClient(rc).resolve("client::http::HttpVersion")),
+        )
+
+    override fun section(section: ServiceConfig) =
+        writable {
+            when (section) {
+                is ServiceConfig.ConfigImpl -> {
+                    rustTemplate(
+                        """
+                        /// Returns the HTTP version used for requests, if one is set.
+                        pub fn http_version(&self) -> #{Option}<#{HttpVersion}> {
+                            self.config.load::<#{HttpVersion}>().copied()
+                        }
+                        """,
+                        *codegenScope,
+                    )
+                }
+
+                ServiceConfig.BuilderImpl -> {
+                    rustTemplate(
+                        """
+                        /// Sets the [`HttpVersion`](#{HttpVersion}) used for requests.
+                        ///
+                        /// When unset, the HTTP client decides. The default clients offer both HTTP/2 and
+                        /// HTTP/1.1 over TLS, and use HTTP/1.1 for plaintext connections.
+                        pub fn http_version(mut self, http_version: #{HttpVersion}) -> Self {
+                            self.set_http_version(#{Some}(http_version));
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+
+                    rustTemplate(
+                        """
+                        /// Sets the [`HttpVersion`](#{HttpVersion}) used for requests.
+                        ///
+                        /// When unset, the HTTP client decides. The default clients offer both HTTP/2 and
+                        /// HTTP/1.1 over TLS, and use HTTP/1.1 for plaintext connections.
+                        pub fn set_http_version(&mut self, http_version: #{Option}<#{HttpVersion}>) -> &mut Self {
+                            self.config.store_or_unset(http_version);
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+                }
+
+                is ServiceConfig.BuilderFromConfigBag -> {
+                    rustTemplate(
+                        "${section.builder}.set_http_version(${section.configBag}.load::<#{HttpVersion}>().copied());",
+                        *codegenScope,
+                    )
+                }
+
+                else -> emptySection
+            }
+        }
+}
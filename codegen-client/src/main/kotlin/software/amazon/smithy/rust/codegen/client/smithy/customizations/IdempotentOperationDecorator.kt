@@ -0,0 +1,133 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+package software.amazon.smithy.rust.codegen.client.smithy.customizations
+
+import software.amazon.smithy.model.shapes.OperationShape
+import software.amazon.smithy.model.traits.IdempotentTrait
+import software.amazon.smithy.model.traits.ReadonlyTrait
+import software.amazon.smithy.rust.codegen.client.smithy.ClientCodegenContext
+import software.amazon.smithy.rust.codegen.client.smithy.configReexport
+import software.amazon.smithy.rust.codegen.client.smithy.customize.ClientCodegenDecorator
+import software.amazon.smithy.rust.codegen.client.smithy.generators.OperationCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.generators.OperationSection
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ConfigCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ServiceConfig
+import software.amazon.smithy.rust.codegen.core.rustlang.Writable
+import software.amazon.smithy.rust.codegen.core.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.core.rustlang.writable
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType.Companion.preludeScope
+import software.amazon.smithy.rust.codegen.core.util.hasTrait
+
+/**
+ * Marks operations modeled as `@readonly` or `@idempotent` with `IdempotentOperation`, since only
+ * these operations are safe to send more than once when hedging requests, and adds a
+ * `hedging_config` field to service config.
+ */
+class IdempotentOperationDecorator : ClientCodegenDecorator {
+    override val name: String get() = "IdempotentOperationDecorator"
+    override val order: Byte get() = 0
+
+    override fun configCustomizations(
+        codegenContext: ClientCodegenContext,
+        baseCustomizations: List<ConfigCustomization>,
+    ): List<ConfigCustomization> = baseCustomizations + HedgingConfigCustomization(codegenContext)
+
+    override fun operationCustomizations(
+        codegenContext: ClientCodegenContext,
+        operation: OperationShape,
+        baseCustomizations: List<OperationCustomization>,
+    ): List<OperationCustomization> =
+        baseCustomizations + listOf(IdempotentOperationCustomization(codegenContext, operation))
+}
+
+private class HedgingConfigCustomization(codegenContext: ClientCodegenContext) : ConfigCustomization() {
+    private val codegenScope =
+        arrayOf(
+            *preludeScope,
+            "HedgingConfig" to
+                configReexport(
+                    RuntimeType.smithyRuntime(codegenContext.runtimeConfig)
+                        .resolve("client::orchestrator::hedging::HedgingConfig"),
+                ),
+        )
+
+    override fun section(section: ServiceConfig) =
+        writable {
+            when (section) {
+                is ServiceConfig.ConfigImpl -> {
+                    rustTemplate(
+                        """
+                        /// Returns the hedging config, if one is set.
+                        pub fn hedging_config(&self) -> #{Option}<&#{HedgingConfig}> {
+                            self.config.load::<#{HedgingConfig}>()
+                        }
+                        """,
+                        *codegenScope,
+                    )
+                }
+
+                ServiceConfig.BuilderImpl -> {
+                    rustTemplate(
+                        """
+                        /// Enables hedged requests with the given [`HedgingConfig`](#{HedgingConfig}).
+                        ///
+                        /// Only operations modeled as `@readonly` or `@idempotent` are hedged.
+                        pub fn hedging_config(mut self, hedging_config: #{HedgingConfig}) -> Self {
+                            self.set_hedging_config(#{Some}(hedging_config));
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+
+                    rustTemplate(
+                        """
+                        /// Enables hedged requests with the given [`HedgingConfig`](#{HedgingConfig}).
+                        ///
+                        /// Only operations modeled as `@readonly` or `@idempotent` are hedged.
+                        pub fn set_hedging_config(&mut self, hedging_config: #{Option}<#{HedgingConfig}>) -> &mut Self {
+                            self.config.store_or_unset(hedging_config);
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+                }
+
+                is ServiceConfig.BuilderFromConfigBag -> {
+                    rustTemplate(
+                        "${section.builder}.set_hedging_config(${section.configBag}.load::<#{HedgingConfig}>().cloned());",
+                        *codegenScope,
+                    )
+                }
+
+                else -> emptySection
+            }
+        }
+}
+
+private class IdempotentOperationCustomization(
+    private val codegenContext: ClientCodegenContext,
+    private val operation: OperationShape,
+) : OperationCustomization() {
+    private val hedging =
+        RuntimeType.smithyRuntime(codegenContext.runtimeConfig).resolve("client::orchestrator::hedging")
+
+    override fun section(section: OperationSection): Writable =
+        writable {
+            if (section is OperationSection.AdditionalRuntimePluginConfig &&
+                (operation.hasTrait<ReadonlyTrait>() || operation.hasTrait<IdempotentTrait>())
+            ) {
+                rustTemplate(
+                    """
+                    ${section.newLayerName}.store_put(#{IdempotentOperation}::new());
+                    """,
+                    "IdempotentOperation" to hedging.resolve("IdempotentOperation"),
+                )
+            }
+        }
+}
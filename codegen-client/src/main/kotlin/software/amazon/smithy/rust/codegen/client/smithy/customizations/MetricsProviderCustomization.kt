@@ -0,0 +1,83 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+package software.amazon.smithy.rust.codegen.client.smithy.customizations
+
+import software.amazon.smithy.rust.codegen.client.smithy.ClientCodegenContext
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ConfigCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.generators.config.ServiceConfig
+import software.amazon.smithy.rust.codegen.core.rustlang.rust
+import software.amazon.smithy.rust.codegen.core.rustlang.rustBlockTemplate
+import software.amazon.smithy.rust.codegen.core.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.core.rustlang.writable
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType.Companion.preludeScope
+
+class MetricsProviderCustomization(codegenContext: ClientCodegenContext) : ConfigCustomization() {
+    private val api = RuntimeType.smithyRuntimeApiClient(codegenContext.runtimeConfig)
+    private val codegenScope =
+        arrayOf(
+            *preludeScope,
+            "IntoShared" to RuntimeType.smithyRuntimeApi(codegenContext.runtimeConfig).resolve("shared::IntoShared"),
+            "MetricsProvider" to api.resolve("client::metrics::MetricsProvider"),
+            "SharedMetricsProvider" to api.resolve("client::metrics::SharedMetricsProvider"),
+        )
+
+    override fun section(section: ServiceConfig) =
+        writable {
+            when (section) {
+                is ServiceConfig.ConfigImpl -> {
+                    rust("/// Returns the metrics provider used for this service, if one is set.")
+                    rustBlockTemplate(
+                        "pub fn metrics_provider(&self) -> #{Option}<#{SharedMetricsProvider}>",
+                        *codegenScope,
+                    ) {
+                        rustTemplate(
+                            """self.runtime_components.metrics_provider()""",
+                            *codegenScope,
+                        )
+                    }
+                }
+
+                ServiceConfig.BuilderImpl -> {
+                    rustTemplate(
+                        """
+                        /// Sets the metrics provider used to record client metrics, such as the duration of each
+                        /// operation, the number of attempts made, and the number of bytes sent and received.
+                        ///
+                        /// No metrics are recorded if no metrics provider is set.
+                        pub fn metrics_provider(
+                            mut self,
+                            metrics_provider: impl #{MetricsProvider} + 'static,
+                        ) -> Self {
+                            self.set_metrics_provider(#{Some}(#{IntoShared}::into_shared(metrics_provider)));
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+
+                    rustTemplate(
+                        """
+                        /// Sets the metrics provider used to record client metrics, such as the duration of each
+                        /// operation, the number of attempts made, and the number of bytes sent and received.
+                        ///
+                        /// No metrics are recorded if no metrics provider is set.
+                        pub fn set_metrics_provider(
+                            &mut self,
+                            metrics_provider: #{Option}<#{SharedMetricsProvider}>,
+                        ) -> &mut Self {
+                            self.runtime_components.set_metrics_provider(metrics_provider);
+                            self
+                        }
+                        """,
+                        *codegenScope,
+                    )
+                }
+
+                else -> emptySection
+            }
+        }
+}
@@ -255,6 +255,12 @@ class RetryClassifierServiceRuntimePluginCustomization(codegenContext: ClientCod
                             "HttpStatusCodeClassifier" to retries.resolve("classifiers::HttpStatusCodeClassifier"),
                         )
                     }
+                    section.registerRetryClassifier(this) {
+                        rustTemplate(
+                            "#{RetryAfterClassifier}::default()",
+                            "RetryAfterClassifier" to retries.resolve("classifiers::RetryAfterClassifier"),
+                        )
+                    }
                 }
 
                 else -> emptySection
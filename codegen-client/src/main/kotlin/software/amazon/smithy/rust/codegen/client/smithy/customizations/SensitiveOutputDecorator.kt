@@ -34,17 +34,27 @@ private class SensitiveOutputCustomization(
 ) : OperationCustomization() {
     private val sensitiveIndex = SensitiveIndex.of(codegenContext.model)
 
+    private val orchestrator = RuntimeType.smithyRuntimeApiClient(codegenContext.runtimeConfig).resolve("client::orchestrator")
+
     override fun section(section: OperationSection): Writable =
         writable {
-            if (section is OperationSection.AdditionalRuntimePluginConfig && sensitiveIndex.hasSensitiveOutput(operation)) {
-                rustTemplate(
-                    """
-                    ${section.newLayerName}.store_put(#{SensitiveOutput});
-                    """,
-                    "SensitiveOutput" to
-                        RuntimeType.smithyRuntimeApiClient(codegenContext.runtimeConfig)
-                            .resolve("client::orchestrator::SensitiveOutput"),
-                )
+            if (section is OperationSection.AdditionalRuntimePluginConfig) {
+                if (sensitiveIndex.hasSensitiveInput(operation)) {
+                    rustTemplate(
+                        """
+                        ${section.newLayerName}.store_put(#{SensitiveInput});
+                        """,
+                        "SensitiveInput" to orchestrator.resolve("SensitiveInput"),
+                    )
+                }
+                if (sensitiveIndex.hasSensitiveOutput(operation)) {
+                    rustTemplate(
+                        """
+                        ${section.newLayerName}.store_put(#{SensitiveOutput});
+                        """,
+                        "SensitiveOutput" to orchestrator.resolve("SensitiveOutput"),
+                    )
+                }
             }
         }
 }
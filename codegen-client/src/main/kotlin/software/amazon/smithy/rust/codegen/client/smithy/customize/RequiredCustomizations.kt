@@ -10,9 +10,11 @@ import software.amazon.smithy.rust.codegen.client.smithy.ClientCodegenContext
 import software.amazon.smithy.rust.codegen.client.smithy.ClientRustModule
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.ConnectionPoisoningRuntimePluginCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.HttpChecksumRequiredGenerator
+import software.amazon.smithy.rust.codegen.client.smithy.customizations.HttpVersionConfigCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.IdentityCacheConfigCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.InterceptorConfigCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.MetadataCustomization
+import software.amazon.smithy.rust.codegen.client.smithy.customizations.MetricsProviderCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.RequestCompressionGenerator
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.ResiliencyConfigCustomization
 import software.amazon.smithy.rust.codegen.client.smithy.customizations.ResiliencyReExportCustomization
@@ -66,6 +68,8 @@ class RequiredCustomizations : ClientCodegenDecorator {
             IdentityCacheConfigCustomization(codegenContext) +
             InterceptorConfigCustomization(codegenContext) +
             TimeSourceCustomization(codegenContext) +
+            MetricsProviderCustomization(codegenContext) +
+            HttpVersionConfigCustomization(codegenContext) +
             RetryClassifierConfigCustomization(codegenContext)
 
     override fun libRsCustomizations(
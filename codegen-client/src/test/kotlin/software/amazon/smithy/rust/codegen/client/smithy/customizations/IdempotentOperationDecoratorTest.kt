@@ -0,0 +1,132 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+package software.amazon.smithy.rust.codegen.client.smithy.customizations
+
+import org.junit.jupiter.api.Test
+import software.amazon.smithy.rust.codegen.client.testutil.clientIntegrationTest
+import software.amazon.smithy.rust.codegen.core.rustlang.rustTemplate
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType
+import software.amazon.smithy.rust.codegen.core.smithy.RuntimeType.Companion.preludeScope
+import software.amazon.smithy.rust.codegen.core.testutil.asSmithyModel
+import software.amazon.smithy.rust.codegen.core.testutil.testModule
+import software.amazon.smithy.rust.codegen.core.testutil.tokioTest
+
+class IdempotentOperationDecoratorTest {
+    private val model =
+        """
+        namespace com.example
+        use aws.protocols#awsJson1_0
+        @awsJson1_0
+        service HelloService {
+            operations: [GetHello, PutHello, SayHello],
+            version: "1"
+        }
+
+        @readonly
+        operation GetHello {}
+
+        @idempotent
+        operation PutHello {}
+
+        operation SayHello {}
+        """.asSmithyModel()
+
+    @Test
+    fun `only readonly and idempotent operations are marked as idempotent`() {
+        clientIntegrationTest(model) { clientCodegenContext, rustCrate ->
+            val runtimeConfig = clientCodegenContext.runtimeConfig
+            val codegenScope =
+                arrayOf(
+                    *preludeScope,
+                    "BeforeTransmitInterceptorContextMut" to RuntimeType.beforeTransmitInterceptorContextMut(runtimeConfig),
+                    "BoxError" to RuntimeType.boxError(runtimeConfig),
+                    "ConfigBag" to RuntimeType.configBag(runtimeConfig),
+                    "Intercept" to RuntimeType.intercept(runtimeConfig),
+                    "IdempotentOperation" to
+                        RuntimeType.smithyRuntime(runtimeConfig)
+                            .resolve("client::orchestrator::hedging::IdempotentOperation"),
+                    "capture_request" to RuntimeType.captureRequest(runtimeConfig),
+                    "RuntimeComponents" to
+                        RuntimeType.smithyRuntimeApiClient(runtimeConfig)
+                            .resolve("client::runtime_components::RuntimeComponents"),
+                )
+            rustCrate.testModule {
+                tokioTest("test_only_readonly_and_idempotent_operations_are_idempotent") {
+                    rustTemplate(
+                        """
+                        ##[derive(Debug, Default)]
+                        struct IsIdempotent(::std::sync::Arc<::std::sync::atomic::AtomicBool>);
+
+                        impl #{Intercept} for IsIdempotent {
+                            fn name(&self) -> &'static str {
+                                "IsIdempotent"
+                            }
+
+                            fn modify_before_signing(
+                                &self,
+                                _context: &mut #{BeforeTransmitInterceptorContextMut}<'_>,
+                                _runtime_components: &#{RuntimeComponents},
+                                cfg: &mut #{ConfigBag},
+                            ) -> #{Result}<(), #{BoxError}> {
+                                let idempotent = cfg.load::<#{IdempotentOperation}>().is_some();
+                                self.0.store(idempotent, ::std::sync::atomic::Ordering::SeqCst);
+                                #{Ok}(())
+                            }
+                        }
+
+                        let (http_client, _captured_request) = #{capture_request}(#{None});
+                        let client_config = crate::config::Config::builder()
+                            .endpoint_url("http://localhost:1234/")
+                            .http_client(http_client)
+                            .build();
+                        let client = crate::client::Client::from_conf(client_config);
+
+                        let is_idempotent = IsIdempotent::default();
+                        let idempotent = is_idempotent.0.clone();
+                        let _ = client.get_hello().customize().interceptor(is_idempotent).send().await;
+                        assert!(idempotent.load(::std::sync::atomic::Ordering::SeqCst));
+
+                        let is_idempotent = IsIdempotent::default();
+                        let idempotent = is_idempotent.0.clone();
+                        let _ = client.put_hello().customize().interceptor(is_idempotent).send().await;
+                        assert!(idempotent.load(::std::sync::atomic::Ordering::SeqCst));
+
+                        let is_idempotent = IsIdempotent(::std::sync::Arc::new(true.into()));
+                        let idempotent = is_idempotent.0.clone();
+                        let _ = client.say_hello().customize().interceptor(is_idempotent).send().await;
+                        assert!(!idempotent.load(::std::sync::atomic::Ordering::SeqCst));
+                        """,
+                        *codegenScope,
+                    )
+                }
+            }
+        }
+    }
+
+    @Test
+    fun `hedging config can be set on the service config`() {
+        clientIntegrationTest(model) { _, rustCrate ->
+            rustCrate.testModule {
+                tokioTest("test_hedging_config_can_be_set") {
+                    rustTemplate(
+                        """
+                        let config = crate::config::Config::builder()
+                            .hedging_config(crate::config::HedgingConfig::after(::std::time::Duration::from_millis(50)))
+                            .build();
+                        assert_eq!(
+                            ::std::time::Duration::from_millis(50),
+                            config.hedging_config().unwrap().delay()
+                        );
+
+                        let config = config.to_builder().build();
+                        assert!(config.hedging_config().is_some());
+                        """,
+                    )
+                }
+            }
+        }
+    }
+}
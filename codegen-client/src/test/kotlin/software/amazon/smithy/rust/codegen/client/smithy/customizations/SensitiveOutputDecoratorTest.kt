@@ -23,6 +23,9 @@ class SensitiveOutputDecoratorTest {
                     .resolve("test_util::capture_test_logs::capture_test_logs"),
             "capture_request" to RuntimeType.captureRequest(runtimeConfig),
             "SdkBody" to RuntimeType.sdkBody(runtimeConfig),
+            "BodyLoggingInterceptor" to
+                RuntimeType.smithyRuntime(runtimeConfig)
+                    .resolve("client::http::body_logging::BodyLoggingInterceptor"),
         )
 
     private val model =
@@ -35,7 +38,7 @@ class SensitiveOutputDecoratorTest {
             version: "1"
         }
         @optionalAuth
-        operation SayHello { output: TestOutput }
+        operation SayHello { input: TestInput, output: TestOutput }
 
         @sensitive
         structure Credentials {
@@ -46,6 +49,40 @@ class SensitiveOutputDecoratorTest {
         structure TestOutput {
            credentials: Credentials,
         }
+
+        structure TestInput {
+           credentials: Credentials,
+        }
+        """.asSmithyModel()
+
+    private val httpBindingModel =
+        """
+        namespace com.example
+        use aws.protocols#restJson1
+        @restJson1
+        service HelloService {
+            operations: [SayHello],
+            version: "1"
+        }
+        @optionalAuth
+        @http(method: "POST", uri: "/hello")
+        operation SayHello { input: TestInput, output: TestOutput }
+
+        @sensitive
+        string Secret
+
+        structure TestInput {
+           @httpHeader("x-secret-header")
+           secretHeader: Secret,
+
+           @httpQuery("secretQuery")
+           secretQuery: Secret,
+        }
+
+        structure TestOutput {
+           @httpHeader("x-secret-header")
+           secretHeader: Secret,
+        }
         """.asSmithyModel()
 
     @Test
@@ -84,4 +121,96 @@ class SensitiveOutputDecoratorTest {
             }
         }
     }
+
+    @Test
+    fun `sensitive input in model should redact logged request body`() {
+        clientIntegrationTest(model) { codegenContext, rustCrate ->
+            rustCrate.integrationTest("redacting_sensitive_request_body") {
+                val moduleName = codegenContext.moduleUseName()
+                Attribute.TokioTest.render(this)
+                rustTemplate(
+                    """
+                    async fn redacting_sensitive_request_body() {
+                        let (_logs, logs_rx) = #{capture_test_logs}();
+                        let (http_client, _r) = #{capture_request}(Some(
+                            http::Response::builder()
+                                .status(200)
+                                .body(#{SdkBody}::from("{}"))
+                                .unwrap(),
+                        ));
+
+                        let config = $moduleName::Config::builder()
+                            .endpoint_url("http://localhost:1234")
+                            .http_client(http_client.clone())
+                            .interceptor(#{BodyLoggingInterceptor}::new())
+                            .build();
+                        let client = $moduleName::Client::from_conf(config);
+                        let _ = client.say_hello()
+                            .credentials(
+                                $moduleName::types::Credentials::builder()
+                                    .username("user")
+                                    .password("hunter2")
+                                    .build(),
+                            )
+                            .send()
+                            .await
+                            .expect("success");
+
+                        let log_contents = logs_rx.contents();
+                        assert!(log_contents.contains("sending HTTP request"));
+                        assert!(log_contents.contains("** REDACTED **"));
+                        assert!(!log_contents.contains("hunter2"));
+                    }
+                    """,
+                    *codegenScope(codegenContext.runtimeConfig),
+                )
+            }
+        }
+    }
+
+    @Test
+    fun `sensitive members bound to headers and the query string should be redacted`() {
+        clientIntegrationTest(httpBindingModel) { codegenContext, rustCrate ->
+            rustCrate.integrationTest("redacting_sensitive_http_bindings") {
+                val moduleName = codegenContext.moduleUseName()
+                Attribute.TokioTest.render(this)
+                rustTemplate(
+                    """
+                    async fn redacting_sensitive_http_bindings() {
+                        let (_logs, logs_rx) = #{capture_test_logs}();
+                        let (http_client, _r) = #{capture_request}(Some(
+                            http::Response::builder()
+                                .status(200)
+                                .header("x-secret-header", "response-secret")
+                                .body(#{SdkBody}::from("{}"))
+                                .unwrap(),
+                        ));
+
+                        let config = $moduleName::Config::builder()
+                            .endpoint_url("http://localhost:1234")
+                            .http_client(http_client.clone())
+                            .interceptor(#{BodyLoggingInterceptor}::new())
+                            .build();
+                        let client = $moduleName::Client::from_conf(config);
+                        let output = client.say_hello()
+                            .secret_header("header-secret")
+                            .secret_query("query-secret")
+                            .send()
+                            .await
+                            .expect("success");
+                        assert_eq!(Some("response-secret"), output.secret_header());
+
+                        let log_contents = logs_rx.contents();
+                        assert!(log_contents.contains("sending HTTP request"));
+                        assert!(log_contents.contains("received HTTP response"));
+                        assert!(!log_contents.contains("header-secret"));
+                        assert!(!log_contents.contains("query-secret"));
+                        assert!(!log_contents.contains("response-secret"));
+                    }
+                    """,
+                    *codegenScope(codegenContext.runtimeConfig),
+                )
+            }
+        }
+    }
 }
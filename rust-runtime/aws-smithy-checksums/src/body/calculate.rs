@@ -98,7 +98,8 @@ impl http_body::Body for ChecksumBody<SdkBody> {
 #[cfg(test)]
 mod tests {
     use super::ChecksumBody;
-    use crate::{http::CRC_32_HEADER_NAME, ChecksumAlgorithm, CRC_32_NAME};
+    use crate::http::{CRC_32_HEADER_NAME, XXHASH_3_HEADER_NAME};
+    use crate::{ChecksumAlgorithm, CRC_32_NAME, XXHASH_3_NAME};
     use aws_smithy_types::base64;
     use aws_smithy_types::body::SdkBody;
     use bytes::Buf;
@@ -155,4 +156,34 @@ mod tests {
         // Known correct checksum for the input "This is some test text for an SdkBody"
         assert_eq!("0x99B01F72", checksum_trailer);
     }
+
+    #[tokio::test]
+    async fn test_xxhash3_checksum_body() {
+        let input_text = "This is some test text for an SdkBody";
+        let body = SdkBody::from(input_text);
+        let checksum = XXHASH_3_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        let mut body = ChecksumBody::new(body, checksum);
+        while let Some(buf) = body.data().await {
+            buf.unwrap();
+        }
+
+        let trailers = body
+            .trailers()
+            .await
+            .expect("checksum generation was without error")
+            .expect("trailers were set");
+        let checksum_trailer = trailers
+            .get(XXHASH_3_HEADER_NAME)
+            .expect("trailers contain xxhash3 checksum");
+        let checksum_trailer = header_value_as_checksum_string(checksum_trailer);
+
+        let expected_checksum = format!(
+            "0x{:016X}",
+            xxhash_rust::xxh3::xxh3_64(input_text.as_bytes())
+        );
+        assert_eq!(expected_checksum, checksum_trailer);
+    }
 }
@@ -218,4 +218,40 @@ mod tests {
         // Verify data is complete and unaltered
         assert_eq!(input_text, output_text);
     }
+
+    #[tokio::test]
+    async fn test_checksum_validated_body_succeeds_on_match_for_every_algorithm() {
+        use sha2::Digest;
+
+        let input_text = "This is some test text for an SdkBody";
+        let checksums = [
+            (
+                ChecksumAlgorithm::Sha512,
+                Bytes::copy_from_slice(&sha2::Sha512::digest(input_text.as_bytes())),
+            ),
+            (
+                ChecksumAlgorithm::XxHash64,
+                Bytes::copy_from_slice(
+                    &xxhash_rust::xxh64::xxh64(input_text.as_bytes(), 0).to_be_bytes(),
+                ),
+            ),
+            (
+                ChecksumAlgorithm::XxHash3,
+                Bytes::copy_from_slice(
+                    &xxhash_rust::xxh3::xxh3_64(input_text.as_bytes()).to_be_bytes(),
+                ),
+            ),
+        ];
+
+        for (checksum_algorithm, actual_checksum) in checksums {
+            let mut body = ChecksumBody::new(
+                SdkBody::from(input_text),
+                checksum_algorithm.into_impl(),
+                actual_checksum,
+            );
+            while let Some(data) = body.data().await {
+                data.unwrap_or_else(|err| panic!("{checksum_algorithm:?} should match: {err}"));
+            }
+        }
+    }
 }
@@ -10,8 +10,9 @@ use http::header::{HeaderMap, HeaderValue};
 
 use crate::Crc64Nvme;
 use crate::{
-    Checksum, Crc32, Crc32c, Md5, Sha1, Sha256, CRC_32_C_NAME, CRC_32_NAME, CRC_64_NVME_NAME,
-    SHA_1_NAME, SHA_256_NAME,
+    Checksum, Crc32, Crc32c, Md5, Sha1, Sha256, Sha512, XxHash3, XxHash64, CRC_32_C_NAME,
+    CRC_32_NAME, CRC_64_NVME_NAME, SHA_1_NAME, SHA_256_NAME, SHA_512_NAME, XXHASH_3_NAME,
+    XXHASH_64_NAME,
 };
 
 pub const CRC_32_HEADER_NAME: &str = "x-amz-checksum-crc32";
@@ -19,6 +20,9 @@ pub const CRC_32_C_HEADER_NAME: &str = "x-amz-checksum-crc32c";
 pub const SHA_1_HEADER_NAME: &str = "x-amz-checksum-sha1";
 pub const SHA_256_HEADER_NAME: &str = "x-amz-checksum-sha256";
 pub const CRC_64_NVME_HEADER_NAME: &str = "x-amz-checksum-crc64nvme";
+pub const SHA_512_HEADER_NAME: &str = "x-amz-checksum-sha512";
+pub const XXHASH_64_HEADER_NAME: &str = "x-amz-checksum-xxhash64";
+pub const XXHASH_3_HEADER_NAME: &str = "x-amz-checksum-xxhash3";
 
 // Preserved for compatibility purposes. This should never be used by users, only within smithy-rs
 pub(crate) static MD5_HEADER_NAME: &str = "content-md5";
@@ -26,6 +30,9 @@ pub(crate) static MD5_HEADER_NAME: &str = "content-md5";
 /// When a response has to be checksum-verified, we have to check possible headers until we find the
 /// header with the precalculated checksum. Because a service may send back multiple headers, we have
 /// to check them in order based on how fast each checksum is to calculate.
+///
+/// This only contains the algorithms that were supported when it was introduced. Use
+/// [`ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER`] to check the headers of every supported algorithm.
 pub const CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER: [&str; 5] = [
     CRC_64_NVME_NAME,
     CRC_32_C_NAME,
@@ -34,6 +41,21 @@ pub const CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER: [&str; 5] = [
     SHA_256_NAME,
 ];
 
+/// Every supported checksum algorithm, in the order in which response checksum headers should be checked.
+///
+/// Like [`CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER`], algorithms are ordered by how fast each checksum is to
+/// calculate. This is a slice so that algorithms can be added without a breaking change.
+pub const ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER: &[&str] = &[
+    CRC_64_NVME_NAME,
+    XXHASH_3_NAME,
+    XXHASH_64_NAME,
+    CRC_32_C_NAME,
+    CRC_32_NAME,
+    SHA_1_NAME,
+    SHA_256_NAME,
+    SHA_512_NAME,
+];
+
 /// Checksum algorithms are use to validate the integrity of data. Structs that implement this trait
 /// can be used as checksum calculators. This trait requires Send + Sync because these checksums are
 /// often used in a threaded context.
@@ -106,6 +128,24 @@ impl HttpChecksum for Sha256 {
     }
 }
 
+impl HttpChecksum for Sha512 {
+    fn header_name(&self) -> &'static str {
+        SHA_512_HEADER_NAME
+    }
+}
+
+impl HttpChecksum for XxHash64 {
+    fn header_name(&self) -> &'static str {
+        XXHASH_64_HEADER_NAME
+    }
+}
+
+impl HttpChecksum for XxHash3 {
+    fn header_name(&self) -> &'static str {
+        XXHASH_3_HEADER_NAME
+    }
+}
+
 impl HttpChecksum for Md5 {
     fn header_name(&self) -> &'static str {
         MD5_HEADER_NAME
@@ -119,6 +159,7 @@ mod tests {
 
     use crate::{
         ChecksumAlgorithm, CRC_32_C_NAME, CRC_32_NAME, CRC_64_NVME_NAME, SHA_1_NAME, SHA_256_NAME,
+        SHA_512_NAME, XXHASH_3_NAME, XXHASH_64_NAME,
     };
 
     use super::HttpChecksum;
@@ -243,4 +284,82 @@ mod tests {
         let actual_value = checksum.header_value();
         assert_eq!(expected_value, actual_value)
     }
+
+    #[test]
+    fn test_trailer_length_of_sha512_checksum_body() {
+        let checksum = SHA_512_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        let expected_size = 110;
+        let actual_size = HttpChecksum::size(&*checksum);
+        assert_eq!(expected_size, actual_size)
+    }
+
+    #[test]
+    fn test_trailer_value_of_sha512_checksum_body() {
+        let checksum = SHA_512_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        // The SHA512 of an empty string is cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e
+        let expected_value = Bytes::from_static(&[
+            0xcf, 0x83, 0xe1, 0x35, 0x7e, 0xef, 0xb8, 0xbd, 0xf1, 0x54, 0x28, 0x50, 0xd6, 0x6d,
+            0x80, 0x07, 0xd6, 0x20, 0xe4, 0x05, 0x0b, 0x57, 0x15, 0xdc, 0x83, 0xf4, 0xa9, 0x21,
+            0xd3, 0x6c, 0xe9, 0xce, 0x47, 0xd0, 0xd1, 0x3c, 0x5d, 0x85, 0xf2, 0xb0, 0xff, 0x83,
+            0x18, 0xd2, 0x87, 0x7e, 0xec, 0x2f, 0x63, 0xb9, 0x31, 0xbd, 0x47, 0x41, 0x7a, 0x81,
+            0xa5, 0x38, 0x32, 0x7a, 0xf9, 0x27, 0xda, 0x3e,
+        ]);
+        let expected_value = base64::encode(&expected_value);
+        let actual_value = checksum.header_value();
+        assert_eq!(expected_value, actual_value)
+    }
+
+    #[test]
+    fn test_trailer_length_of_xxhash64_checksum_body() {
+        let checksum = XXHASH_64_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        let expected_size = 36;
+        let actual_size = HttpChecksum::size(&*checksum);
+        assert_eq!(expected_size, actual_size)
+    }
+
+    #[test]
+    fn test_trailer_value_of_xxhash64_checksum_body() {
+        let checksum = XXHASH_64_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        // The XXH64 of an empty string is ef46db3751d8e999
+        let expected_value = Bytes::from_static(&[0xef, 0x46, 0xdb, 0x37, 0x51, 0xd8, 0xe9, 0x99]);
+        let expected_value = base64::encode(&expected_value);
+        let actual_value = checksum.header_value();
+        assert_eq!(expected_value, actual_value)
+    }
+
+    #[test]
+    fn test_trailer_length_of_xxhash3_checksum_body() {
+        let checksum = XXHASH_3_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        let expected_size = 35;
+        let actual_size = HttpChecksum::size(&*checksum);
+        assert_eq!(expected_size, actual_size)
+    }
+
+    #[test]
+    fn test_trailer_value_of_xxhash3_checksum_body() {
+        let checksum = XXHASH_3_NAME
+            .parse::<ChecksumAlgorithm>()
+            .unwrap()
+            .into_impl();
+        // The XXH3 (64 bit) of an empty string is 2d06800538d394c2
+        let expected_value = Bytes::from_static(&[0x2d, 0x06, 0x80, 0x05, 0x38, 0xd3, 0x94, 0xc2]);
+        let expected_value = base64::encode(&expected_value);
+        let actual_value = checksum.header_value();
+        assert_eq!(expected_value, actual_value)
+    }
 }
@@ -5,10 +5,20 @@
 
 //! HTTP body-wrappers that perform request compression
 
+// Trailers received from the inner body are held back until the end of the compressed or
+// decompressed stream has been sent. `http-body-0-4` polls trailers separately, so there's nothing
+// to hold back.
+#[cfg(feature = "http-body-1-x")]
+type PendingTrailers = Option<http_1_0::HeaderMap>;
+#[cfg(not(feature = "http-body-1-x"))]
+type PendingTrailers = ();
+
 // Putting this in a `mod` since I expect we'll have to handle response
 // decompression some day.
 /// Functionality for compressing an HTTP request body.
 pub mod compress {
+    use super::PendingTrailers;
+    use crate::CompressStream;
     use aws_smithy_types::body::SdkBody;
     use pin_project_lite::pin_project;
 
@@ -17,10 +27,15 @@ pub mod compress {
         ///
         /// Compression options may disable request compression for small data payload, or entirely.
         /// Additionally, some services may not support compression.
+        ///
+        /// All data frames of the inner body are compressed into a single compressed stream. The
+        /// encoder is flushed after every frame, and the stream is finished once the inner body ends.
         pub struct CompressedBody<InnerBody, CompressionImpl> {
             #[pin]
             body: InnerBody,
             compress_request: CompressionImpl,
+            stream: Option<Box<dyn CompressStream>>,
+            trailers: PendingTrailers,
             is_end_stream: bool,
         }
     }
@@ -31,6 +46,8 @@ pub mod compress {
             Self {
                 body,
                 compress_request,
+                stream: None,
+                trailers: Default::default(),
                 is_end_stream: false,
             }
         }
@@ -56,18 +73,32 @@ pub mod compress {
                 self: Pin<&mut Self>,
                 cx: &mut Context<'_>,
             ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
-                let this = self.project();
-                match this.body.poll_data(cx)? {
-                    Poll::Ready(Some(data)) => {
-                        let mut out = Vec::new();
-                        this.compress_request.compress_bytes(&data[..], &mut out)?;
-                        Poll::Ready(Some(Ok(out.into())))
+                let mut this = self.project();
+                if *this.is_end_stream {
+                    return Poll::Ready(None);
+                }
+                let stream = this
+                    .stream
+                    .get_or_insert_with(|| this.compress_request.compress_stream());
+                loop {
+                    let mut out = Vec::new();
+                    match this.body.as_mut().poll_data(cx)? {
+                        // Streaming bodies may yield empty chunks. Flushing the encoder for them
+                        // would only add empty blocks to the output, so they're skipped.
+                        Poll::Ready(Some(data)) if data.is_empty() => continue,
+                        Poll::Ready(Some(data)) => stream.compress_bytes(&data[..], &mut out)?,
+                        Poll::Ready(None) => {
+                            *this.is_end_stream = true;
+                            stream.finish(&mut out)?;
+                            if out.is_empty() {
+                                return Poll::Ready(None);
+                            }
+                        }
+                        Poll::Pending => return Poll::Pending,
                     }
-                    Poll::Ready(None) => {
-                        *this.is_end_stream = true;
-                        Poll::Ready(None)
+                    if !out.is_empty() {
+                        return Poll::Ready(Some(Ok(out.into())));
                     }
-                    Poll::Pending => Poll::Pending,
                 }
             }
 
@@ -124,31 +155,54 @@ pub mod compress {
                 mut self: Pin<&mut Self>,
                 cx: &mut Context<'_>,
             ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
-                let this = self.as_mut().project();
-                Poll::Ready(match ready!(this.body.poll_frame(cx)) {
-                    Some(Ok(f)) => {
-                        if f.is_data() {
-                            let d = f.into_data().expect("we checked for data first");
-                            let mut out = Vec::new();
-                            this.compress_request.compress_bytes(&d, &mut out)?;
-                            Some(Ok(Frame::data(out.into())))
-                        } else if f.is_trailers() {
-                            // Trailers don't get compressed.
-                            Some(Ok(f))
-                        } else {
-                            unreachable!("Frame is either data or trailers")
+                let mut this = self.as_mut().project();
+                if let Some(trailers) = this.trailers.take() {
+                    return Poll::Ready(Some(Ok(Frame::trailers(trailers))));
+                }
+                if *this.is_end_stream {
+                    return Poll::Ready(None);
+                }
+                let stream = this
+                    .stream
+                    .get_or_insert_with(|| this.compress_request.compress_stream());
+                loop {
+                    let mut out = Vec::new();
+                    match ready!(this.body.as_mut().poll_frame(cx)) {
+                        Some(Ok(f)) => match f.into_data() {
+                            // Streaming bodies may yield empty chunks. Flushing the encoder for
+                            // them would only add empty blocks to the output, so they're skipped.
+                            Ok(d) if d.is_empty() => continue,
+                            Ok(d) => stream.compress_bytes(&d, &mut out)?,
+                            Err(f) => {
+                                // Trailers don't get compressed, but the compressed stream must
+                                // be finished before they're sent.
+                                let trailers =
+                                    f.into_trailers().expect("Frame is either data or trailers");
+                                *this.is_end_stream = true;
+                                stream.finish(&mut out)?;
+                                if out.is_empty() {
+                                    return Poll::Ready(Some(Ok(Frame::trailers(trailers))));
+                                }
+                                *this.trailers = Some(trailers);
+                            }
+                        },
+                        Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                        None => {
+                            *this.is_end_stream = true;
+                            stream.finish(&mut out)?;
+                            if out.is_empty() {
+                                return Poll::Ready(None);
+                            }
                         }
                     }
-                    None => {
-                        *this.is_end_stream = true;
-                        None
+                    if !out.is_empty() {
+                        return Poll::Ready(Some(Ok(Frame::data(out.into()))));
                     }
-                    other => other,
-                })
+                }
             }
 
             fn is_end_stream(&self) -> bool {
-                self.is_end_stream
+                self.is_end_stream && self.trailers.is_none()
             }
 
             fn size_hint(&self) -> SizeHint {
@@ -160,10 +214,188 @@ pub mod compress {
     }
 }
 
+/// Functionality for decompressing an HTTP body.
+pub mod decompress {
+    use super::PendingTrailers;
+    use pin_project_lite::pin_project;
+
+    pin_project! {
+        /// A `Body` that decompresses its data with a `Decompress` implementor.
+        ///
+        /// Decompression happens as data is polled, so the inner body is never buffered in full.
+        /// An error is returned if the inner body ends before all compressed data was received.
+        pub struct DecompressedBody<InnerBody, DecompressionImpl> {
+            #[pin]
+            body: InnerBody,
+            decompress: DecompressionImpl,
+            trailers: PendingTrailers,
+            is_end_stream: bool,
+        }
+    }
+
+    impl<B, D> DecompressedBody<B, D> {
+        /// Given a body and a `Box<dyn Decompress>`, create a new `DecompressedBody<B, D>`.
+        pub fn new(body: B, decompress: D) -> Self {
+            Self {
+                body,
+                decompress,
+                trailers: Default::default(),
+                is_end_stream: false,
+            }
+        }
+    }
+
+    /// Support for the `http-body-0-4` and `http-0-2` crates.
+    #[cfg(feature = "http-body-0-4-x")]
+    pub mod http_body_0_4_x {
+        use super::DecompressedBody;
+        use crate::Decompress;
+        use aws_smithy_runtime_api::box_error::BoxError;
+        use http_0_2::HeaderMap;
+        use http_body_0_4::{Body, SizeHint};
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        impl<B> Body for DecompressedBody<B, Box<dyn Decompress>>
+        where
+            B: Body<Data = bytes::Bytes>,
+            B::Error: Into<BoxError>,
+        {
+            type Data = bytes::Bytes;
+            type Error = aws_smithy_types::body::Error;
+
+            fn poll_data(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+                let mut this = self.project();
+                if *this.is_end_stream {
+                    return Poll::Ready(None);
+                }
+                loop {
+                    let mut out = Vec::new();
+                    match this.body.as_mut().poll_data(cx) {
+                        Poll::Ready(Some(Ok(data))) => {
+                            this.decompress.decompress_bytes(&data[..], &mut out)?;
+                        }
+                        Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                        Poll::Ready(None) => {
+                            *this.is_end_stream = true;
+                            this.decompress.finish(&mut out)?;
+                            if out.is_empty() {
+                                return Poll::Ready(None);
+                            }
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    // Compressed data doesn't always produce output right away. Rather than
+                    // returning empty chunks, keep polling the inner body.
+                    if !out.is_empty() {
+                        return Poll::Ready(Some(Ok(out.into())));
+                    }
+                }
+            }
+
+            fn poll_trailers(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+                let this = self.project();
+                this.body.poll_trailers(cx).map_err(Into::into)
+            }
+
+            fn is_end_stream(&self) -> bool {
+                self.is_end_stream
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                // We can't return a hint because we don't know the decompressed size
+                SizeHint::default()
+            }
+        }
+    }
+
+    /// Support for the `http-body-1-0` and `http-1-0` crates.
+    #[cfg(feature = "http-body-1-x")]
+    pub mod http_body_1_x {
+        use super::DecompressedBody;
+        use crate::Decompress;
+        use aws_smithy_runtime_api::box_error::BoxError;
+        use http_body_1_0::{Body, Frame, SizeHint};
+        use std::pin::Pin;
+        use std::task::{ready, Context, Poll};
+
+        impl<B> Body for DecompressedBody<B, Box<dyn Decompress>>
+        where
+            B: Body<Data = bytes::Bytes>,
+            B::Error: Into<BoxError>,
+        {
+            type Data = bytes::Bytes;
+            type Error = aws_smithy_types::body::Error;
+
+            fn poll_frame(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+                let mut this = self.project();
+                if let Some(trailers) = this.trailers.take() {
+                    return Poll::Ready(Some(Ok(Frame::trailers(trailers))));
+                }
+                if *this.is_end_stream {
+                    return Poll::Ready(None);
+                }
+                loop {
+                    let mut out = Vec::new();
+                    match ready!(this.body.as_mut().poll_frame(cx)) {
+                        Some(Ok(f)) => match f.into_data() {
+                            Ok(d) => this.decompress.decompress_bytes(&d, &mut out)?,
+                            Err(f) => {
+                                // Trailers aren't compressed, but the data still buffered in the
+                                // decoder must be sent before them.
+                                let trailers =
+                                    f.into_trailers().expect("Frame is either data or trailers");
+                                *this.is_end_stream = true;
+                                this.decompress.finish(&mut out)?;
+                                if out.is_empty() {
+                                    return Poll::Ready(Some(Ok(Frame::trailers(trailers))));
+                                }
+                                *this.trailers = Some(trailers);
+                            }
+                        },
+                        Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                        None => {
+                            *this.is_end_stream = true;
+                            this.decompress.finish(&mut out)?;
+                            if out.is_empty() {
+                                return Poll::Ready(None);
+                            }
+                        }
+                    }
+                    // Compressed data doesn't always produce output right away. Rather than
+                    // returning empty frames, keep polling the inner body.
+                    if !out.is_empty() {
+                        return Poll::Ready(Some(Ok(Frame::data(out.into()))));
+                    }
+                }
+            }
+
+            fn is_end_stream(&self) -> bool {
+                self.is_end_stream && self.trailers.is_none()
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                // We can't return a hint because we don't know the decompressed size
+                SizeHint::default()
+            }
+        }
+    }
+}
+
 #[cfg(any(feature = "http-body-0-4-x", feature = "http-body-1-x"))]
 #[cfg(test)]
 mod test {
     use crate::body::compress::CompressedBody;
+    use crate::body::decompress::DecompressedBody;
     use crate::{CompressionAlgorithm, CompressionOptions};
     use aws_smithy_types::body::SdkBody;
     use bytes::Buf;
@@ -174,11 +406,40 @@ mod test {
         31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 203, 72, 205, 201, 201, 87, 40, 207, 47, 202, 73, 1, 0,
         133, 17, 74, 13, 11, 0, 0, 0,
     ];
+    // A `CompressedBody` flushes the encoder after every frame, which adds an empty block.
+    const STREAMED_COMPRESSED_OUTPUT: &[u8] = &[
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 202, 72, 205, 201, 201, 87, 40, 207, 47, 202, 73, 1, 0,
+        0, 0, 255, 255, 3, 0, 133, 17, 74, 13, 11, 0, 0, 0,
+    ];
+
+    /// Every compression algorithm enabled for this build.
+    fn algorithms() -> Vec<CompressionAlgorithm> {
+        vec![
+            CompressionAlgorithm::Gzip,
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd,
+            #[cfg(feature = "brotli")]
+            CompressionAlgorithm::Brotli,
+        ]
+    }
+
+    /// Input long enough to be split into several frames.
+    fn multi_frame_input() -> Vec<u8> {
+        (0..2_000)
+            .flat_map(|i: u32| format!("line {i} of a streaming request body\n").into_bytes())
+            .collect()
+    }
 
     #[cfg(feature = "http-body-0-4-x")]
     mod http_body_0_4_x {
         use super::*;
+        use aws_smithy_runtime_api::box_error::BoxError;
+        use bytes::Bytes;
+        use http_0_2::{HeaderMap, HeaderValue};
         use http_body_0_4::Body;
+        use std::collections::VecDeque;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
 
         #[tokio::test]
         async fn test_body_is_compressed() {
@@ -201,7 +462,7 @@ mod test {
                 .read_to_end(&mut actual_output)
                 .expect("Doesn't cause IO errors");
             // Verify data is compressed as expected
-            assert_eq!(COMPRESSED_OUTPUT, actual_output);
+            assert_eq!(STREAMED_COMPRESSED_OUTPUT, actual_output);
         }
 
         #[tokio::test]
@@ -222,12 +483,147 @@ mod test {
                 compressed_sdk_body.bytes().expect("body is in-memory")
             );
         }
+
+        /// A streaming body that yields each of its chunks as a separate frame, followed by trailers.
+        struct ChunkedBody {
+            chunks: VecDeque<Bytes>,
+            trailers: Option<HeaderMap>,
+        }
+
+        impl Body for ChunkedBody {
+            type Data = Bytes;
+            type Error = BoxError;
+
+            fn poll_data(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+            ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+                Poll::Ready(self.chunks.pop_front().map(Ok))
+            }
+
+            fn poll_trailers(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+            ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+                Poll::Ready(Ok(self.trailers.take()))
+            }
+        }
+
+        #[tokio::test]
+        async fn test_streaming_body_is_compressed_frame_by_frame() {
+            let compression_options = CompressionOptions::default()
+                .with_min_compression_size_bytes(0)
+                .unwrap();
+            let compress_request =
+                CompressionAlgorithm::Gzip.into_impl_http_body_0_4_x(&compression_options);
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-amz-checksum-crc32", HeaderValue::from_static("DUoRhQ=="));
+            let body = SdkBody::from_body_0_4(ChunkedBody {
+                chunks: [&b"hello"[..], b"", b" ", b"world"]
+                    .into_iter()
+                    .map(Bytes::from_static)
+                    .collect(),
+                trailers: Some(trailers.clone()),
+            });
+            let mut compressed_body = CompressedBody::new(body, compress_request);
+
+            let mut frames = Vec::new();
+            while let Some(buf) = compressed_body.data().await {
+                frames.push(buf.unwrap());
+            }
+            // The empty chunk doesn't produce a frame, and the end of the compressed stream is
+            // sent in a frame of its own.
+            assert_eq!(4, frames.len());
+            assert!(compressed_body.is_end_stream());
+            // Trailers are passed through as they are.
+            assert_eq!(Some(trailers), compressed_body.trailers().await.unwrap());
+
+            // All frames form a single gzip member that decompresses to the original data.
+            let mut decompressed_body = DecompressedBody::new(
+                SdkBody::from(frames.concat()),
+                CompressionAlgorithm::Gzip.into_impl_decompress(),
+            );
+            let mut output = Vec::new();
+            while let Some(buf) = decompressed_body.data().await {
+                output.extend_from_slice(&buf.unwrap());
+            }
+            assert_eq!(UNCOMPRESSED_INPUT, output);
+        }
+
+        #[tokio::test]
+        async fn test_multi_frame_body_round_trips_for_every_algorithm() {
+            let compression_options = CompressionOptions::default()
+                .with_min_compression_size_bytes(0)
+                .unwrap();
+            let input = multi_frame_input();
+            for algorithm in algorithms() {
+                let body = SdkBody::from_body_0_4(ChunkedBody {
+                    chunks: input.chunks(1_000).map(Bytes::copy_from_slice).collect(),
+                    trailers: None,
+                });
+                let mut compressed_body = CompressedBody::new(
+                    body,
+                    algorithm.into_impl_http_body_0_4_x(&compression_options),
+                );
+                let mut frames = Vec::new();
+                while let Some(buf) = compressed_body.data().await {
+                    frames.push(buf.unwrap());
+                }
+                assert!(
+                    frames.len() > 1,
+                    "{algorithm:?} should produce several frames"
+                );
+
+                let mut decompressed_body = DecompressedBody::new(
+                    SdkBody::from(frames.concat()),
+                    algorithm.into_impl_decompress(),
+                );
+                let mut output = Vec::new();
+                while let Some(buf) = decompressed_body.data().await {
+                    output.extend_from_slice(&buf.unwrap());
+                }
+                assert_eq!(input, output, "{algorithm:?} should round trip");
+            }
+        }
+
+        #[tokio::test]
+        async fn test_body_is_decompressed() {
+            let body = SdkBody::from(COMPRESSED_OUTPUT);
+            let mut decompressed_body =
+                DecompressedBody::new(body, CompressionAlgorithm::Gzip.into_impl_decompress());
+
+            let mut output = Vec::new();
+            while let Some(buf) = decompressed_body.data().await {
+                output.extend_from_slice(&buf.unwrap());
+            }
+            assert_eq!(UNCOMPRESSED_INPUT, output);
+            assert!(decompressed_body.is_end_stream());
+        }
+
+        #[tokio::test]
+        async fn test_truncated_body_fails_to_decompress() {
+            let body = SdkBody::from(&COMPRESSED_OUTPUT[..COMPRESSED_OUTPUT.len() - 4]);
+            let mut decompressed_body =
+                DecompressedBody::new(body, CompressionAlgorithm::Gzip.into_impl_decompress());
+
+            let mut error = None;
+            while let Some(buf) = decompressed_body.data().await {
+                if let Err(err) = buf {
+                    error = Some(err);
+                }
+            }
+            assert!(error.is_some(), "truncated data should fail to decompress");
+        }
     }
 
     #[cfg(feature = "http-body-1-x")]
     mod http_body_1_x {
         use super::*;
-        use http_body_util::BodyExt;
+        use aws_smithy_runtime_api::box_error::BoxError;
+        use bytes::Bytes;
+        use http_1_0::{HeaderMap, HeaderValue};
+        use http_body_1_0::Frame;
+        use http_body_util::{BodyExt, StreamBody};
 
         #[tokio::test]
         async fn test_body_is_compressed() {
@@ -258,7 +654,138 @@ mod test {
                 .read_to_end(&mut actual_output)
                 .expect("Doesn't cause IO errors");
             // Verify data is compressed as expected
-            assert_eq!(COMPRESSED_OUTPUT, actual_output);
+            assert_eq!(STREAMED_COMPRESSED_OUTPUT, actual_output);
+        }
+
+        #[tokio::test]
+        async fn test_streaming_body_is_compressed_frame_by_frame() {
+            let compression_options = CompressionOptions::default()
+                .with_min_compression_size_bytes(0)
+                .unwrap();
+            let compress_request =
+                CompressionAlgorithm::Gzip.into_impl_http_body_1_x(&compression_options);
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-amz-checksum-crc32", HeaderValue::from_static("DUoRhQ=="));
+            let frames = ["hello", "", " ", "world"]
+                .into_iter()
+                .map(|chunk| Ok::<_, BoxError>(Frame::data(Bytes::from_static(chunk.as_bytes()))))
+                .chain(std::iter::once(Ok(Frame::trailers(trailers.clone()))));
+            let body = SdkBody::from_body_1_x(StreamBody::new(futures_util::stream::iter(frames)));
+            let compressed_body = CompressedBody::new(body, compress_request);
+
+            let collected = compressed_body.collect().await.unwrap();
+            // Trailers are passed through as they are.
+            assert_eq!(Some(&trailers), collected.trailers());
+
+            // All frames form a single gzip member that decompresses to the original data.
+            let decompressed_body = DecompressedBody::new(
+                SdkBody::from(collected.to_bytes()),
+                CompressionAlgorithm::Gzip.into_impl_decompress(),
+            );
+            let output = decompressed_body.collect().await.unwrap().to_bytes();
+            assert_eq!(UNCOMPRESSED_INPUT, output);
+        }
+
+        #[tokio::test]
+        async fn test_multi_frame_body_round_trips_for_every_algorithm() {
+            let compression_options = CompressionOptions::default()
+                .with_min_compression_size_bytes(0)
+                .unwrap();
+            let input = multi_frame_input();
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-amz-checksum-crc32", HeaderValue::from_static("DUoRhQ=="));
+            for algorithm in algorithms() {
+                let frames = input
+                    .chunks(1_000)
+                    .map(|chunk| Ok::<_, BoxError>(Frame::data(Bytes::copy_from_slice(chunk))))
+                    .chain(std::iter::once(Ok(Frame::trailers(trailers.clone()))))
+                    .collect::<Vec<_>>();
+                let body =
+                    SdkBody::from_body_1_x(StreamBody::new(futures_util::stream::iter(frames)));
+                let mut compressed_body = CompressedBody::new(
+                    body,
+                    algorithm.into_impl_http_body_1_x(&compression_options),
+                );
+                let mut data = Vec::new();
+                let mut received_trailers = None;
+                while let Some(frame) = compressed_body.frame().await {
+                    let frame = frame.unwrap();
+                    assert!(
+                        received_trailers.is_none(),
+                        "{algorithm:?} sent a frame after the trailers"
+                    );
+                    match frame.into_data() {
+                        Ok(d) => data.push(d),
+                        Err(frame) => received_trailers = frame.into_trailers().ok(),
+                    }
+                }
+                assert!(
+                    data.len() > 1,
+                    "{algorithm:?} should produce several frames"
+                );
+                assert_eq!(Some(&trailers), received_trailers.as_ref());
+
+                let decompressed_body = DecompressedBody::new(
+                    SdkBody::from(data.concat()),
+                    algorithm.into_impl_decompress(),
+                );
+                let output = decompressed_body.collect().await.unwrap().to_bytes();
+                assert_eq!(input, output, "{algorithm:?} should round trip");
+            }
+        }
+
+        #[tokio::test]
+        async fn test_body_is_decompressed() {
+            let body = SdkBody::from(COMPRESSED_OUTPUT);
+            let decompressed_body =
+                DecompressedBody::new(body, CompressionAlgorithm::Gzip.into_impl_decompress());
+
+            let output = decompressed_body.collect().await.unwrap().to_bytes();
+            assert_eq!(UNCOMPRESSED_INPUT, output);
+        }
+
+        fn compressed_body_with_trailers(compressed: &[u8], trailers: HeaderMap) -> SdkBody {
+            let frames = compressed
+                .chunks(10)
+                .map(|chunk| Ok::<_, BoxError>(Frame::data(Bytes::copy_from_slice(chunk))))
+                .chain(std::iter::once(Ok(Frame::trailers(trailers))))
+                .collect::<Vec<_>>();
+            SdkBody::from_body_1_x(StreamBody::new(futures_util::stream::iter(frames)))
+        }
+
+        #[tokio::test]
+        async fn test_trailers_are_sent_after_all_decompressed_data() {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-amz-checksum-crc32", HeaderValue::from_static("DUoRhQ=="));
+            let body = compressed_body_with_trailers(COMPRESSED_OUTPUT, trailers.clone());
+            let mut decompressed_body =
+                DecompressedBody::new(body, CompressionAlgorithm::Gzip.into_impl_decompress());
+
+            let mut data = Vec::new();
+            let mut received_trailers = None;
+            while let Some(frame) = decompressed_body.frame().await {
+                let frame = frame.unwrap();
+                assert!(
+                    received_trailers.is_none(),
+                    "a frame was sent after the trailers"
+                );
+                match frame.into_data() {
+                    Ok(d) => data.extend_from_slice(&d),
+                    Err(frame) => received_trailers = frame.into_trailers().ok(),
+                }
+            }
+            assert_eq!(UNCOMPRESSED_INPUT, data);
+            assert_eq!(Some(&trailers), received_trailers.as_ref());
+        }
+
+        #[tokio::test]
+        async fn test_truncated_body_with_trailers_fails_to_decompress() {
+            let compressed = &COMPRESSED_OUTPUT[..COMPRESSED_OUTPUT.len() - 4];
+            let body = compressed_body_with_trailers(compressed, HeaderMap::new());
+            let decompressed_body =
+                DecompressedBody::new(body, CompressionAlgorithm::Gzip.into_impl_decompress());
+
+            assert!(decompressed_body.collect().await.is_err());
         }
     }
 }
@@ -0,0 +1,247 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::{Compress, CompressStream, CompressionOptions, Decompress, DecompressedOutput};
+use ::brotli::enc::BrotliEncoderParams;
+use ::brotli::{CompressorWriter, DecompressorWriter};
+use aws_smithy_runtime_api::box_error::BoxError;
+use std::io::prelude::*;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Brotli {
+    quality: i32,
+}
+
+impl Brotli {
+    fn compress_bytes(&self, mut bytes: &[u8], mut writer: impl Write) -> Result<(), BoxError> {
+        let params = BrotliEncoderParams {
+            quality: self.quality,
+            ..Default::default()
+        };
+        ::brotli::BrotliCompress(&mut bytes, &mut writer, &params)?;
+
+        Ok(())
+    }
+}
+
+impl Compress for Brotli {
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        Brotli::compress_bytes(self, bytes, writer).map_err(Into::into)
+    }
+
+    fn compress_stream(&self) -> Box<dyn CompressStream> {
+        Box::new(BrotliEncoder {
+            writer: Some(CompressorWriter::new(
+                Vec::new(),
+                COMPRESSOR_BUFFER_SIZE,
+                self.quality as u32,
+                COMPRESSOR_WINDOW_SIZE,
+            )),
+        })
+    }
+}
+
+const COMPRESSOR_BUFFER_SIZE: usize = 4096;
+/// The base-2 logarithm of the window size, matching the default of `BrotliEncoderParams`.
+const COMPRESSOR_WINDOW_SIZE: u32 = 22;
+
+/// A streaming brotli encoder that writes a single brotli stream across all chunks.
+struct BrotliEncoder {
+    // The stream can only be completed by consuming the writer.
+    writer: Option<CompressorWriter<Vec<u8>>>,
+}
+
+impl CompressStream for BrotliEncoder {
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        let encoder = self
+            .writer
+            .as_mut()
+            .ok_or("brotli stream was already finished")?;
+        encoder.write_all(bytes)?;
+        encoder.flush()?;
+        writer.write_all(encoder.get_ref())?;
+        encoder.get_mut().clear();
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        if let Some(encoder) = self.writer.take() {
+            writer.write_all(&encoder.into_inner())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "http-body-0-4-x")]
+mod http_body_0_4_x {
+    use crate::http::http_body_0_4_x::CompressRequest;
+
+    impl CompressRequest for super::Brotli {
+        fn header_value(&self) -> http_0_2::HeaderValue {
+            http_0_2::HeaderValue::from_static("br")
+        }
+    }
+}
+
+#[cfg(feature = "http-body-1-x")]
+mod http_body_1_x {
+    use crate::http::http_body_1_x::CompressRequest;
+
+    impl CompressRequest for super::Brotli {
+        fn header_value(&self) -> http_1_0::HeaderValue {
+            http_1_0::HeaderValue::from_static("br")
+        }
+    }
+}
+
+impl From<&CompressionOptions> for Brotli {
+    fn from(options: &CompressionOptions) -> Self {
+        Brotli {
+            // Levels `0..=9` are a subset of the qualities brotli accepts (`0..=11`).
+            quality: options.level as i32,
+        }
+    }
+}
+
+impl From<CompressionOptions> for Brotli {
+    fn from(options: CompressionOptions) -> Self {
+        Brotli::from(&options)
+    }
+}
+
+const DECOMPRESSOR_BUFFER_SIZE: usize = 4096;
+
+/// A streaming brotli decoder.
+pub(crate) struct BrotliDecoder {
+    writer: DecompressorWriter<DecompressedOutput>,
+}
+
+impl BrotliDecoder {
+    pub(crate) fn new(output: DecompressedOutput) -> Self {
+        Self {
+            writer: DecompressorWriter::new(output, DECOMPRESSOR_BUFFER_SIZE),
+        }
+    }
+}
+
+impl Decompress for BrotliDecoder {
+    fn decompress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self
+            .writer
+            .write_all(bytes)
+            .and_then(|_| self.writer.flush());
+        self.writer.get_ref().check(result)?;
+        self.writer.get_mut().drain(writer)
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self.writer.close();
+        self.writer.get_ref().check(result)?;
+        self.writer.get_mut().drain(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Brotli, BrotliDecoder};
+    use crate::{
+        CompressionOptions, Decompress, DecompressedOutput, MaxDecompressedLengthExceeded,
+    };
+    use pretty_assertions::assert_eq;
+    use std::io::Read;
+
+    fn gettysburg_address() -> &'static [u8] {
+        include_bytes!("../test-data/gettysburg_address.txt")
+    }
+
+    fn decompress(compressed: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        ::brotli::Decompressor::new(compressed, 4096)
+            .read_to_end(&mut out)
+            .expect("valid brotli stream");
+        out
+    }
+
+    #[test]
+    fn test_brotli_compression() {
+        let brotli = Brotli::from(&CompressionOptions::default());
+        let mut compressed_output = Vec::new();
+        brotli
+            .compress_bytes(gettysburg_address(), &mut compressed_output)
+            .expect("compression succeeds");
+        assert!(compressed_output.len() < gettysburg_address().len());
+
+        assert_eq!(gettysburg_address(), &decompress(&compressed_output)[..]);
+    }
+
+    #[test]
+    fn test_brotli_compression_at_every_level() {
+        for level in 0..=9 {
+            let options = CompressionOptions::default().with_level(level).unwrap();
+            let mut compressed_output = Vec::new();
+            Brotli::from(options)
+                .compress_bytes(gettysburg_address(), &mut compressed_output)
+                .expect("compression succeeds");
+
+            assert_eq!(gettysburg_address(), &decompress(&compressed_output)[..]);
+        }
+    }
+
+    #[test]
+    fn test_brotli_streaming_decompression() {
+        let mut compressed = Vec::new();
+        Brotli::from(&CompressionOptions::default())
+            .compress_bytes(gettysburg_address(), &mut compressed)
+            .unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = BrotliDecoder::new(DecompressedOutput::new(None));
+        // Feed the compressed data in small chunks to exercise the streaming decoder
+        for chunk in compressed.chunks(7) {
+            decoder.decompress_bytes(chunk, &mut output).unwrap();
+        }
+        decoder.finish(&mut output).unwrap();
+
+        assert_eq!(gettysburg_address(), &output[..]);
+    }
+
+    #[test]
+    fn test_brotli_decompression_of_truncated_data_fails() {
+        let mut compressed = Vec::new();
+        Brotli::from(&CompressionOptions::default())
+            .compress_bytes(gettysburg_address(), &mut compressed)
+            .unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = BrotliDecoder::new(DecompressedOutput::new(None));
+        decoder
+            .decompress_bytes(&compressed[..compressed.len() - 10], &mut output)
+            .unwrap();
+        decoder
+            .finish(&mut output)
+            .expect_err("truncated data can't be decompressed");
+    }
+
+    #[test]
+    fn test_brotli_decompression_beyond_max_len_fails() {
+        let brotli = Brotli::from(&CompressionOptions::default());
+        let mut compressed = Vec::new();
+        brotli
+            .compress_bytes(&[0; 100_000], &mut compressed)
+            .unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = BrotliDecoder::new(DecompressedOutput::new(Some(1_000)));
+        let error = decoder
+            .decompress_bytes(&compressed, &mut output)
+            .and_then(|_| decoder.finish(&mut output))
+            .expect_err("the decompressed data is too long");
+        assert_eq!(
+            Some(&MaxDecompressedLengthExceeded { max_len: 1_000 }),
+            error.downcast_ref::<MaxDecompressedLengthExceeded>()
+        );
+        assert!(output.len() <= 1_000);
+    }
+}
@@ -3,9 +3,9 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use crate::{Compress, CompressionOptions};
+use crate::{Compress, CompressStream, CompressionOptions, Decompress, DecompressedOutput};
 use aws_smithy_runtime_api::box_error::BoxError;
-use flate2::write::GzEncoder;
+use flate2::write::{GzEncoder, MultiGzDecoder};
 use std::io::prelude::*;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -27,6 +27,34 @@ impl Compress for Gzip {
     fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
         Gzip::compress_bytes(self, bytes, writer).map_err(Into::into)
     }
+
+    fn compress_stream(&self) -> Box<dyn CompressStream> {
+        Box::new(GzipEncoder {
+            encoder: GzEncoder::new(Vec::new(), self.compression),
+        })
+    }
+}
+
+/// A streaming gzip encoder that writes a single gzip member across all chunks.
+struct GzipEncoder {
+    encoder: GzEncoder<Vec<u8>>,
+}
+
+impl CompressStream for GzipEncoder {
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        self.encoder.write_all(bytes)?;
+        self.encoder.flush()?;
+        writer.write_all(self.encoder.get_ref())?;
+        self.encoder.get_mut().clear();
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        self.encoder.try_finish()?;
+        writer.write_all(self.encoder.get_ref())?;
+        self.encoder.get_mut().clear();
+        Ok(())
+    }
 }
 
 #[cfg(feature = "http-body-0-4-x")]
@@ -67,11 +95,46 @@ impl From<CompressionOptions> for Gzip {
     }
 }
 
+/// A streaming gzip decoder.
+///
+/// Data compressed one chunk at a time (e.g. by a `CompressedBody`) is a series of gzip members,
+/// so every member is decoded rather than only the first one.
+pub(crate) struct GzipDecoder {
+    decoder: MultiGzDecoder<DecompressedOutput>,
+}
+
+impl GzipDecoder {
+    pub(crate) fn new(output: DecompressedOutput) -> Self {
+        Self {
+            decoder: MultiGzDecoder::new(output),
+        }
+    }
+}
+
+impl Decompress for GzipDecoder {
+    fn decompress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self
+            .decoder
+            .write_all(bytes)
+            .and_then(|_| self.decoder.flush());
+        self.decoder.get_ref().check(result)?;
+        self.decoder.get_mut().drain(writer)
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self.decoder.try_finish();
+        self.decoder.get_ref().check(result)?;
+        self.decoder.get_mut().drain(writer)
+    }
+}
+
 // Windows line-endings will cause the compression test to fail.
 #[cfg(all(test, not(windows)))]
 mod tests {
-    use super::Gzip;
-    use crate::CompressionOptions;
+    use super::{Gzip, GzipDecoder};
+    use crate::{
+        CompressionOptions, Decompress, DecompressedOutput, MaxDecompressedLengthExceeded,
+    };
     use flate2::read::GzDecoder;
     use pretty_assertions::assert_eq;
     use std::io::Read;
@@ -110,4 +173,65 @@ mod tests {
 
         assert_eq!(uncompressed_expected, uncompressed_actual);
     }
+
+    #[test]
+    fn test_gzip_decompression() {
+        let mut output = Vec::new();
+        let mut decoder = GzipDecoder::new(DecompressedOutput::new(None));
+        // Feed the compressed data in small chunks to exercise the streaming decoder
+        for chunk in gzip_compressed_gettysburg_address().chunks(7) {
+            decoder.decompress_bytes(chunk, &mut output).unwrap();
+        }
+        decoder.finish(&mut output).unwrap();
+
+        assert_eq!(gettysburg_address(), &output[..]);
+    }
+
+    #[test]
+    fn test_gzip_decompression_of_multiple_members() {
+        let gzip = Gzip::from(&CompressionOptions::default());
+        let (first, second) = gettysburg_address().split_at(100);
+        let mut compressed = Vec::new();
+        gzip.compress_bytes(first, &mut compressed).unwrap();
+        gzip.compress_bytes(second, &mut compressed).unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = GzipDecoder::new(DecompressedOutput::new(None));
+        decoder.decompress_bytes(&compressed, &mut output).unwrap();
+        decoder.finish(&mut output).unwrap();
+
+        assert_eq!(gettysburg_address(), &output[..]);
+    }
+
+    #[test]
+    fn test_gzip_decompression_of_truncated_data_fails() {
+        let compressed = gzip_compressed_gettysburg_address();
+        let mut output = Vec::new();
+        let mut decoder = GzipDecoder::new(DecompressedOutput::new(None));
+        decoder
+            .decompress_bytes(&compressed[..compressed.len() - 10], &mut output)
+            .unwrap();
+        decoder
+            .finish(&mut output)
+            .expect_err("truncated data can't be decompressed");
+    }
+
+    #[test]
+    fn test_gzip_decompression_beyond_max_len_fails() {
+        let gzip = Gzip::from(&CompressionOptions::default());
+        let mut compressed = Vec::new();
+        gzip.compress_bytes(&[0; 100_000], &mut compressed).unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = GzipDecoder::new(DecompressedOutput::new(Some(1_000)));
+        let error = decoder
+            .decompress_bytes(&compressed, &mut output)
+            .and_then(|_| decoder.finish(&mut output))
+            .expect_err("the decompressed data is too long");
+        assert_eq!(
+            Some(&MaxDecompressedLengthExceeded { max_len: 1_000 }),
+            error.downcast_ref::<MaxDecompressedLengthExceeded>()
+        );
+        assert!(output.len() <= 1_000);
+    }
 }
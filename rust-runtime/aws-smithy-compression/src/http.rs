@@ -5,6 +5,61 @@
 
 //! Checksum support for HTTP requests and responses.
 
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::http::Request;
+use aws_smithy_types::config_bag::{ConfigBag, Storable, StoreReplace};
+use std::fmt;
+use std::sync::Arc;
+
+type ApplyCompression = dyn Fn(&mut Request, &mut ConfigBag) -> Result<(), BoxError> + Send + Sync;
+
+/// Request compression that has been configured for a request, but not yet applied to its body.
+///
+/// The request compression interceptor stores this in the config bag before the request is
+/// serialized, and applies it once the request has been serialized. An interceptor that wraps the
+/// body in a way that depends on its contents, such as calculating a checksum of it, must take it
+/// from the config bag first. It can then apply it, so that the checksum is calculated from the
+/// compressed body, or decline it. This way, it doesn't matter which interceptor runs first.
+#[derive(Clone)]
+pub struct PendingRequestCompression {
+    apply: Arc<ApplyCompression>,
+}
+
+impl fmt::Debug for PendingRequestCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingRequestCompression").finish()
+    }
+}
+
+impl Storable for PendingRequestCompression {
+    type Storer = StoreReplace<Self>;
+}
+
+impl PendingRequestCompression {
+    /// Creates a new `PendingRequestCompression` that compresses a request with `apply`.
+    pub fn new(
+        apply: impl Fn(&mut Request, &mut ConfigBag) -> Result<(), BoxError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            apply: Arc::new(apply),
+        }
+    }
+
+    /// Removes the pending request compression from `cfg`, if there is one.
+    ///
+    /// Once taken, the request compression is only applied if the caller applies it.
+    pub fn take(cfg: &mut ConfigBag) -> Option<Self> {
+        let pending = cfg.load::<Self>().cloned()?;
+        cfg.interceptor_state().unset::<Self>();
+        Some(pending)
+    }
+
+    /// Compresses the body of `request`.
+    pub fn apply(&self, request: &mut Request, cfg: &mut ConfigBag) -> Result<(), BoxError> {
+        (self.apply)(request, cfg)
+    }
+}
+
 /// Support for the `http-body-0-4` and `http-0-2` crates.
 #[cfg(feature = "http-body-0-4-x")]
 pub mod http_body_0_4_x {
@@ -22,12 +22,20 @@ use std::io::Write;
 use std::str::FromStr;
 
 pub mod body;
+#[cfg(feature = "brotli")]
+mod brotli;
 mod gzip;
 pub mod http;
+#[cfg(feature = "zstd")]
+mod zstd;
 
 // Valid compression algorithm names
 /// The name of the `gzip` algorithm.
 pub const GZIP_NAME: &str = "gzip";
+/// The name of the `zstd` algorithm.
+pub const ZSTD_NAME: &str = "zstd";
+/// The name of the `brotli` algorithm, as used in the `Content-Encoding` header.
+pub const BROTLI_NAME: &str = "br";
 
 /// The maximum-allowable value per internal standards is 10 Megabytes.
 const MAX_MIN_COMPRESSION_SIZE_BYTES: u32 = 10_485_760;
@@ -42,6 +50,124 @@ pub trait Compress: Send + Sync {
     /// bytes to the writer until done.
     // I wanted to use `impl Write` but that's not object-safe
     fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError>;
+
+    /// Return a [`CompressStream`] that compresses data arriving in several chunks into a
+    /// single compressed stream.
+    fn compress_stream(&self) -> Box<dyn CompressStream>;
+}
+
+/// Types implementing this trait compress a stream of data, one chunk at a time.
+///
+/// Unlike [`Compress`], every chunk is written to the same encoder, so the output of all calls
+/// forms one compressed stream that must be completed with [`CompressStream::finish`].
+pub trait CompressStream: Send + Sync {
+    /// Given a chunk of bytes, and a [Write] implementor, compress the chunk and flush the
+    /// encoder so that everything passed in so far can be decompressed from the written bytes.
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError>;
+
+    /// Signal that all bytes have been passed to [`CompressStream::compress_bytes`], and write
+    /// the end of the compressed stream to the writer.
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError>;
+}
+
+/// Types implementing this trait can decompress data.
+///
+/// Unlike [`Compress`], decompression is stateful: compressed data usually arrives split across
+/// several chunks, so implementors write decompressed data out as soon as it is available.
+pub trait Decompress: Send + Sync {
+    /// Given a chunk of compressed bytes, and a [Write] implementor, decompress the chunk and
+    /// write any decompressed bytes that are ready to the writer.
+    fn decompress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError>;
+
+    /// Signal that all compressed bytes have been passed to [`Decompress::decompress_bytes`],
+    /// and write any remaining decompressed bytes to the writer.
+    ///
+    /// An error is returned if the compressed data ended unexpectedly.
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError>;
+}
+
+/// The error returned by a [`Decompress`] implementor created with
+/// [`CompressionAlgorithm::into_impl_decompress_with_max_len`] once the decompressed data exceeds
+/// the maximum length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxDecompressedLengthExceeded {
+    max_len: usize,
+}
+
+impl MaxDecompressedLengthExceeded {
+    /// The maximum length of the decompressed data, in bytes.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl std::fmt::Display for MaxDecompressedLengthExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "decompressed data exceeds the maximum length of {} bytes",
+            self.max_len
+        )
+    }
+}
+
+impl std::error::Error for MaxDecompressedLengthExceeded {}
+
+/// The buffer decoders write decompressed data to until it is drained, which refuses to grow past
+/// the maximum decompressed length.
+pub(crate) struct DecompressedOutput {
+    buffer: Vec<u8>,
+    max_len: Option<usize>,
+    written: usize,
+}
+
+impl DecompressedOutput {
+    pub(crate) fn new(max_len: Option<usize>) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_len,
+            written: 0,
+        }
+    }
+
+    /// Writes the buffered data to `writer`.
+    pub(crate) fn drain(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        writer.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Converts the result of a decoder writing to this buffer, so that exceeding the maximum
+    /// length is reported as [`MaxDecompressedLengthExceeded`] rather than as an I/O error.
+    pub(crate) fn check(&self, result: std::io::Result<()>) -> Result<(), BoxError> {
+        match (result, self.max_len) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(max_len)) if self.written > max_len => {
+                Err(MaxDecompressedLengthExceeded { max_len }.into())
+            }
+            (Err(err), _) => Err(err.into()),
+        }
+    }
+}
+
+impl Write for DecompressedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.written = self.written.saturating_add(buf.len());
+        if let Some(max_len) = self.max_len {
+            if self.written > max_len {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    MaxDecompressedLengthExceeded { max_len },
+                ));
+            }
+        }
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 /// Options for configuring request compression.
@@ -89,7 +215,8 @@ impl CompressionOptions {
 
     /// Set the compression level.
     ///
-    /// Valid values are `0..=9` with lower values configuring less _(but faster)_ compression
+    /// Valid values are `0..=9` with lower values configuring less _(but faster)_ compression.
+    /// For `zstd`, a level of `0` selects the zstd default level.
     pub fn with_level(self, level: u32) -> Result<Self, BoxError> {
         Self::validate_level(level)?;
         Ok(Self { level, ..self })
@@ -145,6 +272,12 @@ impl Storable for CompressionOptions {
 pub enum CompressionAlgorithm {
     /// The [gzip](https://en.wikipedia.org/wiki/Gzip) compression algorithm
     Gzip,
+    /// The [zstd](https://en.wikipedia.org/wiki/Zstd) compression algorithm
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// The [brotli](https://en.wikipedia.org/wiki/Brotli) compression algorithm
+    #[cfg(feature = "brotli")]
+    Brotli,
 }
 
 impl FromStr for CompressionAlgorithm {
@@ -154,14 +287,24 @@ impl FromStr for CompressionAlgorithm {
     ///
     /// Valid algorithm names are:
     /// - "gzip"
+    /// - "zstd" _(requires the `zstd` feature)_
+    /// - "br" _(requires the `brotli` feature)_
     ///
     /// Passing an invalid name will return an error.
     fn from_str(compression_algorithm: &str) -> Result<Self, Self::Err> {
         if compression_algorithm.eq_ignore_ascii_case(GZIP_NAME) {
-            Ok(Self::Gzip)
-        } else {
-            Err(format!("unknown compression algorithm `{compression_algorithm}`").into())
+            return Ok(Self::Gzip);
+        }
+        #[cfg(feature = "zstd")]
+        if compression_algorithm.eq_ignore_ascii_case(ZSTD_NAME) {
+            return Ok(Self::Zstd);
         }
+        #[cfg(feature = "brotli")]
+        if compression_algorithm.eq_ignore_ascii_case(BROTLI_NAME) {
+            return Ok(Self::Brotli);
+        }
+
+        Err(format!("unknown compression algorithm `{compression_algorithm}`").into())
     }
 }
 
@@ -174,6 +317,10 @@ impl CompressionAlgorithm {
     ) -> Box<dyn http::http_body_0_4_x::CompressRequest> {
         match self {
             Self::Gzip => Box::new(gzip::Gzip::from(options)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Box::new(zstd::Zstd::from(options)),
+            #[cfg(feature = "brotli")]
+            Self::Brotli => Box::new(brotli::Brotli::from(options)),
         }
     }
 
@@ -185,6 +332,34 @@ impl CompressionAlgorithm {
     ) -> Box<dyn http::http_body_1_x::CompressRequest> {
         match self {
             Self::Gzip => Box::new(gzip::Gzip::from(options)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Box::new(zstd::Zstd::from(options)),
+            #[cfg(feature = "brotli")]
+            Self::Brotli => Box::new(brotli::Brotli::from(options)),
+        }
+    }
+
+    /// Return a [`Decompress`] implementor for this algorithm.
+    pub fn into_impl_decompress(self) -> Box<dyn Decompress> {
+        self.decompress(DecompressedOutput::new(None))
+    }
+
+    /// Return a [`Decompress`] implementor for this algorithm that fails with
+    /// [`MaxDecompressedLengthExceeded`] once it has decompressed more than `max_len` bytes.
+    ///
+    /// Decompression stops as soon as the limit is reached, so a small chunk of highly
+    /// compressed data can't expand into an unbounded amount of memory.
+    pub fn into_impl_decompress_with_max_len(self, max_len: usize) -> Box<dyn Decompress> {
+        self.decompress(DecompressedOutput::new(Some(max_len)))
+    }
+
+    fn decompress(self, output: DecompressedOutput) -> Box<dyn Decompress> {
+        match self {
+            Self::Gzip => Box::new(gzip::GzipDecoder::new(output)),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Box::new(zstd::ZstdDecoder::new(output)),
+            #[cfg(feature = "brotli")]
+            Self::Brotli => Box::new(brotli::BrotliDecoder::new(output)),
         }
     }
 
@@ -192,6 +367,10 @@ impl CompressionAlgorithm {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Gzip { .. } => GZIP_NAME,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => ZSTD_NAME,
+            #[cfg(feature = "brotli")]
+            Self::Brotli => BROTLI_NAME,
         }
     }
 }
@@ -217,4 +396,20 @@ mod tests {
         let algo = "gzip".parse::<CompressionAlgorithm>().unwrap();
         assert_eq!("gzip", algo.as_str());
     }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compression_algorithm_from_str_zstd() {
+        let algo = "zstd".parse::<CompressionAlgorithm>().unwrap();
+        assert_eq!(CompressionAlgorithm::Zstd, algo);
+        assert_eq!("zstd", algo.as_str());
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_compression_algorithm_from_str_brotli() {
+        let algo = "br".parse::<CompressionAlgorithm>().unwrap();
+        assert_eq!(CompressionAlgorithm::Brotli, algo);
+        assert_eq!("br", algo.as_str());
+    }
 }
@@ -0,0 +1,228 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::{Compress, CompressStream, CompressionOptions, Decompress, DecompressedOutput};
+use ::zstd::stream::{raw, zio};
+use aws_smithy_runtime_api::box_error::BoxError;
+use std::io::prelude::*;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Zstd {
+    level: i32,
+}
+
+impl Zstd {
+    fn compress_bytes(&self, bytes: &[u8], writer: impl Write) -> Result<(), BoxError> {
+        let mut encoder = ::zstd::stream::write::Encoder::new(writer, self.level)?;
+        encoder.write_all(bytes)?;
+        encoder.finish()?;
+
+        Ok(())
+    }
+}
+
+impl Compress for Zstd {
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        Zstd::compress_bytes(self, bytes, writer).map_err(Into::into)
+    }
+
+    fn compress_stream(&self) -> Box<dyn CompressStream> {
+        Box::new(ZstdEncoder {
+            encoder: ::zstd::stream::write::Encoder::new(Vec::new(), self.level)
+                .expect("allocating a zstd compression context succeeds"),
+        })
+    }
+}
+
+/// A streaming zstd encoder that writes a single zstd frame across all chunks.
+struct ZstdEncoder {
+    encoder: ::zstd::stream::write::Encoder<'static, Vec<u8>>,
+}
+
+impl CompressStream for ZstdEncoder {
+    fn compress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        self.encoder.write_all(bytes)?;
+        self.encoder.flush()?;
+        writer.write_all(self.encoder.get_ref())?;
+        self.encoder.get_mut().clear();
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        self.encoder.do_finish()?;
+        writer.write_all(self.encoder.get_ref())?;
+        self.encoder.get_mut().clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "http-body-0-4-x")]
+mod http_body_0_4_x {
+    use crate::http::http_body_0_4_x::CompressRequest;
+
+    impl CompressRequest for super::Zstd {
+        fn header_value(&self) -> http_0_2::HeaderValue {
+            http_0_2::HeaderValue::from_static("zstd")
+        }
+    }
+}
+
+#[cfg(feature = "http-body-1-x")]
+mod http_body_1_x {
+    use crate::http::http_body_1_x::CompressRequest;
+
+    impl CompressRequest for super::Zstd {
+        fn header_value(&self) -> http_1_0::HeaderValue {
+            http_1_0::HeaderValue::from_static("zstd")
+        }
+    }
+}
+
+impl From<&CompressionOptions> for Zstd {
+    fn from(options: &CompressionOptions) -> Self {
+        Zstd {
+            // Levels `0..=9` are a subset of the levels zstd accepts, where `0` selects zstd's default.
+            level: options.level as i32,
+        }
+    }
+}
+
+impl From<CompressionOptions> for Zstd {
+    fn from(options: CompressionOptions) -> Self {
+        Zstd::from(&options)
+    }
+}
+
+/// A streaming zstd decoder.
+///
+/// Data compressed one chunk at a time (e.g. by a `CompressedBody`) is a series of zstd frames,
+/// and every frame is decoded.
+pub(crate) struct ZstdDecoder {
+    writer: zio::Writer<DecompressedOutput, raw::Decoder<'static>>,
+}
+
+impl ZstdDecoder {
+    pub(crate) fn new(output: DecompressedOutput) -> Self {
+        Self {
+            writer: zio::Writer::new(
+                output,
+                raw::Decoder::new().expect("allocating a zstd decompression context succeeds"),
+            ),
+        }
+    }
+}
+
+impl Decompress for ZstdDecoder {
+    fn decompress_bytes(&mut self, bytes: &[u8], writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self
+            .writer
+            .write_all(bytes)
+            .and_then(|_| self.writer.flush());
+        self.writer.writer().check(result)?;
+        self.writer.writer_mut().drain(writer)
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), BoxError> {
+        let result = self.writer.finish();
+        self.writer.writer().check(result)?;
+        self.writer.writer_mut().drain(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Zstd, ZstdDecoder};
+    use crate::{
+        CompressionOptions, Decompress, DecompressedOutput, MaxDecompressedLengthExceeded,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn gettysburg_address() -> &'static [u8] {
+        include_bytes!("../test-data/gettysburg_address.txt")
+    }
+
+    #[test]
+    fn test_zstd_compression() {
+        let zstd = Zstd::from(&CompressionOptions::default());
+        let mut compressed_output = Vec::new();
+        zstd.compress_bytes(gettysburg_address(), &mut compressed_output)
+            .expect("compression succeeds");
+        assert!(compressed_output.len() < gettysburg_address().len());
+
+        let uncompressed_actual =
+            ::zstd::stream::decode_all(&compressed_output[..]).expect("valid zstd frame");
+        assert_eq!(gettysburg_address(), &uncompressed_actual[..]);
+    }
+
+    #[test]
+    fn test_zstd_compression_at_every_level() {
+        for level in 0..=9 {
+            let options = CompressionOptions::default().with_level(level).unwrap();
+            let mut compressed_output = Vec::new();
+            Zstd::from(options)
+                .compress_bytes(gettysburg_address(), &mut compressed_output)
+                .expect("compression succeeds");
+
+            let uncompressed_actual =
+                ::zstd::stream::decode_all(&compressed_output[..]).expect("valid zstd frame");
+            assert_eq!(gettysburg_address(), &uncompressed_actual[..]);
+        }
+    }
+
+    #[test]
+    fn test_zstd_decompression_of_multiple_frames() {
+        let zstd = Zstd::from(&CompressionOptions::default());
+        let (first, second) = gettysburg_address().split_at(100);
+        let mut compressed = Vec::new();
+        zstd.compress_bytes(first, &mut compressed).unwrap();
+        zstd.compress_bytes(second, &mut compressed).unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = ZstdDecoder::new(DecompressedOutput::new(None));
+        // Feed the compressed data in small chunks to exercise the streaming decoder
+        for chunk in compressed.chunks(7) {
+            decoder.decompress_bytes(chunk, &mut output).unwrap();
+        }
+        decoder.finish(&mut output).unwrap();
+
+        assert_eq!(gettysburg_address(), &output[..]);
+    }
+
+    #[test]
+    fn test_zstd_decompression_of_truncated_data_fails() {
+        let mut compressed = Vec::new();
+        Zstd::from(&CompressionOptions::default())
+            .compress_bytes(gettysburg_address(), &mut compressed)
+            .unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = ZstdDecoder::new(DecompressedOutput::new(None));
+        decoder
+            .decompress_bytes(&compressed[..compressed.len() - 10], &mut output)
+            .unwrap();
+        decoder
+            .finish(&mut output)
+            .expect_err("truncated data can't be decompressed");
+    }
+
+    #[test]
+    fn test_zstd_decompression_beyond_max_len_fails() {
+        let zstd = Zstd::from(&CompressionOptions::default());
+        let mut compressed = Vec::new();
+        zstd.compress_bytes(&[0; 100_000], &mut compressed).unwrap();
+
+        let mut output = Vec::new();
+        let mut decoder = ZstdDecoder::new(DecompressedOutput::new(Some(1_000)));
+        let error = decoder
+            .decompress_bytes(&compressed, &mut output)
+            .and_then(|_| decoder.finish(&mut output))
+            .expect_err("the decompressed data is too long");
+        assert_eq!(
+            Some(&MaxDecompressedLengthExceeded { max_len: 1_000 }),
+            error.downcast_ref::<MaxDecompressedLengthExceeded>()
+        );
+        assert!(output.len() <= 1_000);
+    }
+}
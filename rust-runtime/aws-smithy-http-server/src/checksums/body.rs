@@ -0,0 +1,446 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use aws_smithy_checksums::http::HttpChecksum;
+use aws_smithy_types::base64;
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::ready;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use http_body::{Body, SizeHint};
+use pin_project_lite::pin_project;
+
+use crate::error::BoxError;
+
+/// Why a request body failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(super) enum ChecksumError {
+    #[error(
+        "checksum mismatch: the request's {header_name} checksum is {expected} but its body's checksum is {actual}"
+    )]
+    Mismatch {
+        header_name: &'static str,
+        expected: String,
+        actual: String,
+    },
+    #[error("missing trailing checksum: the request's body has no {0} trailer")]
+    MissingTrailer(&'static str),
+    #[error("malformed aws-chunked body: {0}")]
+    MalformedAwsChunked(&'static str),
+}
+
+/// Where a failed validation is recorded, so that the response can be replaced with a `ValidationException` once
+/// the operation has run.
+pub(super) type ValidationFailure = Arc<Mutex<Option<ChecksumError>>>;
+
+/// The checksum a body must match.
+pub(super) enum Expected {
+    /// The base64-decoded value of a checksum header.
+    Header(Bytes),
+    /// A trailer of the `aws-chunked` encoded body, named after the checksum's header.
+    Trailer,
+}
+
+pin_project! {
+    /// A request body that optionally decodes an `aws-chunked` body, and validates its checksum once all of its data
+    /// has been read.
+    pub(super) struct ChecksumValidatedBody<B> {
+        #[pin]
+        inner: B,
+        decoder: Option<AwsChunkedDecoder>,
+        checksum: Option<(Box<dyn HttpChecksum>, Expected)>,
+        failure: ValidationFailure,
+        done: bool,
+    }
+}
+
+impl<B> ChecksumValidatedBody<B> {
+    pub(super) fn new(
+        inner: B,
+        aws_chunked: bool,
+        checksum: Option<(Box<dyn HttpChecksum>, Expected)>,
+        failure: ValidationFailure,
+    ) -> Self {
+        Self {
+            inner,
+            decoder: aws_chunked.then(AwsChunkedDecoder::default),
+            checksum,
+            failure,
+            done: false,
+        }
+    }
+}
+
+impl<B> Body for ChecksumValidatedBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let mut this = self.project();
+        loop {
+            if *this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(this.inner.as_mut().poll_data(cx)) {
+                Some(Ok(data)) => {
+                    let data = match this.decoder.as_mut() {
+                        Some(decoder) => match decoder.decode(&data) {
+                            Ok(data) => data,
+                            Err(err) => return Poll::Ready(Some(Err(fail(this.failure, err)))),
+                        },
+                        None => data,
+                    };
+                    if let Some((checksum, _)) = this.checksum.as_mut() {
+                        checksum.update(&data);
+                    }
+                    // aws-chunked framing doesn't always produce data right away.
+                    if !data.is_empty() {
+                        return Poll::Ready(Some(Ok(data)));
+                    }
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                None => {
+                    *this.done = true;
+                    let trailers = match this.decoder.take().map(AwsChunkedDecoder::finish).transpose() {
+                        Ok(trailers) => trailers.unwrap_or_default(),
+                        Err(err) => return Poll::Ready(Some(Err(fail(this.failure, err)))),
+                    };
+                    if let Some((checksum, expected)) = this.checksum.take() {
+                        if let Err(err) = validate(checksum, expected, &trailers) {
+                            return Poll::Ready(Some(Err(fail(this.failure, err))));
+                        }
+                    }
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().inner.poll_trailers(cx).map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.decoder {
+            // The decoded length is unknown until the whole body has been decoded.
+            Some(_) => SizeHint::default(),
+            None => self.inner.size_hint(),
+        }
+    }
+}
+
+fn fail(failure: &ValidationFailure, err: ChecksumError) -> BoxError {
+    tracing::debug!(error = %err, "request failed checksum validation");
+    *failure.lock().unwrap() = Some(err.clone());
+    err.into()
+}
+
+fn validate(checksum: Box<dyn HttpChecksum>, expected: Expected, trailers: &HeaderMap) -> Result<(), ChecksumError> {
+    let header_name = checksum.header_name();
+    let expected = match expected {
+        Expected::Header(expected) => base64::encode(expected),
+        Expected::Trailer => trailers
+            .get(header_name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(ChecksumError::MissingTrailer(header_name))?
+            .trim()
+            .to_owned(),
+    };
+    let actual = base64::encode(checksum.finalize());
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(ChecksumError::Mismatch {
+            header_name,
+            expected,
+            actual,
+        })
+    }
+}
+
+const CRLF: &[u8] = b"\r\n";
+
+/// The maximum length of a chunk size line or of a trailer, so that a malformed body can't make the decoder buffer
+/// data without limit while it waits for the end of a line.
+const MAX_LINE_LEN: usize = 4 * 1024;
+
+/// The maximum total length of the trailers.
+const MAX_TRAILERS_LEN: usize = 16 * 1024;
+
+#[derive(Debug, Default)]
+enum DecoderState {
+    /// Reading the line with the size of the next chunk, and its optional extensions.
+    #[default]
+    ChunkSize,
+    /// Reading the data of a chunk, with the given number of bytes left.
+    ChunkData(usize),
+    /// Reading the `CRLF` that ends a chunk's data.
+    ChunkEnd,
+    /// Reading the trailers that follow the final, empty chunk.
+    Trailers,
+    /// The final `CRLF` has been read.
+    Done,
+}
+
+/// Decodes an [`aws-chunked`](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming.html) body.
+///
+/// Chunk signatures are not verified.
+#[derive(Debug, Default)]
+struct AwsChunkedDecoder {
+    buffer: BytesMut,
+    state: DecoderState,
+    trailers: HeaderMap,
+    trailers_len: usize,
+}
+
+impl AwsChunkedDecoder {
+    /// Decodes as much of the body as possible, returning the data of the chunks decoded so far.
+    fn decode(&mut self, input: &[u8]) -> Result<Bytes, ChecksumError> {
+        self.buffer.extend_from_slice(input);
+        let mut output = BytesMut::new();
+        loop {
+            match self.state {
+                DecoderState::ChunkSize => {
+                    let Some(line) = self.next_line()? else { break };
+                    let size = line.split(|b| *b == b';').next().unwrap_or_default();
+                    let size = std::str::from_utf8(size)
+                        .ok()
+                        .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+                        .ok_or(ChecksumError::MalformedAwsChunked("invalid chunk size"))?;
+                    self.state = match size {
+                        0 => DecoderState::Trailers,
+                        size => DecoderState::ChunkData(size),
+                    };
+                }
+                DecoderState::ChunkData(remaining) => {
+                    if self.buffer.is_empty() {
+                        break;
+                    }
+                    let len = remaining.min(self.buffer.len());
+                    output.extend_from_slice(&self.buffer.split_to(len));
+                    self.state = match remaining - len {
+                        0 => DecoderState::ChunkEnd,
+                        remaining => DecoderState::ChunkData(remaining),
+                    };
+                }
+                DecoderState::ChunkEnd => {
+                    if self.buffer.len() < CRLF.len() {
+                        break;
+                    }
+                    if !self.buffer.starts_with(CRLF) {
+                        return Err(ChecksumError::MalformedAwsChunked("chunk data is longer than its size"));
+                    }
+                    self.buffer.advance(CRLF.len());
+                    self.state = DecoderState::ChunkSize;
+                }
+                DecoderState::Trailers => {
+                    let Some(line) = self.next_line()? else { break };
+                    if line.is_empty() {
+                        self.state = DecoderState::Done;
+                        continue;
+                    }
+                    self.trailers_len += line.len();
+                    if self.trailers_len > MAX_TRAILERS_LEN {
+                        return Err(ChecksumError::MalformedAwsChunked("the trailers are too long"));
+                    }
+                    let (name, value) = parse_trailer(&line)?;
+                    self.trailers.append(name, value);
+                }
+                DecoderState::Done => {
+                    if !self.buffer.is_empty() {
+                        return Err(ChecksumError::MalformedAwsChunked("unexpected data after the trailers"));
+                    }
+                    break;
+                }
+            }
+        }
+        Ok(output.freeze())
+    }
+
+    /// Returns the trailers, if the whole body has been decoded.
+    fn finish(self) -> Result<HeaderMap, ChecksumError> {
+        match self.state {
+            DecoderState::Done => Ok(self.trailers),
+            // Trailers are optional: a body may end right after the final, empty chunk.
+            DecoderState::Trailers if self.buffer.is_empty() => Ok(self.trailers),
+            _ => Err(ChecksumError::MalformedAwsChunked(
+                "the body ended before its final chunk",
+            )),
+        }
+    }
+
+    /// Returns the next line, without its `CRLF`, if it has been received in full.
+    ///
+    /// Fails if the line is longer than [`MAX_LINE_LEN`].
+    fn next_line(&mut self) -> Result<Option<Bytes>, ChecksumError> {
+        match self.buffer.windows(CRLF.len()).position(|window| window == CRLF) {
+            Some(end) if end <= MAX_LINE_LEN => {
+                let line = self.buffer.split_to(end).freeze();
+                self.buffer.advance(CRLF.len());
+                Ok(Some(line))
+            }
+            // The line's `CR` may have been received without its `LF`.
+            None if self.buffer.len() <= MAX_LINE_LEN + 1 => Ok(None),
+            _ => Err(ChecksumError::MalformedAwsChunked("line is too long")),
+        }
+    }
+}
+
+fn parse_trailer(line: &[u8]) -> Result<(HeaderName, HeaderValue), ChecksumError> {
+    let separator = line
+        .iter()
+        .position(|b| *b == b':')
+        .ok_or(ChecksumError::MalformedAwsChunked("invalid trailer"))?;
+    let name = HeaderName::from_bytes(line[..separator].trim_ascii())
+        .map_err(|_| ChecksumError::MalformedAwsChunked("invalid trailer name"))?;
+    let value = HeaderValue::from_bytes(line[separator + 1..].trim_ascii())
+        .map_err(|_| ChecksumError::MalformedAwsChunked("invalid trailer value"))?;
+    Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_checksums::ChecksumAlgorithm;
+
+    const BODY: &[u8] = b"Hello world";
+    const AWS_CHUNKED_BODY: &[u8] =
+        b"5;chunk-signature=abc\r\nHello\r\n6\r\n world\r\n0\r\nx-amz-checksum-crc32:i9aeUg==\r\n\r\n";
+
+    fn crc32(data: &[u8]) -> Bytes {
+        let mut checksum = ChecksumAlgorithm::Crc32.into_impl();
+        checksum.update(data);
+        checksum.finalize()
+    }
+
+    async fn read(body: ChecksumValidatedBody<hyper::Body>) -> Result<Bytes, BoxError> {
+        hyper::body::to_bytes(body).await
+    }
+
+    #[test]
+    fn aws_chunked_body_is_decoded_byte_by_byte() {
+        let mut decoder = AwsChunkedDecoder::default();
+        let mut output = Vec::new();
+        for byte in AWS_CHUNKED_BODY.chunks(1) {
+            output.extend_from_slice(&decoder.decode(byte).unwrap());
+        }
+        let trailers = decoder.finish().unwrap();
+
+        assert_eq!(BODY, &output[..]);
+        assert_eq!("i9aeUg==", trailers["x-amz-checksum-crc32"]);
+    }
+
+    #[test]
+    fn truncated_aws_chunked_body_is_malformed() {
+        let mut decoder = AwsChunkedDecoder::default();
+        decoder.decode(&AWS_CHUNKED_BODY[..20]).unwrap();
+        assert_eq!(
+            ChecksumError::MalformedAwsChunked("the body ended before its final chunk"),
+            decoder.finish().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn invalid_chunk_size_is_malformed() {
+        let mut decoder = AwsChunkedDecoder::default();
+        assert!(decoder.decode(b"zz\r\nHello\r\n").is_err());
+    }
+
+    #[test]
+    fn chunk_size_line_without_end_is_malformed() {
+        let mut decoder = AwsChunkedDecoder::default();
+        decoder.decode(b"5;chunk-signature=").unwrap();
+        let line = vec![b'a'; MAX_LINE_LEN];
+        assert_eq!(
+            ChecksumError::MalformedAwsChunked("line is too long"),
+            decoder.decode(&line).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn too_many_trailers_are_malformed() {
+        let mut decoder = AwsChunkedDecoder::default();
+        decoder.decode(b"0\r\n").unwrap();
+        let error = (0..MAX_TRAILERS_LEN)
+            .map(|i| decoder.decode(format!("x-amz-meta-{i}:value\r\n").as_bytes()))
+            .find_map(Result::err);
+        assert_eq!(
+            Some(ChecksumError::MalformedAwsChunked("the trailers are too long")),
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn checksum_header_is_validated() {
+        let failure = ValidationFailure::default();
+        let checksum = (ChecksumAlgorithm::Crc32.into_impl(), Expected::Header(crc32(BODY)));
+        let body = ChecksumValidatedBody::new(hyper::Body::from(BODY), false, Some(checksum), failure.clone());
+
+        assert_eq!(BODY, read(body).await.unwrap());
+        assert_eq!(None, *failure.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn checksum_header_mismatch_fails() {
+        let failure = ValidationFailure::default();
+        let checksum = (ChecksumAlgorithm::Crc32.into_impl(), Expected::Header(crc32(b"other")));
+        let body = ChecksumValidatedBody::new(hyper::Body::from(BODY), false, Some(checksum), failure.clone());
+
+        read(body).await.unwrap_err();
+        assert!(matches!(
+            *failure.lock().unwrap(),
+            Some(ChecksumError::Mismatch {
+                header_name: "x-amz-checksum-crc32",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn checksum_trailer_is_validated() {
+        let failure = ValidationFailure::default();
+        let checksum = (ChecksumAlgorithm::Crc32.into_impl(), Expected::Trailer);
+        let body = ChecksumValidatedBody::new(
+            hyper::Body::from(AWS_CHUNKED_BODY),
+            true,
+            Some(checksum),
+            failure.clone(),
+        );
+
+        assert_eq!(BODY, read(body).await.unwrap());
+        assert_eq!(None, *failure.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn missing_checksum_trailer_fails() {
+        let failure = ValidationFailure::default();
+        let checksum = (ChecksumAlgorithm::Sha256.into_impl(), Expected::Trailer);
+        let body = ChecksumValidatedBody::new(
+            hyper::Body::from(AWS_CHUNKED_BODY),
+            true,
+            Some(checksum),
+            failure.clone(),
+        );
+
+        read(body).await.unwrap_err();
+        assert_eq!(
+            Some(ChecksumError::MissingTrailer("x-amz-checksum-sha256")),
+            *failure.lock().unwrap()
+        );
+    }
+}
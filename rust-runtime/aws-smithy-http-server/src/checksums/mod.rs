@@ -0,0 +1,111 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+//! Provides [`ChecksumPlugin`], which validates the [flexible checksums] sent with requests and calculates checksums
+//! for responses when the client asks for them.
+//!
+//! # Example
+//!
+//! ```
+//! # use aws_smithy_http_server::checksums::{ChecksumAlgorithm, ChecksumPlugin};
+//! # use aws_smithy_http_server::plugin::HttpPlugins;
+//! let checksums = ChecksumPlugin::new()
+//!     // Calculate SHA-256 checksums for responses, instead of the default CRC32.
+//!     .response_algorithm(ChecksumAlgorithm::Sha256);
+//! let http_plugins = HttpPlugins::new().push(checksums);
+//! ```
+//!
+//! When used together with the `compression` feature, register [`ChecksumPlugin`] before the compression plugin so
+//! that it runs first: checksums are calculated over the body as it is sent, not over its decompressed contents.
+//!
+//! # Requests
+//!
+//! A request's checksum is either sent in a header, such as `x-amz-checksum-crc32`, or in a trailer of an
+//! `aws-chunked` encoded body, as announced by the `x-amz-trailer` header. `aws-chunked` bodies are decoded before
+//! they reach the operation, and their `Content-Length` is set to the `x-amz-decoded-content-length`.
+//!
+//! The checksum is calculated as the operation reads the body. If it doesn't match, reading the body fails and the
+//! response is replaced with a `ValidationException`, rendered according to the service's protocol. Requests with
+//! a malformed checksum or an unsupported algorithm are rejected with a `ValidationException` before they reach the
+//! operation. The checksum covers the whole body, so it's only validated if the operation reads the body to the end.
+//!
+//! # Responses
+//!
+//! When a request has the `x-amz-checksum-mode: ENABLED` header, the checksum of the response body is returned in
+//! a header, provided that:
+//!
+//! - the response doesn't already have a checksum header,
+//! - the size of the response body is known.
+//!
+//! Streaming responses, such as event streams, are never checksummed.
+//!
+//! [flexible checksums]: https://docs.aws.amazon.com/AmazonS3/latest/userguide/checking-object-integrity.html
+
+mod body;
+mod plugin;
+mod service;
+
+pub use aws_smithy_checksums::ChecksumAlgorithm;
+pub use plugin::*;
+pub use service::*;
+
+use aws_smithy_checksums::http::ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER;
+use http::HeaderMap;
+
+const AWS_CHUNKED: &str = "aws-chunked";
+const CHECKSUM_HEADER_PREFIX: &str = "x-amz-checksum-";
+const CHECKSUM_MODE_HEADER: &str = "x-amz-checksum-mode";
+const DECODED_CONTENT_LENGTH_HEADER: &str = "x-amz-decoded-content-length";
+const TRAILER_HEADER: &str = "x-amz-trailer";
+
+/// Returns the supported algorithms, in the order in which their headers are looked up.
+fn algorithms() -> impl Iterator<Item = ChecksumAlgorithm> {
+    ALL_CHECKSUM_ALGORITHMS_IN_PRIORITY_ORDER
+        .iter()
+        .map(|name| name.parse().expect("only contains valid checksum algorithm names"))
+}
+
+/// Returns the algorithm whose checksum is sent in the header with the given name.
+fn algorithm_for_header(header_name: &str) -> Option<ChecksumAlgorithm> {
+    algorithms().find(|algorithm| algorithm.into_impl().header_name().eq_ignore_ascii_case(header_name))
+}
+
+/// Returns `true` if any checksum header is present, whether its algorithm is supported or not.
+fn has_checksum_header(headers: &HeaderMap) -> bool {
+    headers
+        .keys()
+        .any(|name| name.as_str().starts_with(CHECKSUM_HEADER_PREFIX) && name != CHECKSUM_MODE_HEADER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn algorithm_for_header_name() {
+        assert_eq!(
+            Some(ChecksumAlgorithm::Crc32),
+            algorithm_for_header("x-amz-checksum-crc32")
+        );
+        assert_eq!(
+            Some(ChecksumAlgorithm::XxHash3),
+            algorithm_for_header("X-Amz-Checksum-Xxhash3")
+        );
+        assert_eq!(None, algorithm_for_header("x-amz-checksum-md5"));
+        assert_eq!(None, algorithm_for_header("content-md5"));
+    }
+
+    #[test]
+    fn checksum_mode_is_not_a_checksum_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CHECKSUM_MODE_HEADER, HeaderValue::from_static("ENABLED"));
+        assert!(!has_checksum_header(&headers));
+        headers.insert("x-amz-checksum-unknown", HeaderValue::from_static("AAAA"));
+        assert!(has_checksum_header(&headers));
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use aws_smithy_checksums::ChecksumAlgorithm;
+
+use crate::operation::OperationShape;
+use crate::plugin::{HttpMarker, Plugin};
+use crate::service::ServiceShape;
+use crate::shape_id::ShapeId;
+
+use super::ChecksumService;
+
+/// A [`Plugin`] which applies [`ChecksumService`] to every operation, except the ones excluded with
+/// [`ChecksumPlugin::exclude_operation`].
+///
+/// See the [module](crate::checksums) documentation for more information.
+#[derive(Debug, Clone, Default)]
+pub struct ChecksumPlugin {
+    response_algorithm: ChecksumAlgorithm,
+    excluded_operations: Vec<ShapeId>,
+}
+
+impl ChecksumPlugin {
+    /// Creates a new [`ChecksumPlugin`] which calculates CRC32 checksums for responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the algorithm used to calculate response checksums.
+    pub fn response_algorithm(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.response_algorithm = algorithm;
+        self
+    }
+
+    /// Opts the operation with the given [`ShapeId`] out of checksum validation and calculation.
+    pub fn exclude_operation(mut self, operation: ShapeId) -> Self {
+        self.excluded_operations.push(operation);
+        self
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for ChecksumPlugin
+where
+    Ser: ServiceShape,
+    Op: OperationShape,
+{
+    type Output = ChecksumService<T, Ser::Protocol>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        let service = ChecksumService::new(inner).response_algorithm(self.response_algorithm);
+        if self.excluded_operations.contains(&Op::ID) {
+            service.disabled()
+        } else {
+            service
+        }
+    }
+}
+
+impl HttpMarker for ChecksumPlugin {}
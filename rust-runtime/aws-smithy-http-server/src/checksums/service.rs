@@ -0,0 +1,611 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use aws_smithy_checksums::{http::HttpChecksum, ChecksumAlgorithm};
+use aws_smithy_types::base64;
+use bytes::BytesMut;
+use futures_util::ready;
+use http::{header, response::Parts, HeaderValue, Request, Response};
+use http_body::Body as _;
+use tower::Service;
+
+use crate::body::{boxed, Body, BoxBody};
+use crate::response::IntoResponse;
+use crate::runtime_error::ValidationException;
+
+use super::body::{ChecksumValidatedBody, Expected, ValidationFailure};
+use super::{
+    algorithm_for_header, algorithms, has_checksum_header, AWS_CHUNKED, CHECKSUM_HEADER_PREFIX, CHECKSUM_MODE_HEADER,
+    DECODED_CONTENT_LENGTH_HEADER, TRAILER_HEADER,
+};
+
+/// A middleware [`Service`] responsible for:
+///   - Validating the checksum of the request body, sent in a header or in an `aws-chunked` trailer.
+///   - Calculating the checksum of the response body, when the request has the `x-amz-checksum-mode: ENABLED` header.
+///
+/// Validation failures are returned as a `ValidationException` of the protocol `P`.
+///
+/// See the [module](crate::checksums) documentation for more information.
+pub struct ChecksumService<S, P> {
+    inner: S,
+    response_algorithm: ChecksumAlgorithm,
+    enabled: bool,
+    _protocol: PhantomData<fn(P)>,
+}
+
+impl<S, P> ChecksumService<S, P> {
+    /// Constructs a new [`ChecksumService`] which calculates CRC32 checksums for responses.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            response_algorithm: ChecksumAlgorithm::default(),
+            enabled: true,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Sets the algorithm used to calculate response checksums.
+    pub fn response_algorithm(self, response_algorithm: ChecksumAlgorithm) -> Self {
+        Self {
+            response_algorithm,
+            ..self
+        }
+    }
+
+    /// Passes requests and responses through untouched.
+    pub(crate) fn disabled(self) -> Self {
+        Self { enabled: false, ..self }
+    }
+}
+
+impl<S, P> Clone for ChecksumService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            response_algorithm: self.response_algorithm,
+            enabled: self.enabled,
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<S, P> std::fmt::Debug for ChecksumService<S, P>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChecksumService")
+            .field("inner", &self.inner)
+            .field("response_algorithm", &self.response_algorithm)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl<S, P> Service<Request<Body>> for ChecksumService<S, P>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>>,
+    ValidationException: IntoResponse<P>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = ChecksumFuture<S::Future, P>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if !self.enabled {
+            return ChecksumFuture::inner(self.inner.call(request), ValidationFailure::default(), None);
+        }
+
+        let response_algorithm = request
+            .headers()
+            .get(CHECKSUM_MODE_HEADER)
+            .is_some_and(|mode| mode.as_bytes().eq_ignore_ascii_case(b"enabled"))
+            .then_some(self.response_algorithm);
+        let failure = ValidationFailure::default();
+        let request = match validate_request(request, &failure) {
+            Ok(request) => request,
+            Err(message) => {
+                tracing::debug!(%message, "rejecting request with invalid checksum headers");
+                return ChecksumFuture::rejected(ValidationException::new(message).into_response());
+            }
+        };
+
+        ChecksumFuture::inner(self.inner.call(request), failure, response_algorithm)
+    }
+}
+
+/// Wraps the request body in a [`ChecksumValidatedBody`] if it has a checksum or is `aws-chunked` encoded.
+///
+/// Returns a message describing the problem if the checksum headers are invalid.
+fn validate_request(mut request: Request<Body>, failure: &ValidationFailure) -> Result<Request<Body>, String> {
+    let headers = request.headers();
+    let aws_chunked = headers
+        .get_all(header::CONTENT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|encoding| encoding.trim().eq_ignore_ascii_case(AWS_CHUNKED));
+    let trailer = headers
+        .get_all(TRAILER_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find(|name| name.to_ascii_lowercase().starts_with(CHECKSUM_HEADER_PREFIX));
+
+    let header_checksum = algorithms().find_map(|algorithm| {
+        let checksum = algorithm.into_impl();
+        headers.get(checksum.header_name()).map(|value| (checksum, value))
+    });
+    let checksum = match (header_checksum, trailer) {
+        (Some((checksum, value)), _) => {
+            let expected = value
+                .to_str()
+                .ok()
+                .and_then(|value| base64::decode(value.trim()).ok())
+                .ok_or_else(|| format!("invalid `{}` checksum header", checksum.header_name()))?;
+            Some((checksum, Expected::Header(expected.into())))
+        }
+        (None, Some(trailer)) => {
+            let algorithm =
+                algorithm_for_header(trailer).ok_or_else(|| format!("unsupported trailing checksum `{trailer}`"))?;
+            if !aws_chunked {
+                return Err(format!(
+                    "the trailing checksum `{trailer}` requires an `{AWS_CHUNKED}` encoded body"
+                ));
+            }
+            Some((algorithm.into_impl(), Expected::Trailer))
+        }
+        (None, None) if has_checksum_header(headers) => return Err("unsupported checksum algorithm".to_owned()),
+        (None, None) => None,
+    };
+
+    if checksum.is_none() && !aws_chunked {
+        return Ok(request);
+    }
+    if aws_chunked {
+        remove_aws_chunked_encoding(request.headers_mut());
+    }
+
+    let failure = failure.clone();
+    Ok(request.map(|body| {
+        let mut body = ChecksumValidatedBody::new(body, aws_chunked, checksum, failure);
+        Body::wrap_stream(futures_util::stream::poll_fn(move |cx| {
+            Pin::new(&mut body).poll_data(cx)
+        }))
+    }))
+}
+
+/// The body handed to the operation is no longer `aws-chunked` encoded, and has its decoded length.
+fn remove_aws_chunked_encoding(headers: &mut http::HeaderMap) {
+    let encodings = headers
+        .get_all(header::CONTENT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|encoding| !encoding.is_empty() && !encoding.eq_ignore_ascii_case(AWS_CHUNKED))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match HeaderValue::from_str(&encodings) {
+        Ok(encodings) if !encodings.is_empty() => {
+            headers.insert(header::CONTENT_ENCODING, encodings);
+        }
+        _ => {
+            headers.remove(header::CONTENT_ENCODING);
+        }
+    }
+
+    match headers.remove(DECODED_CONTENT_LENGTH_HEADER) {
+        Some(decoded_content_length) => headers.insert(header::CONTENT_LENGTH, decoded_content_length),
+        None => headers.remove(header::CONTENT_LENGTH),
+    };
+}
+
+pin_project_lite::pin_project! {
+    #[project = InnerProj]
+    enum Inner<Fut> {
+        Inner {
+            #[pin]
+            future: Fut,
+            failure: ValidationFailure,
+            response_algorithm: Option<ChecksumAlgorithm>,
+        },
+        Buffering {
+            parts: Option<Parts>,
+            #[pin]
+            body: BoxBody,
+            buffer: BytesMut,
+            checksum: Option<Box<dyn HttpChecksum>>,
+        },
+        Rejected {
+            response: Option<Response<BoxBody>>,
+        },
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The [`Service::Future`] of [`ChecksumService`].
+    pub struct ChecksumFuture<Fut, P> {
+        #[pin]
+        inner: Inner<Fut>,
+        _protocol: PhantomData<fn(P)>,
+    }
+}
+
+impl<Fut, P> std::fmt::Debug for ChecksumFuture<Fut, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChecksumFuture").finish_non_exhaustive()
+    }
+}
+
+impl<Fut, P> ChecksumFuture<Fut, P> {
+    fn inner(future: Fut, failure: ValidationFailure, response_algorithm: Option<ChecksumAlgorithm>) -> Self {
+        Self {
+            inner: Inner::Inner {
+                future,
+                failure,
+                response_algorithm,
+            },
+            _protocol: PhantomData,
+        }
+    }
+
+    fn rejected(response: Response<BoxBody>) -> Self {
+        Self {
+            inner: Inner::Rejected {
+                response: Some(response),
+            },
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<Fut, P, E> Future for ChecksumFuture<Fut, P>
+where
+    Fut: Future<Output = Result<Response<BoxBody>, E>>,
+    ValidationException: IntoResponse<P>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match this.inner.as_mut().project() {
+                InnerProj::Inner {
+                    future,
+                    failure,
+                    response_algorithm,
+                } => {
+                    let response = ready!(future.poll(cx))?;
+                    // A checksum failure found while the operation read the body replaces its response, whether or not
+                    // the operation failed because of it. A body the operation didn't read to the end is never
+                    // validated, since its checksum covers all of its data.
+                    if let Some(err) = failure.lock().unwrap().take() {
+                        return Poll::Ready(Ok(ValidationException::new(err.to_string()).into_response()));
+                    }
+
+                    // Only responses of a known size are checksummed: streaming responses are left untouched so that
+                    // their data reaches the client as soon as it's available.
+                    let checksum = match response_algorithm.take() {
+                        Some(algorithm)
+                            if !has_checksum_header(response.headers())
+                                && response.body().size_hint().exact().is_some() =>
+                        {
+                            algorithm.into_impl()
+                        }
+                        _ => return Poll::Ready(Ok(response)),
+                    };
+                    let (parts, body) = response.into_parts();
+                    this.inner.set(Inner::Buffering {
+                        parts: Some(parts),
+                        body,
+                        buffer: BytesMut::new(),
+                        checksum: Some(checksum),
+                    });
+                }
+                InnerProj::Buffering {
+                    parts,
+                    mut body,
+                    buffer,
+                    checksum,
+                } => {
+                    let parts = loop {
+                        match ready!(body.as_mut().poll_data(cx)) {
+                            Some(Ok(data)) => buffer.extend_from_slice(&data),
+                            Some(Err(err)) => {
+                                // Pass the data read so far through, followed by the error.
+                                let parts = parts.take().expect("futures cannot be polled after completion");
+                                let stream = futures_util::stream::iter([Ok(buffer.split().freeze()), Err(err)]);
+                                return Poll::Ready(Ok(Response::from_parts(parts, boxed(Body::wrap_stream(stream)))));
+                            }
+                            None => break parts.take().expect("futures cannot be polled after completion"),
+                        }
+                    };
+                    let mut checksum = checksum.take().expect("futures cannot be polled after completion");
+                    let data = buffer.split().freeze();
+                    checksum.update(&data);
+
+                    let mut response = Response::from_parts(parts, boxed(Body::from(data)));
+                    response
+                        .headers_mut()
+                        .insert(checksum.header_name(), checksum.header_value());
+                    return Poll::Ready(Ok(response));
+                }
+                InnerProj::Rejected { response } => {
+                    return Poll::Ready(Ok(response.take().expect("futures cannot be polled after completion")))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::rest_json_1::RestJson1;
+    use crate::protocol::rest_xml::RestXml;
+    use crate::protocol::rpc_v2_cbor::RpcV2Cbor;
+    use http::StatusCode;
+    use std::convert::Infallible;
+    use tower::{service_fn, ServiceExt};
+
+    const BODY: &str = "Hello world";
+    // The CRC32 checksum of `BODY`.
+    const CRC32: &str = "i9aeUg==";
+    const AWS_CHUNKED_BODY: &str = "b\r\nHello world\r\n0\r\nx-amz-checksum-crc32:i9aeUg==\r\n\r\n";
+
+    /// A service that echoes the request body back in the response, with the request headers it received.
+    fn echo() -> impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible> + Clone {
+        service_fn(|request: Request<Body>| async move {
+            let (parts, body) = request.into_parts();
+            let response = match hyper::body::to_bytes(body).await {
+                Ok(body) => Response::new(boxed(Body::from(body))),
+                Err(_) => Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .body(crate::body::empty())
+                    .unwrap(),
+            };
+            let (mut response_parts, body) = response.into_parts();
+            for (name, value) in &parts.headers {
+                let name = format!("x-request-{name}");
+                response_parts
+                    .headers
+                    .insert(header::HeaderName::from_bytes(name.as_bytes()).unwrap(), value.clone());
+            }
+            Ok::<_, Infallible>(Response::from_parts(response_parts, body))
+        })
+    }
+
+    fn service(
+    ) -> ChecksumService<impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible>, RestJson1> {
+        ChecksumService::new(echo())
+    }
+
+    async fn body_string(response: Response<BoxBody>) -> String {
+        String::from_utf8(hyper::body::to_bytes(response.into_body()).await.unwrap().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_checksum_header() {
+        let request = Request::builder()
+            .header("x-amz-checksum-crc32", CRC32)
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(BODY, body_string(response).await);
+    }
+
+    #[tokio::test]
+    async fn mismatched_checksum_header_is_a_validation_exception() {
+        let request = Request::builder()
+            .header("x-amz-checksum-crc32", "AAAAAA==")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("ValidationException", response.headers()["x-amzn-errortype"]);
+        let body = body_string(response).await;
+        assert!(
+            body.starts_with(r#"{"__type":"ValidationException","message":"checksum mismatch"#),
+            "{body}"
+        );
+    }
+
+    #[tokio::test]
+    async fn validation_exception_is_rendered_in_the_protocol_error_format() {
+        let request = || {
+            Request::builder()
+                .header("x-amz-checksum-crc32", "AAAAAA==")
+                .body(Body::from(BODY))
+                .unwrap()
+        };
+
+        let response = ChecksumService::<_, RestXml>::new(echo())
+            .oneshot(request())
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("application/xml", response.headers()[header::CONTENT_TYPE]);
+        let body = body_string(response).await;
+        assert!(
+            body.starts_with(
+                "<ErrorResponse><Error><Type>Sender</Type><Code>ValidationException</Code>\
+                 <Message>checksum mismatch"
+            ),
+            "{body}"
+        );
+
+        let response = ChecksumService::<_, RpcV2Cbor>::new(echo())
+            .oneshot(request())
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("application/cbor", response.headers()[header::CONTENT_TYPE]);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut decoder = aws_smithy_cbor::Decoder::new(&body);
+        assert_eq!(Some(2), decoder.map().unwrap());
+        assert_eq!("__type", decoder.str().unwrap());
+        assert_eq!("ValidationException", decoder.str().unwrap());
+        assert_eq!("message", decoder.str().unwrap());
+        assert!(decoder.str().unwrap().starts_with("checksum mismatch"));
+    }
+
+    #[tokio::test]
+    async fn malformed_checksum_header_is_rejected() {
+        let request = Request::builder()
+            .header("x-amz-checksum-sha256", "not base64!")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("ValidationException", response.headers()["x-amzn-errortype"]);
+    }
+
+    #[tokio::test]
+    async fn unsupported_checksum_algorithm_is_rejected() {
+        let request = Request::builder()
+            .header("x-amz-checksum-crc16", "AAA=")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[tokio::test]
+    async fn aws_chunked_body_with_trailing_checksum() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "aws-chunked, gzip")
+            .header(header::CONTENT_LENGTH, AWS_CHUNKED_BODY.len())
+            .header(DECODED_CONTENT_LENGTH_HEADER, BODY.len())
+            .header(TRAILER_HEADER, "x-amz-checksum-crc32")
+            .body(Body::from(AWS_CHUNKED_BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("gzip", response.headers()["x-request-content-encoding"]);
+        assert_eq!("11", response.headers()["x-request-content-length"]);
+        assert_eq!(BODY, body_string(response).await);
+    }
+
+    #[tokio::test]
+    async fn mismatched_trailing_checksum_is_a_validation_exception() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "aws-chunked")
+            .header(TRAILER_HEADER, "x-amz-checksum-crc32")
+            .body(Body::from(AWS_CHUNKED_BODY.replace(CRC32, "AAAAAA==")))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!("ValidationException", response.headers()["x-amzn-errortype"]);
+    }
+
+    #[tokio::test]
+    async fn trailing_checksum_requires_aws_chunked_body() {
+        let request = Request::builder()
+            .header(TRAILER_HEADER, "x-amz-checksum-crc32")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    }
+
+    #[tokio::test]
+    async fn response_checksum_is_calculated_when_enabled() {
+        let request = Request::builder()
+            .header(CHECKSUM_MODE_HEADER, "ENABLED")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().oneshot(request).await.unwrap();
+
+        assert_eq!(CRC32, response.headers()["x-amz-checksum-crc32"]);
+        assert_eq!(BODY, body_string(response).await);
+    }
+
+    #[tokio::test]
+    async fn response_checksum_uses_configured_algorithm() {
+        let request = Request::builder()
+            .header(CHECKSUM_MODE_HEADER, "enabled")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service()
+            .response_algorithm(ChecksumAlgorithm::Sha256)
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert!(response.headers().contains_key("x-amz-checksum-sha256"));
+        assert!(!response.headers().contains_key("x-amz-checksum-crc32"));
+    }
+
+    #[tokio::test]
+    async fn response_checksum_is_not_calculated_unless_enabled() {
+        let response = service().oneshot(Request::new(Body::from(BODY))).await.unwrap();
+
+        assert!(!has_checksum_header(response.headers()));
+    }
+
+    #[tokio::test]
+    async fn streaming_response_is_not_checksummed() {
+        let svc = ChecksumService::<_, RestJson1>::new(service_fn(|_request: Request<Body>| async move {
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move { sender.send_data(BODY.into()).await });
+            Ok::<_, Infallible>(Response::new(boxed(body)))
+        }));
+        let request = Request::builder()
+            .header(CHECKSUM_MODE_HEADER, "ENABLED")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = svc.oneshot(request).await.unwrap();
+
+        assert!(!has_checksum_header(response.headers()));
+    }
+
+    #[tokio::test]
+    async fn disabled_service_passes_requests_through() {
+        let request = Request::builder()
+            .header("x-amz-checksum-crc32", "AAAAAA==")
+            .header(CHECKSUM_MODE_HEADER, "ENABLED")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = service().disabled().oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert!(!response.headers().contains_key("x-amz-checksum-crc32"));
+    }
+}
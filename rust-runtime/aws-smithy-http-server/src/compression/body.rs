@@ -0,0 +1,86 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use aws_smithy_compression::CompressStream;
+use bytes::Bytes;
+use futures_util::ready;
+use http::HeaderMap;
+use http_body::{Body, SizeHint};
+use pin_project_lite::pin_project;
+
+use crate::body::BoxBody;
+use crate::error::Error;
+
+pin_project! {
+    /// A response body that is compressed as it is read.
+    ///
+    /// Every chunk of the inner body is passed to the same encoder, so the output is a single compressed stream that
+    /// every client can decode. The encoder is flushed after every chunk, and the stream is finished once the inner
+    /// body ends.
+    pub(super) struct CompressedResponseBody {
+        #[pin]
+        inner: BoxBody,
+        stream: Box<dyn CompressStream>,
+        done: bool,
+    }
+}
+
+impl CompressedResponseBody {
+    pub(super) fn new(inner: BoxBody, stream: Box<dyn CompressStream>) -> Self {
+        Self {
+            inner,
+            stream,
+            done: false,
+        }
+    }
+}
+
+impl Body for CompressedResponseBody {
+    type Data = Bytes;
+    type Error = Error;
+
+    fn poll_data(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+        loop {
+            let mut compressed = Vec::new();
+            match ready!(this.inner.as_mut().poll_data(cx)?) {
+                // Flushing the encoder for an empty chunk would only add an empty block to the output.
+                Some(data) if data.is_empty() => continue,
+                Some(data) => this.stream.compress_bytes(&data, &mut compressed).map_err(Error::new)?,
+                None => {
+                    *this.done = true;
+                    this.stream.finish(&mut compressed).map_err(Error::new)?;
+                    if compressed.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                }
+            }
+            if !compressed.is_empty() {
+                return Poll::Ready(Some(Ok(compressed.into())));
+            }
+        }
+    }
+
+    fn poll_trailers(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().inner.poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        // The compressed size is only known once the whole body has been compressed.
+        SizeHint::default()
+    }
+}
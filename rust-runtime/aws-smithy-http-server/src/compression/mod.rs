@@ -0,0 +1,188 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+//! Provides [`CompressionPlugin`], which decompresses request bodies sent with a `Content-Encoding` header and
+//! compresses response bodies according to the `Accept-Encoding` header, before a request is deserialized and after
+//! a response is serialized.
+//!
+//! `gzip` is always supported. `zstd` and `br` are supported when the `zstd` and `brotli` features of
+//! `aws-smithy-compression` are enabled.
+//!
+//! # Example
+//!
+//! ```
+//! # use aws_smithy_http_server::compression::CompressionPlugin;
+//! # use aws_smithy_http_server::plugin::HttpPlugins;
+//! # use aws_smithy_http_server::shape_id::ShapeId;
+//! # use aws_smithy_compression::CompressionOptions;
+//! # struct StreamEvents;
+//! # impl StreamEvents { const ID: ShapeId = ShapeId::new("namespace#StreamEvents", "namespace", "StreamEvents"); }
+//! let options = CompressionOptions::default()
+//!     // Don't compress responses smaller than 1 KiB.
+//!     .with_min_compression_size_bytes(1024)
+//!     .unwrap();
+//! let compression = CompressionPlugin::new()
+//!     .options(options)
+//!     // Leave the bodies of `StreamEvents` untouched.
+//!     .exclude_operation(StreamEvents::ID);
+//! let http_plugins = HttpPlugins::new().push(compression);
+//! ```
+//!
+//! # Requests
+//!
+//! A request with a `Content-Encoding` of a supported algorithm has its body decompressed as it is read, and the
+//! `Content-Encoding` and `Content-Length` headers are removed. A request with an unsupported `Content-Encoding` is
+//! rejected with the protocol's `UnsupportedMediaTypeException`, a `415 Unsupported Media Type` response listing the
+//! supported encodings in `Accept-Encoding`.
+//!
+//! Request bodies may decompress to at most [`CompressionPlugin::max_decompressed_len`] bytes. Reading a larger body
+//! fails, and the request is rejected with the protocol's error for a body that can't be read. Trailers sent with a
+//! compressed body are not passed on to the operation.
+//!
+//! # Responses
+//!
+//! A response is compressed with the algorithm the client prefers, according to the quality values in its
+//! `Accept-Encoding` header, provided that:
+//!
+//! - compression is enabled in the [`CompressionOptions`],
+//! - the response doesn't already have a `Content-Encoding`,
+//! - the size of the response body is known and at least [`CompressionOptions::min_compression_size_bytes`].
+//!
+//! Streaming responses, such as event streams, are never compressed. When compression is enabled, every response has
+//! a `Vary: accept-encoding` header, whether or not it is compressed.
+
+mod body;
+mod plugin;
+mod service;
+
+pub use aws_smithy_compression::CompressionOptions;
+pub use plugin::*;
+pub use service::*;
+
+use aws_smithy_compression::{CompressionAlgorithm, BROTLI_NAME, GZIP_NAME, ZSTD_NAME};
+use http::HeaderMap;
+
+const IDENTITY: &str = "identity";
+
+/// Returns the names of the algorithms supported by this build, in order of preference.
+fn supported_encodings() -> impl Iterator<Item = &'static str> {
+    [GZIP_NAME, ZSTD_NAME, BROTLI_NAME]
+        .into_iter()
+        .filter(|name| name.parse::<CompressionAlgorithm>().is_ok())
+}
+
+/// Picks the algorithm to compress a response with, based on the `Accept-Encoding` request header.
+///
+/// Returns `None` when the client did not ask for compression, or only accepts algorithms that are not supported.
+fn preferred_algorithm(headers: &HeaderMap) -> Option<CompressionAlgorithm> {
+    let codings: Vec<(&str, f32)> = headers
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|coding| {
+            let mut params = coding.split(';');
+            let name = params.next().unwrap_or_default().trim();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (name, quality)
+        })
+        .collect();
+
+    let mut preferred: Option<(CompressionAlgorithm, f32)> = None;
+    for &(name, quality) in &codings {
+        if quality <= 0.0 {
+            continue;
+        }
+
+        let algorithm = if name == "*" {
+            // The wildcard only stands for the encodings the client didn't list, including those it refused with
+            // `q=0`.
+            let unlisted = supported_encodings()
+                .find(|encoding| !codings.iter().any(|(name, _)| name.eq_ignore_ascii_case(encoding)));
+            match unlisted {
+                Some(encoding) => encoding.parse().expect("supported encodings can be parsed"),
+                None => continue,
+            }
+        } else {
+            match name.parse::<CompressionAlgorithm>() {
+                Ok(algorithm) => algorithm,
+                Err(_) => continue,
+            }
+        };
+        // When qualities are equal, the first algorithm listed by the client wins.
+        if preferred.map_or(true, |(_, preferred_quality)| quality > preferred_quality) {
+            preferred = Some((algorithm, quality));
+        }
+    }
+
+    preferred.map(|(algorithm, _)| algorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn accept_encoding(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_ENCODING, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn no_accept_encoding() {
+        assert_eq!(None, preferred_algorithm(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn accept_encoding_gzip() {
+        assert_eq!(
+            Some(CompressionAlgorithm::Gzip),
+            preferred_algorithm(&accept_encoding("deflate, gzip"))
+        );
+        assert_eq!(
+            Some(CompressionAlgorithm::Gzip),
+            preferred_algorithm(&accept_encoding("*"))
+        );
+    }
+
+    #[test]
+    fn accept_encoding_unsupported_or_refused() {
+        assert_eq!(None, preferred_algorithm(&accept_encoding("deflate, identity")));
+        assert_eq!(None, preferred_algorithm(&accept_encoding("gzip;q=0")));
+        assert_eq!(None, preferred_algorithm(&accept_encoding("gzip; q=0.0, compress")));
+    }
+
+    #[test]
+    fn accept_encoding_wildcard_excludes_refused_encodings() {
+        let expected = supported_encodings()
+            .nth(1)
+            .map(|encoding| encoding.parse::<CompressionAlgorithm>().unwrap());
+        assert_eq!(expected, preferred_algorithm(&accept_encoding("gzip;q=0, *")));
+        assert_eq!(expected, preferred_algorithm(&accept_encoding("*;q=0.5, gzip;q=0")));
+        assert_eq!(
+            Some(CompressionAlgorithm::Gzip),
+            preferred_algorithm(&accept_encoding("gzip;q=0.5, *;q=0.2"))
+        );
+    }
+
+    #[test]
+    fn accept_encoding_respects_quality() {
+        assert_eq!(
+            Some(CompressionAlgorithm::Gzip),
+            preferred_algorithm(&accept_encoding("unknown;q=1.0, gzip;q=0.5"))
+        );
+    }
+
+    #[test]
+    fn gzip_is_always_supported() {
+        assert_eq!(Some(GZIP_NAME), supported_encodings().next());
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use aws_smithy_compression::CompressionOptions;
+
+use crate::operation::OperationShape;
+use crate::plugin::{HttpMarker, Plugin};
+use crate::service::ServiceShape;
+use crate::shape_id::ShapeId;
+
+use super::service::DEFAULT_MAX_DECOMPRESSED_LEN;
+use super::CompressionService;
+
+/// A [`Plugin`] which applies [`CompressionService`] to every operation, except the ones excluded with
+/// [`CompressionPlugin::exclude_operation`].
+///
+/// See the [module](crate::compression) documentation for more information.
+#[derive(Debug, Clone)]
+pub struct CompressionPlugin {
+    options: CompressionOptions,
+    max_decompressed_len: usize,
+    excluded_operations: Vec<ShapeId>,
+}
+
+impl Default for CompressionPlugin {
+    fn default() -> Self {
+        Self {
+            options: CompressionOptions::default(),
+            max_decompressed_len: DEFAULT_MAX_DECOMPRESSED_LEN,
+            excluded_operations: Vec::new(),
+        }
+    }
+}
+
+impl CompressionPlugin {
+    /// Creates a new [`CompressionPlugin`] with the default [`CompressionOptions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`CompressionOptions`] used to compress responses.
+    ///
+    /// If compression is disabled in the options, request bodies are still decompressed.
+    pub fn options(mut self, options: CompressionOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the maximum length, in bytes, of a decompressed request body. Defaults to 16 MiB.
+    ///
+    /// Requests whose body decompresses to more than this are rejected, which protects the service from small request
+    /// bodies that expand into a very large amount of data.
+    pub fn max_decompressed_len(mut self, max_decompressed_len: usize) -> Self {
+        self.max_decompressed_len = max_decompressed_len;
+        self
+    }
+
+    /// Opts the operation with the given [`ShapeId`] out of request decompression and response compression.
+    pub fn exclude_operation(mut self, operation: ShapeId) -> Self {
+        self.excluded_operations.push(operation);
+        self
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for CompressionPlugin
+where
+    Ser: ServiceShape,
+    Op: OperationShape,
+{
+    type Output = CompressionService<T, Ser::Protocol>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        let service = CompressionService::new(inner)
+            .options(self.options.clone())
+            .max_decompressed_len(self.max_decompressed_len);
+        if self.excluded_operations.contains(&Op::ID) {
+            service.disabled()
+        } else {
+            service
+        }
+    }
+}
+
+impl HttpMarker for CompressionPlugin {}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use http::{header, Request, Response};
+    use tower::{service_fn, ServiceExt};
+
+    use crate::body::{boxed, Body};
+    use crate::operation::OperationShape;
+    use crate::plugin::Plugin;
+    use crate::protocol::rest_json_1::RestJson1;
+    use crate::service::ServiceShape;
+    use crate::shape_id::ShapeId;
+
+    use super::CompressionPlugin;
+
+    struct TestService;
+
+    impl ServiceShape for TestService {
+        const ID: ShapeId = ShapeId::new("test#TestService", "test", "TestService");
+        const VERSION: Option<&'static str> = None;
+        type Protocol = RestJson1;
+        type Operations = ();
+    }
+
+    struct OperationA;
+    struct OperationB;
+
+    impl OperationShape for OperationA {
+        const ID: ShapeId = ShapeId::new("test#OperationA", "test", "OperationA");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    impl OperationShape for OperationB {
+        const ID: ShapeId = ShapeId::new("test#OperationB", "test", "OperationB");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    #[tokio::test]
+    async fn excluded_operations_are_not_compressed() {
+        let plugin = CompressionPlugin::new().exclude_operation(OperationB::ID);
+        let service = service_fn(|_request: Request<Body>| async {
+            Ok::<_, Infallible>(Response::new(boxed(Body::from(vec![b'a'; 20_000]))))
+        });
+        let request = || {
+            Request::builder()
+                .header(header::ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = Plugin::<TestService, OperationA, _>::apply(&plugin, service)
+            .oneshot(request())
+            .await
+            .unwrap();
+        assert_eq!("gzip", response.headers()[header::CONTENT_ENCODING]);
+
+        let response = Plugin::<TestService, OperationB, _>::apply(&plugin, service)
+            .oneshot(request())
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+}
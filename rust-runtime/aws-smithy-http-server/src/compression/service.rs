@@ -0,0 +1,619 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use aws_smithy_compression::body::decompress::DecompressedBody;
+use aws_smithy_compression::{CompressionAlgorithm, CompressionOptions};
+use futures_util::ready;
+use http::{header, HeaderMap, HeaderValue, Request, Response};
+use http_body::Body as _;
+use tower::Service;
+
+use crate::body::{Body, BoxBody};
+use crate::response::IntoResponse;
+use crate::runtime_error::UnsupportedMediaTypeException;
+
+use super::body::CompressedResponseBody;
+use super::{preferred_algorithm, supported_encodings, IDENTITY};
+
+/// The default maximum length of a decompressed request body: 16 MiB.
+pub(crate) const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 16 * 1024 * 1024;
+
+/// A middleware [`Service`] responsible for:
+///   - Decompressing the request body, according to the `Content-Encoding` header.
+///   - Compressing the response body, according to the `Accept-Encoding` header.
+///
+/// Requests with an unsupported `Content-Encoding` get an `UnsupportedMediaTypeException` of the protocol `P`.
+///
+/// See the [module](crate::compression) documentation for more information.
+pub struct CompressionService<S, P> {
+    inner: S,
+    options: CompressionOptions,
+    max_decompressed_len: usize,
+    enabled: bool,
+    _protocol: PhantomData<fn(P)>,
+}
+
+impl<S, P> CompressionService<S, P> {
+    /// Constructs a new [`CompressionService`] with the default [`CompressionOptions`].
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            options: CompressionOptions::default(),
+            max_decompressed_len: DEFAULT_MAX_DECOMPRESSED_LEN,
+            enabled: true,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Sets the [`CompressionOptions`] used to compress responses.
+    pub fn options(self, options: CompressionOptions) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Sets the maximum length, in bytes, of a decompressed request body. Defaults to 16 MiB.
+    ///
+    /// Reading a request body that decompresses to more than this fails, so the request is rejected like any other
+    /// request whose body can't be read.
+    pub fn max_decompressed_len(self, max_decompressed_len: usize) -> Self {
+        Self {
+            max_decompressed_len,
+            ..self
+        }
+    }
+
+    /// Passes requests and responses through untouched.
+    pub(crate) fn disabled(self) -> Self {
+        Self { enabled: false, ..self }
+    }
+}
+
+impl<S, P> Clone for CompressionService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            options: self.options.clone(),
+            max_decompressed_len: self.max_decompressed_len,
+            enabled: self.enabled,
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<S, P> std::fmt::Debug for CompressionService<S, P>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionService")
+            .field("inner", &self.inner)
+            .field("options", &self.options)
+            .field("max_decompressed_len", &self.max_decompressed_len)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl<S, P> Service<Request<Body>> for CompressionService<S, P>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>>,
+    UnsupportedMediaTypeException: IntoResponse<P>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = CompressionFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if !self.enabled {
+            return CompressionFuture::inner(self.inner.call(request), None);
+        }
+
+        let request = match decompress_request(request, self.max_decompressed_len) {
+            Ok(request) => request,
+            Err(UnsupportedContentEncoding) => {
+                let mut response = UnsupportedMediaTypeException.into_response();
+                response
+                    .headers_mut()
+                    .insert(header::ACCEPT_ENCODING, accept_encoding());
+                return CompressionFuture::rejected(response);
+            }
+        };
+        let negotiation = self.options.is_enabled().then(|| Negotiation {
+            algorithm: preferred_algorithm(request.headers()),
+            options: self.options.clone(),
+        });
+
+        CompressionFuture::inner(self.inner.call(request), negotiation)
+    }
+}
+
+/// The `Content-Encoding` of a request is not supported.
+struct UnsupportedContentEncoding;
+
+/// Decompresses the request body if it has a supported `Content-Encoding`.
+///
+/// The body is decompressed as the operation reads it. If decompression fails, reading the body fails too.
+fn decompress_request(
+    mut request: Request<Body>,
+    max_decompressed_len: usize,
+) -> Result<Request<Body>, UnsupportedContentEncoding> {
+    let algorithm = match request.headers().get(header::CONTENT_ENCODING) {
+        None => return Ok(request),
+        Some(value) => {
+            let encoding = value.to_str().unwrap_or_default().trim();
+            if encoding.eq_ignore_ascii_case(IDENTITY) {
+                return Ok(request);
+            }
+            match encoding.parse::<CompressionAlgorithm>() {
+                Ok(algorithm) => algorithm,
+                Err(_) => {
+                    tracing::debug!(content_encoding = ?value, "rejecting request with unsupported content encoding");
+                    return Err(UnsupportedContentEncoding);
+                }
+            }
+        }
+    };
+
+    // The body handed to the operation is no longer encoded, and its length is unknown.
+    let headers = request.headers_mut();
+    headers.remove(header::CONTENT_ENCODING);
+    headers.remove(header::CONTENT_LENGTH);
+
+    Ok(request.map(|body| {
+        let decompress = algorithm.into_impl_decompress_with_max_len(max_decompressed_len);
+        let mut body = DecompressedBody::new(body, decompress);
+        Body::wrap_stream(futures_util::stream::poll_fn(move |cx| {
+            Pin::new(&mut body).poll_data(cx).map_err(|err| {
+                tracing::debug!(error = %err, "failed to decompress the request body");
+                err
+            })
+        }))
+    }))
+}
+
+/// Returns the supported encodings as the value of an `Accept-Encoding` header.
+fn accept_encoding() -> HeaderValue {
+    let accept_encoding = supported_encodings().collect::<Vec<_>>().join(", ");
+    HeaderValue::from_str(&accept_encoding).expect("encoding names are valid header values")
+}
+
+/// The outcome of content negotiation for a request: the algorithm to compress its response with, if any.
+struct Negotiation {
+    algorithm: Option<CompressionAlgorithm>,
+    options: CompressionOptions,
+}
+
+/// Adds `Vary: accept-encoding` to the response, then compresses its body if the client accepts an algorithm.
+///
+/// Every response is marked as varying by `Accept-Encoding`, whether or not it is compressed, so that caches don't
+/// serve an uncompressed response to a client that asked for a compressed one, and vice versa.
+fn negotiate_response(mut response: Response<BoxBody>, negotiation: Negotiation) -> Response<BoxBody> {
+    add_vary_accept_encoding(response.headers_mut());
+    match negotiation.algorithm {
+        Some(algorithm) => compress_response(response, algorithm, &negotiation.options),
+        None => response,
+    }
+}
+
+fn add_vary_accept_encoding(headers: &mut HeaderMap) {
+    let varies = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|name| name == "*" || name.eq_ignore_ascii_case("accept-encoding"));
+    if !varies {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+}
+
+/// Compresses the response body with `algorithm` if the response is eligible for compression.
+fn compress_response(
+    mut response: Response<BoxBody>,
+    algorithm: CompressionAlgorithm,
+    options: &CompressionOptions,
+) -> Response<BoxBody> {
+    if response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+    // Only responses of a known size are compressed: streaming responses are left untouched so that their data
+    // reaches the client as soon as it's available.
+    match response.body().size_hint().exact() {
+        Some(size) if size > 0 && size >= options.min_compression_size_bytes() as u64 => {}
+        _ => return response,
+    }
+
+    let compress_request = algorithm.into_impl_http_body_0_4_x(options);
+    let headers = response.headers_mut();
+    headers.insert(compress_request.header_name(), compress_request.header_value());
+    headers.remove(header::CONTENT_LENGTH);
+
+    response.map(|body| crate::body::boxed(CompressedResponseBody::new(body, compress_request.compress_stream())))
+}
+
+pin_project_lite::pin_project! {
+    #[project = InnerProj]
+    enum Inner<Fut> {
+        Inner {
+            #[pin]
+            future: Fut,
+            negotiation: Option<Negotiation>,
+        },
+        Rejected {
+            response: Option<Response<BoxBody>>,
+        },
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The [`Service::Future`] of [`CompressionService`].
+    pub struct CompressionFuture<Fut> {
+        #[pin]
+        inner: Inner<Fut>,
+    }
+}
+
+impl<Fut> std::fmt::Debug for CompressionFuture<Fut> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionFuture").finish_non_exhaustive()
+    }
+}
+
+impl<Fut> CompressionFuture<Fut> {
+    fn inner(future: Fut, negotiation: Option<Negotiation>) -> Self {
+        Self {
+            inner: Inner::Inner { future, negotiation },
+        }
+    }
+
+    fn rejected(response: Response<BoxBody>) -> Self {
+        Self {
+            inner: Inner::Rejected {
+                response: Some(response),
+            },
+        }
+    }
+}
+
+impl<Fut, E> Future for CompressionFuture<Fut>
+where
+    Fut: Future<Output = Result<Response<BoxBody>, E>>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().inner.project() {
+            InnerProj::Inner { future, negotiation } => {
+                let response = ready!(future.poll(cx))?;
+                let response = match negotiation.take() {
+                    Some(negotiation) => negotiate_response(response, negotiation),
+                    None => response,
+                };
+                Poll::Ready(Ok(response))
+            }
+            InnerProj::Rejected { response } => {
+                Poll::Ready(Ok(response.take().expect("futures cannot be polled after completion")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{boxed, empty};
+    use crate::extension::RuntimeErrorExtension;
+    use crate::protocol::rest_json_1::RestJson1;
+    use bytes::Bytes;
+    use flate2::read::GzDecoder;
+    use http::StatusCode;
+    use std::convert::Infallible;
+    use std::io::Read;
+    use tower::{service_fn, ServiceExt};
+
+    const BODY: &str = "Four score and seven years ago our fathers brought forth on this continent, a new nation";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        CompressionAlgorithm::Gzip
+            .into_impl_http_body_0_4_x(&CompressionOptions::default())
+            .compress_bytes(data, &mut compressed)
+            .unwrap();
+        compressed
+    }
+
+    fn gunzip(data: &[u8]) -> String {
+        let mut decompressed = String::new();
+        GzDecoder::new(data).read_to_string(&mut decompressed).unwrap();
+        decompressed
+    }
+
+    fn options(min_compression_size_bytes: u32) -> CompressionOptions {
+        CompressionOptions::default()
+            .with_min_compression_size_bytes(min_compression_size_bytes)
+            .unwrap()
+    }
+
+    fn compression<S>(inner: S) -> CompressionService<S, RestJson1> {
+        CompressionService::new(inner)
+    }
+
+    /// A service that echoes the request body back in the response.
+    fn echo() -> impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible> + Clone {
+        service_fn(|request: Request<Body>| async move {
+            let headers = request.headers().clone();
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let mut response = Response::new(boxed(Body::from(body)));
+            if let Some(content_encoding) = headers.get(header::CONTENT_ENCODING) {
+                response
+                    .headers_mut()
+                    .insert("x-request-content-encoding", content_encoding.clone());
+            }
+            Ok::<_, Infallible>(response)
+        })
+    }
+
+    #[tokio::test]
+    async fn request_is_decompressed() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(BODY.as_bytes())))
+            .unwrap();
+
+        let response = compression(echo()).oneshot(request).await.unwrap();
+
+        assert!(!response.headers().contains_key("x-request-content-encoding"));
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(BODY.as_bytes(), body);
+    }
+
+    #[tokio::test]
+    async fn corrupt_request_body_fails_to_decompress() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(&b"not gzip"[..]))
+            .unwrap();
+        let svc = compression(service_fn(|request: Request<Body>| async move {
+            assert!(hyper::body::to_bytes(request.into_body()).await.is_err());
+            Ok::<_, Infallible>(Response::new(empty()))
+        }));
+
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn request_body_longer_than_max_decompressed_len_fails_to_decompress() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(gzip(&[b'a'; 100_000])))
+            .unwrap();
+        let svc = compression(service_fn(|request: Request<Body>| async move {
+            assert!(hyper::body::to_bytes(request.into_body()).await.is_err());
+            Ok::<_, Infallible>(Response::new(empty()))
+        }))
+        .max_decompressed_len(1_000);
+
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn request_is_decompressed_as_it_is_read() {
+        let compressed = gzip(BODY.as_bytes());
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            for chunk in compressed.chunks(10) {
+                sender.send_data(Bytes::copy_from_slice(chunk)).await.unwrap();
+            }
+        });
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(body)
+            .unwrap();
+
+        let response = compression(echo()).oneshot(request).await.unwrap();
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(BODY.as_bytes(), body);
+    }
+
+    #[tokio::test]
+    async fn unsupported_content_encoding_is_rejected() {
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "compress")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = compression(echo()).oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, response.status());
+        assert_eq!("UnsupportedMediaTypeException", response.headers()["x-amzn-errortype"]);
+        assert_eq!(
+            "UnsupportedMediaTypeException",
+            response
+                .extensions()
+                .get::<RuntimeErrorExtension>()
+                .unwrap()
+                .to_string()
+        );
+        let accept_encoding = response.headers().get(header::ACCEPT_ENCODING).unwrap();
+        assert!(accept_encoding.to_str().unwrap().starts_with("gzip"));
+    }
+
+    #[tokio::test]
+    async fn response_is_compressed() {
+        let request = Request::builder()
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = compression(echo()).options(options(0)).oneshot(request).await.unwrap();
+
+        assert_eq!("gzip", response.headers()[header::CONTENT_ENCODING]);
+        assert_eq!("accept-encoding", response.headers()[header::VARY]);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(BODY, gunzip(&body));
+    }
+
+    /// A body of several chunks, whose total size is known.
+    struct Chunks(std::collections::VecDeque<&'static str>);
+
+    impl http_body::Body for Chunks {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_data(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Infallible>>> {
+            Poll::Ready(
+                self.get_mut()
+                    .0
+                    .pop_front()
+                    .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes()))),
+            )
+        }
+
+        fn poll_trailers(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Infallible>> {
+            Poll::Ready(Ok(None))
+        }
+
+        fn size_hint(&self) -> http_body::SizeHint {
+            http_body::SizeHint::with_exact(self.0.iter().map(|chunk| chunk.len() as u64).sum())
+        }
+    }
+
+    #[tokio::test]
+    async fn multi_chunk_response_is_compressed_as_one_stream() {
+        let chunks = ["Four score ", "", "and seven years ago"];
+        let svc = compression(service_fn(move |_request: Request<Body>| async move {
+            Ok::<_, Infallible>(Response::new(boxed(Chunks(chunks.into()))))
+        }))
+        .options(options(0));
+        let request = Request::builder()
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = svc.oneshot(request).await.unwrap();
+
+        assert_eq!("gzip", response.headers()[header::CONTENT_ENCODING]);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(chunks.concat(), gunzip(&body));
+    }
+
+    #[tokio::test]
+    async fn negotiated_responses_vary_by_accept_encoding() {
+        let request = |accept_encoding: Option<&'static str>| {
+            let mut request = Request::builder();
+            if let Some(accept_encoding) = accept_encoding {
+                request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+            }
+            request.body(Body::from(BODY)).unwrap()
+        };
+        let svc = compression(echo()).options(options(BODY.len() as u32 + 1));
+
+        // Not compressed because the response is too small.
+        let response = svc.clone().oneshot(request(Some("gzip"))).await.unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        assert_eq!("accept-encoding", response.headers()[header::VARY]);
+
+        // Not compressed because the client didn't ask for it.
+        let response = svc.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!("accept-encoding", response.headers()[header::VARY]);
+
+        // Compression is disabled, so responses don't vary.
+        let response = svc
+            .options(CompressionOptions::default().with_enabled(false))
+            .oneshot(request(Some("gzip")))
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key(header::VARY));
+    }
+
+    #[tokio::test]
+    async fn vary_is_not_duplicated() {
+        let svc = compression(service_fn(|_request: Request<Body>| async {
+            let mut response = Response::new(empty());
+            response
+                .headers_mut()
+                .insert(header::VARY, HeaderValue::from_static("Accept-Encoding, Origin"));
+            Ok::<_, Infallible>(response)
+        }));
+
+        let response = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+
+        let vary = response.headers().get_all(header::VARY).iter().collect::<Vec<_>>();
+        assert_eq!(vec!["Accept-Encoding, Origin"], vary);
+    }
+
+    #[tokio::test]
+    async fn response_below_minimum_size_is_not_compressed() {
+        let request = Request::builder()
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::from(BODY))
+            .unwrap();
+
+        let response = compression(echo())
+            .options(options(BODY.len() as u32 + 1))
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(BODY.as_bytes(), body);
+    }
+
+    #[tokio::test]
+    async fn streaming_response_is_not_compressed() {
+        let svc = compression(service_fn(|_request: Request<Body>| async move {
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move { sender.send_data(BODY.into()).await });
+            Ok::<_, Infallible>(Response::new(boxed(body)))
+        }))
+        .options(options(0));
+        let request = Request::builder()
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = svc.oneshot(request).await.unwrap();
+
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn disabled_service_passes_bodies_through() {
+        let compressed = gzip(BODY.as_bytes());
+        let request = Request::builder()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::from(compressed.clone()))
+            .unwrap();
+
+        let response = compression(echo())
+            .options(options(0))
+            .disabled()
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!("gzip", response.headers()["x-request-content-encoding"]);
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(compressed, body);
+    }
+}
@@ -58,15 +58,24 @@
 //! ```
 //!
 //! [sensitive trait]: https://smithy.io/2.0/spec/documentation-traits.html#sensitive-trait
+//!
+//! # Distributed tracing
+//!
+//! [`TraceContextPlugin`] continues the trace propagated by the caller in W3C Trace Context or B3 headers. When it's
+//! applied before [`InstrumentPlugin`], the operation span includes the `trace_id`, `span_id` and `parent_span_id`
+//! of the request, and [`TraceContextPlugin::on_operation_span`] hands the caller's context and the operation span
+//! to the application.
 
 mod plugin;
 pub mod sensitivity;
 mod service;
+mod trace_context;
 
 use std::fmt::{Debug, Display};
 
 pub use plugin::*;
 pub use service::*;
+pub use trace_context::*;
 
 /// A standard interface for taking some component of the HTTP request/response and transforming it into new struct
 /// which enjoys [`Debug`] or [`Display`]. This allows for polymorphism over formatting approaches.
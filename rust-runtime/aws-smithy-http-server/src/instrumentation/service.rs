@@ -11,14 +11,15 @@ use std::{
     task::{Context, Poll},
 };
 
+use aws_smithy_types::trace_context::TraceContext;
 use futures_util::{ready, TryFuture};
 use http::{HeaderMap, Request, Response, StatusCode, Uri};
 use tower::Service;
-use tracing::{debug, debug_span, instrument::Instrumented, Instrument};
+use tracing::{debug, debug_span, field, instrument::Instrumented, Instrument};
 
 use crate::shape_id::ShapeId;
 
-use super::{MakeDebug, MakeDisplay, MakeIdentity};
+use super::{MakeDebug, MakeDisplay, MakeIdentity, OperationSpanHook, ParentSpanId};
 
 pin_project_lite::pin_project! {
     /// A [`Future`] responsible for logging the response status code and headers.
@@ -79,7 +80,10 @@ where
 
 /// A middleware [`Service`] responsible for:
 ///   - Opening a [`tracing::debug_span`] for the lifetime of the request, which includes the operation name, the
-///     [`Uri`], and the request headers.
+///     [`Uri`], and the request headers. When the request has a [`TraceContext`], for example because
+///     [`TraceContextPlugin`](super::TraceContextPlugin) was applied first, the span also includes the trace ID, the
+///     span ID and the caller's span ID, and is handed to the
+///     [`on_operation_span`](super::TraceContextPlugin::on_operation_span) hook.
 ///   - A [`tracing::debug`] during response, which includes the response status code and headers.
 ///
 /// The [`Display`](std::fmt::Display) and [`Debug`] of the request and response components can be modified using
@@ -174,8 +178,27 @@ where
         let span = {
             let headers = self.make_request.make_debug(request.headers());
             let uri = self.make_request.make_display(request.uri());
-            debug_span!("request", operation = %self.operation_id.absolute(), method = %request.method(), %uri, ?headers)
+            debug_span!(
+                "request",
+                operation = %self.operation_id.absolute(),
+                method = %request.method(),
+                %uri,
+                ?headers,
+                trace_id = field::Empty,
+                span_id = field::Empty,
+                parent_span_id = field::Empty,
+            )
         };
+        if let Some(context) = request.extensions().get::<TraceContext>() {
+            span.record("trace_id", format_args!("{:032x}", context.trace_id()));
+            span.record("span_id", format_args!("{:016x}", context.span_id()));
+        }
+        if let Some(ParentSpanId(parent_span_id)) = request.extensions().get::<ParentSpanId>() {
+            span.record("parent_span_id", format_args!("{parent_span_id:016x}"));
+        }
+        if let Some(hook) = request.extensions().get::<OperationSpanHook>() {
+            hook.call(&span);
+        }
 
         InstrumentedFuture {
             inner: InnerFuture {
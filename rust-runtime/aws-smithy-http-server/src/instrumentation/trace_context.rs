@@ -0,0 +1,313 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use aws_smithy_types::trace_context::{Propagation, TraceContext, WithTraceContext};
+use http::Request;
+use tower::Service;
+
+use crate::plugin::{HttpMarker, Plugin};
+
+/// The formats [`TraceContextPlugin`] extracts trace context from by default, in order of precedence.
+const DEFAULT_PROPAGATIONS: [Propagation; 3] = [Propagation::W3c, Propagation::B3Single, Propagation::B3Multi];
+
+/// The span ID of the caller, inserted in the request extensions along with the [`TraceContext`] of the request.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ParentSpanId(pub(crate) u64);
+
+type OperationSpanHookFn = dyn Fn(&TraceContext, &tracing::Span) + Send + Sync;
+
+/// The [hook](TraceContextPlugin::on_operation_span) and the caller's [`TraceContext`], inserted in the request
+/// extensions for [`InstrumentOperation`](super::InstrumentOperation) to call once it opens the operation span.
+#[derive(Clone)]
+pub(crate) struct OperationSpanHook {
+    hook: Arc<OperationSpanHookFn>,
+    parent: TraceContext,
+}
+
+impl OperationSpanHook {
+    pub(crate) fn call(&self, span: &tracing::Span) {
+        (self.hook)(&self.parent, span)
+    }
+}
+
+impl fmt::Debug for OperationSpanHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperationSpanHook")
+            .field("hook", &"** function **")
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
+
+/// A [`Plugin`] which applies [`TraceContextService`] to every operation.
+///
+/// # Example
+///
+/// ```
+/// # use aws_smithy_http_server::instrumentation::{InstrumentExt, TraceContextPlugin};
+/// # use aws_smithy_http_server::plugin::HttpPlugins;
+/// // `TraceContextPlugin` must be pushed before `InstrumentPlugin`, so it runs first and the operation span includes
+/// // the trace.
+/// let http_plugins = HttpPlugins::new().push(TraceContextPlugin::new()).instrument();
+/// ```
+///
+/// Applications that export their traces with another library, such as OpenTelemetry, can make the operation span a
+/// child of the caller's span in that library with [`on_operation_span`](Self::on_operation_span):
+///
+/// ```ignore
+/// use opentelemetry::trace::{SpanContext, TraceContextExt, TraceFlags, TraceState};
+/// use tracing_opentelemetry::OpenTelemetrySpanExt;
+///
+/// let plugin = TraceContextPlugin::new().on_operation_span(|parent, span| {
+///     let parent = SpanContext::new(
+///         parent.trace_id().into(),
+///         parent.span_id().into(),
+///         if parent.is_sampled() { TraceFlags::SAMPLED } else { TraceFlags::default() },
+///         true,
+///         TraceState::default(),
+///     );
+///     span.set_parent(opentelemetry::Context::new().with_remote_span_context(parent));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct TraceContextPlugin {
+    propagations: Arc<[Propagation]>,
+    on_operation_span: Option<Arc<OperationSpanHookFn>>,
+}
+
+impl fmt::Debug for TraceContextPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceContextPlugin")
+            .field("propagations", &self.propagations)
+            .field(
+                "on_operation_span",
+                &self.on_operation_span.as_ref().map(|_| "** function **"),
+            )
+            .finish()
+    }
+}
+
+impl Default for TraceContextPlugin {
+    fn default() -> Self {
+        Self {
+            propagations: DEFAULT_PROPAGATIONS.into(),
+            on_operation_span: None,
+        }
+    }
+}
+
+impl TraceContextPlugin {
+    /// Creates a new [`TraceContextPlugin`], which accepts trace context in the W3C Trace Context and B3 formats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Formats to extract the trace context from, in order of precedence.
+    pub fn propagations(mut self, propagations: impl IntoIterator<Item = Propagation>) -> Self {
+        self.propagations = propagations.into_iter().collect();
+        self
+    }
+
+    /// Calls `hook` with the caller's trace context and the operation span of each request that continues a
+    /// propagated trace, so that the application can pass the trace on to its tracing library.
+    ///
+    /// The operation span is opened by [`InstrumentPlugin`](super::InstrumentPlugin), so the hook is only called when
+    /// that plugin is applied after this one.
+    pub fn on_operation_span(mut self, hook: impl Fn(&TraceContext, &tracing::Span) + Send + Sync + 'static) -> Self {
+        self.on_operation_span = Some(Arc::new(hook));
+        self
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for TraceContextPlugin {
+    type Output = TraceContextService<T>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        TraceContextService {
+            inner,
+            propagations: self.propagations.clone(),
+            on_operation_span: self.on_operation_span.clone(),
+        }
+    }
+}
+
+impl HttpMarker for TraceContextPlugin {}
+
+/// A middleware [`Service`] which continues the trace propagated in the request headers.
+///
+/// Each request is handled as a new span of the caller's trace, or of a new trace when the caller didn't propagate
+/// one. The [`TraceContext`] of that span is:
+///   - inserted in the request extensions, where [`InstrumentOperation`](super::InstrumentOperation) adds it to the
+///     operation span, and from where handlers can extract it,
+///   - the [current](TraceContext::current) context while the request is handled, so that requests the handler sends
+///     with a client that propagates trace context continue the trace.
+///
+/// The current context is thread-local, and only set while the handler's future is polled. It doesn't follow work
+/// the handler moves to another task, for example with `tokio::spawn`. Pass the context from the request extensions
+/// to that work, or wrap its future with [`TraceContext::scope`].
+#[derive(Clone)]
+pub struct TraceContextService<S> {
+    inner: S,
+    propagations: Arc<[Propagation]>,
+    on_operation_span: Option<Arc<OperationSpanHookFn>>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for TraceContextService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceContextService")
+            .field("inner", &self.inner)
+            .field("propagations", &self.propagations)
+            .field(
+                "on_operation_span",
+                &self.on_operation_span.as_ref().map(|_| "** function **"),
+            )
+            .finish()
+    }
+}
+
+impl<S, B> Service<Request<B>> for TraceContextService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = WithTraceContext<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let headers = request.headers();
+        let parent = self.propagations.iter().find_map(|propagation| {
+            propagation.extract(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+        });
+        let context = match parent {
+            Some(parent) => {
+                request.extensions_mut().insert(ParentSpanId(parent.span_id()));
+                let context = parent.child(fastrand::u64(1..));
+                if let Some(hook) = &self.on_operation_span {
+                    request.extensions_mut().insert(OperationSpanHook {
+                        hook: hook.clone(),
+                        parent,
+                    });
+                }
+                context
+            }
+            None => TraceContext::new(fastrand::u128(1..), fastrand::u64(1..)),
+        };
+        request.extensions_mut().insert(context.clone());
+
+        let future = context.in_scope(|| self.inner.call(request));
+        context.scope(future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use std::sync::Mutex;
+
+    use http::Response;
+    use tower::{service_fn, ServiceExt};
+
+    use crate::instrumentation::InstrumentOperation;
+    use crate::shape_id::ShapeId;
+
+    use super::*;
+
+    const TRACE_ID: u128 = 0x4bf92f3577b34da6a3ce929d0e0e4736;
+    const SPAN_ID: u64 = 0x00f067aa0ba902b7;
+
+    /// Responds with the trace context of the request, its parent span ID, and the current trace context.
+    async fn handle(
+        plugin: TraceContextPlugin,
+        request: Request<()>,
+    ) -> (TraceContext, Option<u64>, Option<TraceContext>) {
+        let svc = service_fn(|request: Request<()>| async move {
+            let context = request.extensions().get::<TraceContext>().cloned().unwrap();
+            let parent = request.extensions().get::<ParentSpanId>().map(|parent| parent.0);
+            tokio::task::yield_now().await;
+            Ok::<_, Infallible>(Response::new((context, parent, TraceContext::current())))
+        });
+        let svc = Plugin::<(), (), _>::apply(&plugin, svc);
+        svc.oneshot(request).await.unwrap().into_body()
+    }
+
+    #[tokio::test]
+    async fn continues_the_propagated_trace() {
+        let request = Request::builder()
+            .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00")
+            .header("tracestate", "vendor=value")
+            .body(())
+            .unwrap();
+        let (context, parent, current) = handle(TraceContextPlugin::new(), request).await;
+
+        assert_eq!(TRACE_ID, context.trace_id());
+        assert_ne!(SPAN_ID, context.span_id());
+        assert!(!context.is_sampled());
+        assert_eq!(Some("vendor=value"), context.get_trace_state());
+        assert_eq!(Some(SPAN_ID), parent);
+        assert_eq!(Some(context), current);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_b3() {
+        let request = Request::builder()
+            .header("x-b3-traceid", "4bf92f3577b34da6a3ce929d0e0e4736")
+            .header("x-b3-spanid", "00f067aa0ba902b7")
+            .body(())
+            .unwrap();
+        let (context, parent, _) = handle(TraceContextPlugin::new(), request).await;
+        assert_eq!(TRACE_ID, context.trace_id());
+        assert_eq!(Some(SPAN_ID), parent);
+    }
+
+    #[tokio::test]
+    async fn starts_a_new_trace_without_a_supported_context() {
+        let request = Request::builder()
+            .header("b3", "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1")
+            .body(())
+            .unwrap();
+        let plugin = TraceContextPlugin::new().propagations([Propagation::W3c]);
+        let (context, parent, current) = handle(plugin, request).await;
+
+        assert_ne!(TRACE_ID, context.trace_id());
+        assert!(context.is_sampled());
+        assert_eq!(None, parent);
+        assert_eq!(Some(context), current);
+        assert_eq!(None, TraceContext::current());
+    }
+
+    #[tokio::test]
+    async fn hands_the_callers_context_and_the_operation_span_to_the_hook() {
+        const ID: ShapeId = ShapeId::new("namespace#operation", "namespace", "operation");
+        let parents = Arc::new(Mutex::new(Vec::new()));
+        let plugin = TraceContextPlugin::new().on_operation_span({
+            let parents = parents.clone();
+            move |parent, _span| parents.lock().unwrap().push(parent.clone())
+        });
+        let svc = service_fn(|_request: Request<()>| async { Ok::<_, Infallible>(Response::new(())) });
+        let svc = Plugin::<(), (), _>::apply(&plugin, InstrumentOperation::new(svc, ID));
+
+        let request = Request::builder()
+            .header("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+            .body(())
+            .unwrap();
+        svc.clone().oneshot(request).await.unwrap();
+        // Without a propagated trace, there is no caller to hand to the hook
+        svc.oneshot(Request::new(())).await.unwrap();
+
+        let parents = parents.lock().unwrap();
+        assert_eq!(1, parents.len());
+        assert_eq!(TRACE_ID, parents[0].trace_id());
+        assert_eq!(SPAN_ID, parents[0].span_id());
+    }
+}
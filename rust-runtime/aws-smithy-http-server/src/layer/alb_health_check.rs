@@ -33,6 +33,7 @@ use pin_project_lite::pin_project;
 use tower::{service_fn, util::Oneshot, Layer, Service, ServiceExt};
 
 use crate::body::BoxBody;
+use crate::shutdown::Readiness;
 
 use crate::plugin::either::Either;
 use crate::plugin::either::EitherProj;
@@ -62,6 +63,31 @@ impl AlbHealthCheckLayer<()> {
         AlbHealthCheckLayer::new(health_check_uri, service)
     }
 
+    /// Handle health check requests at `health_check_uri` by returning a `200 OK` while the server is ready, and a
+    /// `503 Service Unavailable` once a [`GracefulShutdown`] has started draining it.
+    ///
+    /// [`GracefulShutdown`]: crate::shutdown::GracefulShutdown
+    pub fn from_readiness(
+        health_check_uri: impl Into<Cow<'static, str>>,
+        readiness: Readiness,
+    ) -> AlbHealthCheckLayer<
+        impl Service<
+                Request<Body>,
+                Response = StatusCode,
+                Error = Infallible,
+                Future = impl Future<Output = Result<StatusCode, Infallible>>,
+            > + Clone,
+    > {
+        Self::from_handler(health_check_uri, move |_req| {
+            let status = if readiness.is_ready() {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            std::future::ready(status)
+        })
+    }
+
     /// Handle health check requests at `health_check_uri` with the specified service.
     pub fn new<H: Service<Request<Body>, Response = StatusCode>>(
         health_check_uri: impl Into<Cow<'static, str>>,
@@ -16,10 +16,18 @@
 pub(crate) mod macros;
 
 pub mod body;
+#[cfg(feature = "checksums")]
+#[cfg_attr(docsrs, doc(cfg(feature = "checksums")))]
+pub mod checksums;
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub mod compression;
 pub(crate) mod error;
 pub mod extension;
 pub mod instrumentation;
 pub mod layer;
+pub mod limit;
+pub mod metrics;
 pub mod operation;
 pub mod plugin;
 #[doc(hidden)]
@@ -34,6 +42,8 @@ pub mod routing;
 pub mod runtime_error;
 pub mod service;
 pub mod shape_id;
+pub mod shutdown;
+pub mod timeout;
 
 #[doc(inline)]
 pub(crate) use self::error::Error;
@@ -0,0 +1,222 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::{Limits, Rate};
+
+/// Idle partitions are only removed once there are more than this many, to avoid scanning small maps repeatedly.
+const MIN_PARTITIONS_BEFORE_CLEANUP: usize = 1024;
+
+/// Identifies the partition whose limits a request counts against.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PartitionKey {
+    Shared,
+    Peer(IpAddr),
+    Caller(String),
+}
+
+/// Why a request was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Rejection {
+    Rate { retry_after: Duration },
+    Concurrency,
+}
+
+/// Tracks the limits of one operation, for each partition.
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    limits: Limits,
+    partitions: Mutex<Partitions>,
+}
+
+#[derive(Debug)]
+struct Partitions {
+    partitions: HashMap<PartitionKey, Partition>,
+    cleanup_at: usize,
+}
+
+#[derive(Debug)]
+struct Partition {
+    tokens: f64,
+    refilled_at: Instant,
+    in_flight: usize,
+}
+
+impl Partition {
+    fn new(limits: &Limits, now: Instant) -> Self {
+        Self {
+            tokens: limits.rate.map(|rate| f64::from(rate.burst)).unwrap_or_default(),
+            refilled_at: now,
+            in_flight: 0,
+        }
+    }
+
+    fn refill(&mut self, rate: &Rate, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate.per_second).min(f64::from(rate.burst));
+        self.refilled_at = now;
+    }
+
+    /// Returns `true` if forgetting this partition wouldn't change how future requests are limited.
+    fn is_idle(&mut self, limits: &Limits, now: Instant) -> bool {
+        if self.in_flight > 0 {
+            return false;
+        }
+        match &limits.rate {
+            Some(rate) => {
+                self.refill(rate, now);
+                self.tokens >= f64::from(rate.burst)
+            }
+            None => true,
+        }
+    }
+}
+
+impl Limiter {
+    pub(crate) fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            partitions: Mutex::new(Partitions {
+                partitions: HashMap::new(),
+                cleanup_at: MIN_PARTITIONS_BEFORE_CLEANUP,
+            }),
+        }
+    }
+
+    /// Admits a request to the given partition, unless it's over a limit.
+    ///
+    /// The request counts against the concurrency limit until the returned [`Permit`] is dropped.
+    pub(crate) fn acquire(self: &Arc<Self>, key: PartitionKey) -> Result<Permit, Rejection> {
+        let now = Instant::now();
+        let mut partitions = self.partitions.lock().unwrap();
+        if partitions.partitions.len() >= partitions.cleanup_at {
+            partitions
+                .partitions
+                .retain(|_, partition| !partition.is_idle(&self.limits, now));
+            partitions.cleanup_at = (partitions.partitions.len() * 2).max(MIN_PARTITIONS_BEFORE_CLEANUP);
+        }
+
+        let partition = partitions
+            .partitions
+            .entry(key.clone())
+            .or_insert_with(|| Partition::new(&self.limits, now));
+        if let Some(max_concurrency) = self.limits.max_concurrency {
+            if partition.in_flight >= max_concurrency {
+                return Err(Rejection::Concurrency);
+            }
+        }
+        if let Some(rate) = &self.limits.rate {
+            partition.refill(rate, now);
+            if partition.tokens < 1.0 {
+                return Err(Rejection::Rate {
+                    retry_after: rate.time_to_earn(1.0 - partition.tokens),
+                });
+            }
+            partition.tokens -= 1.0;
+        }
+        partition.in_flight += 1;
+
+        Ok(Permit {
+            limiter: self.clone(),
+            key,
+        })
+    }
+
+    #[cfg(test)]
+    fn partition_count(&self) -> usize {
+        self.partitions.lock().unwrap().partitions.len()
+    }
+}
+
+/// A request admitted by a [`Limiter`].
+#[derive(Debug)]
+pub(crate) struct Permit {
+    limiter: Arc<Limiter>,
+    key: PartitionKey,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut partitions = self.limiter.partitions.lock().unwrap();
+        if let Some(partition) = partitions.partitions.get_mut(&self.key) {
+            partition.in_flight -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(limits: Limits) -> Arc<Limiter> {
+        Arc::new(Limiter::new(limits))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limit_allows_bursts_and_refills() {
+        let limiter = limiter(Limits::new().rate(2.0, 3));
+
+        for _ in 0..3 {
+            limiter.acquire(PartitionKey::Shared).unwrap();
+        }
+        assert_eq!(
+            Rejection::Rate {
+                retry_after: Duration::from_millis(500)
+            },
+            limiter.acquire(PartitionKey::Shared).unwrap_err()
+        );
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        limiter.acquire(PartitionKey::Shared).unwrap();
+        assert!(limiter.acquire(PartitionKey::Shared).is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_is_released_by_permits() {
+        let limiter = limiter(Limits::new().max_concurrency(2));
+
+        let first = limiter.acquire(PartitionKey::Shared).unwrap();
+        let _second = limiter.acquire(PartitionKey::Shared).unwrap();
+        assert_eq!(
+            Rejection::Concurrency,
+            limiter.acquire(PartitionKey::Shared).unwrap_err()
+        );
+
+        drop(first);
+        limiter.acquire(PartitionKey::Shared).unwrap();
+    }
+
+    #[tokio::test]
+    async fn partitions_are_limited_separately() {
+        let limiter = limiter(Limits::new().max_concurrency(1));
+
+        let _alice = limiter.acquire(PartitionKey::Caller("alice".into())).unwrap();
+        let _bob = limiter.acquire(PartitionKey::Caller("bob".into())).unwrap();
+        assert!(limiter.acquire(PartitionKey::Caller("alice".into())).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_partitions_are_removed() {
+        let limiter = limiter(Limits::new().rate(1.0, 1).max_concurrency(1));
+
+        let busy = limiter.acquire(PartitionKey::Caller("busy".into())).unwrap();
+        for caller in 1..MIN_PARTITIONS_BEFORE_CLEANUP {
+            drop(limiter.acquire(PartitionKey::Caller(caller.to_string())).unwrap());
+        }
+        tokio::time::advance(Duration::from_secs(1)).await;
+        drop(limiter.acquire(PartitionKey::Shared).unwrap());
+
+        // Only the partition with a request in flight and the new one are left.
+        assert_eq!(2, limiter.partition_count());
+        drop(busy);
+        assert!(limiter.acquire(PartitionKey::Caller("busy".into())).is_ok());
+    }
+}
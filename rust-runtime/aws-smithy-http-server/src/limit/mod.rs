@@ -0,0 +1,102 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+//! Provides [`LimitPlugin`], which limits the rate and concurrency of requests to each operation.
+//!
+//! # Example
+//!
+//! ```
+//! # use aws_smithy_http_server::limit::{LimitPlugin, Limits};
+//! # use aws_smithy_http_server::plugin::HttpPlugins;
+//! # use aws_smithy_http_server::shape_id::ShapeId;
+//! # const GET_POKEMON_SPECIES: ShapeId =
+//! #     ShapeId::new("com.aws.example#GetPokemonSpecies", "com.aws.example", "GetPokemonSpecies");
+//! let limits = LimitPlugin::new()
+//!     // Every operation accepts up to 100 concurrent requests from each client IP address...
+//!     .default_limits(Limits::new().max_concurrency(100))
+//!     // ...except `GetPokemonSpecies`, which also accepts 10 requests per second, in bursts of up to 20.
+//!     .operation_limits(GET_POKEMON_SPECIES, Limits::new().max_concurrency(100).rate(10.0, 20))
+//!     .key_by_peer_address();
+//! let http_plugins = HttpPlugins::new().push(limits);
+//! ```
+//!
+//! # Partitions
+//!
+//! By default, all the requests to an operation share its limits. Limits can instead be applied separately to each
+//! caller, with [`LimitPlugin::key_by_peer_address`] or [`LimitPlugin::key_by_extension`]. Requests whose caller
+//! can't be determined share a single partition.
+//!
+//! # Rejections
+//!
+//! Requests over a limit are rejected before they reach the operation with a `ThrottlingException` and a
+//! `429 Too Many Requests` status code, rendered according to the service's protocol. Requests over the rate limit
+//! also get a `Retry-After` header, telling the client when a request would be accepted.
+//!
+//! A request counts against the concurrency limit until its operation returns a response. Streaming responses, such
+//! as event streams, no longer count once the operation has returned.
+
+mod limiter;
+mod plugin;
+mod service;
+
+pub use plugin::*;
+pub use service::*;
+
+use std::time::Duration;
+
+/// The rate and concurrency limits of an operation.
+///
+/// Without any limits set, requests are not limited.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    rate: Option<Rate>,
+    max_concurrency: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rate {
+    per_second: f64,
+    burst: u32,
+}
+
+impl Rate {
+    /// Returns how long it takes to earn `tokens` tokens.
+    fn time_to_earn(&self, tokens: f64) -> Duration {
+        Duration::from_secs_f64(tokens / self.per_second)
+    }
+}
+
+impl Limits {
+    /// Creates new [`Limits`] that don't limit requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts `requests_per_second` requests per second on average, with bursts of up to `burst` requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` isn't a positive number, or if `burst` is zero.
+    pub fn rate(mut self, requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second.is_finite() && requests_per_second > 0.0,
+            "requests_per_second must be a positive number"
+        );
+        assert!(burst > 0, "burst must be at least 1");
+        self.rate = Some(Rate {
+            per_second: requests_per_second,
+            burst,
+        });
+        self
+    }
+
+    /// Accepts up to `max_concurrency` requests at a time.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::operation::OperationShape;
+use crate::plugin::{HttpMarker, Plugin};
+use crate::service::ServiceShape;
+use crate::shape_id::ShapeId;
+
+use super::limiter::Limiter;
+use super::{LimitService, Limits};
+
+/// Determines the caller of a request from its extensions.
+pub(crate) type CallerFn = Arc<dyn Fn(&http::Extensions) -> Option<String> + Send + Sync>;
+
+/// How requests are partitioned.
+#[derive(Clone, Default)]
+pub(crate) enum KeyBy {
+    #[default]
+    Operation,
+    PeerAddress,
+    Caller(CallerFn),
+}
+
+impl fmt::Debug for KeyBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Operation => f.write_str("Operation"),
+            Self::PeerAddress => f.write_str("PeerAddress"),
+            Self::Caller(_) => f.write_str("Caller"),
+        }
+    }
+}
+
+/// A [`Plugin`] which applies [`LimitService`] to every operation with [`Limits`].
+///
+/// See the [module](crate::limit) documentation for more information.
+#[derive(Debug, Clone, Default)]
+pub struct LimitPlugin {
+    default_limits: Option<Limits>,
+    operation_limits: HashMap<ShapeId, Limits>,
+    key_by: KeyBy,
+}
+
+impl LimitPlugin {
+    /// Creates a new [`LimitPlugin`] which doesn't limit any operation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Limits`] of the operations without limits of their own.
+    pub fn default_limits(mut self, limits: Limits) -> Self {
+        self.default_limits = Some(limits);
+        self
+    }
+
+    /// Sets the [`Limits`] of the operation with the given [`ShapeId`], instead of the default limits.
+    pub fn operation_limits(mut self, operation: ShapeId, limits: Limits) -> Self {
+        self.operation_limits.insert(operation, limits);
+        self
+    }
+
+    /// Limits the requests from each client IP address separately.
+    ///
+    /// The address is read from the [`ConnectInfo<SocketAddr>`](crate::request::connect_info::ConnectInfo)
+    /// extension, which requires serving the application with
+    /// [`IntoMakeServiceWithConnectInfo`](crate::routing::IntoMakeServiceWithConnectInfo).
+    pub fn key_by_peer_address(mut self) -> Self {
+        self.key_by = KeyBy::PeerAddress;
+        self
+    }
+
+    /// Limits the requests from each caller separately, identified by the `T` request extension.
+    ///
+    /// `T` is typically inserted by an authentication middleware, which must run before this plugin.
+    pub fn key_by_extension<T>(mut self) -> Self
+    where
+        T: fmt::Display + Send + Sync + 'static,
+    {
+        self.key_by = KeyBy::Caller(Arc::new(|extensions| extensions.get::<T>().map(ToString::to_string)));
+        self
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for LimitPlugin
+where
+    Ser: ServiceShape,
+    Op: OperationShape,
+{
+    type Output = LimitService<T, Ser::Protocol>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        let limits = self.operation_limits.get(&Op::ID).or(self.default_limits.as_ref());
+        let limiter = limits.map(|limits| Arc::new(Limiter::new(*limits)));
+        LimitService::new(inner, Op::ID, limiter, self.key_by.clone())
+    }
+}
+
+impl HttpMarker for LimitPlugin {}
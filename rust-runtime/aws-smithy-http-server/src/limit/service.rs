@@ -0,0 +1,429 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::ready;
+use http::{Request, Response};
+use tower::Service;
+
+use crate::body::BoxBody;
+use crate::request::connect_info::ConnectInfo;
+use crate::response::IntoResponse;
+use crate::runtime_error::ThrottlingException;
+use crate::shape_id::ShapeId;
+
+use super::limiter::{Limiter, PartitionKey, Permit, Rejection};
+use super::plugin::KeyBy;
+
+/// A middleware [`Service`] that rejects requests over an operation's [`Limits`](super::Limits).
+///
+/// Rejected requests get a `ThrottlingException` of the protocol `P`.
+///
+/// See the [module](crate::limit) documentation for more information.
+pub struct LimitService<S, P> {
+    inner: S,
+    operation: ShapeId,
+    limiter: Option<Arc<Limiter>>,
+    key_by: KeyBy,
+    _protocol: PhantomData<fn(P)>,
+}
+
+impl<S, P> LimitService<S, P> {
+    pub(crate) fn new(inner: S, operation: ShapeId, limiter: Option<Arc<Limiter>>, key_by: KeyBy) -> Self {
+        Self {
+            inner,
+            operation,
+            limiter,
+            key_by,
+            _protocol: PhantomData,
+        }
+    }
+
+    fn partition_key<B>(&self, request: &Request<B>) -> PartitionKey {
+        let key = match &self.key_by {
+            KeyBy::Operation => None,
+            KeyBy::PeerAddress => request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|connect_info| PartitionKey::Peer(connect_info.0.ip())),
+            KeyBy::Caller(caller) => caller(request.extensions()).map(PartitionKey::Caller),
+        };
+        key.unwrap_or(PartitionKey::Shared)
+    }
+}
+
+impl<S, P> Clone for LimitService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            operation: self.operation.clone(),
+            limiter: self.limiter.clone(),
+            key_by: self.key_by.clone(),
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<S, P> std::fmt::Debug for LimitService<S, P>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LimitService")
+            .field("inner", &self.inner)
+            .field("operation", &self.operation)
+            .field("limiter", &self.limiter)
+            .field("key_by", &self.key_by)
+            .finish()
+    }
+}
+
+impl<S, P, B> Service<Request<B>> for LimitService<S, P>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>>,
+    ThrottlingException: IntoResponse<P>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = LimitFuture<S::Future, P>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let Some(limiter) = &self.limiter else {
+            return LimitFuture::inner(self.inner.call(request), None);
+        };
+        let key = self.partition_key(&request);
+        let exception = match limiter.acquire(key.clone()) {
+            Ok(permit) => return LimitFuture::inner(self.inner.call(request), Some(permit)),
+            Err(Rejection::Rate { retry_after }) => {
+                ThrottlingException::new("rate limit exceeded").retry_after(retry_after)
+            }
+            Err(Rejection::Concurrency) => ThrottlingException::new("too many concurrent requests"),
+        };
+        tracing::debug!(
+            operation = %self.operation.absolute(),
+            partition = ?key,
+            reason = exception.message(),
+            "rejecting request over the operation's limits"
+        );
+        LimitFuture::rejected(exception.into_response())
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = InnerProj]
+    enum Inner<Fut> {
+        Inner {
+            #[pin]
+            future: Fut,
+            permit: Option<Permit>,
+        },
+        Rejected {
+            response: Option<Response<BoxBody>>,
+        },
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The [`Service::Future`] of [`LimitService`].
+    pub struct LimitFuture<Fut, P> {
+        #[pin]
+        inner: Inner<Fut>,
+        _protocol: PhantomData<fn(P)>,
+    }
+}
+
+impl<Fut, P> std::fmt::Debug for LimitFuture<Fut, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LimitFuture").finish_non_exhaustive()
+    }
+}
+
+impl<Fut, P> LimitFuture<Fut, P> {
+    fn inner(future: Fut, permit: Option<Permit>) -> Self {
+        Self {
+            inner: Inner::Inner { future, permit },
+            _protocol: PhantomData,
+        }
+    }
+
+    fn rejected(response: Response<BoxBody>) -> Self {
+        Self {
+            inner: Inner::Rejected {
+                response: Some(response),
+            },
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<Fut, P, E> Future for LimitFuture<Fut, P>
+where
+    Fut: Future<Output = Result<Response<BoxBody>, E>>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().inner.project() {
+            InnerProj::Inner { future, permit } => {
+                let response = ready!(future.poll(cx));
+                // The request no longer counts against the concurrency limit once the operation has responded.
+                permit.take();
+                Poll::Ready(response)
+            }
+            InnerProj::Rejected { response } => {
+                Poll::Ready(Ok(response.take().expect("futures cannot be polled after completion")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{boxed, Body};
+    use crate::limit::{LimitPlugin, Limits};
+    use crate::operation::OperationShape;
+    use crate::plugin::Plugin;
+    use crate::protocol::rest_json_1::RestJson1;
+    use crate::protocol::rest_xml::RestXml;
+    use crate::protocol::rpc_v2_cbor::RpcV2Cbor;
+    use crate::service::ServiceShape;
+    use http::StatusCode;
+    use std::convert::Infallible;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+    use tower::{service_fn, ServiceExt};
+
+    struct TestService<P>(PhantomData<P>);
+
+    impl<P> ServiceShape for TestService<P> {
+        const ID: ShapeId = ShapeId::new("com.example#Service", "com.example", "Service");
+        const VERSION: Option<&'static str> = None;
+        type Protocol = P;
+        type Operations = ();
+    }
+
+    struct Limited;
+    struct Unlimited;
+
+    impl OperationShape for Limited {
+        const ID: ShapeId = ShapeId::new("com.example#Limited", "com.example", "Limited");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    impl OperationShape for Unlimited {
+        const ID: ShapeId = ShapeId::new("com.example#Unlimited", "com.example", "Unlimited");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    fn ok() -> impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible> + Clone {
+        service_fn(|_request: Request<Body>| async { Ok::<_, Infallible>(Response::new(boxed(Body::from("ok")))) })
+    }
+
+    fn apply<P, Op, S>(plugin: &LimitPlugin, inner: S) -> LimitService<S, P>
+    where
+        Op: OperationShape,
+    {
+        Plugin::<TestService<P>, Op, S>::apply(plugin, inner)
+    }
+
+    async fn body_string(response: Response<BoxBody>) -> String {
+        String::from_utf8(hyper::body::to_bytes(response.into_body()).await.unwrap().to_vec()).unwrap()
+    }
+
+    fn request_from(caller: Option<&'static str>, address: Option<&str>) -> Request<Body> {
+        let mut request = Request::new(Body::empty());
+        if let Some(caller) = caller {
+            request.extensions_mut().insert(caller.to_owned());
+        }
+        if let Some(address) = address {
+            request
+                .extensions_mut()
+                .insert(ConnectInfo::<SocketAddr>(address.parse().unwrap()));
+        }
+        request
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_over_the_rate_limit_are_throttled() {
+        let plugin = LimitPlugin::new().operation_limits(Limited::ID, Limits::new().rate(1.0, 1));
+        let mut svc = apply::<RestJson1, Limited, _>(&plugin, ok());
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert_eq!("ThrottlingException", response.headers()["x-amzn-errortype"]);
+        assert_eq!("1", response.headers()[http::header::RETRY_AFTER]);
+        assert_eq!(
+            r#"{"__type":"ThrottlingException","message":"rate limit exceeded"}"#,
+            body_string(response).await
+        );
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[tokio::test]
+    async fn requests_over_the_concurrency_limit_are_throttled() {
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let release_rx = Arc::new(std::sync::Mutex::new(Some(release_rx)));
+        let inner = service_fn(move |_request: Request<Body>| {
+            let release_rx = release_rx.lock().unwrap().take();
+            async move {
+                if let Some(release_rx) = release_rx {
+                    release_rx.await.unwrap();
+                }
+                Ok::<_, Infallible>(Response::new(crate::body::empty()))
+            }
+        });
+        let plugin = LimitPlugin::new().default_limits(Limits::new().max_concurrency(1));
+        let mut svc = apply::<RestJson1, Limited, _>(&plugin, inner);
+
+        let in_flight = tokio::spawn(svc.clone().oneshot(Request::new(Body::empty())));
+        tokio::task::yield_now().await;
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert!(!response.headers().contains_key(http::header::RETRY_AFTER));
+
+        release_tx.send(()).unwrap();
+        assert_eq!(StatusCode::OK, in_flight.await.unwrap().unwrap().status());
+        let response = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[tokio::test]
+    async fn operations_without_limits_are_not_limited() {
+        let plugin = LimitPlugin::new().operation_limits(Limited::ID, Limits::new().rate(1.0, 1));
+        let mut svc = apply::<RestJson1, Unlimited, _>(&plugin, ok());
+
+        for _ in 0..3 {
+            let response = svc
+                .ready()
+                .await
+                .unwrap()
+                .call(Request::new(Body::empty()))
+                .await
+                .unwrap();
+            assert_eq!(StatusCode::OK, response.status());
+        }
+    }
+
+    #[tokio::test]
+    async fn peers_are_limited_separately() {
+        let plugin = LimitPlugin::new()
+            .default_limits(Limits::new().rate(1.0, 1))
+            .key_by_peer_address();
+        let svc = apply::<RestJson1, Limited, _>(&plugin, ok());
+
+        let call = |address| svc.clone().oneshot(request_from(None, Some(address)));
+        assert_eq!(StatusCode::OK, call("10.0.0.1:1000").await.unwrap().status());
+        assert_eq!(StatusCode::OK, call("10.0.0.2:1000").await.unwrap().status());
+        // Connections from the same address share a partition.
+        assert_eq!(
+            StatusCode::TOO_MANY_REQUESTS,
+            call("10.0.0.1:2000").await.unwrap().status()
+        );
+    }
+
+    #[tokio::test]
+    async fn callers_are_limited_separately() {
+        let plugin = LimitPlugin::new()
+            .default_limits(Limits::new().rate(1.0, 1))
+            .key_by_extension::<String>();
+        let svc = apply::<RestJson1, Limited, _>(&plugin, ok());
+
+        let call = |caller| svc.clone().oneshot(request_from(caller, None));
+        assert_eq!(StatusCode::OK, call(Some("alice")).await.unwrap().status());
+        assert_eq!(StatusCode::OK, call(Some("bob")).await.unwrap().status());
+        assert_eq!(
+            StatusCode::TOO_MANY_REQUESTS,
+            call(Some("alice")).await.unwrap().status()
+        );
+        // Requests without a caller share a partition.
+        assert_eq!(StatusCode::OK, call(None).await.unwrap().status());
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, call(None).await.unwrap().status());
+    }
+
+    #[tokio::test]
+    async fn throttling_exception_is_rendered_for_the_protocol() {
+        let plugin = LimitPlugin::new().default_limits(Limits::new().max_concurrency(0));
+
+        let response = apply::<RestXml, Limited, _>(&plugin, ok())
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert_eq!("application/xml", response.headers()[http::header::CONTENT_TYPE]);
+        assert_eq!(
+            "<ErrorResponse><Error><Type>Sender</Type><Code>ThrottlingException</Code>\
+             <Message>too many concurrent requests</Message></Error></ErrorResponse>",
+            body_string(response).await
+        );
+
+        let response = apply::<RpcV2Cbor, Limited, _>(&plugin, ok())
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert_eq!("application/cbor", response.headers()[http::header::CONTENT_TYPE]);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut decoder = aws_smithy_cbor::Decoder::new(&body);
+        assert_eq!(Some(2), decoder.map().unwrap());
+        assert_eq!("__type", decoder.str().unwrap());
+        assert_eq!("ThrottlingException", decoder.str().unwrap());
+    }
+}
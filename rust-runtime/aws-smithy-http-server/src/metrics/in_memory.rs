@@ -0,0 +1,176 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::shape_id::ShapeId;
+
+use super::{MetricsSink, Outcome, RequestMetrics};
+
+/// The upper bounds of the buckets of a [`LatencyHistogram`], in milliseconds.
+const LATENCY_BUCKETS_MS: [u64; 14] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000];
+
+/// A [`MetricsSink`] which aggregates metrics in memory, for tests and diagnostics.
+///
+/// Clones share the same metrics, so a clone can be given to a [`MetricsPlugin`](super::MetricsPlugin) and the
+/// original used to read them. Every [`RequestMetrics`] is kept, so this sink isn't meant for long-running servers.
+#[derive(Clone, Debug, Default)]
+pub struct InMemorySink {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    operations: HashMap<ShapeId, OperationStats>,
+    unrouted: Option<OperationStats>,
+    records: Vec<RequestMetrics>,
+}
+
+impl InMemorySink {
+    /// Creates a new, empty [`InMemorySink`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the statistics of the operation with the given [`ShapeId`], if it received any request.
+    pub fn operation(&self, operation: &ShapeId) -> Option<OperationStats> {
+        self.state.lock().unwrap().operations.get(operation).cloned()
+    }
+
+    /// Returns the statistics of the requests that weren't routed to an operation, if there were any.
+    pub fn unrouted(&self) -> Option<OperationStats> {
+        self.state.lock().unwrap().unrouted.clone()
+    }
+
+    /// Returns the metrics of every request, in the order they were recorded.
+    pub fn records(&self) -> Vec<RequestMetrics> {
+        self.state.lock().unwrap().records.clone()
+    }
+}
+
+impl MetricsSink for InMemorySink {
+    fn record(&self, metrics: &RequestMetrics) {
+        let mut state = self.state.lock().unwrap();
+        let stats = match metrics.operation() {
+            Some(operation) => state.operations.entry(operation.clone()).or_default(),
+            None => state.unrouted.get_or_insert_with(Default::default),
+        };
+        stats.record(metrics);
+        state.records.push(metrics.clone());
+    }
+}
+
+/// The aggregated metrics of the requests to an operation, returned by [`InMemorySink::operation`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct OperationStats {
+    requests: u64,
+    successes: u64,
+    modeled_errors: HashMap<&'static str, u64>,
+    rejection_reasons: HashMap<String, u64>,
+    status_counts: HashMap<u16, u64>,
+    latency: LatencyHistogram,
+}
+
+impl OperationStats {
+    fn record(&mut self, metrics: &RequestMetrics) {
+        self.requests += 1;
+        match metrics.outcome() {
+            Outcome::Success => self.successes += 1,
+            Outcome::ModeledError(name) => *self.modeled_errors.entry(name).or_default() += 1,
+            Outcome::RuntimeError(name) => *self.rejection_reasons.entry(name.clone()).or_default() += 1,
+        }
+        *self.status_counts.entry(metrics.status().as_u16()).or_default() += 1;
+        self.latency.record(metrics.latency());
+    }
+
+    /// The number of requests.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// The number of requests that succeeded.
+    pub fn successes(&self) -> u64 {
+        self.successes
+    }
+
+    /// The number of requests for which the operation handler returned a modeled error.
+    pub fn modeled_errors(&self) -> u64 {
+        self.modeled_errors.values().sum()
+    }
+
+    /// The number of modeled errors, by error name.
+    pub fn modeled_error_counts(&self) -> &HashMap<&'static str, u64> {
+        &self.modeled_errors
+    }
+
+    /// The number of requests that the framework rejected with a runtime error.
+    pub fn runtime_errors(&self) -> u64 {
+        self.rejection_reasons.values().sum()
+    }
+
+    /// The number of runtime errors, by error name.
+    pub fn rejection_reasons(&self) -> &HashMap<String, u64> {
+        &self.rejection_reasons
+    }
+
+    /// The number of responses, by status code.
+    pub fn status_counts(&self) -> &HashMap<u16, u64> {
+        &self.status_counts
+    }
+
+    /// The latency of the requests.
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+}
+
+/// A histogram of request latencies, with buckets from 1 millisecond to 30 seconds.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| latency <= Duration::from_millis(*bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+
+    /// The number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The sum of the recorded latencies.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// The highest recorded latency.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the inclusive upper bound and the number of latencies of each bucket, in increasing order.
+    ///
+    /// The last bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS_MS
+            .iter()
+            .map(|bound| Some(Duration::from_millis(*bound)))
+            .chain([None])
+            .zip(self.counts.iter().copied())
+    }
+}
@@ -0,0 +1,126 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+//! Provides [`MetricsPlugin`], which records the number, latency and outcome of the requests to each operation in a
+//! [`MetricsSink`].
+//!
+//! # Example
+//!
+//! ```
+//! # use aws_smithy_http_server::metrics::{InMemorySink, MetricsPlugin};
+//! # use aws_smithy_http_server::plugin::HttpPlugins;
+//! # use aws_smithy_http_server::shape_id::ShapeId;
+//! # const GET_POKEMON_SPECIES: ShapeId =
+//! #     ShapeId::new("com.aws.example#GetPokemonSpecies", "com.aws.example", "GetPokemonSpecies");
+//! let sink = InMemorySink::new();
+//! let http_plugins = HttpPlugins::new().push(MetricsPlugin::new(sink.clone()));
+//!
+//! // ... serve requests ...
+//!
+//! if let Some(stats) = sink.operation(&GET_POKEMON_SPECIES) {
+//!     println!("{} requests, {} modeled errors", stats.requests(), stats.modeled_errors());
+//! }
+//! ```
+//!
+//! # Outcomes
+//!
+//! The [`Outcome`] of a request is determined from the extensions of its response:
+//!
+//! - a [`ModeledErrorExtension`](crate::extension::ModeledErrorExtension) means the operation handler returned an
+//!   error defined in the Smithy model,
+//! - a [`RuntimeErrorExtension`](crate::extension::RuntimeErrorExtension) means the request was rejected by the
+//!   framework, for example because it failed to deserialize or was throttled, and the handler was not invoked. The
+//!   name of the runtime error is the rejection reason.
+//!
+//! # Requests that aren't routed to an operation
+//!
+//! [`MetricsPlugin`] only sees the requests routed to an operation. To also record the requests the router rejects,
+//! apply [`MetricsLayer`] around the whole service instead. It reads the operation from the
+//! [`OperationExtension`](crate::extension::OperationExtension), so the
+//! [`OperationExtensionPlugin`](crate::extension::OperationExtensionPlugin) must be applied as well. Requests without
+//! one are recorded without an operation.
+//!
+//! # Latency
+//!
+//! Latency is measured until the response is returned, and doesn't include the time spent sending the response body.
+
+mod in_memory;
+mod plugin;
+mod service;
+
+pub use in_memory::*;
+pub use plugin::*;
+pub use service::*;
+
+use std::fmt::Debug;
+use std::time::Duration;
+
+use http::StatusCode;
+
+use crate::shape_id::ShapeId;
+
+/// Receives the [`RequestMetrics`] of every request.
+///
+/// Implement this trait to export metrics to a monitoring system.
+pub trait MetricsSink: Debug + Send + Sync {
+    /// Records a request once its response has been returned.
+    fn record(&self, metrics: &RequestMetrics);
+}
+
+impl<T> MetricsSink for std::sync::Arc<T>
+where
+    T: MetricsSink + ?Sized,
+{
+    fn record(&self, metrics: &RequestMetrics) {
+        (**self).record(metrics)
+    }
+}
+
+/// How a request ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// The operation returned a successful response.
+    Success,
+    /// The operation handler returned the modeled error with the given name.
+    ModeledError(&'static str),
+    /// The framework rejected the request with the runtime error with the given name, such as
+    /// `SerializationException` or `ThrottlingException`.
+    RuntimeError(String),
+}
+
+/// The metrics of a single request.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    operation: Option<ShapeId>,
+    status: StatusCode,
+    latency: Duration,
+    outcome: Outcome,
+}
+
+impl RequestMetrics {
+    /// The operation the request was routed to, if any.
+    pub fn operation(&self) -> Option<&ShapeId> {
+        self.operation.as_ref()
+    }
+
+    /// The status code of the response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// How long it took to return the response.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// How the request ended.
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
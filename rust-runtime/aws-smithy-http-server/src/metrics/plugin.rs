@@ -0,0 +1,206 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::sync::Arc;
+
+use tower::Layer;
+
+use crate::operation::OperationShape;
+use crate::plugin::{HttpMarker, Plugin};
+
+use super::{MetricsService, MetricsSink};
+
+/// A [`Plugin`] which applies [`MetricsService`] to every operation.
+///
+/// See the [module](crate::metrics) documentation for more information.
+#[derive(Clone, Debug)]
+pub struct MetricsPlugin {
+    sink: Arc<dyn MetricsSink>,
+}
+
+impl MetricsPlugin {
+    /// Creates a new [`MetricsPlugin`] which records metrics in the given sink.
+    pub fn new(sink: impl MetricsSink + 'static) -> Self {
+        Self { sink: Arc::new(sink) }
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for MetricsPlugin
+where
+    Op: OperationShape,
+{
+    type Output = MetricsService<T>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        MetricsService::new(inner, Op::ID, self.sink.clone())
+    }
+}
+
+impl HttpMarker for MetricsPlugin {}
+
+/// A [`Layer`] which applies [`MetricsService`] to a whole service, including the requests that aren't routed to an
+/// operation.
+///
+/// The operation is read from the [`OperationExtension`](crate::extension::OperationExtension) of each response. See
+/// the [module](crate::metrics) documentation for more information.
+#[derive(Clone, Debug)]
+pub struct MetricsLayer {
+    sink: Arc<dyn MetricsSink>,
+}
+
+impl MetricsLayer {
+    /// Creates a new [`MetricsLayer`] which records metrics in the given sink.
+    pub fn new(sink: impl MetricsSink + 'static) -> Self {
+        Self { sink: Arc::new(sink) }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService::from_extension(inner, self.sink.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::time::Duration;
+
+    use http::{Request, Response, StatusCode};
+    use tower::{service_fn, Service, ServiceExt};
+
+    use crate::body::{empty, Body, BoxBody};
+    use crate::extension::{ModeledErrorExtension, OperationExtension, RuntimeErrorExtension};
+    use crate::metrics::{InMemorySink, Outcome};
+    use crate::shape_id::ShapeId;
+
+    use super::*;
+
+    struct Operation;
+
+    impl OperationShape for Operation {
+        const ID: ShapeId = ShapeId::new("com.example#Operation", "com.example", "Operation");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    /// Responds with the status and extension given by `respond`, after `delay`.
+    fn service<F>(
+        delay: Duration,
+        respond: F,
+    ) -> impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible> + Clone
+    where
+        F: Fn(&mut Response<BoxBody>) + Clone,
+    {
+        service_fn(move |_request: Request<Body>| {
+            let respond = respond.clone();
+            async move {
+                tokio::time::sleep(delay).await;
+                let mut response = Response::new(empty());
+                respond(&mut response);
+                Ok::<_, Infallible>(response)
+            }
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn records_outcomes_and_latency_per_operation() {
+        let sink = InMemorySink::new();
+        let plugin = MetricsPlugin::new(sink.clone());
+        let ok = Plugin::<(), Operation, _>::apply(&plugin, service(Duration::from_millis(30), |_| {}));
+        let modeled_error = Plugin::<(), Operation, _>::apply(
+            &plugin,
+            service(Duration::from_millis(3), |response| {
+                *response.status_mut() = StatusCode::NOT_FOUND;
+                response
+                    .extensions_mut()
+                    .insert(ModeledErrorExtension::new("ResourceNotFoundException"));
+            }),
+        );
+        let runtime_error = Plugin::<(), Operation, _>::apply(
+            &plugin,
+            service(Duration::ZERO, |response| {
+                *response.status_mut() = StatusCode::BAD_REQUEST;
+                response
+                    .extensions_mut()
+                    .insert(RuntimeErrorExtension::new("SerializationException".to_owned()));
+            }),
+        );
+
+        ok.clone().oneshot(Request::new(Body::empty())).await.unwrap();
+        ok.oneshot(Request::new(Body::empty())).await.unwrap();
+        modeled_error.oneshot(Request::new(Body::empty())).await.unwrap();
+        runtime_error.oneshot(Request::new(Body::empty())).await.unwrap();
+
+        let stats = sink.operation(&Operation::ID).unwrap();
+        assert_eq!(4, stats.requests());
+        assert_eq!(2, stats.successes());
+        assert_eq!(1, stats.modeled_errors());
+        assert_eq!(
+            Some(1),
+            stats.modeled_error_counts().get("ResourceNotFoundException").copied()
+        );
+        assert_eq!(1, stats.runtime_errors());
+        assert_eq!(
+            Some(1),
+            stats.rejection_reasons().get("SerializationException").copied()
+        );
+        assert_eq!(Some(2), stats.status_counts().get(&200).copied());
+        assert_eq!(Some(1), stats.status_counts().get(&404).copied());
+
+        let latency = stats.latency();
+        assert_eq!(4, latency.count());
+        assert_eq!(Duration::from_millis(63), latency.sum());
+        assert_eq!(Duration::from_millis(30), latency.max());
+        assert_eq!(
+            vec![
+                (Some(Duration::from_millis(1)), 1),
+                (Some(Duration::from_millis(5)), 1),
+                (Some(Duration::from_millis(50)), 2)
+            ],
+            latency.buckets().filter(|(_, count)| *count > 0).collect::<Vec<_>>()
+        );
+        assert!(sink.unrouted().is_none());
+    }
+
+    #[tokio::test]
+    async fn layer_reads_the_operation_from_the_response() {
+        let sink = InMemorySink::new();
+        let layer = MetricsLayer::new(sink.clone());
+
+        let routed = layer.layer(service(Duration::ZERO, |response| {
+            response.extensions_mut().insert(OperationExtension(Operation::ID));
+        }));
+        routed.oneshot(Request::new(Body::empty())).await.unwrap();
+        let unrouted = layer.layer(service(Duration::ZERO, |response| {
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response
+                .extensions_mut()
+                .insert(RuntimeErrorExtension::new("UnknownOperationException".to_owned()));
+        }));
+        unrouted.oneshot(Request::new(Body::empty())).await.unwrap();
+
+        assert_eq!(1, sink.operation(&Operation::ID).unwrap().successes());
+        let unrouted = sink.unrouted().unwrap();
+        assert_eq!(1, unrouted.requests());
+        assert_eq!(
+            Some(1),
+            unrouted.rejection_reasons().get("UnknownOperationException").copied()
+        );
+        assert_eq!(
+            vec![
+                Outcome::Success,
+                Outcome::RuntimeError("UnknownOperationException".to_owned())
+            ],
+            sink.records()
+                .iter()
+                .map(|record| record.outcome().clone())
+                .collect::<Vec<_>>()
+        );
+    }
+}
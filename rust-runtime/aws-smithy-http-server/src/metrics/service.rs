@@ -0,0 +1,127 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::ready;
+use http::{Request, Response};
+use tokio::time::Instant;
+use tower::Service;
+
+use crate::extension::{ModeledErrorExtension, OperationExtension, RuntimeErrorExtension};
+use crate::shape_id::ShapeId;
+
+use super::{MetricsSink, Outcome, RequestMetrics};
+
+/// A middleware [`Service`] that records the [`RequestMetrics`] of every request in a [`MetricsSink`].
+///
+/// See the [module](crate::metrics) documentation for more information.
+#[derive(Clone, Debug)]
+pub struct MetricsService<S> {
+    inner: S,
+    operation: Option<ShapeId>,
+    sink: Arc<dyn MetricsSink>,
+}
+
+impl<S> MetricsService<S> {
+    /// Records the metrics of `inner`'s requests as the requests to the given operation.
+    pub fn new(inner: S, operation: ShapeId, sink: Arc<dyn MetricsSink>) -> Self {
+        Self {
+            inner,
+            operation: Some(operation),
+            sink,
+        }
+    }
+
+    /// Reads the operation of each request from the [`OperationExtension`] of its response.
+    pub(crate) fn from_extension(inner: S, sink: Arc<dyn MetricsSink>) -> Self {
+        Self {
+            inner,
+            operation: None,
+            sink,
+        }
+    }
+}
+
+impl<S, B, RespB> Service<Request<B>> for MetricsService<S>
+where
+    S: Service<Request<B>, Response = Response<RespB>>,
+{
+    type Response = Response<RespB>;
+    type Error = S::Error;
+    type Future = MetricsFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        MetricsFuture {
+            inner: self.inner.call(request),
+            start: Instant::now(),
+            operation: self.operation.clone(),
+            sink: self.sink.clone(),
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The [`Service::Future`] of [`MetricsService`].
+    pub struct MetricsFuture<Fut> {
+        #[pin]
+        inner: Fut,
+        start: Instant,
+        operation: Option<ShapeId>,
+        sink: Arc<dyn MetricsSink>,
+    }
+}
+
+impl<Fut> std::fmt::Debug for MetricsFuture<Fut> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsFuture")
+            .field("start", &self.start)
+            .field("operation", &self.operation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Fut, RespB, E> Future for MetricsFuture<Fut>
+where
+    Fut: Future<Output = Result<Response<RespB>, E>>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx));
+        if let Ok(response) = &result {
+            let extensions = response.extensions();
+            let outcome = if let Some(error) = extensions.get::<ModeledErrorExtension>() {
+                Outcome::ModeledError(**error)
+            } else if let Some(error) = extensions.get::<RuntimeErrorExtension>() {
+                Outcome::RuntimeError((**error).clone())
+            } else {
+                Outcome::Success
+            };
+            let operation = this.operation.take().or_else(|| {
+                extensions
+                    .get::<OperationExtension>()
+                    .map(|operation| operation.0.clone())
+            });
+            this.sink.record(&RequestMetrics {
+                operation,
+                status: response.status(),
+                latency: this.start.elapsed(),
+                outcome,
+            });
+        }
+        Poll::Ready(result)
+    }
+}
@@ -5,7 +5,10 @@
 
 use crate::protocol::aws_json_11::AwsJson1_1;
 use crate::response::IntoResponse;
-use crate::runtime_error::{InternalFailureException, INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE};
+use crate::runtime_error::{
+    exception_response, InternalFailureException, RequestTimeoutException, ThrottlingException,
+    UnsupportedMediaTypeException, ValidationException, INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE,
+};
 use crate::{extension::RuntimeErrorExtension, protocol::aws_json_10::AwsJson1_0};
 use http::StatusCode;
 
@@ -58,12 +61,92 @@ impl IntoResponse<AwsJson1_0> for InternalFailureException {
     }
 }
 
+impl IntoResponse<AwsJson1_0> for UnsupportedMediaTypeException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        IntoResponse::<AwsJson1_0>::into_response(RuntimeError::UnsupportedMediaType)
+    }
+}
+
 impl IntoResponse<AwsJson1_1> for InternalFailureException {
     fn into_response(self) -> http::Response<crate::body::BoxBody> {
         IntoResponse::<AwsJson1_1>::into_response(RuntimeError::InternalFailure(crate::Error::new(String::new())))
     }
 }
 
+impl IntoResponse<AwsJson1_1> for UnsupportedMediaTypeException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        IntoResponse::<AwsJson1_1>::into_response(RuntimeError::UnsupportedMediaType)
+    }
+}
+
+impl IntoResponse<AwsJson1_0> for ValidationException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::BAD_REQUEST,
+            "application/x-amz-json-1.0",
+            "ValidationException",
+            self.to_json(),
+        )
+    }
+}
+
+impl IntoResponse<AwsJson1_1> for ValidationException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::BAD_REQUEST,
+            "application/x-amz-json-1.1",
+            "ValidationException",
+            self.to_json(),
+        )
+    }
+}
+
+impl IntoResponse<AwsJson1_0> for ThrottlingException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        let response = exception_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "application/x-amz-json-1.0",
+            "ThrottlingException",
+            self.to_json(),
+        );
+        self.with_retry_after_header(response)
+    }
+}
+
+impl IntoResponse<AwsJson1_1> for ThrottlingException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        let response = exception_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "application/x-amz-json-1.1",
+            "ThrottlingException",
+            self.to_json(),
+        );
+        self.with_retry_after_header(response)
+    }
+}
+
+impl IntoResponse<AwsJson1_0> for RequestTimeoutException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::GATEWAY_TIMEOUT,
+            "application/x-amz-json-1.0",
+            "RequestTimeoutException",
+            self.to_json(),
+        )
+    }
+}
+
+impl IntoResponse<AwsJson1_1> for RequestTimeoutException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::GATEWAY_TIMEOUT,
+            "application/x-amz-json-1.1",
+            "RequestTimeoutException",
+            self.to_json(),
+        )
+    }
+}
+
 impl IntoResponse<AwsJson1_0> for RuntimeError {
     fn into_response(self) -> http::Response<crate::body::BoxBody> {
         let res = http::Response::builder()
@@ -34,8 +34,11 @@ use super::rejection::ResponseRejection;
 use super::RestJson1;
 use crate::extension::RuntimeErrorExtension;
 use crate::response::IntoResponse;
-use crate::runtime_error::InternalFailureException;
-use crate::runtime_error::INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE;
+use crate::runtime_error::{exception_response, INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE};
+use crate::runtime_error::{
+    InternalFailureException, RequestTimeoutException, ThrottlingException, UnsupportedMediaTypeException,
+    ValidationException,
+};
 use http::StatusCode;
 
 #[derive(Debug, thiserror::Error)]
@@ -94,6 +97,45 @@ impl IntoResponse<RestJson1> for InternalFailureException {
     }
 }
 
+impl IntoResponse<RestJson1> for UnsupportedMediaTypeException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        IntoResponse::<RestJson1>::into_response(RuntimeError::UnsupportedMediaType)
+    }
+}
+
+/// Renders an exception raised by a middleware like a [`RuntimeError`], including the `X-Amzn-Errortype` header.
+fn rest_json_exception_response(
+    status: StatusCode,
+    name: &'static str,
+    body: String,
+) -> http::Response<crate::body::BoxBody> {
+    let mut response = exception_response(status, "application/json", name, body);
+    response
+        .headers_mut()
+        .insert("x-amzn-errortype", http::HeaderValue::from_static(name));
+    response
+}
+
+impl IntoResponse<RestJson1> for ValidationException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        rest_json_exception_response(StatusCode::BAD_REQUEST, "ValidationException", self.to_json())
+    }
+}
+
+impl IntoResponse<RestJson1> for ThrottlingException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        let response =
+            rest_json_exception_response(StatusCode::TOO_MANY_REQUESTS, "ThrottlingException", self.to_json());
+        self.with_retry_after_header(response)
+    }
+}
+
+impl IntoResponse<RestJson1> for RequestTimeoutException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        rest_json_exception_response(StatusCode::GATEWAY_TIMEOUT, "RequestTimeoutException", self.to_json())
+    }
+}
+
 impl IntoResponse<RestJson1> for RuntimeError {
     fn into_response(self) -> http::Response<crate::body::BoxBody> {
         let res = http::Response::builder()
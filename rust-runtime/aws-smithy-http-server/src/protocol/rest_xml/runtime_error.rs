@@ -5,7 +5,10 @@
 
 use crate::protocol::rest_xml::RestXml;
 use crate::response::IntoResponse;
-use crate::runtime_error::InternalFailureException;
+use crate::runtime_error::{
+    exception_response, InternalFailureException, RequestTimeoutException, ThrottlingException,
+    UnsupportedMediaTypeException, ValidationException,
+};
 use crate::{extension::RuntimeErrorExtension, runtime_error::INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE};
 use http::StatusCode;
 
@@ -58,6 +61,46 @@ impl IntoResponse<RestXml> for InternalFailureException {
     }
 }
 
+impl IntoResponse<RestXml> for UnsupportedMediaTypeException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        IntoResponse::<RestXml>::into_response(RuntimeError::UnsupportedMediaType)
+    }
+}
+
+impl IntoResponse<RestXml> for ValidationException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::BAD_REQUEST,
+            "application/xml",
+            "ValidationException",
+            self.to_xml(),
+        )
+    }
+}
+
+impl IntoResponse<RestXml> for ThrottlingException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        let response = exception_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "application/xml",
+            "ThrottlingException",
+            self.to_xml(),
+        );
+        self.with_retry_after_header(response)
+    }
+}
+
+impl IntoResponse<RestXml> for RequestTimeoutException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::GATEWAY_TIMEOUT,
+            "application/xml",
+            "RequestTimeoutException",
+            self.to_xml(),
+        )
+    }
+}
+
 impl IntoResponse<RestXml> for RuntimeError {
     fn into_response(self) -> http::Response<crate::body::BoxBody> {
         let res = http::Response::builder()
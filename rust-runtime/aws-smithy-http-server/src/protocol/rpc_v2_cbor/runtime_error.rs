@@ -4,7 +4,10 @@
  */
 
 use crate::response::IntoResponse;
-use crate::runtime_error::{InternalFailureException, INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE};
+use crate::runtime_error::{
+    exception_response, InternalFailureException, RequestTimeoutException, ThrottlingException,
+    UnsupportedMediaTypeException, ValidationException, INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE,
+};
 use crate::{extension::RuntimeErrorExtension, protocol::rpc_v2_cbor::RpcV2Cbor};
 use bytes::Bytes;
 use http::StatusCode;
@@ -60,6 +63,46 @@ impl IntoResponse<RpcV2Cbor> for InternalFailureException {
     }
 }
 
+impl IntoResponse<RpcV2Cbor> for UnsupportedMediaTypeException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        IntoResponse::<RpcV2Cbor>::into_response(RuntimeError::UnsupportedMediaType)
+    }
+}
+
+impl IntoResponse<RpcV2Cbor> for ValidationException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::BAD_REQUEST,
+            "application/cbor",
+            "ValidationException",
+            self.to_cbor(),
+        )
+    }
+}
+
+impl IntoResponse<RpcV2Cbor> for ThrottlingException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        let response = exception_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "application/cbor",
+            "ThrottlingException",
+            self.to_cbor(),
+        );
+        self.with_retry_after_header(response)
+    }
+}
+
+impl IntoResponse<RpcV2Cbor> for RequestTimeoutException {
+    fn into_response(self) -> http::Response<crate::body::BoxBody> {
+        exception_response(
+            StatusCode::GATEWAY_TIMEOUT,
+            "application/cbor",
+            "RequestTimeoutException",
+            self.to_cbor(),
+        )
+    }
+}
+
 impl IntoResponse<RpcV2Cbor> for RuntimeError {
     fn into_response(self) -> http::Response<crate::body::BoxBody> {
         let res = http::Response::builder()
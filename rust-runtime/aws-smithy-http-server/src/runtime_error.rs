@@ -10,4 +10,200 @@
 /// [`crate::protocol::rest_json_1::runtime_error::RuntimeError::InternalFailure`] variant.
 pub struct InternalFailureException;
 
+/// A _protocol-agnostic_ type representing a request that was rejected by a middleware because its
+/// `Content-Encoding` is not supported. It is converted into the protocol-specific
+/// `RuntimeError::UnsupportedMediaType` variant, for example
+/// [`crate::protocol::rest_json_1::runtime_error::RuntimeError::UnsupportedMediaType`].
+pub struct UnsupportedMediaTypeException;
+
 pub const INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE: &str = "invalid HTTP response for `RuntimeError`; please file a bug report under https://github.com/smithy-lang/smithy-rs/issues";
+
+/// Renders the response of an exception raised by a middleware, the way the protocol renders its `RuntimeError`s:
+/// with the given status code and `Content-Type`, and a [`RuntimeErrorExtension`] named after the exception.
+///
+/// Middleware exceptions are not `RuntimeError` variants, since adding variants to those enums would break exhaustive
+/// `match`es downstream.
+///
+/// [`RuntimeErrorExtension`]: crate::extension::RuntimeErrorExtension
+pub(crate) fn exception_response<B>(
+    status: http::StatusCode,
+    content_type: &'static str,
+    name: &'static str,
+    body: B,
+) -> http::Response<crate::body::BoxBody>
+where
+    crate::body::Body: From<B>,
+{
+    http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, content_type)
+        .extension(crate::extension::RuntimeErrorExtension::new(name.to_string()))
+        .body(crate::body::to_boxed(body))
+        .expect(INVALID_HTTP_RESPONSE_FOR_RUNTIME_ERROR_PANIC_MESSAGE)
+}
+
+/// A _protocol-agnostic_ type representing a request that was rejected by a middleware because its
+/// contents are invalid, for example because its checksum does not match. It is rendered like the
+/// protocol-specific `RuntimeError::Validation` variant, so that clients receive a `ValidationException`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationException {
+    message: String,
+}
+
+impl ValidationException {
+    /// Creates a new [`ValidationException`] with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// Returns the message describing why the request is invalid.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Serializes this exception as the JSON body of a `smithy.framework#ValidationException`.
+    pub(crate) fn to_json(&self) -> String {
+        error_json("ValidationException", &self.message)
+    }
+
+    /// Serializes this exception as the XML body of a `ValidationException`, wrapped in an `ErrorResponse`.
+    pub(crate) fn to_xml(&self) -> String {
+        error_xml("Sender", "ValidationException", &self.message)
+    }
+
+    /// Serializes this exception as the CBOR body of a `ValidationException`.
+    pub(crate) fn to_cbor(&self) -> Vec<u8> {
+        error_cbor("ValidationException", &self.message)
+    }
+}
+
+/// A _protocol-agnostic_ type representing a request that was rejected by a middleware because the caller exceeded
+/// its allowed request rate or concurrency. It is rendered with [`exception_response`] in the protocol's error format,
+/// so that clients receive a `ThrottlingException` with a `429 Too Many Requests` status code, which they retry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrottlingException {
+    message: String,
+    retry_after: Option<std::time::Duration>,
+}
+
+impl ThrottlingException {
+    /// Creates a new [`ThrottlingException`] with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Tells the client how long to wait before retrying, with a `Retry-After` header.
+    pub fn retry_after(mut self, retry_after: std::time::Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// Returns the message describing why the request was throttled.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns how long the client is told to wait before retrying, if set.
+    pub fn get_retry_after(&self) -> Option<std::time::Duration> {
+        self.retry_after
+    }
+
+    /// Serializes this exception as the JSON body of a `ThrottlingException`.
+    pub(crate) fn to_json(&self) -> String {
+        error_json("ThrottlingException", &self.message)
+    }
+
+    /// Serializes this exception as the XML body of a `ThrottlingException`, wrapped in an `ErrorResponse`.
+    pub(crate) fn to_xml(&self) -> String {
+        error_xml("Sender", "ThrottlingException", &self.message)
+    }
+
+    /// Serializes this exception as the CBOR body of a `ThrottlingException`.
+    pub(crate) fn to_cbor(&self) -> Vec<u8> {
+        error_cbor("ThrottlingException", &self.message)
+    }
+
+    /// Adds the `Retry-After` header to a response rendered from this exception, rounded up to whole seconds.
+    pub(crate) fn with_retry_after_header(
+        &self,
+        mut response: http::Response<crate::body::BoxBody>,
+    ) -> http::Response<crate::body::BoxBody> {
+        if let Some(retry_after) = self.retry_after {
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(http::header::RETRY_AFTER, http::HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+/// A _protocol-agnostic_ type representing a request that was cancelled by a middleware because its operation didn't
+/// respond within the configured deadline. It is rendered with [`exception_response`] in the protocol's error format, so
+/// that clients receive a `RequestTimeoutException` with a `504 Gateway Timeout` status code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTimeoutException {
+    message: String,
+}
+
+impl RequestTimeoutException {
+    /// Creates a new [`RequestTimeoutException`] with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// Returns the message describing why the request timed out.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Serializes this exception as the JSON body of a `RequestTimeoutException`.
+    pub(crate) fn to_json(&self) -> String {
+        error_json("RequestTimeoutException", &self.message)
+    }
+
+    /// Serializes this exception as the XML body of a `RequestTimeoutException`, wrapped in an `ErrorResponse`.
+    pub(crate) fn to_xml(&self) -> String {
+        error_xml("Receiver", "RequestTimeoutException", &self.message)
+    }
+
+    /// Serializes this exception as the CBOR body of a `RequestTimeoutException`.
+    pub(crate) fn to_cbor(&self) -> Vec<u8> {
+        error_cbor("RequestTimeoutException", &self.message)
+    }
+}
+
+fn error_json(name: &str, message: &str) -> String {
+    let mut out = String::new();
+    let mut object = aws_smithy_json::serialize::JsonObjectWriter::new(&mut out);
+    object.key("__type").string(name);
+    object.key("message").string(message);
+    object.finish();
+    out
+}
+
+fn error_xml(fault: &str, name: &str, message: &str) -> String {
+    let mut out = String::new();
+    let mut writer = aws_smithy_xml::encode::XmlWriter::new(&mut out);
+    let mut error_response = writer.start_el("ErrorResponse").finish();
+    let mut error = error_response.start_el("Error").finish();
+    error.start_el("Type").finish().data(fault);
+    error.start_el("Code").finish().data(name);
+    error.start_el("Message").finish().data(message);
+    error.finish();
+    error_response.finish();
+    out
+}
+
+fn error_cbor(name: &str, message: &str) -> Vec<u8> {
+    let mut encoder = aws_smithy_cbor::Encoder::new(Vec::new());
+    encoder.map(2).str("__type").str(name).str("message").str(message);
+    encoder.into_writer()
+}
@@ -0,0 +1,754 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Graceful shutdown for servers.
+//!
+//! When a server is asked to terminate, [`GracefulShutdown`]:
+//!
+//! 1. marks the server as not ready, so that [`AlbHealthCheckLayer::from_readiness`] health checks fail and load
+//!    balancers stop routing new requests to it,
+//! 2. optionally waits for a [readiness delay](GracefulShutdown::readiness_delay), to give load balancers time to
+//!    notice,
+//! 3. stops accepting connections and lets in-flight requests complete, including long-lived streaming responses
+//!    such as event streams,
+//! 4. cuts off the requests that are still running when the [drain timeout](GracefulShutdown::drain_timeout)
+//!    elapses, and returns a [`ShutdownReport`] listing them.
+//!
+//! Connections that still haven't closed a [cut-off timeout](GracefulShutdown::cut_off_timeout) after the requests
+//! were cut off, such as connections to clients that stopped reading a response, are dropped.
+//!
+//! Requests are only tracked if the service is wrapped in the [`GracefulShutdownLayer`] returned by
+//! [`GracefulShutdown::layer`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::convert::Infallible;
+//! # use std::time::Duration;
+//! # use aws_smithy_http_server::body::{boxed, BoxBody};
+//! use aws_smithy_http_server::layer::alb_health_check::AlbHealthCheckLayer;
+//! use aws_smithy_http_server::routing::IntoMakeService;
+//! use aws_smithy_http_server::shutdown::GracefulShutdown;
+//! use tower::Layer;
+//!
+//! # async fn run() -> Result<(), hyper::Error> {
+//! # let app = tower::service_fn(|_req: http::Request<hyper::Body>| async {
+//! #     Ok::<_, Infallible>(http::Response::new(boxed(hyper::Body::empty())))
+//! # });
+//! let shutdown = GracefulShutdown::new().drain_timeout(Duration::from_secs(30));
+//! let app = shutdown.layer().layer(app);
+//! let app = AlbHealthCheckLayer::from_readiness("/ping", shutdown.readiness()).layer(app);
+//!
+//! let server = hyper::Server::bind(&"0.0.0.0:8080".parse().unwrap()).serve(IntoMakeService::new(app));
+//! let report = shutdown.serve(|drain| server.with_graceful_shutdown(drain)).await?;
+//! for request in report.cut_off_requests() {
+//!     eprintln!("cut off {} {}", request.method(), request.uri());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`AlbHealthCheckLayer::from_readiness`]: crate::layer::alb_health_check::AlbHealthCheckLayer::from_readiness
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, Request, Response, StatusCode, Uri};
+use http_body::{Body as HttpBody, SizeHint};
+use tokio::sync::{oneshot, watch};
+use tokio::time::Instant;
+use tower::{Layer, Service};
+
+use crate::body::{boxed, BoxBody};
+
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_CUT_OFF_TIMEOUT: Duration = Duration::from_secs(5);
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Whether a server is ready to receive requests.
+///
+/// Clones share the same state. A server is ready until a [`GracefulShutdown`] starts draining it.
+#[derive(Clone, Debug)]
+pub struct Readiness {
+    ready: Arc<AtomicBool>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self {
+            ready: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+impl Readiness {
+    /// Creates a new `Readiness` that is ready.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the server is ready to receive requests.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Relaxed)
+    }
+
+    /// Marks the server as ready or not ready to receive requests.
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, Ordering::Relaxed);
+    }
+}
+
+/// Serves requests until a termination signal is received, and then drains in-flight requests.
+///
+/// See the [module documentation](self) for details.
+pub struct GracefulShutdown {
+    drain_timeout: Duration,
+    cut_off_timeout: Duration,
+    readiness_delay: Duration,
+    signal: Option<BoxFuture<()>>,
+    readiness: Readiness,
+    tracker: Tracker,
+}
+
+impl fmt::Debug for GracefulShutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GracefulShutdown")
+            .field("drain_timeout", &self.drain_timeout)
+            .field("cut_off_timeout", &self.cut_off_timeout)
+            .field("readiness_delay", &self.readiness_delay)
+            .field("readiness", &self.readiness)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for GracefulShutdown {
+    fn default() -> Self {
+        Self {
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            cut_off_timeout: DEFAULT_CUT_OFF_TIMEOUT,
+            readiness_delay: Duration::ZERO,
+            signal: None,
+            readiness: Readiness::new(),
+            tracker: Tracker::default(),
+        }
+    }
+}
+
+impl GracefulShutdown {
+    /// Creates a new `GracefulShutdown` that is triggered by `SIGTERM` or `Ctrl-C`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long in-flight requests may take to complete once the server stops accepting connections.
+    ///
+    /// Requests that are still running after this are cut off. Defaults to 30 seconds.
+    pub fn drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
+    /// How long to wait for connections to close once in-flight requests are cut off.
+    ///
+    /// A connection may not close when requests are cut off, for example when its client stopped reading a response.
+    /// The server is dropped when this elapses, which closes the remaining connections. Defaults to 5 seconds.
+    pub fn cut_off_timeout(mut self, cut_off_timeout: Duration) -> Self {
+        self.cut_off_timeout = cut_off_timeout;
+        self
+    }
+
+    /// How long to keep accepting connections after the server is marked as not ready.
+    ///
+    /// This gives load balancers time to notice failing health checks before connections are refused.
+    /// Defaults to zero.
+    pub fn readiness_delay(mut self, readiness_delay: Duration) -> Self {
+        self.readiness_delay = readiness_delay;
+        self
+    }
+
+    /// Triggers the shutdown when `signal` completes, instead of on `SIGTERM` or `Ctrl-C`.
+    pub fn signal(mut self, signal: impl Future<Output = ()> + Send + 'static) -> Self {
+        self.signal = Some(Box::pin(signal));
+        self
+    }
+
+    /// Returns the server's [`Readiness`], which is flipped to not ready when the shutdown starts.
+    pub fn readiness(&self) -> Readiness {
+        self.readiness.clone()
+    }
+
+    /// Returns a layer that tracks in-flight requests, so that they can be drained.
+    pub fn layer(&self) -> GracefulShutdownLayer {
+        GracefulShutdownLayer {
+            tracker: self.tracker.clone(),
+        }
+    }
+
+    /// Runs the server returned by `serve` until the shutdown completes.
+    ///
+    /// `serve` is given a [`DrainSignal`] that completes when the server should stop accepting connections, such as
+    /// the signal given to [`hyper::Server::with_graceful_shutdown`].
+    pub async fn serve<F, E>(self, serve: impl FnOnce(DrainSignal) -> F) -> Result<ShutdownReport, E>
+    where
+        F: Future<Output = Result<(), E>>,
+    {
+        let (drain_tx, drain_rx) = oneshot::channel();
+        let server = serve(DrainSignal(drain_rx));
+        tokio::pin!(server);
+
+        let signal = self.signal.unwrap_or_else(|| Box::pin(termination_signal()));
+        tokio::select! {
+            result = &mut server => return result.map(|_| ShutdownReport::default()),
+            _ = signal => {}
+        }
+        tracing::info!("shutdown signal received, the server is no longer ready");
+        self.readiness.set_ready(false);
+        if !self.readiness_delay.is_zero() {
+            tokio::select! {
+                result = &mut server => return result.map(|_| ShutdownReport::default()),
+                _ = tokio::time::sleep(self.readiness_delay) => {}
+            }
+        }
+
+        tracing::info!(drain_timeout = ?self.drain_timeout, "draining in-flight requests");
+        let drain_start = Instant::now();
+        self.tracker.start_draining();
+        let _ = drain_tx.send(());
+        let mut cut_off = Vec::new();
+        tokio::select! {
+            result = &mut server => result?,
+            _ = tokio::time::sleep(self.drain_timeout) => {
+                cut_off = self.tracker.cut_off();
+                tracing::warn!(count = cut_off.len(), "drain timeout elapsed, cutting off in-flight requests");
+                match tokio::time::timeout(self.cut_off_timeout, server).await {
+                    Ok(result) => result?,
+                    Err(_) => tracing::warn!(
+                        cut_off_timeout = ?self.cut_off_timeout,
+                        "connections didn't close after in-flight requests were cut off, dropping them"
+                    ),
+                }
+            }
+        }
+        let report = ShutdownReport {
+            drained_requests: self.tracker.drained(),
+            cut_off,
+            drain_time: drain_start.elapsed(),
+        };
+        tracing::info!(
+            drained_requests = report.drained_requests,
+            cut_off_requests = report.cut_off.len(),
+            drain_time = ?report.drain_time,
+            "shutdown complete"
+        );
+        Ok(report)
+    }
+}
+
+/// Completes when the server should stop accepting connections.
+///
+/// Returned by [`GracefulShutdown::serve`].
+#[derive(Debug)]
+pub struct DrainSignal(oneshot::Receiver<()>);
+
+impl Future for DrainSignal {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // If the sender is dropped, `GracefulShutdown::serve` was cancelled, so stop the server as well.
+        Pin::new(&mut self.0).poll(cx).map(|_| ())
+    }
+}
+
+async fn termination_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// The outcome of a [`GracefulShutdown`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    drained_requests: u64,
+    cut_off: Vec<CutOffRequest>,
+    drain_time: Duration,
+}
+
+impl ShutdownReport {
+    /// The number of in-flight requests that completed while the server was draining.
+    pub fn drained_requests(&self) -> u64 {
+        self.drained_requests
+    }
+
+    /// The requests that were cut off because they didn't complete before the drain timeout.
+    pub fn cut_off_requests(&self) -> &[CutOffRequest] {
+        &self.cut_off
+    }
+
+    /// How long draining took.
+    pub fn drain_time(&self) -> Duration {
+        self.drain_time
+    }
+}
+
+/// A request that was cut off by a [`GracefulShutdown`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct CutOffRequest {
+    method: Method,
+    uri: Uri,
+    stage: RequestStage,
+    running_for: Duration,
+}
+
+impl CutOffRequest {
+    /// The request's method.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The request's URI.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// What the request was doing when it was cut off.
+    pub fn stage(&self) -> RequestStage {
+        self.stage
+    }
+
+    /// How long the request had been running when it was cut off.
+    pub fn running_for(&self) -> Duration {
+        self.running_for
+    }
+}
+
+/// The stage of an in-flight request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RequestStage {
+    /// The operation handler hadn't returned a response yet. The client receives a `503 Service Unavailable`.
+    Handler,
+    /// The response body, such as an event stream, was still being sent. The connection is closed.
+    ResponseBody,
+}
+
+#[derive(Debug)]
+struct InFlight {
+    method: Method,
+    uri: Uri,
+    stage: RequestStage,
+    started: Instant,
+}
+
+#[derive(Debug)]
+struct TrackerState {
+    next_id: AtomicU64,
+    in_flight: Mutex<HashMap<u64, InFlight>>,
+    draining: AtomicBool,
+    drained: AtomicU64,
+    cut_off: watch::Sender<bool>,
+}
+
+#[derive(Clone, Debug)]
+struct Tracker(Arc<TrackerState>);
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self(Arc::new(TrackerState {
+            next_id: AtomicU64::new(0),
+            in_flight: Default::default(),
+            draining: AtomicBool::new(false),
+            drained: AtomicU64::new(0),
+            cut_off: watch::channel(false).0,
+        }))
+    }
+}
+
+impl Tracker {
+    fn start<B>(&self, request: &Request<B>) -> RequestGuard {
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed);
+        self.0.in_flight.lock().unwrap().insert(
+            id,
+            InFlight {
+                method: request.method().clone(),
+                uri: request.uri().clone(),
+                stage: RequestStage::Handler,
+                started: Instant::now(),
+            },
+        );
+        RequestGuard {
+            tracker: self.clone(),
+            id,
+        }
+    }
+
+    fn start_draining(&self) {
+        self.0.draining.store(true, Ordering::Relaxed);
+    }
+
+    fn drained(&self) -> u64 {
+        self.0.drained.load(Ordering::Relaxed)
+    }
+
+    /// Cuts off all in-flight requests, and returns them.
+    fn cut_off(&self) -> Vec<CutOffRequest> {
+        let in_flight = self.0.in_flight.lock().unwrap();
+        let now = Instant::now();
+        let cut_off = in_flight
+            .values()
+            .map(|request| CutOffRequest {
+                method: request.method.clone(),
+                uri: request.uri.clone(),
+                stage: request.stage,
+                running_for: now.duration_since(request.started),
+            })
+            .collect();
+        self.0.cut_off.send_replace(true);
+        cut_off
+    }
+
+    /// Completes when in-flight requests should be cut off.
+    fn cut_off_signal(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut cut_off = self.0.cut_off.subscribe();
+        async move {
+            while !*cut_off.borrow_and_update() {
+                if cut_off.changed().await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            }
+        }
+    }
+}
+
+/// Stops tracking a request when dropped.
+#[derive(Debug)]
+struct RequestGuard {
+    tracker: Tracker,
+    id: u64,
+}
+
+impl RequestGuard {
+    fn set_stage(&self, stage: RequestStage) {
+        if let Some(request) = self.tracker.0.in_flight.lock().unwrap().get_mut(&self.id) {
+            request.stage = stage;
+        }
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        let state = &self.tracker.0;
+        state.in_flight.lock().unwrap().remove(&self.id);
+        if state.draining.load(Ordering::Relaxed) && !*state.cut_off.borrow() {
+            state.drained.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A [`Layer`] that applies [`GracefulShutdownService`]. Returned by [`GracefulShutdown::layer`].
+#[derive(Clone, Debug)]
+pub struct GracefulShutdownLayer {
+    tracker: Tracker,
+}
+
+impl<S> Layer<S> for GracefulShutdownLayer {
+    type Service = GracefulShutdownService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GracefulShutdownService {
+            inner,
+            tracker: self.tracker.clone(),
+        }
+    }
+}
+
+/// A middleware [`Service`] that tracks in-flight requests for a [`GracefulShutdown`], and cuts them off when the
+/// drain timeout elapses.
+#[derive(Clone, Debug)]
+pub struct GracefulShutdownService<S> {
+    inner: S,
+    tracker: Tracker,
+}
+
+impl<S, B> Service<Request<B>> for GracefulShutdownService<S>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>>,
+    S::Future: Send + 'static,
+    S::Error: 'static,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let guard = self.tracker.start(&request);
+        let response = self.inner.call(request);
+        let mut cut_off = Box::pin(self.tracker.cut_off_signal());
+        Box::pin(async move {
+            tokio::select! {
+                response = response => {
+                    let response = response?;
+                    if response.body().is_end_stream() {
+                        return Ok(response);
+                    }
+                    guard.set_stage(RequestStage::ResponseBody);
+                    Ok(response.map(|body| boxed(TrackedBody { inner: body, cut_off, guard })))
+                }
+                _ = &mut cut_off => {
+                    let mut response = Response::new(crate::body::empty());
+                    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                    response
+                        .headers_mut()
+                        .insert(http::header::CONNECTION, http::HeaderValue::from_static("close"));
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+/// A response body that fails when in-flight requests are cut off, which closes the connection.
+struct TrackedBody {
+    inner: BoxBody,
+    cut_off: BoxFuture<()>,
+    // Stops tracking the request when the body is dropped
+    #[allow(dead_code)]
+    guard: RequestGuard,
+}
+
+#[derive(Debug)]
+struct CutOffError;
+
+impl fmt::Display for CutOffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the response was cut off because the server's drain timeout elapsed")
+    }
+}
+
+impl std::error::Error for CutOffError {}
+
+impl HttpBody for TrackedBody {
+    type Data = Bytes;
+    type Error = crate::Error;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        if self.cut_off.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Some(Err(crate::Error::new(CutOffError))));
+        }
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::IntoMakeService;
+    use hyper::Body;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use tokio::sync::Notify;
+
+    /// Serves `handler` with a graceful shutdown that is triggered by sending on the returned channel.
+    fn serve<H, F>(
+        shutdown: GracefulShutdown,
+        handler: H,
+    ) -> (
+        SocketAddr,
+        oneshot::Sender<()>,
+        tokio::task::JoinHandle<Result<ShutdownReport, hyper::Error>>,
+    )
+    where
+        H: Fn(Request<Body>) -> F + Clone + Send + 'static,
+        F: Future<Output = Response<BoxBody>> + Send + 'static,
+    {
+        let (signal_tx, signal_rx) = oneshot::channel::<()>();
+        let shutdown = shutdown.signal(async move {
+            let _ = signal_rx.await;
+        });
+        let app = tower::service_fn(move |request| {
+            let response = handler(request);
+            async move { Ok::<_, Infallible>(response.await) }
+        });
+        let app = shutdown.layer().layer(app);
+        let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(IntoMakeService::new(app));
+        let addr = server.local_addr();
+        let task = tokio::spawn(shutdown.serve(|drain| server.with_graceful_shutdown(drain)));
+        (addr, signal_tx, task)
+    }
+
+    fn get(addr: SocketAddr) -> tokio::task::JoinHandle<Result<Response<Body>, hyper::Error>> {
+        tokio::spawn(hyper::Client::new().get(format!("http://{addr}/resource").parse().unwrap()))
+    }
+
+    #[tokio::test]
+    async fn in_flight_requests_are_drained() {
+        let started = Arc::new(Notify::new());
+        let shutdown = GracefulShutdown::new();
+        let readiness = shutdown.readiness();
+        let (addr, signal, server) = serve(shutdown, {
+            let started = started.clone();
+            move |_| {
+                let started = started.clone();
+                async move {
+                    started.notify_one();
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    Response::new(boxed(Body::from("done")))
+                }
+            }
+        });
+
+        let response = get(addr);
+        started.notified().await;
+        assert!(readiness.is_ready());
+        signal.send(()).unwrap();
+
+        let response = response.await.unwrap().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!("done", hyper::body::to_bytes(response.into_body()).await.unwrap());
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(1, report.drained_requests());
+        assert!(report.cut_off_requests().is_empty());
+        assert!(!readiness.is_ready());
+    }
+
+    #[tokio::test]
+    async fn streams_are_cut_off_after_the_drain_timeout() {
+        let senders = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = GracefulShutdown::new().drain_timeout(Duration::from_millis(100));
+        let (addr, signal, server) = serve(shutdown, {
+            let senders = senders.clone();
+            move |_| {
+                // Keep the stream open by holding on to its sender
+                let (sender, body) = Body::channel();
+                senders.lock().unwrap().push(sender);
+                async move { Response::new(boxed(body)) }
+            }
+        });
+
+        let response = get(addr).await.unwrap().unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+        signal.send(()).unwrap();
+
+        assert!(hyper::body::to_bytes(response.into_body()).await.is_err());
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(0, report.drained_requests());
+        let cut_off = report.cut_off_requests();
+        assert_eq!(1, cut_off.len());
+        assert_eq!(RequestStage::ResponseBody, cut_off[0].stage());
+        assert_eq!(Method::GET, *cut_off[0].method());
+        assert_eq!("/resource", cut_off[0].uri().path());
+        assert!(cut_off[0].running_for() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn slow_handlers_are_cut_off_after_the_drain_timeout() {
+        let started = Arc::new(Notify::new());
+        let shutdown = GracefulShutdown::new().drain_timeout(Duration::from_millis(100));
+        let (addr, signal, server) = serve(shutdown, {
+            let started = started.clone();
+            move |_| {
+                let started = started.clone();
+                async move {
+                    started.notify_one();
+                    std::future::pending::<()>().await;
+                    unreachable!()
+                }
+            }
+        });
+
+        let response = get(addr);
+        started.notified().await;
+        signal.send(()).unwrap();
+
+        let response = response.await.unwrap().unwrap();
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(1, report.cut_off_requests().len());
+        assert_eq!(RequestStage::Handler, report.cut_off_requests()[0].stage());
+    }
+
+    #[tokio::test]
+    async fn connections_are_dropped_when_clients_stop_reading() {
+        use tokio::io::AsyncWriteExt;
+
+        let started = Arc::new(Notify::new());
+        let shutdown = GracefulShutdown::new()
+            .drain_timeout(Duration::from_millis(100))
+            .cut_off_timeout(Duration::from_millis(100));
+        let (addr, signal, server) = serve(shutdown, {
+            let started = started.clone();
+            move |_| {
+                let started = started.clone();
+                async move {
+                    started.notify_one();
+                    let chunks =
+                        futures_util::stream::repeat_with(|| Ok::<_, Infallible>(Bytes::from(vec![0; 64 * 1024])));
+                    Response::new(boxed(Body::wrap_stream(chunks)))
+                }
+            }
+        });
+
+        // The client never reads the response, so the server blocks on writing it once the socket buffers are full
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"GET /resource HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        started.notified().await;
+        signal.send(()).unwrap();
+
+        let report = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("the shutdown doesn't wait for the stalled connection")
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, report.cut_off_requests().len());
+        assert_eq!(RequestStage::ResponseBody, report.cut_off_requests()[0].stage());
+        drop(client);
+    }
+}
@@ -0,0 +1,43 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![deny(missing_docs, missing_debug_implementations)]
+
+//! Provides [`TimeoutPlugin`], which cancels the requests to each operation that don't get a response within a
+//! deadline.
+//!
+//! # Example
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use aws_smithy_http_server::plugin::HttpPlugins;
+//! # use aws_smithy_http_server::shape_id::ShapeId;
+//! # use aws_smithy_http_server::timeout::TimeoutPlugin;
+//! # const GET_STORAGE: ShapeId = ShapeId::new("com.aws.example#GetStorage", "com.aws.example", "GetStorage");
+//! let timeouts = TimeoutPlugin::new()
+//!     // Every operation must respond within 5 seconds...
+//!     .default_timeout(Duration::from_secs(5))
+//!     // ...except `GetStorage`, which has 30 seconds.
+//!     .operation_timeout(GET_STORAGE, Duration::from_secs(30));
+//! let http_plugins = HttpPlugins::new().push(timeouts);
+//! ```
+//!
+//! # Timed out requests
+//!
+//! When an operation doesn't respond in time, its handler future is dropped, cancelling the work it was doing at its
+//! next `.await`. The client gets a `RequestTimeoutException` with a `504 Gateway Timeout` status code, rendered
+//! according to the service's protocol.
+//!
+//! The deadline covers deserializing the request, running the handler and serializing the response. It doesn't cover
+//! sending the response body, so streaming responses, such as event streams, aren't cut off once they've started.
+//!
+//! Plugins pushed before [`TimeoutPlugin`] see timed out requests as `RequestTimeoutException` runtime errors, so
+//! push it after [`MetricsPlugin`](crate::metrics::MetricsPlugin) to record them.
+
+mod plugin;
+mod service;
+
+pub use plugin::*;
+pub use service::*;
@@ -0,0 +1,57 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::operation::OperationShape;
+use crate::plugin::{HttpMarker, Plugin};
+use crate::service::ServiceShape;
+use crate::shape_id::ShapeId;
+
+use super::TimeoutService;
+
+/// A [`Plugin`] which applies [`TimeoutService`] to every operation with a timeout.
+///
+/// See the [module](crate::timeout) documentation for more information.
+#[derive(Debug, Clone, Default)]
+pub struct TimeoutPlugin {
+    default_timeout: Option<Duration>,
+    operation_timeouts: HashMap<ShapeId, Duration>,
+}
+
+impl TimeoutPlugin {
+    /// Creates a new [`TimeoutPlugin`] which doesn't time out any operation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the timeout of the operations without a timeout of their own.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout of the operation with the given [`ShapeId`], instead of the default timeout.
+    pub fn operation_timeout(mut self, operation: ShapeId, timeout: Duration) -> Self {
+        self.operation_timeouts.insert(operation, timeout);
+        self
+    }
+}
+
+impl<Ser, Op, T> Plugin<Ser, Op, T> for TimeoutPlugin
+where
+    Ser: ServiceShape,
+    Op: OperationShape,
+{
+    type Output = TimeoutService<T, Ser::Protocol>;
+
+    fn apply(&self, inner: T) -> Self::Output {
+        let timeout = self.operation_timeouts.get(&Op::ID).or(self.default_timeout.as_ref());
+        TimeoutService::new(inner, Op::ID, timeout.copied())
+    }
+}
+
+impl HttpMarker for TimeoutPlugin {}
@@ -0,0 +1,330 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http::{Request, Response};
+use tokio::time::Sleep;
+use tower::Service;
+
+use crate::body::BoxBody;
+use crate::response::IntoResponse;
+use crate::runtime_error::RequestTimeoutException;
+use crate::shape_id::ShapeId;
+
+/// A middleware [`Service`] that cancels the requests its inner service doesn't respond to within a timeout.
+///
+/// Timed out requests get a `RequestTimeoutException` of the protocol `P`.
+///
+/// See the [module](crate::timeout) documentation for more information.
+pub struct TimeoutService<S, P> {
+    inner: S,
+    operation: ShapeId,
+    timeout: Option<Duration>,
+    _protocol: PhantomData<fn(P)>,
+}
+
+impl<S, P> TimeoutService<S, P> {
+    pub(crate) fn new(inner: S, operation: ShapeId, timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            operation,
+            timeout,
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<S, P> Clone for TimeoutService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            operation: self.operation.clone(),
+            timeout: self.timeout,
+            _protocol: PhantomData,
+        }
+    }
+}
+
+impl<S, P> std::fmt::Debug for TimeoutService<S, P>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutService")
+            .field("inner", &self.inner)
+            .field("operation", &self.operation)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<S, P, B> Service<Request<B>> for TimeoutService<S, P>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>>,
+    RequestTimeoutException: IntoResponse<P>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = TimeoutFuture<S::Future, P>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        TimeoutFuture {
+            inner: Inner::Running {
+                future: self.inner.call(request),
+                deadline: self.timeout.map(tokio::time::sleep),
+            },
+            operation: self.operation.clone(),
+            timeout: self.timeout,
+            _protocol: PhantomData,
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[project = InnerProj]
+    #[project_replace = InnerReplace]
+    enum Inner<Fut> {
+        Running {
+            #[pin]
+            future: Fut,
+            #[pin]
+            deadline: Option<Sleep>,
+        },
+        TimedOut,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The [`Service::Future`] of [`TimeoutService`].
+    pub struct TimeoutFuture<Fut, P> {
+        #[pin]
+        inner: Inner<Fut>,
+        operation: ShapeId,
+        timeout: Option<Duration>,
+        _protocol: PhantomData<fn(P)>,
+    }
+}
+
+impl<Fut, P> std::fmt::Debug for TimeoutFuture<Fut, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutFuture")
+            .field("operation", &self.operation)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Fut, P, E> Future for TimeoutFuture<Fut, P>
+where
+    Fut: Future<Output = Result<Response<BoxBody>, E>>,
+    RequestTimeoutException: IntoResponse<P>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        let InnerProj::Running { future, deadline } = this.inner.as_mut().project() else {
+            panic!("futures cannot be polled after completion");
+        };
+        if let Poll::Ready(response) = future.poll(cx) {
+            return Poll::Ready(response);
+        }
+        match deadline.as_pin_mut().map(|deadline| deadline.poll(cx)) {
+            Some(Poll::Ready(())) => {}
+            _ => return Poll::Pending,
+        }
+
+        // Dropping the inner future cancels the operation.
+        this.inner.project_replace(Inner::TimedOut);
+        tracing::debug!(
+            operation = %this.operation.absolute(),
+            timeout = ?this.timeout,
+            "cancelling request that exceeded the operation's timeout"
+        );
+        let exception = RequestTimeoutException::new("the operation did not respond in time");
+        Poll::Ready(Ok(exception.into_response()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{boxed, Body};
+    use crate::operation::OperationShape;
+    use crate::plugin::Plugin;
+    use crate::protocol::aws_json_10::AwsJson1_0;
+    use crate::protocol::rest_json_1::RestJson1;
+    use crate::protocol::rest_xml::RestXml;
+    use crate::protocol::rpc_v2_cbor::RpcV2Cbor;
+    use crate::service::ServiceShape;
+    use crate::timeout::TimeoutPlugin;
+    use http::StatusCode;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tower::{service_fn, ServiceExt};
+
+    struct TestService<P>(PhantomData<P>);
+
+    impl<P> ServiceShape for TestService<P> {
+        const ID: ShapeId = ShapeId::new("com.example#Service", "com.example", "Service");
+        const VERSION: Option<&'static str> = None;
+        type Protocol = P;
+        type Operations = ();
+    }
+
+    struct Slow;
+    struct Fast;
+
+    impl OperationShape for Slow {
+        const ID: ShapeId = ShapeId::new("com.example#Slow", "com.example", "Slow");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    impl OperationShape for Fast {
+        const ID: ShapeId = ShapeId::new("com.example#Fast", "com.example", "Fast");
+        type Input = ();
+        type Output = ();
+        type Error = ();
+    }
+
+    /// Sets a flag when dropped.
+    struct DropGuard(Arc<AtomicBool>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Responds after `delay`, and sets `dropped` if it's cancelled or completes.
+    fn sleepy(
+        delay: Duration,
+        dropped: Arc<AtomicBool>,
+    ) -> impl Service<Request<Body>, Response = Response<BoxBody>, Error = Infallible> + Clone {
+        service_fn(move |_request: Request<Body>| {
+            let guard = DropGuard(dropped.clone());
+            async move {
+                let _guard = guard;
+                tokio::time::sleep(delay).await;
+                Ok::<_, Infallible>(Response::new(boxed(Body::from("ok"))))
+            }
+        })
+    }
+
+    fn apply<P, Op, S>(plugin: &TimeoutPlugin, inner: S) -> TimeoutService<S, P>
+    where
+        Op: OperationShape,
+    {
+        Plugin::<TestService<P>, Op, S>::apply(plugin, inner)
+    }
+
+    async fn timed_out_response<P>() -> Response<BoxBody>
+    where
+        RequestTimeoutException: IntoResponse<P>,
+    {
+        let plugin = TimeoutPlugin::new().default_timeout(Duration::from_secs(1));
+        let inner = sleepy(Duration::from_secs(2), Default::default());
+        apply::<P, Slow, _>(&plugin, inner)
+            .oneshot(Request::new(Body::empty()))
+            .await
+            .unwrap()
+    }
+
+    async fn body_bytes(response: Response<BoxBody>) -> bytes::Bytes {
+        hyper::body::to_bytes(response.into_body()).await.unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_operations_are_cancelled() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let plugin = TimeoutPlugin::new().operation_timeout(Slow::ID, Duration::from_secs(1));
+        let svc = apply::<RestJson1, Slow, _>(&plugin, sleepy(Duration::from_secs(60), dropped.clone()));
+
+        let start = tokio::time::Instant::now();
+        let response = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(Duration::from_secs(1), start.elapsed());
+        assert!(dropped.load(Ordering::SeqCst));
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, response.status());
+        assert_eq!("RequestTimeoutException", response.headers()["x-amzn-errortype"]);
+        assert_eq!(
+            r#"{"__type":"RequestTimeoutException","message":"the operation did not respond in time"}"#,
+            body_bytes(response).await
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn operations_that_respond_in_time_are_not_affected() {
+        let plugin = TimeoutPlugin::new()
+            .default_timeout(Duration::from_secs(1))
+            .operation_timeout(Slow::ID, Duration::from_secs(10));
+
+        let svc = apply::<RestJson1, Slow, _>(&plugin, sleepy(Duration::from_secs(5), Default::default()));
+        let response = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+
+        let svc = apply::<RestJson1, Fast, _>(&plugin, sleepy(Duration::from_secs(5), Default::default()));
+        let response = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, response.status());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn operations_without_a_timeout_are_not_timed_out() {
+        let plugin = TimeoutPlugin::new().operation_timeout(Slow::ID, Duration::from_secs(1));
+        let svc = apply::<RestJson1, Fast, _>(&plugin, sleepy(Duration::from_secs(60), Default::default()));
+        let response = svc.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_timeout_exception_is_rendered_for_the_protocol() {
+        let response = timed_out_response::<RestXml>().await;
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, response.status());
+        assert_eq!("application/xml", response.headers()[http::header::CONTENT_TYPE]);
+        assert_eq!(
+            "<ErrorResponse><Error><Type>Receiver</Type><Code>RequestTimeoutException</Code>\
+             <Message>the operation did not respond in time</Message></Error></ErrorResponse>",
+            body_bytes(response).await
+        );
+
+        let response = timed_out_response::<AwsJson1_0>().await;
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, response.status());
+        assert_eq!(
+            "application/x-amz-json-1.0",
+            response.headers()[http::header::CONTENT_TYPE]
+        );
+        assert_eq!(
+            r#"{"__type":"RequestTimeoutException","message":"the operation did not respond in time"}"#,
+            body_bytes(response).await
+        );
+
+        let response = timed_out_response::<RpcV2Cbor>().await;
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, response.status());
+        assert_eq!("application/cbor", response.headers()[http::header::CONTENT_TYPE]);
+        let body = body_bytes(response).await;
+        let mut decoder = aws_smithy_cbor::Decoder::new(&body);
+        assert_eq!(Some(2), decoder.map().unwrap());
+        assert_eq!("__type", decoder.str().unwrap());
+        assert_eq!("RequestTimeoutException", decoder.str().unwrap());
+        assert_eq!("message", decoder.str().unwrap());
+        assert_eq!("the operation did not respond in time", decoder.str().unwrap());
+    }
+}
@@ -108,6 +108,8 @@ pub mod identity;
 
 pub mod interceptors;
 
+pub mod metrics;
+
 pub mod orchestrator;
 
 pub mod result;
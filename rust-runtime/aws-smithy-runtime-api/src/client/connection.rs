@@ -52,6 +52,11 @@ impl ConnectionMetadata {
         ConnectionMetadataBuilder::new()
     }
 
+    /// Returns true if this connection was made through a proxy.
+    pub fn is_proxied(&self) -> bool {
+        self.is_proxied
+    }
+
     /// Get the remote address for this connection, if one is set.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.remote_addr
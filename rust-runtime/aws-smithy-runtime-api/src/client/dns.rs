@@ -48,6 +48,14 @@ new_type_future! {
 pub trait ResolveDns: fmt::Debug + Send + Sync {
     /// Asynchronously resolve the given domain name
     fn resolve_dns<'a>(&'a self, name: &'a str) -> DnsFuture<'a>;
+
+    /// Report that connecting to `addr`, an address returned by this resolver, failed.
+    ///
+    /// Resolvers can use this to return other addresses first for a while. The default
+    /// implementation does nothing.
+    fn report_connect_failure(&self, addr: IpAddr) {
+        let _ = addr;
+    }
 }
 
 /// Shared instance of [`ResolveDns`].
@@ -65,6 +73,10 @@ impl ResolveDns for SharedDnsResolver {
     fn resolve_dns<'a>(&'a self, name: &'a str) -> DnsFuture<'a> {
         self.0.resolve_dns(name)
     }
+
+    fn report_connect_failure(&self, addr: IpAddr) {
+        self.0.report_connect_failure(addr)
+    }
 }
 
 impl_shared_conversions!(convert SharedDnsResolver from ResolveDns using SharedDnsResolver::new);
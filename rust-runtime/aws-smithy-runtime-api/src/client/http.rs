@@ -44,7 +44,7 @@
 //! - Timeouts
 //!
 //! Some of these aren't implemented yet, but they will appear in the [`HttpConnectorSettings`] struct
-//! once they are.
+//! once they are. The HTTP protocol version is selected with [`HttpVersion`].
 //!
 //! [`hyper`]: https://crates.io/crates/hyper
 //! [`tower`]: https://crates.io/crates/tower
@@ -57,7 +57,7 @@ use crate::client::result::ConnectorError;
 use crate::client::runtime_components::sealed::ValidateConfig;
 use crate::client::runtime_components::{RuntimeComponents, RuntimeComponentsBuilder};
 use crate::impl_shared_conversions;
-use aws_smithy_types::config_bag::ConfigBag;
+use aws_smithy_types::config_bag::{ConfigBag, Storable, StoreReplace};
 use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
@@ -227,12 +227,36 @@ impl ValidateConfig for SharedHttpClient {
 
 impl_shared_conversions!(convert SharedHttpClient from HttpClient using SharedHttpClient::new);
 
+/// The HTTP protocol version used by an HTTP connector.
+///
+/// When stored in the config bag, this is passed to the HTTP client in its [`HttpConnectorSettings`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum HttpVersion {
+    /// Only use HTTP/1.1.
+    Http1Only,
+    /// Negotiate HTTP/2 with ALPN during the TLS handshake, falling back to HTTP/1.1 if the
+    /// server doesn't support HTTP/2.
+    ///
+    /// Plaintext connections use HTTP/1.1 since there is no TLS handshake to negotiate with.
+    Http2Negotiated,
+    /// Use HTTP/2 without negotiating it first, also known as "prior knowledge".
+    ///
+    /// This applies to both plaintext and TLS connections, and fails if the server doesn't support HTTP/2.
+    Http2PriorKnowledge,
+}
+
+impl Storable for HttpVersion {
+    type Storer = StoreReplace<Self>;
+}
+
 /// Builder for [`HttpConnectorSettings`].
 #[non_exhaustive]
 #[derive(Default, Debug)]
 pub struct HttpConnectorSettingsBuilder {
     connect_timeout: Option<Duration>,
     read_timeout: Option<Duration>,
+    http_version: Option<HttpVersion>,
 }
 
 impl HttpConnectorSettingsBuilder {
@@ -275,11 +299,28 @@ impl HttpConnectorSettingsBuilder {
         self
     }
 
+    /// Sets the HTTP protocol version that should be used.
+    ///
+    /// If this isn't set, the HTTP client uses its default protocol version.
+    pub fn http_version(mut self, http_version: HttpVersion) -> Self {
+        self.http_version = Some(http_version);
+        self
+    }
+
+    /// Sets the HTTP protocol version that should be used.
+    ///
+    /// If this isn't set, the HTTP client uses its default protocol version.
+    pub fn set_http_version(&mut self, http_version: Option<HttpVersion>) -> &mut Self {
+        self.http_version = http_version;
+        self
+    }
+
     /// Builds the [`HttpConnectorSettings`].
     pub fn build(self) -> HttpConnectorSettings {
         HttpConnectorSettings {
             connect_timeout: self.connect_timeout,
             read_timeout: self.read_timeout,
+            http_version: self.http_version,
         }
     }
 }
@@ -290,6 +331,7 @@ impl HttpConnectorSettingsBuilder {
 pub struct HttpConnectorSettings {
     connect_timeout: Option<Duration>,
     read_timeout: Option<Duration>,
+    http_version: Option<HttpVersion>,
 }
 
 impl HttpConnectorSettings {
@@ -312,4 +354,11 @@ impl HttpConnectorSettings {
     pub fn read_timeout(&self) -> Option<Duration> {
         self.read_timeout
     }
+
+    /// Returns the HTTP protocol version that should be used.
+    ///
+    /// If this is `None`, the HTTP client uses its default protocol version.
+    pub fn http_version(&self) -> Option<HttpVersion> {
+        self.http_version
+    }
 }
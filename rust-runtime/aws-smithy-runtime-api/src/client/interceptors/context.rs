@@ -361,6 +361,23 @@ impl<I, O, E> InterceptorContext<I, O, E> {
         }
     }
 
+    /// Creates a context for another attempt of the same operation, starting from the saved request
+    /// checkpoint. Returns `None` if no checkpoint was saved.
+    ///
+    /// Note: This method is intended for internal use only.
+    pub fn fork_from_checkpoint(&self) -> Option<Self> {
+        let request = self.request_checkpoint.as_ref()?.try_clone()?;
+        Some(Self {
+            input: None,
+            output_or_error: None,
+            request: Some(request),
+            response: None,
+            phase: Phase::BeforeTransmit,
+            tainted: true,
+            request_checkpoint: self.request_checkpoint.as_ref().and_then(|r| r.try_clone()),
+        })
+    }
+
     /// Returns false if rewinding isn't possible
     ///
     /// Note: This method is intended for internal use only.
@@ -610,6 +627,26 @@ mod tests {
         assert_eq!("output", output.downcast_ref::<String>().unwrap());
     }
 
+    #[test]
+    fn fork_from_checkpoint() {
+        let mut context = InterceptorContext::new(Input::doesnt_matter());
+        context.enter_serialization_phase();
+        let _ = context.take_input();
+        context.set_request(HttpRequest::new(SdkBody::from("hello")));
+        context.enter_before_transmit_phase();
+        context.save_checkpoint();
+
+        let mut fork = context
+            .fork_from_checkpoint()
+            .expect("checkpoint was saved");
+        assert_eq!(b"hello", fork.request().unwrap().body().bytes().unwrap());
+        // The fork can go through an attempt on its own, and be rewound for another one
+        fork.enter_transmit_phase();
+        let _ = fork.take_request();
+        assert_eq!(fork.rewind(&mut ConfigBag::base()), RewindResult::Occurred);
+        assert!(context.request().is_some());
+    }
+
     #[test]
     fn try_clone_clones_all_data() {
         let request: HttpRequest = http_02x::Request::builder()
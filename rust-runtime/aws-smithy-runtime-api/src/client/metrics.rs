@@ -0,0 +1,409 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Interfaces for recording client metrics.
+//!
+//! The orchestrator records measurements, such as the duration of each operation and the number
+//! of attempts made, with the [`Meter`] returned by the [`MetricsProvider`] set in the runtime
+//! components. No metrics are recorded if no metrics provider is set.
+//!
+//! The instruments recorded by the orchestrator are listed in [`instruments`]. Every measurement
+//! has the [`attributes::RPC_SERVICE`] and [`attributes::RPC_METHOD`] attributes.
+
+use crate::impl_shared_conversions;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+/// Names of the instruments recorded by the orchestrator.
+///
+/// Durations are recorded in seconds and sizes in bytes.
+pub mod instruments {
+    /// Histogram of the time taken by an operation, including all attempts and retry delays.
+    pub const CALL_DURATION: &str = "smithy.client.call.duration";
+
+    /// Counter of attempts made, including the initial attempt of each operation.
+    pub const CALL_ATTEMPTS: &str = "smithy.client.call.attempts";
+
+    /// Histogram of the time taken by each attempt.
+    pub const CALL_ATTEMPT_DURATION: &str = "smithy.client.call.attempt_duration";
+
+    /// Counter of operations that failed.
+    pub const CALL_ERRORS: &str = "smithy.client.call.errors";
+
+    /// Counter of retries, with a [`RETRY_REASON`](super::attributes::RETRY_REASON) attribute.
+    ///
+    /// This is recorded by the standard retry strategy. Other retry strategies may not record it.
+    pub const CALL_RETRIES: &str = "smithy.client.call.retries";
+
+    /// Histogram of the time taken to serialize the input of an operation.
+    pub const SERIALIZATION_DURATION: &str = "smithy.client.call.serialization_duration";
+
+    /// Histogram of the time taken to deserialize the response of an attempt.
+    ///
+    /// This doesn't include the time taken to read the response body from the network.
+    pub const DESERIALIZATION_DURATION: &str = "smithy.client.call.deserialization_duration";
+
+    /// Histogram of the time taken to resolve an identity, including time spent in the identity cache.
+    pub const RESOLVE_IDENTITY_DURATION: &str = "smithy.client.call.auth.resolve_identity_duration";
+
+    /// Counter of request body bytes sent.
+    ///
+    /// Bodies whose length isn't known up front, such as streaming bodies without a
+    /// `Content-Length`, aren't counted.
+    pub const BYTES_SENT: &str = "smithy.client.http.bytes_sent";
+
+    /// Counter of response body bytes received.
+    ///
+    /// Bodies whose length isn't known up front, such as streaming bodies without a
+    /// `Content-Length`, aren't counted.
+    pub const BYTES_RECEIVED: &str = "smithy.client.http.bytes_received";
+}
+
+/// Names of the attributes recorded by the orchestrator.
+pub mod attributes {
+    /// The name of the service being called.
+    pub const RPC_SERVICE: &str = "rpc.service";
+
+    /// The name of the operation being called.
+    pub const RPC_METHOD: &str = "rpc.method";
+
+    /// The kind of error that caused a retry, e.g. `throttling_error` or `transient_error`.
+    pub const RETRY_REASON: &str = "retry.reason";
+
+    /// The ID of the auth scheme an identity was resolved for.
+    pub const AUTH_SCHEME_ID: &str = "auth.scheme_id";
+}
+
+/// Key-value pairs that describe a measurement, such as the operation it was recorded for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Attributes {
+    attributes: Vec<(&'static str, Cow<'static, str>)>,
+}
+
+impl Attributes {
+    /// Creates an empty set of attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the attribute `key` to `value`, replacing any existing value.
+    pub fn with(mut self, key: &'static str, value: impl Into<Cow<'static, str>>) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    /// Sets the attribute `key` to `value`, replacing any existing value.
+    pub fn set(&mut self, key: &'static str, value: impl Into<Cow<'static, str>>) -> &mut Self {
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.attributes.push((key, value)),
+        }
+        self
+    }
+
+    /// Returns the value of the attribute `key`, if it is set.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Returns an iterator over the attributes, in the order they were first set.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.attributes
+            .iter()
+            .map(|(key, value)| (*key, value.as_ref()))
+    }
+
+    /// Returns true if no attributes are set.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+    }
+}
+
+/// Records measurements for named instruments.
+///
+/// Implementations are expected to forward measurements to a metrics library, such as
+/// OpenTelemetry, and should not block.
+pub trait Meter: Send + Sync + fmt::Debug {
+    /// Adds `value` to the monotonic counter named `name`.
+    fn add(&self, name: &'static str, value: u64, attributes: &Attributes);
+
+    /// Records `value` in the histogram named `name`.
+    fn record(&self, name: &'static str, value: f64, attributes: &Attributes);
+}
+
+/// Shared instance of [`Meter`].
+#[derive(Clone, Debug)]
+pub struct SharedMeter(Arc<dyn Meter>);
+
+impl SharedMeter {
+    /// Creates a new [`SharedMeter`].
+    pub fn new(meter: impl Meter + 'static) -> Self {
+        Self(Arc::new(meter))
+    }
+}
+
+impl Meter for SharedMeter {
+    fn add(&self, name: &'static str, value: u64, attributes: &Attributes) {
+        self.0.add(name, value, attributes)
+    }
+
+    fn record(&self, name: &'static str, value: f64, attributes: &Attributes) {
+        self.0.record(name, value, attributes)
+    }
+}
+
+impl_shared_conversions!(convert SharedMeter from Meter using SharedMeter::new);
+
+/// Provides [`Meter`]s for recording measurements.
+pub trait MetricsProvider: Send + Sync + fmt::Debug {
+    /// Returns a meter for the given instrumentation `scope`, such as `aws-smithy-runtime`.
+    fn meter(&self, scope: &'static str) -> SharedMeter;
+}
+
+/// Shared instance of [`MetricsProvider`].
+#[derive(Clone, Debug)]
+pub struct SharedMetricsProvider(Arc<dyn MetricsProvider>);
+
+impl SharedMetricsProvider {
+    /// Creates a new [`SharedMetricsProvider`].
+    pub fn new(provider: impl MetricsProvider + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl MetricsProvider for SharedMetricsProvider {
+    fn meter(&self, scope: &'static str) -> SharedMeter {
+        self.0.meter(scope)
+    }
+}
+
+impl_shared_conversions!(convert SharedMetricsProvider from MetricsProvider using SharedMetricsProvider::new);
+
+/// Metrics provider that discards every measurement.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct NoopMetricsProvider;
+
+impl NoopMetricsProvider {
+    /// Creates a new [`NoopMetricsProvider`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl MetricsProvider for NoopMetricsProvider {
+    fn meter(&self, _scope: &'static str) -> SharedMeter {
+        SharedMeter::new(NoopMeter)
+    }
+}
+
+/// Meter that discards every measurement.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct NoopMeter;
+
+impl NoopMeter {
+    /// Creates a new [`NoopMeter`].
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Meter for NoopMeter {
+    fn add(&self, _name: &'static str, _value: u64, _attributes: &Attributes) {}
+
+    fn record(&self, _name: &'static str, _value: f64, _attributes: &Attributes) {}
+}
+
+/// Metrics provider that keeps measurements in memory for tests.
+#[cfg(feature = "test-util")]
+pub mod test_util {
+    use super::{Attributes, Meter, MetricsProvider, SharedMeter};
+    use std::sync::{Arc, Mutex};
+
+    /// The kind of instrument a [`Measurement`] was recorded with.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum InstrumentKind {
+        /// A monotonic counter.
+        Counter,
+        /// A histogram.
+        Histogram,
+    }
+
+    /// A measurement recorded by an [`InMemoryMetricsProvider`].
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Measurement {
+        scope: &'static str,
+        name: &'static str,
+        kind: InstrumentKind,
+        value: f64,
+        attributes: Attributes,
+    }
+
+    impl Measurement {
+        /// The instrumentation scope of the meter this was recorded with.
+        pub fn scope(&self) -> &'static str {
+            self.scope
+        }
+
+        /// The name of the instrument.
+        pub fn name(&self) -> &'static str {
+            self.name
+        }
+
+        /// The kind of instrument.
+        pub fn kind(&self) -> InstrumentKind {
+            self.kind
+        }
+
+        /// The recorded value. Counter values are converted to `f64`.
+        pub fn value(&self) -> f64 {
+            self.value
+        }
+
+        /// The attributes recorded with the value.
+        pub fn attributes(&self) -> &Attributes {
+            &self.attributes
+        }
+    }
+
+    /// Metrics provider that keeps every measurement in memory.
+    ///
+    /// Clones share the same measurements, so a clone can be given to a client and the
+    /// original inspected after making requests.
+    #[derive(Clone, Debug, Default)]
+    pub struct InMemoryMetricsProvider {
+        measurements: Arc<Mutex<Vec<Measurement>>>,
+    }
+
+    impl InMemoryMetricsProvider {
+        /// Creates a new, empty [`InMemoryMetricsProvider`].
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Returns every measurement recorded so far, in the order they were recorded.
+        pub fn measurements(&self) -> Vec<Measurement> {
+            self.measurements.lock().unwrap().clone()
+        }
+
+        /// Returns the measurements recorded for the instrument `name`.
+        pub fn measurements_named(&self, name: &str) -> Vec<Measurement> {
+            self.measurements
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|m| m.name == name)
+                .cloned()
+                .collect()
+        }
+
+        /// Returns the sum of the values recorded for the counter `name`.
+        pub fn counter_total(&self, name: &str) -> u64 {
+            self.measurements_named(name)
+                .iter()
+                .filter(|m| m.kind == InstrumentKind::Counter)
+                .map(|m| m.value as u64)
+                .sum()
+        }
+
+        /// Discards all measurements recorded so far.
+        pub fn clear(&self) {
+            self.measurements.lock().unwrap().clear();
+        }
+    }
+
+    impl MetricsProvider for InMemoryMetricsProvider {
+        fn meter(&self, scope: &'static str) -> SharedMeter {
+            SharedMeter::new(InMemoryMeter {
+                scope,
+                measurements: self.measurements.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct InMemoryMeter {
+        scope: &'static str,
+        measurements: Arc<Mutex<Vec<Measurement>>>,
+    }
+
+    impl InMemoryMeter {
+        fn push(
+            &self,
+            name: &'static str,
+            kind: InstrumentKind,
+            value: f64,
+            attributes: &Attributes,
+        ) {
+            self.measurements.lock().unwrap().push(Measurement {
+                scope: self.scope,
+                name,
+                kind,
+                value,
+                attributes: attributes.clone(),
+            });
+        }
+    }
+
+    impl Meter for InMemoryMeter {
+        fn add(&self, name: &'static str, value: u64, attributes: &Attributes) {
+            self.push(name, InstrumentKind::Counter, value as f64, attributes);
+        }
+
+        fn record(&self, name: &'static str, value: f64, attributes: &Attributes) {
+            self.push(name, InstrumentKind::Histogram, value, attributes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes() {
+        let mut attributes = Attributes::new()
+            .with(attributes::RPC_SERVICE, "s3")
+            .with(attributes::RPC_METHOD, "GetObject");
+        attributes.set(attributes::RPC_METHOD, String::from("PutObject"));
+        assert_eq!(Some("s3"), attributes.get(attributes::RPC_SERVICE));
+        assert_eq!(Some("PutObject"), attributes.get(attributes::RPC_METHOD));
+        assert_eq!(None, attributes.get(attributes::RETRY_REASON));
+        assert_eq!(
+            vec![("rpc.service", "s3"), ("rpc.method", "PutObject")],
+            attributes.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn in_memory_provider() {
+        use test_util::{InMemoryMetricsProvider, InstrumentKind};
+
+        let provider = InMemoryMetricsProvider::new();
+        let meter = SharedMetricsProvider::new(provider.clone()).meter("test");
+        let attributes = Attributes::new().with(attributes::RPC_SERVICE, "s3");
+        meter.add(instruments::CALL_ATTEMPTS, 1, &attributes);
+        meter.add(instruments::CALL_ATTEMPTS, 2, &attributes);
+        meter.record(instruments::CALL_DURATION, 0.5, &attributes);
+
+        assert_eq!(3, provider.counter_total(instruments::CALL_ATTEMPTS));
+        let durations = provider.measurements_named(instruments::CALL_DURATION);
+        assert_eq!(1, durations.len());
+        assert_eq!("test", durations[0].scope());
+        assert_eq!(InstrumentKind::Histogram, durations[0].kind());
+        assert_eq!(0.5, durations[0].value());
+        assert_eq!(&attributes, durations[0].attributes());
+
+        provider.clear();
+        assert!(provider.measurements().is_empty());
+    }
+}
@@ -65,6 +65,14 @@ impl Storable for SensitiveOutput {
     type Storer = StoreReplace<Self>;
 }
 
+/// Marker type stored in the config bag to indicate that a request body should be redacted.
+#[derive(Debug)]
+pub struct SensitiveInput;
+
+impl Storable for SensitiveInput {
+    type Storer = StoreReplace<Self>;
+}
+
 #[derive(Debug)]
 enum ErrorKind<E> {
     /// An error occurred within an interceptor.
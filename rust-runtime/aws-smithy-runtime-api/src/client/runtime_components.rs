@@ -21,6 +21,7 @@ use crate::client::identity::{
     ResolveCachedIdentity, ResolveIdentity, SharedIdentityCache, SharedIdentityResolver,
 };
 use crate::client::interceptors::{Intercept, SharedInterceptor};
+use crate::client::metrics::{MetricsProvider, SharedMetricsProvider};
 use crate::client::retries::classifiers::{ClassifyRetry, SharedRetryClassifier};
 use crate::client::retries::{RetryStrategy, SharedRetryStrategy};
 use crate::impl_shared_conversions;
@@ -376,6 +377,8 @@ declare_runtime_components! {
 
         sleep_impl: Option<SharedAsyncSleep>,
 
+        metrics_provider: Option<SharedMetricsProvider>,
+
         config_validators: Vec<SharedConfigValidator>,
     }
 }
@@ -453,6 +456,11 @@ impl RuntimeComponents {
         self.time_source.as_ref().map(|s| s.value.clone())
     }
 
+    /// Returns the metrics provider.
+    pub fn metrics_provider(&self) -> Option<SharedMetricsProvider> {
+        self.metrics_provider.as_ref().map(|s| s.value.clone())
+    }
+
     /// Returns the config validators.
     pub fn config_validators(&self) -> impl Iterator<Item = SharedConfigValidator> + '_ {
         self.config_validators.iter().map(|s| s.value.clone())
@@ -521,6 +529,7 @@ impl RuntimeComponentsBuilder {
             retry_strategy: Some(rc.retry_strategy),
             time_source: rc.time_source,
             sleep_impl: rc.sleep_impl,
+            metrics_provider: rc.metrics_provider,
             config_validators: rc.config_validators,
         }
     }
@@ -826,6 +835,29 @@ impl RuntimeComponentsBuilder {
         self
     }
 
+    /// Returns the metrics provider.
+    pub fn metrics_provider(&self) -> Option<SharedMetricsProvider> {
+        self.metrics_provider.as_ref().map(|s| s.value.clone())
+    }
+
+    /// Sets the metrics provider.
+    pub fn set_metrics_provider(
+        &mut self,
+        metrics_provider: Option<SharedMetricsProvider>,
+    ) -> &mut Self {
+        self.metrics_provider = self.tracked(metrics_provider);
+        self
+    }
+
+    /// Sets the metrics provider.
+    pub fn with_metrics_provider(
+        mut self,
+        metrics_provider: Option<impl MetricsProvider + 'static>,
+    ) -> Self {
+        self.set_metrics_provider(metrics_provider.map(IntoShared::into_shared));
+        self
+    }
+
     /// Returns the config validators.
     pub fn config_validators(&self) -> impl Iterator<Item = SharedConfigValidator> + '_ {
         self.config_validators.iter().map(|s| s.value.clone())
@@ -47,3 +47,270 @@ mod tokio {
 
 #[cfg(all(feature = "rt-tokio", not(target_family = "wasm")))]
 pub use self::tokio::TokioDnsResolver;
+
+mod caching {
+    use aws_smithy_async::time::{SharedTimeSource, TimeSource};
+    use aws_smithy_runtime_api::client::dns::{DnsFuture, ResolveDns, SharedDnsResolver};
+    use aws_smithy_runtime_api::shared::IntoShared;
+    use std::collections::HashMap;
+    use std::net::IpAddr;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    const DEFAULT_TTL: Duration = Duration::from_secs(30);
+    const DEFAULT_BAD_ADDRESS_TTL: Duration = Duration::from_secs(30);
+
+    /// DNS resolver that caches the addresses returned by another resolver.
+    ///
+    /// Addresses are cached for a fixed [TTL](CachingDnsResolverBuilder::ttl), since [`ResolveDns`]
+    /// doesn't expose the TTL of DNS records. Each lookup of a cached name rotates the addresses,
+    /// so that connections are spread across all of them.
+    ///
+    /// Addresses [reported](ResolveDns::report_connect_failure) as failing are considered bad for
+    /// a [while](CachingDnsResolverBuilder::bad_address_ttl), and are returned after all other
+    /// addresses during that time.
+    ///
+    /// Clones share the same cache.
+    #[derive(Clone, Debug)]
+    pub struct CachingDnsResolver {
+        inner: Arc<Inner>,
+    }
+
+    #[derive(Debug)]
+    struct Inner {
+        resolver: SharedDnsResolver,
+        ttl: Duration,
+        bad_address_ttl: Duration,
+        time_source: SharedTimeSource,
+        entries: Mutex<HashMap<String, CacheEntry>>,
+        bad_addresses: Mutex<HashMap<IpAddr, SystemTime>>,
+    }
+
+    #[derive(Debug)]
+    struct CacheEntry {
+        addresses: Vec<IpAddr>,
+        expires_at: SystemTime,
+        next: usize,
+    }
+
+    impl CacheEntry {
+        /// Returns the addresses starting at the next one in the rotation.
+        fn rotate(&mut self) -> Vec<IpAddr> {
+            let mut addresses = self.addresses.clone();
+            let len = addresses.len();
+            if len > 0 {
+                addresses.rotate_left(self.next % len);
+                self.next = self.next.wrapping_add(1);
+            }
+            addresses
+        }
+    }
+
+    impl CachingDnsResolver {
+        /// Creates a caching resolver for `resolver` with the default settings.
+        pub fn new(resolver: impl ResolveDns + 'static) -> Self {
+            Self::builder().build(resolver)
+        }
+
+        /// Returns a builder for a caching resolver.
+        pub fn builder() -> CachingDnsResolverBuilder {
+            CachingDnsResolverBuilder::default()
+        }
+
+        fn cached(&self, name: &str, now: SystemTime) -> Option<Vec<IpAddr>> {
+            let mut entries = self.inner.entries.lock().unwrap();
+            entries
+                .get_mut(name)
+                .filter(|entry| entry.expires_at > now)
+                .map(CacheEntry::rotate)
+        }
+
+        fn insert(&self, name: &str, addresses: Vec<IpAddr>, now: SystemTime) -> Vec<IpAddr> {
+            let mut entries = self.inner.entries.lock().unwrap();
+            entries.retain(|_, entry| entry.expires_at > now);
+            let mut entry = CacheEntry {
+                addresses,
+                expires_at: now + self.inner.ttl,
+                next: 0,
+            };
+            let addresses = entry.rotate();
+            entries.insert(name.to_owned(), entry);
+            addresses
+        }
+
+        /// Moves bad addresses after the others, keeping their relative order.
+        fn deprioritize_bad(&self, addresses: Vec<IpAddr>, now: SystemTime) -> Vec<IpAddr> {
+            let mut bad_addresses = self.inner.bad_addresses.lock().unwrap();
+            bad_addresses.retain(|_, until| *until > now);
+            if bad_addresses.is_empty() {
+                return addresses;
+            }
+            let (mut good, bad): (Vec<_>, Vec<_>) = addresses
+                .into_iter()
+                .partition(|addr| !bad_addresses.contains_key(addr));
+            good.extend(bad);
+            good
+        }
+    }
+
+    impl ResolveDns for CachingDnsResolver {
+        fn resolve_dns<'a>(&'a self, name: &'a str) -> DnsFuture<'a> {
+            DnsFuture::new(async move {
+                let now = self.inner.time_source.now();
+                let addresses = match self.cached(name, now) {
+                    Some(addresses) => addresses,
+                    None => {
+                        let addresses = self.inner.resolver.resolve_dns(name).await?;
+                        // Empty results aren't cached so that the name is looked up again next time
+                        if addresses.is_empty() {
+                            return Ok(addresses);
+                        }
+                        self.insert(name, addresses, now)
+                    }
+                };
+                Ok(self.deprioritize_bad(addresses, now))
+            })
+        }
+
+        fn report_connect_failure(&self, addr: IpAddr) {
+            let until = self.inner.time_source.now() + self.inner.bad_address_ttl;
+            tracing::debug!(address = %addr, "connecting failed; deprioritizing address until {until:?}");
+            self.inner.bad_addresses.lock().unwrap().insert(addr, until);
+            self.inner.resolver.report_connect_failure(addr);
+        }
+    }
+
+    /// Builder for [`CachingDnsResolver`].
+    #[derive(Debug, Default)]
+    pub struct CachingDnsResolverBuilder {
+        ttl: Option<Duration>,
+        bad_address_ttl: Option<Duration>,
+        time_source: Option<SharedTimeSource>,
+    }
+
+    impl CachingDnsResolverBuilder {
+        /// Set how long resolved addresses are cached. Defaults to 30 seconds.
+        pub fn ttl(mut self, ttl: Duration) -> Self {
+            self.set_ttl(Some(ttl));
+            self
+        }
+
+        /// Set how long resolved addresses are cached. Defaults to 30 seconds.
+        pub fn set_ttl(&mut self, ttl: Option<Duration>) -> &mut Self {
+            self.ttl = ttl;
+            self
+        }
+
+        /// Set how long an address that failed to connect is returned after the others.
+        /// Defaults to 30 seconds.
+        pub fn bad_address_ttl(mut self, bad_address_ttl: Duration) -> Self {
+            self.set_bad_address_ttl(Some(bad_address_ttl));
+            self
+        }
+
+        /// Set how long an address that failed to connect is returned after the others.
+        /// Defaults to 30 seconds.
+        pub fn set_bad_address_ttl(&mut self, bad_address_ttl: Option<Duration>) -> &mut Self {
+            self.bad_address_ttl = bad_address_ttl;
+            self
+        }
+
+        /// Set the time source used to expire cached addresses.
+        pub fn time_source(mut self, time_source: impl TimeSource + 'static) -> Self {
+            self.set_time_source(Some(time_source.into_shared()));
+            self
+        }
+
+        /// Set the time source used to expire cached addresses.
+        pub fn set_time_source(&mut self, time_source: Option<SharedTimeSource>) -> &mut Self {
+            self.time_source = time_source;
+            self
+        }
+
+        /// Builds a [`CachingDnsResolver`] that caches the addresses returned by `resolver`.
+        pub fn build(self, resolver: impl ResolveDns + 'static) -> CachingDnsResolver {
+            CachingDnsResolver {
+                inner: Arc::new(Inner {
+                    resolver: resolver.into_shared(),
+                    ttl: self.ttl.unwrap_or(DEFAULT_TTL),
+                    bad_address_ttl: self.bad_address_ttl.unwrap_or(DEFAULT_BAD_ADDRESS_TTL),
+                    time_source: self.time_source.unwrap_or_default(),
+                    entries: Default::default(),
+                    bad_addresses: Default::default(),
+                }),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use aws_smithy_async::test_util::ManualTimeSource;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Clone, Debug, Default)]
+        struct CountingResolver {
+            lookups: Arc<AtomicUsize>,
+        }
+
+        impl ResolveDns for CountingResolver {
+            fn resolve_dns<'a>(&'a self, _name: &'a str) -> DnsFuture<'a> {
+                self.lookups.fetch_add(1, Ordering::Relaxed);
+                DnsFuture::ready(Ok(addresses(&[1, 2, 3])))
+            }
+        }
+
+        fn addresses(last_octets: &[u8]) -> Vec<IpAddr> {
+            last_octets
+                .iter()
+                .map(|octet| IpAddr::from([10, 0, 0, *octet]))
+                .collect()
+        }
+
+        fn resolver() -> (CachingDnsResolver, CountingResolver, ManualTimeSource) {
+            let counting = CountingResolver::default();
+            let time_source = ManualTimeSource::new(SystemTime::UNIX_EPOCH);
+            let resolver = CachingDnsResolver::builder()
+                .ttl(Duration::from_secs(10))
+                .bad_address_ttl(Duration::from_secs(5))
+                .time_source(time_source.clone())
+                .build(counting.clone());
+            (resolver, counting, time_source)
+        }
+
+        #[tokio::test]
+        async fn caches_for_ttl_and_rotates() {
+            let (resolver, counting, time_source) = resolver();
+            let resolve = || resolver.resolve_dns("example.com");
+
+            assert_eq!(addresses(&[1, 2, 3]), resolve().await.unwrap());
+            assert_eq!(addresses(&[2, 3, 1]), resolve().await.unwrap());
+            assert_eq!(addresses(&[3, 1, 2]), resolve().await.unwrap());
+            assert_eq!(addresses(&[1, 2, 3]), resolve().await.unwrap());
+            assert_eq!(1, counting.lookups.load(Ordering::Relaxed));
+
+            time_source.advance(Duration::from_secs(10));
+            assert_eq!(addresses(&[1, 2, 3]), resolve().await.unwrap());
+            assert_eq!(2, counting.lookups.load(Ordering::Relaxed));
+
+            resolver.resolve_dns("example.org").await.unwrap();
+            assert_eq!(3, counting.lookups.load(Ordering::Relaxed));
+        }
+
+        #[tokio::test]
+        async fn bad_addresses_are_returned_last() {
+            let (resolver, _, time_source) = resolver();
+            resolver.report_connect_failure(IpAddr::from([10, 0, 0, 1]));
+
+            let resolve = || resolver.resolve_dns("example.com");
+            assert_eq!(addresses(&[2, 3, 1]), resolve().await.unwrap());
+            assert_eq!(addresses(&[2, 3, 1]), resolve().await.unwrap());
+            assert_eq!(addresses(&[3, 2, 1]), resolve().await.unwrap());
+
+            time_source.advance(Duration::from_secs(5));
+            assert_eq!(addresses(&[1, 2, 3]), resolve().await.unwrap());
+        }
+    }
+}
+
+pub use self::caching::{CachingDnsResolver, CachingDnsResolverBuilder};
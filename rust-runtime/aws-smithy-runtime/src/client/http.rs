@@ -6,6 +6,12 @@
 /// Interceptor for connection poisoning.
 pub mod connection_poisoning;
 
+/// Interceptor that logs serialized requests and responses.
+pub mod body_logging;
+
+/// Interceptor that propagates trace context in request headers.
+pub mod trace_context;
+
 #[cfg(feature = "test-util")]
 pub mod test_util;
 
@@ -16,5 +22,36 @@ pub mod test_util;
 #[cfg(feature = "connector-hyper-0-14-x")]
 pub mod hyper_014;
 
+/// Default HTTP and TLS connectors that use hyper 1.x and rustls.
+///
+/// The cryptography implementation used by rustls is selected with the `tls-rustls-ring`,
+/// `tls-rustls-aws-lc` and `tls-rustls-aws-lc-fips` features.
+#[cfg(feature = "connector-hyper-1-x")]
+pub mod hyper_1;
+
 /// HTTP body and body-wrapper types
 pub mod body;
+
+/// Proxy configuration for HTTP clients.
+pub mod proxy;
+
+/// Connection pool statistics for HTTP clients.
+pub mod pool;
+
+/// TLS settings, such as client certificates, for the default rustls HTTPS connectors.
+#[cfg(any(
+    feature = "tls-rustls",
+    feature = "tls-rustls-ring",
+    feature = "tls-rustls-aws-lc",
+    feature = "tls-rustls-aws-lc-fips"
+))]
+pub mod tls;
+
+/// Connection racing for hosts with several addresses.
+#[cfg(any(
+    feature = "tls-rustls",
+    feature = "tls-rustls-ring",
+    feature = "tls-rustls-aws-lc",
+    feature = "tls-rustls-aws-lc-fips"
+))]
+pub(crate) mod happy_eyeballs;
@@ -0,0 +1,535 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::interceptors::context::{
+    AfterDeserializationInterceptorContextRef, BeforeSerializationInterceptorContextRef,
+    BeforeTransmitInterceptorContextRef,
+};
+use aws_smithy_runtime_api::client::interceptors::Intercept;
+use aws_smithy_runtime_api::client::orchestrator::{SensitiveInput, SensitiveOutput};
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_runtime_api::http::Headers;
+use aws_smithy_types::body::SdkBody;
+use aws_smithy_types::config_bag::ConfigBag;
+use std::borrow::Cow;
+use std::fmt;
+
+const DEFAULT_MAX_BODY_SIZE: usize = 4 * 1024;
+const REDACTED: &str = "** REDACTED **";
+
+/// Headers that carry credentials, and are always redacted.
+const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-amz-security-token",
+    "x-amz-sso_bearer_token",
+];
+
+/// Query parameters that carry credentials in presigned URIs, and are always redacted.
+const CREDENTIAL_QUERY_PARAMS: &[&str] = &[
+    "x-amz-credential",
+    "x-amz-security-token",
+    "x-amz-signature",
+];
+
+/// Headers that protocols set themselves, and are logged even for operations with sensitive members.
+///
+/// All other headers of those operations are redacted, since `@sensitive` members can be bound to headers.
+const PROTOCOL_HEADERS: &[&str] = &[
+    "accept",
+    "amz-sdk-invocation-id",
+    "amz-sdk-request",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "date",
+    "host",
+    "transfer-encoding",
+    "user-agent",
+    "x-amz-date",
+    "x-amz-id-2",
+    "x-amz-request-id",
+    "x-amz-user-agent",
+    "x-amzn-errortype",
+    "x-amzn-requestid",
+    "x-amzn-trace-id",
+];
+
+/// An interceptor that logs serialized requests and responses at the debug level.
+///
+/// Headers that carry credentials, such as `Authorization` and `X-Amz-Security-Token`, are redacted,
+/// as are the `X-Amz-Signature`, `X-Amz-Security-Token` and `X-Amz-Credential` query parameters of
+/// presigned URIs.
+/// Bodies are logged up to a [maximum size](Self::max_body_size). When an operation's input or
+/// output has members modeled as `@sensitive`, its body, the path and query of its URI, and every
+/// header that the protocol doesn't set itself are redacted, since those members may be bound to
+/// any of them. The modeled input or output is logged instead, with the same redaction its `Debug`
+/// implementation applies.
+/// Streaming bodies are never read, and are logged as `<streaming>`.
+///
+/// Since logs may still contain information that isn't modeled as sensitive, this interceptor
+/// should only be enabled while debugging.
+///
+/// # Examples
+///
+/// ```no_run
+/// use aws_smithy_runtime::client::http::body_logging::BodyLoggingInterceptor;
+///
+/// let interceptor = BodyLoggingInterceptor::new()
+///     .max_body_size(16 * 1024)
+///     .redact_header("x-api-key");
+/// # /*
+/// let config = some_service::Config::builder()
+///     .interceptor(interceptor)
+///     // ...
+///     .build();
+/// # */
+/// ```
+#[derive(Debug)]
+pub struct BodyLoggingInterceptor {
+    max_body_size: usize,
+    redacted_headers: Vec<Cow<'static, str>>,
+}
+
+impl Default for BodyLoggingInterceptor {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            redacted_headers: CREDENTIAL_HEADERS
+                .iter()
+                .map(|header| Cow::Borrowed(*header))
+                .collect(),
+        }
+    }
+}
+
+impl BodyLoggingInterceptor {
+    /// Creates a new `BodyLoggingInterceptor`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of body bytes to log. Longer bodies are truncated.
+    ///
+    /// Defaults to 4 KiB.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Redacts the value of the given header, in addition to the credential headers that are always redacted.
+    pub fn redact_header(mut self, header: impl Into<Cow<'static, str>>) -> Self {
+        self.redacted_headers.push(header.into());
+        self
+    }
+
+    fn headers<'a>(&'a self, headers: &'a Headers, sensitive: bool) -> RedactedHeaders<'a> {
+        RedactedHeaders {
+            headers,
+            redacted: &self.redacted_headers,
+            sensitive,
+        }
+    }
+
+    fn body<'a>(&self, body: &'a SdkBody, sensitive: bool) -> LoggedBody<'a> {
+        if sensitive {
+            return LoggedBody::Redacted;
+        }
+        match body.bytes() {
+            Some(bytes) => LoggedBody::Bytes {
+                bytes,
+                max_size: self.max_body_size,
+            },
+            None => LoggedBody::Streaming,
+        }
+    }
+}
+
+impl Intercept for BodyLoggingInterceptor {
+    fn name(&self) -> &'static str {
+        "BodyLoggingInterceptor"
+    }
+
+    fn read_before_serialization(
+        &self,
+        context: &BeforeSerializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        // The serialized body will be redacted, so log the modeled input instead
+        if cfg.load::<SensitiveInput>().is_some() {
+            tracing::debug!(input = ?context.input(), "serializing request input");
+        }
+        Ok(())
+    }
+
+    fn read_before_transmit(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let request = context.request();
+        let sensitive = cfg.load::<SensitiveInput>().is_some();
+        tracing::debug!(
+            method = request.method(),
+            uri = %LoggedUri {
+                uri: request.uri(),
+                sensitive
+            },
+            headers = ?self.headers(request.headers(), sensitive),
+            body = %self.body(request.body(), sensitive),
+            "sending HTTP request"
+        );
+        Ok(())
+    }
+
+    fn read_after_deserialization(
+        &self,
+        context: &AfterDeserializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let response = context.response();
+        let sensitive = cfg.load::<SensitiveOutput>().is_some();
+        tracing::debug!(
+            status = response.status().as_u16(),
+            headers = ?self.headers(response.headers(), sensitive),
+            body = %self.body(response.body(), sensitive),
+            "received HTTP response"
+        );
+        // The response body was redacted, so log the modeled output instead
+        if sensitive {
+            if let Ok(output) = context.output_or_error() {
+                tracing::debug!(output = ?output, "deserialized response output");
+            }
+        }
+        Ok(())
+    }
+}
+
+struct RedactedHeaders<'a> {
+    headers: &'a Headers,
+    redacted: &'a [Cow<'static, str>],
+    /// Whether headers other than the protocol headers are redacted.
+    sensitive: bool,
+}
+
+impl fmt::Debug for RedactedHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.headers.iter().map(|(name, value)| {
+                let redact = self
+                    .redacted
+                    .iter()
+                    .any(|redacted| redacted.eq_ignore_ascii_case(name))
+                    || (self.sensitive
+                        && !PROTOCOL_HEADERS
+                            .iter()
+                            .any(|allowed| allowed.eq_ignore_ascii_case(name)));
+                (name, if redact { REDACTED } else { value })
+            }))
+            .finish()
+    }
+}
+
+struct LoggedUri<'a> {
+    uri: &'a str,
+    /// Whether the path and query are redacted.
+    sensitive: bool,
+}
+
+impl fmt::Display for LoggedUri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.sensitive {
+            return self.fmt_with_redacted_credentials(f);
+        }
+        let authority_start = self.uri.find("://").map(|i| i + 3).unwrap_or(0);
+        let authority_end = self.uri[authority_start..]
+            .find(['/', '?'])
+            .map(|i| authority_start + i)
+            .unwrap_or(self.uri.len());
+        if matches!(&self.uri[authority_end..], "" | "/") {
+            return f.write_str(self.uri);
+        }
+        write!(f, "{}/{REDACTED}", &self.uri[..authority_end])
+    }
+}
+
+impl LoggedUri<'_> {
+    /// Writes the URI with the values of [`CREDENTIAL_QUERY_PARAMS`] redacted.
+    fn fmt_with_redacted_credentials(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((path, query)) = self.uri.split_once('?') else {
+            return f.write_str(self.uri);
+        };
+        write!(f, "{path}?")?;
+        for (i, param) in query.split('&').enumerate() {
+            if i > 0 {
+                f.write_str("&")?;
+            }
+            match param.split_once('=') {
+                Some((name, _))
+                    if CREDENTIAL_QUERY_PARAMS
+                        .iter()
+                        .any(|credential| name.eq_ignore_ascii_case(credential)) =>
+                {
+                    write!(f, "{name}={REDACTED}")?
+                }
+                _ => f.write_str(param)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+enum LoggedBody<'a> {
+    Bytes { bytes: &'a [u8], max_size: usize },
+    Streaming,
+    Redacted,
+}
+
+impl fmt::Display for LoggedBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes { bytes, max_size } if bytes.len() > *max_size => write!(
+                f,
+                "{}... ({} more bytes)",
+                String::from_utf8_lossy(&bytes[..*max_size]),
+                bytes.len() - max_size
+            ),
+            Self::Bytes { bytes, .. } => f.write_str(&String::from_utf8_lossy(bytes)),
+            Self::Streaming => f.write_str("<streaming>"),
+            Self::Redacted => f.write_str(REDACTED),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_runtime_api::client::interceptors::context::{
+        Input, InterceptorContext, Output,
+    };
+    use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+    use aws_smithy_runtime_api::client::runtime_components::RuntimeComponentsBuilder;
+    use aws_smithy_types::body::SdkBody;
+    use tracing_test::traced_test;
+
+    #[derive(Debug)]
+    struct SensitiveInputShape;
+
+    fn request(body: SdkBody) -> HttpRequest {
+        let mut request = HttpRequest::new(body);
+        request
+            .set_uri("https://example.com/resource/secret-label?token=secret-query")
+            .unwrap();
+        request
+            .headers_mut()
+            .insert("Authorization", "AWS4-HMAC-SHA256 secret-signature");
+        request.headers_mut().insert("x-api-key", "secret-key");
+        request
+            .headers_mut()
+            .insert("content-type", "application/json");
+        request
+    }
+
+    fn log_request(interceptor: &BodyLoggingInterceptor, body: SdkBody, cfg: &mut ConfigBag) {
+        let components = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        let mut context = InterceptorContext::new(Input::erase(SensitiveInputShape));
+        interceptor
+            .read_before_serialization(&(&context).into(), &components, cfg)
+            .unwrap();
+        context.enter_serialization_phase();
+        context.take_input();
+        context.set_request(request(body));
+        context.enter_before_transmit_phase();
+        interceptor
+            .read_before_transmit(&(&context).into(), &components, cfg)
+            .unwrap();
+    }
+
+    #[test]
+    #[traced_test]
+    fn logs_requests_with_redacted_credential_headers() {
+        let interceptor = BodyLoggingInterceptor::new().redact_header("X-Api-Key");
+        log_request(
+            &interceptor,
+            SdkBody::from(r#"{"name":"value"}"#),
+            &mut ConfigBag::base(),
+        );
+
+        assert!(logs_contain(r#"body={"name":"value"}"#));
+        assert!(logs_contain(
+            "uri=https://example.com/resource/secret-label?token=secret-query"
+        ));
+        assert!(logs_contain(r#""content-type": "application/json""#));
+        assert!(logs_contain(r#""authorization": "** REDACTED **""#));
+        assert!(logs_contain(r#""x-api-key": "** REDACTED **""#));
+        assert!(!logs_contain("secret-key"));
+        assert!(!logs_contain("secret-signature"));
+        assert!(!logs_contain("SensitiveInputShape"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn truncates_large_bodies() {
+        let interceptor = BodyLoggingInterceptor::new().max_body_size(4);
+        log_request(
+            &interceptor,
+            SdkBody::from("0123456789"),
+            &mut ConfigBag::base(),
+        );
+        assert!(logs_contain("body=0123... (6 more bytes)"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn leaves_streaming_bodies_untouched() {
+        let interceptor = BodyLoggingInterceptor::new();
+        let body = SdkBody::from_body_0_4(http_body_04x::Full::new(bytes::Bytes::from("streamed")));
+        log_request(&interceptor, body, &mut ConfigBag::base());
+        assert!(logs_contain("body=<streaming>"));
+        assert!(!logs_contain("streamed"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn redacts_sensitive_bodies() {
+        let interceptor = BodyLoggingInterceptor::new();
+        let mut cfg = ConfigBag::base();
+        let mut layer = aws_smithy_types::config_bag::Layer::new("sensitive");
+        layer.store_put(SensitiveInput);
+        cfg.push_layer(layer);
+        log_request(&interceptor, SdkBody::from("secret body"), &mut cfg);
+
+        assert!(logs_contain("body=** REDACTED **"));
+        assert!(logs_contain("SensitiveInputShape"));
+        assert!(!logs_contain("secret"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn redacts_uris_and_headers_of_sensitive_requests() {
+        let interceptor = BodyLoggingInterceptor::new();
+        let mut cfg = ConfigBag::base();
+        let mut layer = aws_smithy_types::config_bag::Layer::new("sensitive");
+        layer.store_put(SensitiveInput);
+        cfg.push_layer(layer);
+        log_request(&interceptor, SdkBody::empty(), &mut cfg);
+
+        assert!(logs_contain("uri=https://example.com/** REDACTED **"));
+        assert!(logs_contain(r#""content-type": "application/json""#));
+        assert!(logs_contain(r#""x-api-key": "** REDACTED **""#));
+        assert!(!logs_contain("secret"));
+    }
+
+    #[test]
+    fn only_redacts_uris_with_a_path_or_query() {
+        let logged = |uri| {
+            LoggedUri {
+                uri,
+                sensitive: true,
+            }
+            .to_string()
+        };
+        assert_eq!("https://example.com", logged("https://example.com"));
+        assert_eq!("https://example.com/", logged("https://example.com/"));
+        assert_eq!(
+            "https://example.com:8443/** REDACTED **",
+            logged("https://example.com:8443?secret")
+        );
+        assert_eq!(
+            "https://example.com/** REDACTED **",
+            logged("https://example.com/a/secret")
+        );
+    }
+
+    #[test]
+    fn always_redacts_credential_query_params() {
+        let logged = |uri| {
+            LoggedUri {
+                uri,
+                sensitive: false,
+            }
+            .to_string()
+        };
+        assert_eq!(
+            "https://bucket.s3.amazonaws.com/key?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+             &X-Amz-Credential=** REDACTED **&X-Amz-Date=20240101T000000Z\
+             &X-Amz-Security-Token=** REDACTED **&X-Amz-Signature=** REDACTED **",
+            logged(
+                "https://bucket.s3.amazonaws.com/key?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+                 &X-Amz-Credential=AKIDEXAMPLE%2F20240101%2Fus-east-1%2Fs3%2Faws4_request\
+                 &X-Amz-Date=20240101T000000Z&X-Amz-Security-Token=session-token\
+                 &X-Amz-Signature=abcdef0123456789"
+            )
+        );
+        assert_eq!(
+            "https://example.com/?x-amz-signature=** REDACTED **&token",
+            logged("https://example.com/?x-amz-signature=abc&token")
+        );
+        assert_eq!(
+            "https://example.com/resource",
+            logged("https://example.com/resource")
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn logs_responses() {
+        log_response(&mut ConfigBag::base());
+
+        assert!(logs_contain("status=200"));
+        assert!(logs_contain("body=response body"));
+        assert!(logs_contain(r#""x-custom": "value""#));
+        assert!(logs_contain(r#""set-cookie": "** REDACTED **""#));
+        assert!(!logs_contain("secret"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn redacts_headers_of_sensitive_responses() {
+        let mut cfg = ConfigBag::base();
+        let mut layer = aws_smithy_types::config_bag::Layer::new("sensitive");
+        layer.store_put(SensitiveOutput);
+        cfg.push_layer(layer);
+        log_response(&mut cfg);
+
+        assert!(logs_contain("body=** REDACTED **"));
+        assert!(logs_contain(r#""x-custom": "** REDACTED **""#));
+        assert!(logs_contain(r#""content-type": "text/plain""#));
+        assert!(!logs_contain("response body"));
+    }
+
+    fn log_response(cfg: &mut ConfigBag) {
+        let interceptor = BodyLoggingInterceptor::new();
+        let components = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        let mut context = InterceptorContext::new(Input::doesnt_matter());
+        context.enter_serialization_phase();
+        context.take_input();
+        context.set_request(request(SdkBody::empty()));
+        context.enter_before_transmit_phase();
+        context.enter_transmit_phase();
+        context.take_request();
+        let mut response =
+            HttpResponse::new(200.try_into().unwrap(), SdkBody::from("response body"));
+        response
+            .headers_mut()
+            .insert("Set-Cookie", "session=secret");
+        response.headers_mut().insert("x-custom", "value");
+        response.headers_mut().insert("content-type", "text/plain");
+        context.set_response(response);
+        context.enter_before_deserialization_phase();
+        context.enter_deserialization_phase();
+        context.set_output_or_error(Ok(Output::doesnt_matter()));
+        context.enter_after_deserialization_phase();
+        interceptor
+            .read_after_deserialization(&(&context).into(), &components, cfg)
+            .unwrap();
+    }
+}
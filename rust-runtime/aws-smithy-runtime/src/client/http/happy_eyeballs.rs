@@ -0,0 +1,190 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! A simplified version of the "Happy Eyeballs" algorithm from [RFC 8305].
+//!
+//! Connection attempts are made to each address in turn, alternating between IPv4 and IPv6
+//! addresses. If an attempt hasn't succeeded after [`ATTEMPT_DELAY`], the next attempt starts
+//! while the previous one continues, and the first connection established wins. A failed
+//! attempt starts the next one immediately.
+//!
+//! [RFC 8305]: https://datatracker.ietf.org/doc/html/rfc8305
+
+use std::future::{poll_fn, Future};
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// How long to wait for an attempt before starting the next one. This is the value
+/// recommended by RFC 8305.
+pub(crate) const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+type Attempt = Pin<Box<dyn Future<Output = (SocketAddr, io::Result<TcpStream>)> + Send>>;
+
+/// Connects to one of `addrs`, calling `on_failure` for each address that fails to connect.
+pub(crate) async fn connect(
+    addrs: Vec<SocketAddr>,
+    attempt_delay: Duration,
+    on_failure: impl Fn(SocketAddr),
+) -> io::Result<TcpStream> {
+    let mut remaining = interleave_families(addrs).into_iter();
+    let mut attempts: Vec<Attempt> = Vec::new();
+    let mut last_error = None;
+    let mut delay = Box::pin(tokio::time::sleep(attempt_delay));
+
+    let mut start_next = |attempts: &mut Vec<Attempt>| match remaining.next() {
+        Some(addr) => {
+            tracing::trace!(address = %addr, "attempting to connect");
+            attempts.push(Box::pin(
+                async move { (addr, TcpStream::connect(addr).await) },
+            ));
+            true
+        }
+        None => false,
+    };
+
+    poll_fn(|cx| loop {
+        if attempts.is_empty() {
+            if !start_next(&mut attempts) {
+                let err = last_error.take().unwrap_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotFound, "no addresses to connect to")
+                });
+                return Poll::Ready(Err(err));
+            }
+            delay = Box::pin(tokio::time::sleep(attempt_delay));
+        }
+
+        let mut failed = false;
+        let mut i = 0;
+        while i < attempts.len() {
+            match attempts[i].as_mut().poll(cx) {
+                Poll::Ready((_, Ok(stream))) => return Poll::Ready(Ok(stream)),
+                Poll::Ready((addr, Err(err))) => {
+                    tracing::debug!(address = %addr, error = %err, "failed to connect");
+                    on_failure(addr);
+                    last_error = Some(err);
+                    drop(attempts.swap_remove(i));
+                    failed = true;
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+        if failed {
+            // Start the next attempt right away rather than waiting for the delay
+            if start_next(&mut attempts) {
+                delay = Box::pin(tokio::time::sleep(attempt_delay));
+            }
+            continue;
+        }
+
+        if delay.as_mut().poll(cx).is_ready() && start_next(&mut attempts) {
+            delay = Box::pin(tokio::time::sleep(attempt_delay));
+            continue;
+        }
+        return Poll::Pending;
+    })
+    .await
+}
+
+/// Orders addresses so that address families alternate, starting with the family of the first address.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first) = addrs.first() else {
+        return addrs;
+    };
+    let preferred_v6 = first.is_ipv6();
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == preferred_v6);
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    fn addr(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn interleave() {
+        let addrs = vec![
+            addr("[::1]:1"),
+            addr("[::2]:1"),
+            addr("[::3]:1"),
+            addr("10.0.0.1:1"),
+        ];
+        assert_eq!(
+            vec![
+                addr("[::1]:1"),
+                addr("10.0.0.1:1"),
+                addr("[::2]:1"),
+                addr("[::3]:1")
+            ],
+            interleave_families(addrs)
+        );
+        assert!(interleave_families(Vec::new()).is_empty());
+    }
+
+    /// Returns an address on the loopback interface that refuses connections.
+    async fn refused_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_addresses_are_skipped_and_reported() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+        let bad = refused_addr().await;
+        let failures = Mutex::new(Vec::new());
+
+        let stream = connect(vec![bad, good], ATTEMPT_DELAY, |addr| {
+            failures.lock().unwrap().push(addr)
+        })
+        .await
+        .unwrap();
+        assert_eq!(good, stream.peer_addr().unwrap());
+        assert_eq!(vec![bad], *failures.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn all_addresses_failing_is_an_error() {
+        let bad = refused_addr().await;
+        let err = connect(vec![bad], ATTEMPT_DELAY, |_| {}).await.unwrap_err();
+        assert_eq!(io::ErrorKind::ConnectionRefused, err.kind());
+
+        let err = connect(Vec::new(), ATTEMPT_DELAY, |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_attempts_are_raced() {
+        // 192.0.2.0/24 is reserved for documentation, so connecting to it hangs
+        let unroutable = addr("192.0.2.1:443");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+
+        let stream = connect(vec![unroutable, good], ATTEMPT_DELAY, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(good, stream.peer_addr().unwrap());
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Connection pool statistics for HTTP clients.
+//!
+//! HTTP clients keep connections open between requests so that they can be reused. A
+//! [`ConnectionPoolMonitor`] given to an HTTP client builder counts the connections the client
+//! opens and closes, and the requests in flight on them, so that pool exhaustion and connection
+//! churn can be diagnosed.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Tracks the connections and requests of an HTTP client's connection pool.
+///
+/// Clones share the same counters, so a clone can be given to an HTTP client builder and
+/// the original used to read [`stats`](Self::stats).
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "tls-rustls")]
+/// # {
+/// use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
+/// use aws_smithy_runtime::client::http::pool::ConnectionPoolMonitor;
+///
+/// let monitor = ConnectionPoolMonitor::new();
+/// let http_client = HyperClientBuilder::new()
+///     .pool_monitor(monitor.clone())
+///     .build_https();
+///
+/// // ... make requests with the client ...
+///
+/// let stats = monitor.stats();
+/// println!("{} open, {} idle", stats.open_connections(), stats.idle_connections());
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionPoolMonitor {
+    counters: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    opened: AtomicU64,
+    closed: AtomicU64,
+    in_flight: AtomicU64,
+}
+
+impl ConnectionPoolMonitor {
+    /// Creates a new monitor with all counters at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of the pool's current statistics.
+    pub fn stats(&self) -> ConnectionPoolStats {
+        ConnectionPoolStats {
+            connections_opened: self.counters.opened.load(Ordering::Relaxed),
+            connections_closed: self.counters.closed.load(Ordering::Relaxed),
+            in_flight_requests: self.counters.in_flight.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records that a connection was opened. The connection is counted as closed when the guard is dropped.
+    #[cfg_attr(
+        not(any(feature = "connector-hyper-0-14-x", feature = "connector-hyper-1-x")),
+        allow(dead_code)
+    )]
+    pub(crate) fn connection_opened(&self) -> ConnectionGuard {
+        self.counters.opened.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard {
+            counters: self.counters.clone(),
+        }
+    }
+
+    /// Records that a request was sent. The request is counted as in flight until the guard is dropped.
+    #[cfg_attr(
+        not(any(feature = "connector-hyper-0-14-x", feature = "connector-hyper-1-x")),
+        allow(dead_code)
+    )]
+    pub(crate) fn request_started(&self) -> RequestGuard {
+        self.counters.in_flight.fetch_add(1, Ordering::Relaxed);
+        RequestGuard {
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+/// Counts a connection as closed when dropped.
+#[derive(Debug)]
+pub(crate) struct ConnectionGuard {
+    counters: Arc<Counters>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.counters.closed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a request as no longer in flight when dropped.
+#[derive(Debug)]
+pub(crate) struct RequestGuard {
+    counters: Arc<Counters>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.counters.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of a connection pool's statistics, returned by [`ConnectionPoolMonitor::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConnectionPoolStats {
+    connections_opened: u64,
+    connections_closed: u64,
+    in_flight_requests: u64,
+}
+
+impl ConnectionPoolStats {
+    /// The number of connections that are currently open, whether idle or in use.
+    pub fn open_connections(&self) -> u64 {
+        self.connections_opened
+            .saturating_sub(self.connections_closed)
+    }
+
+    /// The number of open connections that aren't serving a request.
+    ///
+    /// This assumes each connection serves one request at a time, as HTTP/1.1 connections do.
+    /// Since HTTP/2 connections serve many requests at once, this is a lower bound for them.
+    pub fn idle_connections(&self) -> u64 {
+        self.open_connections()
+            .saturating_sub(self.in_flight_requests)
+    }
+
+    /// The number of requests that have been sent and whose response body hasn't been read
+    /// to the end or dropped yet.
+    pub fn in_flight_requests(&self) -> u64 {
+        self.in_flight_requests
+    }
+
+    /// The total number of connections opened since the monitor was created.
+    ///
+    /// A value that grows much faster than the number of requests made indicates connection churn,
+    /// for example because of a short idle timeout.
+    pub fn connections_opened(&self) -> u64 {
+        self.connections_opened
+    }
+
+    /// The total number of connections closed since the monitor was created.
+    pub fn connections_closed(&self) -> u64 {
+        self.connections_closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let monitor = ConnectionPoolMonitor::new();
+        assert_eq!(ConnectionPoolStats::default(), monitor.stats());
+
+        let first = monitor.connection_opened();
+        let second = monitor.clone().connection_opened();
+        let request = monitor.request_started();
+        let stats = monitor.stats();
+        assert_eq!(2, stats.open_connections());
+        assert_eq!(1, stats.idle_connections());
+        assert_eq!(1, stats.in_flight_requests());
+
+        drop(request);
+        drop(first);
+        let stats = monitor.stats();
+        assert_eq!(1, stats.open_connections());
+        assert_eq!(1, stats.idle_connections());
+        assert_eq!(0, stats.in_flight_requests());
+        assert_eq!(2, stats.connections_opened());
+        assert_eq!(1, stats.connections_closed());
+
+        drop(second);
+        assert_eq!(0, monitor.stats().open_connections());
+    }
+
+    #[test]
+    fn idle_connections_with_multiplexing() {
+        let monitor = ConnectionPoolMonitor::new();
+        let _connection = monitor.connection_opened();
+        let _requests = [monitor.request_started(), monitor.request_started()];
+        assert_eq!(0, monitor.stats().idle_connections());
+    }
+}
@@ -0,0 +1,473 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// TLS settings for the default rustls HTTPS connectors.
+///
+/// A TLS context sets which certificate authorities are trusted to identify servers, and the
+/// identity that the client presents when a server asks for a client certificate (mutual TLS).
+///
+/// The client identity can be [updated](Self::update_client_identity) at any time without
+/// rebuilding the client, for example when short-lived certificates are rotated. Clones share
+/// the client identity, so an update through one clone affects every client built with the
+/// context. The new identity is presented on new connections. Connections that were already
+/// established keep the identity they were established with until they are closed. For the
+/// same reason, TLS sessions aren't resumed by connectors that use a TLS context, since a
+/// resumed session keeps the identity of the session it resumes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use aws_smithy_runtime::client::http::tls::{ClientIdentity, TlsContext, TrustStore};
+///
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let trust_store = TrustStore::default()
+///     .with_pem_certificates(&std::fs::read("internal-ca.pem")?)?;
+/// let identity = ClientIdentity::from_pem(
+///     &std::fs::read("client.crt")?,
+///     &std::fs::read("client.key")?,
+/// )?;
+/// let tls_context = TlsContext::builder()
+///     .trust_store(trust_store)
+///     .client_identity(identity)
+///     .build();
+///
+/// // Later, when the certificate is rotated
+/// tls_context.update_client_identity(Some(ClientIdentity::from_pem(
+///     &std::fs::read("client.crt")?,
+///     &std::fs::read("client.key")?,
+/// )?));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TlsContext {
+    trust_store: TrustStore,
+    client_identity: Arc<RwLock<Option<Arc<ClientIdentity>>>>,
+}
+
+impl TlsContext {
+    /// Returns a builder for a TLS context.
+    pub fn builder() -> TlsContextBuilder {
+        TlsContextBuilder::default()
+    }
+
+    /// Replaces the identity presented to servers that ask for a client certificate.
+    ///
+    /// No client certificate is presented if `client_identity` is `None`.
+    pub fn update_client_identity(&self, client_identity: Option<ClientIdentity>) {
+        *self.client_identity.write().unwrap() = client_identity.map(Arc::new);
+    }
+
+    /// Returns true if a client identity is set.
+    pub fn has_client_identity(&self) -> bool {
+        self.client_identity.read().unwrap().is_some()
+    }
+
+    pub(crate) fn trust_store(&self) -> &TrustStore {
+        &self.trust_store
+    }
+
+    fn client_identity(&self) -> Option<Arc<ClientIdentity>> {
+        self.client_identity.read().unwrap().clone()
+    }
+}
+
+/// Builder for [`TlsContext`].
+#[derive(Debug, Default)]
+pub struct TlsContextBuilder {
+    trust_store: Option<TrustStore>,
+    client_identity: Option<ClientIdentity>,
+}
+
+impl TlsContextBuilder {
+    /// Set the certificate authorities trusted to identify servers.
+    ///
+    /// Defaults to the platform's native certificate authorities.
+    pub fn trust_store(mut self, trust_store: TrustStore) -> Self {
+        self.set_trust_store(Some(trust_store));
+        self
+    }
+
+    /// Set the certificate authorities trusted to identify servers.
+    ///
+    /// Defaults to the platform's native certificate authorities.
+    pub fn set_trust_store(&mut self, trust_store: Option<TrustStore>) -> &mut Self {
+        self.trust_store = trust_store;
+        self
+    }
+
+    /// Set the identity presented to servers that ask for a client certificate.
+    pub fn client_identity(mut self, client_identity: ClientIdentity) -> Self {
+        self.set_client_identity(Some(client_identity));
+        self
+    }
+
+    /// Set the identity presented to servers that ask for a client certificate.
+    pub fn set_client_identity(&mut self, client_identity: Option<ClientIdentity>) -> &mut Self {
+        self.client_identity = client_identity;
+        self
+    }
+
+    /// Builds a [`TlsContext`].
+    pub fn build(self) -> TlsContext {
+        TlsContext {
+            trust_store: self.trust_store.unwrap_or_default(),
+            client_identity: Arc::new(RwLock::new(self.client_identity.map(Arc::new))),
+        }
+    }
+}
+
+/// Certificate authorities trusted to identify servers.
+///
+/// The default trust store contains the platform's native certificate authorities.
+#[derive(Clone, Debug)]
+pub struct TrustStore {
+    native_roots: bool,
+    certificates: Vec<Vec<u8>>,
+}
+
+impl Default for TrustStore {
+    fn default() -> Self {
+        Self {
+            native_roots: true,
+            certificates: Vec::new(),
+        }
+    }
+}
+
+impl TrustStore {
+    /// Returns a trust store that doesn't trust any certificate authority.
+    pub fn empty() -> Self {
+        Self {
+            native_roots: false,
+            certificates: Vec::new(),
+        }
+    }
+
+    /// Set whether the platform's native certificate authorities are trusted.
+    pub fn with_native_roots(mut self, native_roots: bool) -> Self {
+        self.native_roots = native_roots;
+        self
+    }
+
+    /// Trust the PEM-encoded certificates in `pem`.
+    ///
+    /// Returns an error if `pem` doesn't contain any certificate.
+    pub fn with_pem_certificates(mut self, pem: &[u8]) -> Result<Self, InvalidCertificateError> {
+        let certificates = rustls_pemfile::certs(&mut Cursor::new(pem))
+            .map_err(|err| InvalidCertificateError::new(format!("invalid PEM: {err}")))?;
+        if certificates.is_empty() {
+            return Err(InvalidCertificateError::new(
+                "no certificate was found in the PEM data",
+            ));
+        }
+        self.certificates.extend(certificates);
+        Ok(self)
+    }
+
+    /// Trust the DER-encoded certificate `der`.
+    pub fn with_der_certificate(mut self, der: impl Into<Vec<u8>>) -> Self {
+        self.certificates.push(der.into());
+        self
+    }
+
+    /// Returns the DER-encoded certificates to trust, including the native ones if enabled.
+    pub(crate) fn certificates(&self) -> impl Iterator<Item = &[u8]> {
+        let native: &[Vec<u8>] = if self.native_roots {
+            &NATIVE_ROOTS
+        } else {
+            &[]
+        };
+        native
+            .iter()
+            .chain(self.certificates.iter())
+            .map(Vec::as_slice)
+    }
+}
+
+// Loading the native certificates takes 300ms on OS X. Cache them so that we
+// don't need to repeatedly incur that cost.
+static NATIVE_ROOTS: once_cell::sync::Lazy<Vec<Vec<u8>>> =
+    once_cell::sync::Lazy::new(|| match rustls_native_certs::load_native_certs() {
+        Ok(certificates) => certificates
+            .into_iter()
+            .map(|certificate| certificate.0)
+            .collect(),
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to load the native certificate authorities");
+            Vec::new()
+        }
+    });
+
+/// A certificate chain and private key presented to servers that ask for a client certificate.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    certificate_chain: Vec<Vec<u8>>,
+    private_key: Vec<u8>,
+}
+
+impl ClientIdentity {
+    /// Creates a client identity from a PEM-encoded certificate chain and private key.
+    ///
+    /// The certificate chain starts with the client certificate, followed by any intermediate
+    /// certificates. The private key may be a PKCS#8, PKCS#1 (RSA) or SEC1 (EC) key.
+    pub fn from_pem(
+        certificate_chain: &[u8],
+        private_key: &[u8],
+    ) -> Result<Self, InvalidCertificateError> {
+        let certificate_chain = rustls_pemfile::certs(&mut Cursor::new(certificate_chain))
+            .map_err(|err| InvalidCertificateError::new(format!("invalid PEM: {err}")))?;
+        let private_key = rustls_pemfile::read_all(&mut Cursor::new(private_key))
+            .map_err(|err| InvalidCertificateError::new(format!("invalid PEM: {err}")))?
+            .into_iter()
+            .find_map(|item| match item {
+                rustls_pemfile::Item::PKCS8Key(key)
+                | rustls_pemfile::Item::RSAKey(key)
+                | rustls_pemfile::Item::ECKey(key) => Some(key),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                InvalidCertificateError::new("no private key was found in the PEM data")
+            })?;
+        Self::from_der(certificate_chain, private_key)
+    }
+
+    /// Creates a client identity from a DER-encoded certificate chain and private key.
+    ///
+    /// The certificate chain starts with the client certificate, followed by any intermediate
+    /// certificates. The private key may be a PKCS#8, PKCS#1 (RSA) or SEC1 (EC) key, of a type
+    /// that rustls can sign with.
+    pub fn from_der(
+        certificate_chain: Vec<Vec<u8>>,
+        private_key: impl Into<Vec<u8>>,
+    ) -> Result<Self, InvalidCertificateError> {
+        if certificate_chain.is_empty() {
+            return Err(InvalidCertificateError::new(
+                "the client certificate chain is empty",
+            ));
+        }
+        let private_key = private_key.into();
+        validate_private_key(&private_key)?;
+        Ok(Self {
+            certificate_chain,
+            private_key,
+        })
+    }
+
+    pub(crate) fn certificate_chain(&self) -> &[Vec<u8>] {
+        &self.certificate_chain
+    }
+
+    pub(crate) fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+}
+
+/// Checks that rustls can sign with `private_key`, so that an invalid key is reported when it's
+/// loaded rather than when a server asks for a client certificate.
+fn validate_private_key(private_key: &[u8]) -> Result<(), InvalidCertificateError> {
+    let invalid_key = |err: &dyn fmt::Display| {
+        InvalidCertificateError::new(format!("invalid private key: {err}"))
+    };
+    #[cfg(feature = "tls-rustls")]
+    rustls::sign::any_supported_type(&rustls::PrivateKey(private_key.to_vec()))
+        .map_err(|err| invalid_key(&err))?;
+    #[cfg(any(
+        feature = "tls-rustls-ring",
+        feature = "tls-rustls-aws-lc",
+        feature = "tls-rustls-aws-lc-fips"
+    ))]
+    {
+        let key = rustls_0_23::pki_types::PrivateKeyDer::try_from(private_key.to_vec())
+            .map_err(|err| invalid_key(&err))?;
+        #[cfg(feature = "tls-rustls-ring")]
+        rustls_0_23::crypto::ring::sign::any_supported_type(&key)
+            .map_err(|err| invalid_key(&err))?;
+        #[cfg(all(
+            not(feature = "tls-rustls-ring"),
+            any(feature = "tls-rustls-aws-lc", feature = "tls-rustls-aws-lc-fips")
+        ))]
+        rustls_0_23::crypto::aws_lc_rs::sign::any_supported_type(&key)
+            .map_err(|err| invalid_key(&err))?;
+    }
+    Ok(())
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("certificate_chain_len", &self.certificate_chain.len())
+            .field("private_key", &"** redacted **")
+            .finish()
+    }
+}
+
+/// Resolves the client identity of a [`TlsContext`] into the certified key of a rustls version.
+///
+/// The certified key is only rebuilt after the identity is updated.
+pub(crate) struct IdentityResolver<K> {
+    tls_context: TlsContext,
+    cached: Mutex<Option<CachedKey<K>>>,
+}
+
+struct CachedKey<K> {
+    identity: Arc<ClientIdentity>,
+    /// `None` if the identity couldn't be loaded
+    key: Option<Arc<K>>,
+}
+
+impl<K> IdentityResolver<K> {
+    pub(crate) fn new(tls_context: TlsContext) -> Self {
+        Self {
+            tls_context,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn has_certs(&self) -> bool {
+        self.tls_context.has_client_identity()
+    }
+
+    /// Returns the certified key for the current identity, building it with `load` if the
+    /// identity changed since the last call.
+    ///
+    /// If `load` fails for an updated identity, the certified key of the previous identity is
+    /// still returned. Returns `None` if no identity is set, or if no identity could be loaded.
+    pub(crate) fn resolve<E: fmt::Display>(
+        &self,
+        load: impl FnOnce(&ClientIdentity) -> Result<K, E>,
+    ) -> Option<Arc<K>> {
+        let identity = self.tls_context.client_identity()?;
+        let mut cached = self.cached.lock().unwrap();
+        match &*cached {
+            Some(cached) if Arc::ptr_eq(&cached.identity, &identity) => cached.key.clone(),
+            _ => {
+                let key = match load(&identity) {
+                    Ok(key) => Some(Arc::new(key)),
+                    Err(err) => {
+                        let previous = cached.as_ref().and_then(|cached| cached.key.clone());
+                        if previous.is_some() {
+                            tracing::warn!(error = %err, "the client identity can't be used; the previous client identity will be presented");
+                        } else {
+                            tracing::warn!(error = %err, "the client identity can't be used; no client certificate will be presented");
+                        }
+                        previous
+                    }
+                };
+                *cached = Some(CachedKey {
+                    identity,
+                    key: key.clone(),
+                });
+                key
+            }
+        }
+    }
+}
+
+impl<K> fmt::Debug for IdentityResolver<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdentityResolver")
+            .field("tls_context", &self.tls_context)
+            .finish()
+    }
+}
+
+/// An error returned when a certificate or private key is invalid.
+#[derive(Debug)]
+pub struct InvalidCertificateError {
+    message: Cow<'static, str>,
+}
+
+impl InvalidCertificateError {
+    fn new(message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidCertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for InvalidCertificateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CA: &[u8] = include_bytes!("../../../test-data/tls/ca.crt");
+    const CLIENT_CERT: &[u8] = include_bytes!("../../../test-data/tls/client-a.crt");
+    const CLIENT_KEY: &[u8] = include_bytes!("../../../test-data/tls/client-a.key");
+
+    #[test]
+    fn parse_pem() {
+        let identity = ClientIdentity::from_pem(CLIENT_CERT, CLIENT_KEY).unwrap();
+        assert_eq!(1, identity.certificate_chain().len());
+        assert!(!format!("{identity:?}").contains(&format!("{:?}", identity.private_key())));
+
+        assert!(ClientIdentity::from_pem(CLIENT_CERT, CLIENT_CERT).is_err());
+        assert!(ClientIdentity::from_pem(CLIENT_KEY, CLIENT_KEY).is_err());
+        assert!(
+            ClientIdentity::from_der(vec![CLIENT_CERT.to_vec()], b"not a key".to_vec()).is_err()
+        );
+        assert!(TrustStore::empty()
+            .with_pem_certificates(CLIENT_KEY)
+            .is_err());
+
+        let trust_store = TrustStore::empty().with_pem_certificates(CA).unwrap();
+        assert_eq!(1, trust_store.certificates().count());
+    }
+
+    #[test]
+    fn identity_is_reloaded_after_update() {
+        let identity = || ClientIdentity::from_pem(CLIENT_CERT, CLIENT_KEY).unwrap();
+        let tls_context = TlsContext::builder().client_identity(identity()).build();
+        let resolver = IdentityResolver::new(tls_context.clone());
+        let mut loads = 0;
+        let mut resolve = || {
+            resolver.resolve(|_| {
+                loads += 1;
+                Ok::<_, InvalidCertificateError>(loads)
+            })
+        };
+
+        assert_eq!(Some(Arc::new(1)), resolve());
+        assert_eq!(Some(Arc::new(1)), resolve());
+
+        tls_context.update_client_identity(Some(identity()));
+        assert_eq!(Some(Arc::new(2)), resolve());
+
+        tls_context.update_client_identity(None);
+        assert_eq!(None, resolve());
+        assert!(!resolver.has_certs());
+    }
+
+    #[test]
+    fn previous_identity_is_kept_when_reload_fails() {
+        let identity = || ClientIdentity::from_pem(CLIENT_CERT, CLIENT_KEY).unwrap();
+        let tls_context = TlsContext::builder().client_identity(identity()).build();
+        let resolver = IdentityResolver::new(tls_context.clone());
+
+        assert_eq!(
+            Some(Arc::new(1)),
+            resolver.resolve(|_| Ok::<_, InvalidCertificateError>(1))
+        );
+
+        tls_context.update_client_identity(Some(identity()));
+        assert_eq!(
+            Some(Arc::new(1)),
+            resolver.resolve(|_| Err::<i32, _>(InvalidCertificateError::new("unsupported")))
+        );
+    }
+}
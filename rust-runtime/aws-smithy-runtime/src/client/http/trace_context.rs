@@ -0,0 +1,233 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::interceptors::context::BeforeTransmitInterceptorContextMut;
+use aws_smithy_runtime_api::client::interceptors::Intercept;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::config_bag::ConfigBag;
+use aws_smithy_types::trace_context::{Propagation, TraceContext};
+use std::fmt;
+use std::sync::Arc;
+
+type ContextProviderFn = dyn Fn() -> Option<TraceContext> + Send + Sync;
+
+/// An interceptor that propagates the current trace context in request headers.
+///
+/// Each request attempt is sent as a new span, a child of the current [`TraceContext`], so the
+/// service can continue the caller's trace. By default the context is read from
+/// [`TraceContext::current`] and propagated in the W3C Trace Context `traceparent` and
+/// `tracestate` headers. When there's no current context, no headers are added.
+///
+/// Applications that track their traces with another library, such as OpenTelemetry, can read
+/// the context from that library with a [context provider](Self::context_provider).
+///
+/// # Examples
+///
+/// ```no_run
+/// use aws_smithy_runtime::client::http::trace_context::TraceContextInterceptor;
+/// use aws_smithy_types::trace_context::{Propagation, TraceContext};
+///
+/// let interceptor = TraceContextInterceptor::new()
+///     .propagations([Propagation::W3c, Propagation::B3Multi]);
+/// # /*
+/// let config = some_service::Config::builder()
+///     .interceptor(interceptor)
+///     // ...
+///     .build();
+/// let client = some_service::Client::from_conf(config);
+///
+/// // Requests sent while polling the future are part of the trace
+/// let context = TraceContext::new(trace_id, span_id);
+/// context.scope(client.some_operation().send()).await?;
+/// # */
+/// ```
+pub struct TraceContextInterceptor {
+    propagations: Vec<Propagation>,
+    context_provider: Option<Arc<ContextProviderFn>>,
+}
+
+impl fmt::Debug for TraceContextInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceContextInterceptor")
+            .field("propagations", &self.propagations)
+            .field(
+                "context_provider",
+                &self.context_provider.as_ref().map(|_| "** function **"),
+            )
+            .finish()
+    }
+}
+
+impl Default for TraceContextInterceptor {
+    fn default() -> Self {
+        Self {
+            propagations: vec![Propagation::W3c],
+            context_provider: None,
+        }
+    }
+}
+
+impl TraceContextInterceptor {
+    /// Creates a new `TraceContextInterceptor`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Formats to propagate the trace context in. Every format given is added to each request.
+    ///
+    /// Defaults to [`Propagation::W3c`].
+    pub fn propagations(mut self, propagations: impl IntoIterator<Item = Propagation>) -> Self {
+        self.propagations = propagations.into_iter().collect();
+        self
+    }
+
+    /// Reads the trace context of each request from the given function, instead of from
+    /// [`TraceContext::current`].
+    pub fn context_provider(
+        mut self,
+        context_provider: impl Fn() -> Option<TraceContext> + Send + Sync + 'static,
+    ) -> Self {
+        self.context_provider = Some(Arc::new(context_provider));
+        self
+    }
+
+    fn parent(&self) -> Option<TraceContext> {
+        match &self.context_provider {
+            Some(context_provider) => context_provider(),
+            None => TraceContext::current(),
+        }
+    }
+}
+
+impl Intercept for TraceContextInterceptor {
+    fn name(&self) -> &'static str {
+        "TraceContextInterceptor"
+    }
+
+    fn modify_before_transmit(
+        &self,
+        context: &mut BeforeTransmitInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let Some(parent) = self.parent() else {
+            return Ok(());
+        };
+        let span = parent.child(fastrand::u64(1..));
+        let headers = context.request_mut().headers_mut();
+        let mut result = Ok(());
+        for propagation in &self.propagations {
+            propagation.inject(&span, |name, value| {
+                if let Err(err) = headers.try_insert(name, value) {
+                    result = Err(InvalidTraceContextHeader { name, source: err });
+                }
+            });
+        }
+        Ok(result?)
+    }
+}
+
+/// A trace context header, such as a `tracestate` set by the application, isn't a valid header value.
+#[derive(Debug)]
+struct InvalidTraceContextHeader {
+    name: &'static str,
+    source: aws_smithy_runtime_api::http::HttpError,
+}
+
+impl fmt::Display for InvalidTraceContextHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the trace context `{}` header is invalid", self.name)
+    }
+}
+
+impl std::error::Error for InvalidTraceContextHeader {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_runtime_api::client::interceptors::context::{Input, InterceptorContext};
+    use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+    use aws_smithy_runtime_api::client::runtime_components::RuntimeComponentsBuilder;
+    use aws_smithy_types::body::SdkBody;
+
+    const TRACE_ID: u128 = 0x4bf92f3577b34da6a3ce929d0e0e4736;
+    const SPAN_ID: u64 = 0x00f067aa0ba902b7;
+
+    fn try_transmit(interceptor: &TraceContextInterceptor) -> Result<HttpRequest, BoxError> {
+        let components = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        let mut context = InterceptorContext::new(Input::doesnt_matter());
+        context.enter_serialization_phase();
+        context.take_input();
+        context.set_request(HttpRequest::new(SdkBody::empty()));
+        context.enter_before_transmit_phase();
+        interceptor.modify_before_transmit(
+            &mut (&mut context).into(),
+            &components,
+            &mut ConfigBag::base(),
+        )?;
+        Ok(context.take_request().unwrap())
+    }
+
+    fn transmit(interceptor: &TraceContextInterceptor) -> HttpRequest {
+        try_transmit(interceptor).unwrap()
+    }
+
+    fn extract(propagation: Propagation, request: &HttpRequest) -> Option<TraceContext> {
+        propagation.extract(|name| request.headers().get(name))
+    }
+
+    #[test]
+    fn propagates_a_child_of_the_current_context() {
+        let parent = TraceContext::new(TRACE_ID, SPAN_ID).trace_state("vendor=value");
+        let request = parent.in_scope(|| transmit(&TraceContextInterceptor::new()));
+
+        let sent = extract(Propagation::W3c, &request).unwrap();
+        assert_eq!(TRACE_ID, sent.trace_id());
+        assert_ne!(SPAN_ID, sent.span_id());
+        assert!(sent.is_sampled());
+        assert_eq!(Some("vendor=value"), sent.get_trace_state());
+        assert!(request.headers().get("b3").is_none());
+    }
+
+    #[test]
+    fn no_headers_without_a_context() {
+        let request = transmit(&TraceContextInterceptor::new());
+        assert_eq!(0, request.headers().iter().count());
+    }
+
+    #[test]
+    fn propagates_in_every_configured_format() {
+        let parent = TraceContext::new(TRACE_ID, SPAN_ID).sampled(false);
+        let interceptor = TraceContextInterceptor::new()
+            .propagations([Propagation::B3Single, Propagation::B3Multi])
+            .context_provider(move || Some(parent.clone()));
+        let request = transmit(&interceptor);
+
+        assert!(request.headers().get("traceparent").is_none());
+        let single = extract(Propagation::B3Single, &request).unwrap();
+        let multi = extract(Propagation::B3Multi, &request).unwrap();
+        assert_eq!(single, multi);
+        assert_eq!(TRACE_ID, single.trace_id());
+        assert!(!single.is_sampled());
+    }
+
+    #[test]
+    fn invalid_trace_state_fails_the_request() {
+        let parent =
+            TraceContext::new(TRACE_ID, SPAN_ID).trace_state("vendor=value\r\nx-injected: 1");
+        let err = parent
+            .in_scope(|| try_transmit(&TraceContextInterceptor::new()))
+            .unwrap_err();
+        assert_eq!(
+            "the trace context `tracestate` header is invalid",
+            err.to_string()
+        );
+    }
+}
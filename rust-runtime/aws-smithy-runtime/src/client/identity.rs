@@ -4,7 +4,11 @@
  */
 
 mod cache;
-pub use cache::{IdentityCache, LazyCacheBuilder};
+#[cfg(all(feature = "identity-file-cache", feature = "http-auth"))]
+pub use cache::TokenSerializer;
+#[cfg(feature = "identity-file-cache")]
+pub use cache::{FileCacheBuilder, SerializeIdentity};
+pub use cache::{IdentityCache, IdentityCacheMonitor, IdentityCacheStats, LazyCacheBuilder};
 
 /// Identity resolver implementation for "no auth".
 pub mod no_auth;
@@ -10,9 +10,17 @@ use aws_smithy_runtime_api::client::identity::{
 use aws_smithy_runtime_api::shared::IntoShared;
 use aws_smithy_types::config_bag::ConfigBag;
 
+#[cfg(feature = "identity-file-cache")]
+mod file;
 mod lazy;
+mod monitor;
 use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+#[cfg(all(feature = "identity-file-cache", feature = "http-auth"))]
+pub use file::TokenSerializer;
+#[cfg(feature = "identity-file-cache")]
+pub use file::{FileCacheBuilder, SerializeIdentity};
 pub use lazy::LazyCacheBuilder;
+pub use monitor::{IdentityCacheMonitor, IdentityCacheStats};
 
 /// Identity cache configuration.
 ///
@@ -74,6 +82,14 @@ impl IdentityCache {
     pub fn lazy() -> LazyCacheBuilder {
         LazyCacheBuilder::new()
     }
+
+    /// Configure an identity cache that persists identities to files.
+    ///
+    /// Identities are shared with other processes that use the same cache key for an identity resolver.
+    #[cfg(feature = "identity-file-cache")]
+    pub fn file() -> FileCacheBuilder {
+        FileCacheBuilder::new()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,910 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use crate::expiring_cache::ExpiringCache;
+use aws_smithy_async::future::timeout::Timeout;
+use aws_smithy_async::rt::sleep::AsyncSleep;
+use aws_smithy_async::time::TimeSource;
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::identity::{
+    Identity, IdentityCachePartition, IdentityFuture, ResolveCachedIdentity, ResolveIdentity,
+    SharedIdentityCache, SharedIdentityResolver,
+};
+use aws_smithy_runtime_api::client::runtime_components::{
+    RuntimeComponents, RuntimeComponentsBuilder,
+};
+use aws_smithy_runtime_api::shared::IntoShared;
+use aws_smithy_types::config_bag::ConfigBag;
+use aws_smithy_types::date_time::Format;
+use aws_smithy_types::error::display::DisplayErrorContext;
+use aws_smithy_types::DateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::Instrument;
+
+const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_EXPIRATION: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_BUFFER_TIME: Duration = Duration::from_secs(10);
+/// How often to check whether another process released the cache file lock.
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long a lock may be held beyond the load timeout before it is assumed to be abandoned.
+const LOCK_STALE_GRACE: Duration = Duration::from_secs(5);
+
+/// Serializes identities so that they can be persisted by a [file identity cache](FileCacheBuilder).
+pub trait SerializeIdentity: Send + Sync + fmt::Debug {
+    /// Serializes the given identity, or returns `None` if it shouldn't be persisted.
+    fn serialize(&self, identity: &Identity) -> Option<String>;
+
+    /// Deserializes an identity returned by [`serialize`](Self::serialize).
+    ///
+    /// The `expiration` is the expiration of the serialized identity.
+    fn deserialize(&self, data: &str, expiration: Option<SystemTime>)
+        -> Result<Identity, BoxError>;
+}
+
+/// Serializes [`Token`](aws_smithy_runtime_api::client::identity::http::Token) identities.
+#[cfg(feature = "http-auth")]
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct TokenSerializer;
+
+#[cfg(feature = "http-auth")]
+impl TokenSerializer {
+    /// Creates a new `TokenSerializer`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "http-auth")]
+impl SerializeIdentity for TokenSerializer {
+    fn serialize(&self, identity: &Identity) -> Option<String> {
+        use aws_smithy_runtime_api::client::identity::http::Token;
+        identity
+            .data::<Token>()
+            .map(|token| token.token().to_string())
+    }
+
+    fn deserialize(
+        &self,
+        data: &str,
+        expiration: Option<SystemTime>,
+    ) -> Result<Identity, BoxError> {
+        use aws_smithy_runtime_api::client::identity::http::Token;
+        Ok(Token::new(data, expiration).into())
+    }
+}
+
+/// Builder for an identity cache that persists identities to files.
+///
+/// Short-lived processes, such as command line tools, can't benefit from an in-memory cache,
+/// since they exit before their identities expire. This cache writes resolved identities to a file
+/// so that later processes can reuse them until they expire. Concurrent processes take a lock
+/// before resolving an identity, so that only one of them calls the identity resolver.
+///
+/// Identities are only persisted for the identity resolvers given a [cache key](Self::resolver_cache_key).
+/// Each of them gets a file of its own, named after a hash of its key. The key should uniquely identify
+/// the resolver's configuration, such as the role to assume and the profile it is assumed from, and must
+/// be the same in every process, since processes that use the same key share the cached identity.
+/// Identities of other resolvers are only cached in memory.
+///
+/// On Unix, the cache directory is created with `0700` permissions and cache files with `0600`
+/// permissions, since they contain secrets.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "http-auth")]
+/// # {
+/// use aws_smithy_runtime::client::identity::{FileCacheBuilder, TokenSerializer};
+/// use aws_smithy_runtime_api::client::identity::SharedIdentityResolver;
+/// # use aws_smithy_runtime_api::client::identity::{IdentityFuture, ResolveIdentity};
+/// # use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+/// # use aws_smithy_types::config_bag::ConfigBag;
+/// # #[derive(Debug)]
+/// # struct SsoTokenResolver;
+/// # impl ResolveIdentity for SsoTokenResolver {
+/// #     fn resolve_identity<'a>(&'a self, _: &'a RuntimeComponents, _: &'a ConfigBag) -> IdentityFuture<'a> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+///
+/// let resolver = SharedIdentityResolver::new(SsoTokenResolver);
+/// let identity_cache = FileCacheBuilder::new()
+///     .directory("/home/user/.example/cache")
+///     .resolver_cache_key(&resolver, "https://example.com/sso/start#my-role")
+///     .serializer(TokenSerializer::new())
+///     .build();
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct FileCacheBuilder {
+    directory: Option<PathBuf>,
+    cache_keys: HashMap<IdentityCachePartition, String>,
+    serializer: Option<Arc<dyn SerializeIdentity>>,
+    load_timeout: Option<Duration>,
+    buffer_time: Option<Duration>,
+    default_expiration: Option<Duration>,
+}
+
+impl FileCacheBuilder {
+    /// Create a new builder.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Directory to write cache files to. This is required.
+    pub fn directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.set_directory(Some(directory.into()));
+        self
+    }
+
+    /// Directory to write cache files to. This is required.
+    pub fn set_directory(&mut self, directory: Option<PathBuf>) -> &mut Self {
+        self.directory = directory;
+        self
+    }
+
+    /// Persists the identities of `resolver` under a key that identifies its configuration.
+    ///
+    /// The key must be stable across processes, since processes using the same key share cached
+    /// identities. At least one resolver is required.
+    pub fn resolver_cache_key(
+        mut self,
+        resolver: &SharedIdentityResolver,
+        cache_key: impl Into<String>,
+    ) -> Self {
+        self.set_resolver_cache_key(resolver, cache_key);
+        self
+    }
+
+    /// Persists the identities of `resolver` under a key that identifies its configuration.
+    ///
+    /// The key must be stable across processes, since processes using the same key share cached
+    /// identities. At least one resolver is required.
+    pub fn set_resolver_cache_key(
+        &mut self,
+        resolver: &SharedIdentityResolver,
+        cache_key: impl Into<String>,
+    ) -> &mut Self {
+        self.cache_keys
+            .insert(resolver.cache_partition(), cache_key.into());
+        self
+    }
+
+    /// Serializer for the cached identities. This is required.
+    ///
+    /// Identities that the serializer doesn't serialize are only cached in memory.
+    pub fn serializer(mut self, serializer: impl SerializeIdentity + 'static) -> Self {
+        self.set_serializer(Some(Arc::new(serializer)));
+        self
+    }
+
+    /// Serializer for the cached identities. This is required.
+    ///
+    /// Identities that the serializer doesn't serialize are only cached in memory.
+    pub fn set_serializer(&mut self, serializer: Option<Arc<dyn SerializeIdentity>>) -> &mut Self {
+        self.serializer = serializer;
+        self
+    }
+
+    /// Timeout for identity resolution.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn load_timeout(mut self, timeout: Duration) -> Self {
+        self.set_load_timeout(Some(timeout));
+        self
+    }
+
+    /// Timeout for identity resolution.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn set_load_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.load_timeout = timeout;
+        self
+    }
+
+    /// Amount of time before the actual identity expiration time where the identity is considered expired.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn buffer_time(mut self, buffer_time: Duration) -> Self {
+        self.set_buffer_time(Some(buffer_time));
+        self
+    }
+
+    /// Amount of time before the actual identity expiration time where the identity is considered expired.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn set_buffer_time(&mut self, buffer_time: Option<Duration>) -> &mut Self {
+        self.buffer_time = buffer_time;
+        self
+    }
+
+    /// Default expiration time to set on an identity if it doesn't have an expiration time.
+    ///
+    /// Defaults to 15 minutes.
+    pub fn default_expiration(mut self, duration: Duration) -> Self {
+        self.set_default_expiration(Some(duration));
+        self
+    }
+
+    /// Default expiration time to set on an identity if it doesn't have an expiration time.
+    ///
+    /// Defaults to 15 minutes.
+    pub fn set_default_expiration(&mut self, duration: Option<Duration>) -> &mut Self {
+        self.default_expiration = duration;
+        self
+    }
+
+    /// Builds a [`SharedIdentityCache`] from this builder.
+    ///
+    /// # Panics
+    ///
+    /// This builder will panic if the directory, a resolver cache key, or serializer are not given.
+    pub fn build(self) -> SharedIdentityCache {
+        let directory = self.directory.expect("a cache directory is required");
+        assert!(
+            !self.cache_keys.is_empty(),
+            "a resolver cache key is required"
+        );
+        FileCache {
+            directory,
+            cache_keys: self.cache_keys,
+            serializer: self.serializer.expect("an identity serializer is required"),
+            partitions: Default::default(),
+            load_timeout: self.load_timeout.unwrap_or(DEFAULT_LOAD_TIMEOUT),
+            buffer_time: self.buffer_time.unwrap_or(DEFAULT_BUFFER_TIME),
+            default_expiration: self.default_expiration.unwrap_or(DEFAULT_EXPIRATION),
+        }
+        .into_shared()
+    }
+}
+
+fn hex_digest(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+/// Contents of a cache file.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheFile {
+    cache_key: String,
+    /// When the cached identity should no longer be used.
+    cached_until: String,
+    /// The expiration of the identity itself, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiration: Option<String>,
+    identity: String,
+}
+
+fn format_time(time: SystemTime) -> Result<String, BoxError> {
+    Ok(DateTime::from(time).fmt(Format::DateTime)?)
+}
+
+fn parse_time(time: &str) -> Result<SystemTime, BoxError> {
+    Ok(SystemTime::try_from(DateTime::from_str(
+        time,
+        Format::DateTime,
+    )?)?)
+}
+
+/// The cache file of one identity resolver.
+#[derive(Debug)]
+struct CachePaths {
+    path: PathBuf,
+    lock_path: PathBuf,
+    cache_key: String,
+}
+
+impl CachePaths {
+    fn new(directory: &Path, cache_key: &str) -> Self {
+        let file_name = hex_digest(cache_key);
+        Self {
+            path: directory.join(format!("{file_name}.json")),
+            lock_path: directory.join(format!("{file_name}.lock")),
+            cache_key: cache_key.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Partition {
+    /// Identities are also cached in memory, so that long-lived processes don't read the file for every request.
+    cache: ExpiringCache<Identity, BoxError>,
+    /// The cache file, if the resolver has a cache key.
+    paths: Option<Arc<CachePaths>>,
+}
+
+#[derive(Debug)]
+struct FileCache {
+    directory: PathBuf,
+    cache_keys: HashMap<IdentityCachePartition, String>,
+    serializer: Arc<dyn SerializeIdentity>,
+    partitions: RwLock<HashMap<IdentityCachePartition, Partition>>,
+    load_timeout: Duration,
+    buffer_time: Duration,
+    default_expiration: Duration,
+}
+
+impl FileCache {
+    fn partition(&self, key: IdentityCachePartition) -> Partition {
+        if let Some(partition) = self.partitions.read().unwrap().get(&key) {
+            return partition.clone();
+        }
+        self.partitions
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Partition {
+                cache: ExpiringCache::new(self.buffer_time),
+                paths: self
+                    .cache_keys
+                    .get(&key)
+                    .map(|cache_key| Arc::new(CachePaths::new(&self.directory, cache_key))),
+            })
+            .clone()
+    }
+
+    /// Loads an identity from the cache file, or resolves and persists a new one.
+    ///
+    /// Without a cache file, the identity is only resolved.
+    async fn load(
+        &self,
+        paths: Option<&CachePaths>,
+        resolver: &SharedIdentityResolver,
+        runtime_components: &RuntimeComponents,
+        config_bag: &ConfigBag,
+        time_source: &dyn TimeSource,
+        sleep_impl: &dyn AsyncSleep,
+    ) -> Result<(Identity, SystemTime), BoxError> {
+        let mut lock = None;
+        if let Some(paths) = paths {
+            if let Some(cached) = self.read(paths, time_source.now()).await {
+                return Ok(cached);
+            }
+
+            lock = self.lock(paths, time_source, sleep_impl).await;
+            // Another process may have resolved an identity while this one waited for the lock
+            if lock.is_some() {
+                if let Some(cached) = self.read(paths, time_source.now()).await {
+                    return Ok(cached);
+                }
+            }
+        }
+
+        let fut = Timeout::new(
+            resolver.resolve_identity(runtime_components, config_bag),
+            sleep_impl.sleep(self.load_timeout),
+        );
+        let identity = match fut.await {
+            Ok(result) => result?,
+            Err(_err) => match resolver.fallback_on_interrupt() {
+                Some(identity) => identity,
+                None => return Err(BoxError::from(TimedOutError(self.load_timeout))),
+            },
+        };
+        let cached_until = identity
+            .expiration()
+            .unwrap_or(time_source.now() + self.default_expiration);
+        if let Some((paths, data)) = paths.zip(self.serializer.serialize(&identity)) {
+            if let Err(err) = self
+                .write(paths, data, cached_until, identity.expiration())
+                .await
+            {
+                tracing::warn!(
+                    path = %paths.path.display(),
+                    "failed to write identity cache file: {}",
+                    DisplayErrorContext(&*err)
+                );
+            }
+        }
+        drop(lock);
+        Ok((identity, cached_until))
+    }
+
+    /// Reads an identity from the cache file if it exists and hasn't expired.
+    async fn read(&self, paths: &CachePaths, now: SystemTime) -> Option<(Identity, SystemTime)> {
+        let contents = match tokio::fs::read(&paths.path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                tracing::warn!(path = %paths.path.display(), "failed to read identity cache file: {err}");
+                return None;
+            }
+        };
+        match self.parse(paths, &contents, now) {
+            Ok(cached) => cached,
+            Err(err) => {
+                tracing::warn!(
+                    path = %paths.path.display(),
+                    "ignoring invalid identity cache file: {}",
+                    DisplayErrorContext(&*err)
+                );
+                None
+            }
+        }
+    }
+
+    fn parse(
+        &self,
+        paths: &CachePaths,
+        contents: &[u8],
+        now: SystemTime,
+    ) -> Result<Option<(Identity, SystemTime)>, BoxError> {
+        let file: CacheFile = serde_json::from_slice(contents)?;
+        // Guard against hash collisions between cache keys
+        if file.cache_key != paths.cache_key {
+            return Ok(None);
+        }
+        let cached_until = parse_time(&file.cached_until)?;
+        if now >= cached_until - self.buffer_time {
+            tracing::debug!(cached_until = ?cached_until, "identity in cache file has expired");
+            return Ok(None);
+        }
+        let expiration = file.expiration.as_deref().map(parse_time).transpose()?;
+        let identity = self.serializer.deserialize(&file.identity, expiration)?;
+        tracing::debug!(path = %paths.path.display(), "loaded identity from cache file");
+        Ok(Some((identity, cached_until)))
+    }
+
+    /// Atomically replaces the cache file, so that other processes never read a partial file.
+    async fn write(
+        &self,
+        paths: &CachePaths,
+        identity: String,
+        cached_until: SystemTime,
+        expiration: Option<SystemTime>,
+    ) -> Result<(), BoxError> {
+        let file = CacheFile {
+            cache_key: paths.cache_key.clone(),
+            cached_until: format_time(cached_until)?,
+            expiration: expiration.map(format_time).transpose()?,
+            identity,
+        };
+        let contents = serde_json::to_vec(&file)?;
+
+        self.create_directory().await?;
+        let temp_path = paths
+            .path
+            .with_extension(format!("{:016x}.tmp", fastrand::u64(..)));
+        let result = async {
+            let mut file = restricted_options().open(&temp_path).await?;
+            tokio::io::AsyncWriteExt::write_all(&mut file, &contents).await?;
+            file.sync_all().await?;
+            tokio::fs::rename(&temp_path, &paths.path).await
+        }
+        .await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+        }
+        Ok(result?)
+    }
+
+    async fn create_directory(&self) -> io::Result<()> {
+        let mut dir_builder = tokio::fs::DirBuilder::new();
+        dir_builder.recursive(true);
+        #[cfg(unix)]
+        dir_builder.mode(0o700);
+        dir_builder.create(&self.directory).await
+    }
+
+    /// Takes the lock that makes other processes wait for this one to resolve an identity.
+    ///
+    /// Returns `None` if the lock can't be taken, in which case the identity is resolved without it.
+    async fn lock(
+        &self,
+        paths: &CachePaths,
+        time_source: &dyn TimeSource,
+        sleep_impl: &dyn AsyncSleep,
+    ) -> Option<LockGuard> {
+        let stale_after = self.load_timeout + LOCK_STALE_GRACE;
+        let give_up_at = time_source.now() + stale_after;
+        loop {
+            if let Err(err) = self.create_directory().await {
+                tracing::warn!(path = %self.directory.display(), "failed to create identity cache directory: {err}");
+                return None;
+            }
+            match restricted_options().open(&paths.lock_path).await {
+                Ok(_) => {
+                    return Some(LockGuard {
+                        path: paths.lock_path.clone(),
+                    })
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(err) => {
+                    tracing::warn!(path = %paths.lock_path.display(), "failed to lock identity cache file: {err}");
+                    return None;
+                }
+            }
+
+            let now = time_source.now();
+            if is_stale(&paths.lock_path, now, stale_after).await {
+                tracing::debug!(path = %paths.lock_path.display(), "removing abandoned identity cache lock");
+                let _ = tokio::fs::remove_file(&paths.lock_path).await;
+                continue;
+            }
+            if now >= give_up_at {
+                tracing::warn!(path = %paths.lock_path.display(), "timed out waiting for identity cache lock");
+                return None;
+            }
+            sleep_impl.sleep(LOCK_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn restricted_options() -> tokio::fs::OpenOptions {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+}
+
+async fn is_stale(path: &Path, now: SystemTime, stale_after: Duration) -> bool {
+    match tokio::fs::metadata(path).await.and_then(|m| m.modified()) {
+        Ok(modified) => modified + stale_after <= now,
+        Err(_) => false,
+    }
+}
+
+/// Releases the cache file lock when dropped.
+#[derive(Debug)]
+struct LockGuard {
+    path: PathBuf,
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn validate_components(time_source: bool, sleep_impl: bool) -> Result<(), BoxError> {
+    if !time_source || !sleep_impl {
+        return Err(BoxError::from(
+            "File identity caching requires a time source and an async sleep implementation to be configured. \
+             Set them using the `time_source` and `sleep_impl` methods on config.",
+        ));
+    }
+    Ok(())
+}
+
+impl ResolveCachedIdentity for FileCache {
+    fn validate_base_client_config(
+        &self,
+        runtime_components: &RuntimeComponentsBuilder,
+        _cfg: &ConfigBag,
+    ) -> Result<(), BoxError> {
+        validate_components(
+            runtime_components.time_source().is_some(),
+            runtime_components.sleep_impl().is_some(),
+        )
+    }
+
+    fn validate_final_config(
+        &self,
+        runtime_components: &RuntimeComponents,
+        _cfg: &ConfigBag,
+    ) -> Result<(), BoxError> {
+        validate_components(
+            runtime_components.time_source().is_some(),
+            runtime_components.sleep_impl().is_some(),
+        )
+    }
+
+    fn resolve_cached_identity<'a>(
+        &'a self,
+        resolver: SharedIdentityResolver,
+        runtime_components: &'a RuntimeComponents,
+        config_bag: &'a ConfigBag,
+    ) -> IdentityFuture<'a> {
+        let (time_source, sleep_impl) = (
+            runtime_components.time_source().expect("validated"),
+            runtime_components.sleep_impl().expect("validated"),
+        );
+        let Partition { cache, paths } = self.partition(resolver.cache_partition());
+
+        IdentityFuture::new(async move {
+            if let Some(identity) = cache.yield_or_clear_if_expired(time_source.now()).await {
+                return Ok(identity);
+            }
+            cache
+                .get_or_load(|| {
+                    let span = tracing::info_span!("file_cache_load_identity");
+                    async {
+                        self.load(
+                            paths.as_deref(),
+                            &resolver,
+                            runtime_components,
+                            config_bag,
+                            &time_source,
+                            &sleep_impl,
+                        )
+                        .await
+                    }
+                    .instrument(span)
+                })
+                .await
+        })
+    }
+}
+
+#[derive(Debug)]
+struct TimedOutError(Duration);
+
+impl std::error::Error for TimedOutError {}
+
+impl fmt::Display for TimedOutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "identity resolver timed out after {:?}", self.0)
+    }
+}
+
+#[cfg(all(test, feature = "http-auth"))]
+mod tests {
+    use super::*;
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_async::test_util::ManualTimeSource;
+    use aws_smithy_async::time::SystemTimeSource;
+    use aws_smithy_runtime_api::client::identity::http::Token;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingResolver {
+        calls: Arc<AtomicUsize>,
+        expires_in: Duration,
+        delay: Duration,
+    }
+
+    impl ResolveIdentity for CountingResolver {
+        fn resolve_identity<'a>(
+            &'a self,
+            runtime_components: &'a RuntimeComponents,
+            _config_bag: &'a ConfigBag,
+        ) -> IdentityFuture<'a> {
+            IdentityFuture::new(async move {
+                let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+                tokio::time::sleep(self.delay).await;
+                let now = runtime_components.time_source().unwrap().now();
+                Ok(Token::new(format!("token-{call}"), Some(now + self.expires_in)).into())
+            })
+        }
+    }
+
+    fn resolver(calls: &Arc<AtomicUsize>, expires_in: Duration) -> SharedIdentityResolver {
+        SharedIdentityResolver::new(CountingResolver {
+            calls: calls.clone(),
+            expires_in,
+            delay: Duration::ZERO,
+        })
+    }
+
+    fn components(time_source: impl TimeSource + 'static) -> RuntimeComponents {
+        RuntimeComponentsBuilder::for_tests()
+            .with_time_source(Some(time_source))
+            .with_sleep_impl(Some(TokioSleep::new()))
+            .build()
+            .unwrap()
+    }
+
+    /// Each cache stands in for a separate process, which has a resolver instance of its own.
+    fn process_cache(
+        directory: &Path,
+        resolver: &SharedIdentityResolver,
+        cache_key: &str,
+    ) -> SharedIdentityCache {
+        FileCacheBuilder::new()
+            .directory(directory)
+            .resolver_cache_key(resolver, cache_key)
+            .serializer(TokenSerializer::new())
+            .build()
+    }
+
+    /// Path of the cache file with the given extension for the given cache key.
+    fn cache_path(directory: &Path, cache_key: &str, extension: &str) -> PathBuf {
+        CachePaths::new(directory, cache_key)
+            .path
+            .with_extension(extension)
+    }
+
+    async fn token(
+        cache: &SharedIdentityCache,
+        resolver: SharedIdentityResolver,
+        components: &RuntimeComponents,
+    ) -> String {
+        let identity = cache
+            .resolve_cached_identity(resolver, components, &ConfigBag::base())
+            .await
+            .unwrap();
+        identity.data::<Token>().unwrap().token().to_string()
+    }
+
+    #[tokio::test]
+    async fn identities_are_shared_between_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = components(SystemTimeSource::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let first_resolver = resolver(&calls, Duration::from_secs(3600));
+        let first = process_cache(dir.path(), &first_resolver, "key");
+        assert_eq!("token-1", token(&first, first_resolver, &components).await);
+        let second_resolver = resolver(&calls, Duration::from_secs(3600));
+        let second = process_cache(dir.path(), &second_resolver, "key");
+        assert_eq!(
+            "token-1",
+            token(&second, second_resolver, &components).await
+        );
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+
+        // A different provider configuration doesn't use the cached identity
+        let other_resolver = resolver(&calls, Duration::from_secs(3600));
+        let other = process_cache(dir.path(), &other_resolver, "other key");
+        assert_eq!("token-2", token(&other, other_resolver, &components).await);
+    }
+
+    #[tokio::test]
+    async fn expired_identities_are_resolved_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let time = ManualTimeSource::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1000));
+        let components = components(time.clone());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = resolver(&calls, Duration::from_secs(60));
+
+        let first = process_cache(dir.path(), &resolver, "key");
+        assert_eq!(
+            "token-1",
+            token(&first, resolver.clone(), &components).await
+        );
+        time.advance(Duration::from_secs(45));
+        let second = process_cache(dir.path(), &resolver, "key");
+        assert_eq!(
+            "token-1",
+            token(&second, resolver.clone(), &components).await
+        );
+        // The identity is inside the buffer time
+        time.advance(Duration::from_secs(10));
+        let third = process_cache(dir.path(), &resolver, "key");
+        assert_eq!("token-2", token(&third, resolver, &components).await);
+    }
+
+    #[tokio::test]
+    async fn concurrent_processes_resolve_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = components(SystemTimeSource::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let slow_resolver = || {
+            SharedIdentityResolver::new(CountingResolver {
+                calls: calls.clone(),
+                expires_in: Duration::from_secs(3600),
+                delay: Duration::from_millis(200),
+            })
+        };
+        let (first_resolver, second_resolver) = (slow_resolver(), slow_resolver());
+
+        let (first, second) = (
+            process_cache(dir.path(), &first_resolver, "key"),
+            process_cache(dir.path(), &second_resolver, "key"),
+        );
+        let (a, b) = tokio::join!(
+            token(&first, first_resolver, &components),
+            token(&second, second_resolver, &components),
+        );
+        assert_eq!(("token-1", "token-1"), (a.as_str(), b.as_str()));
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+        assert!(!cache_path(dir.path(), "key", "lock").exists());
+    }
+
+    #[tokio::test]
+    async fn abandoned_locks_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = resolver(&calls, Duration::from_secs(3600));
+        let lock_path = cache_path(dir.path(), "key", "lock");
+        std::fs::write(&lock_path, b"").unwrap();
+        let modified = std::fs::metadata(&lock_path).unwrap().modified().unwrap();
+        let components = components(ManualTimeSource::new(modified + Duration::from_secs(60)));
+
+        let cache = process_cache(dir.path(), &resolver, "key");
+        assert_eq!("token-1", token(&cache, resolver, &components).await);
+        assert!(!lock_path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cache_files_are_only_readable_by_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let components = components(SystemTimeSource::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = resolver(&calls, Duration::from_secs(3600));
+        let cache = process_cache(&cache_dir, &resolver, "key");
+        token(&cache, resolver, &components).await;
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o700, mode(&cache_dir));
+        assert_eq!(0o600, mode(&cache_path(&cache_dir, "key", "json")));
+    }
+
+    #[tokio::test]
+    async fn invalid_cache_files_are_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = components(SystemTimeSource::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let resolver = resolver(&calls, Duration::from_secs(3600));
+        let path = cache_path(dir.path(), "key", "json");
+        std::fs::write(&path, b"not json").unwrap();
+
+        let cache = process_cache(dir.path(), &resolver, "key");
+        assert_eq!("token-1", token(&cache, resolver, &components).await);
+        let file: CacheFile = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!("token-1", file.identity);
+    }
+
+    #[tokio::test]
+    async fn each_resolver_gets_its_own_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = components(SystemTimeSource::new());
+        let (first_calls, second_calls) =
+            (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let process = || {
+            let first = resolver(&first_calls, Duration::from_secs(3600));
+            let second = resolver(&second_calls, Duration::from_secs(3600));
+            let cache = FileCacheBuilder::new()
+                .directory(dir.path())
+                .resolver_cache_key(&first, "first")
+                .resolver_cache_key(&second, "second")
+                .serializer(TokenSerializer::new())
+                .build();
+            (cache, first, second)
+        };
+
+        let (cache, first, second) = process();
+        assert_eq!("token-1", token(&cache, first, &components).await);
+        assert_eq!("token-1", token(&cache, second, &components).await);
+        assert!(cache_path(dir.path(), "first", "json").exists());
+        assert!(cache_path(dir.path(), "second", "json").exists());
+
+        // Another process loads each resolver's identity from its own file
+        let (other_process, first, second) = process();
+        assert_eq!("token-1", token(&other_process, second, &components).await);
+        assert_eq!("token-1", token(&other_process, first, &components).await);
+        assert_eq!(
+            (1, 1),
+            (
+                first_calls.load(Ordering::SeqCst),
+                second_calls.load(Ordering::SeqCst)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn resolvers_without_a_cache_key_are_only_cached_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let components = components(SystemTimeSource::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let keyed = resolver(&calls, Duration::from_secs(3600));
+        let unkeyed = resolver(&calls, Duration::from_secs(3600));
+
+        let cache = process_cache(dir.path(), &keyed, "key");
+        assert_eq!("token-1", token(&cache, unkeyed.clone(), &components).await);
+        assert_eq!("token-1", token(&cache, unkeyed, &components).await);
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+        assert_eq!(0, std::fs::read_dir(dir.path()).unwrap().count());
+    }
+}
@@ -3,9 +3,10 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use super::IdentityCacheMonitor;
 use crate::expiring_cache::ExpiringCache;
 use aws_smithy_async::future::timeout::Timeout;
-use aws_smithy_async::rt::sleep::{AsyncSleep, SharedAsyncSleep};
+use aws_smithy_async::rt::sleep::{AsyncSleep, SharedAsyncSleep, Sleep};
 use aws_smithy_async::time::{SharedTimeSource, TimeSource};
 use aws_smithy_runtime_api::box_error::BoxError;
 use aws_smithy_runtime_api::client::identity::{
@@ -15,17 +16,20 @@ use aws_smithy_runtime_api::client::identity::{
 use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
 use aws_smithy_runtime_api::shared::IntoShared;
 use aws_smithy_types::config_bag::ConfigBag;
+use aws_smithy_types::error::display::DisplayErrorContext;
 use aws_smithy_types::DateTime;
 use std::collections::HashMap;
 use std::fmt;
-use std::sync::RwLock;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
 use tracing::Instrument;
 
 const DEFAULT_LOAD_TIMEOUT: Duration = Duration::from_secs(5);
 const DEFAULT_EXPIRATION: Duration = Duration::from_secs(15 * 60);
 const DEFAULT_BUFFER_TIME: Duration = Duration::from_secs(10);
 const DEFAULT_BUFFER_TIME_JITTER_FRACTION: fn() -> f64 = || fastrand::f64() * 0.5;
+const DEFAULT_REFRESH_JITTER_FRACTION: fn() -> f64 = || fastrand::f64() * 0.5;
+const REFRESH_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Builder for lazy identity caching.
 #[derive(Default, Debug)]
@@ -36,6 +40,9 @@ pub struct LazyCacheBuilder {
     buffer_time: Option<Duration>,
     buffer_time_jitter_fraction: Option<fn() -> f64>,
     default_expiration: Option<Duration>,
+    refresh_ahead: Option<Duration>,
+    refresh_jitter_fraction: Option<fn() -> f64>,
+    monitor: Option<IdentityCacheMonitor>,
 }
 
 impl LazyCacheBuilder {
@@ -161,6 +168,57 @@ impl LazyCacheBuilder {
         self
     }
 
+    /// Amount of time before the identity expiration time where the identity is speculatively refreshed.
+    ///
+    /// The first request made inside this window resolves a new identity before it is sent, while
+    /// the other requests keep using the cached identity instead of waiting for it. If the refresh
+    /// fails, the cached identity continues to be used until it expires, and a new refresh is
+    /// attempted after 10 seconds.
+    ///
+    /// The window is jittered so that processes sharing an identity don't refresh it at the same time:
+    /// each identity is refreshed at a random point between `refresh_ahead` and half of `refresh_ahead`
+    /// before it expires, but never before half of its remaining lifetime has passed. This should be
+    /// larger than the [buffer time](Self::buffer_time), since identities inside the buffer time are
+    /// loaded by every caller that needs them.
+    ///
+    /// Disabled by default.
+    pub fn refresh_ahead(mut self, refresh_ahead: Duration) -> Self {
+        self.set_refresh_ahead(Some(refresh_ahead));
+        self
+    }
+
+    /// Amount of time before the identity expiration time where the identity is speculatively refreshed.
+    ///
+    /// See [`refresh_ahead`](Self::refresh_ahead) for details.
+    ///
+    /// Disabled by default.
+    pub fn set_refresh_ahead(&mut self, refresh_ahead: Option<Duration>) -> &mut Self {
+        self.refresh_ahead = refresh_ahead;
+        self
+    }
+
+    /// A random percentage by which the refresh ahead window is shortened.
+    ///
+    /// Defaults to a randomly generated value between 0.0 and 0.5. This setter is for testing only.
+    #[allow(unused)]
+    #[cfg(test)]
+    fn refresh_jitter_fraction(mut self, refresh_jitter_fraction: fn() -> f64) -> Self {
+        self.refresh_jitter_fraction = Some(refresh_jitter_fraction);
+        self
+    }
+
+    /// Sets a monitor that counts cache hits, misses, and refreshes.
+    pub fn monitor(mut self, monitor: IdentityCacheMonitor) -> Self {
+        self.set_monitor(Some(monitor));
+        self
+    }
+
+    /// Sets a monitor that counts cache hits, misses, and refreshes.
+    pub fn set_monitor(&mut self, monitor: Option<IdentityCacheMonitor>) -> &mut Self {
+        self.monitor = monitor;
+        self
+    }
+
     /// Builds a [`SharedIdentityCache`] from this builder.
     ///
     /// # Panics
@@ -179,13 +237,66 @@ impl LazyCacheBuilder {
                 .unwrap_or(DEFAULT_BUFFER_TIME_JITTER_FRACTION),
             default_expiration,
         )
+        .with_refresh_ahead(
+            self.refresh_ahead,
+            self.refresh_jitter_fraction
+                .unwrap_or(DEFAULT_REFRESH_JITTER_FRACTION),
+        )
+        .with_monitor(self.monitor.unwrap_or_default())
         .into_shared()
     }
 }
 
+/// A cached identity along with when it should be speculatively refreshed.
+#[derive(Clone, Debug)]
+struct Partition {
+    cache: ExpiringCache<Identity, BoxError>,
+    refresh: Arc<Mutex<RefreshSchedule>>,
+}
+
+#[derive(Debug, Default)]
+struct RefreshSchedule {
+    refresh_at: Option<SystemTime>,
+    in_progress: bool,
+}
+
+impl Partition {
+    fn new(buffer_time: Duration) -> Self {
+        Self {
+            cache: ExpiringCache::new(buffer_time),
+            refresh: Default::default(),
+        }
+    }
+
+    fn schedule_refresh(&self, refresh_at: Option<SystemTime>) {
+        self.refresh.lock().unwrap().refresh_at = refresh_at;
+    }
+
+    /// Claims the refresh of the cached identity if it is due and no one else is refreshing it.
+    fn claim_refresh(&self, now: SystemTime) -> Option<RefreshClaim> {
+        let mut schedule = self.refresh.lock().unwrap();
+        match schedule.refresh_at {
+            Some(refresh_at) if now >= refresh_at && !schedule.in_progress => {
+                schedule.in_progress = true;
+                Some(RefreshClaim(self.refresh.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Releases a refresh claim when dropped, including when the refresh is cancelled.
+struct RefreshClaim(Arc<Mutex<RefreshSchedule>>);
+
+impl Drop for RefreshClaim {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().in_progress = false;
+    }
+}
+
 #[derive(Debug)]
 struct CachePartitions {
-    partitions: RwLock<HashMap<IdentityCachePartition, ExpiringCache<Identity, BoxError>>>,
+    partitions: RwLock<HashMap<IdentityCachePartition, Partition>>,
     buffer_time: Duration,
 }
 
@@ -197,7 +308,7 @@ impl CachePartitions {
         }
     }
 
-    fn partition(&self, key: IdentityCachePartition) -> ExpiringCache<Identity, BoxError> {
+    fn partition(&self, key: IdentityCachePartition) -> Partition {
         let mut partition = self.partitions.read().unwrap().get(&key).cloned();
         // Add the partition to the cache if it doesn't already exist.
         // Partitions will never be removed.
@@ -207,7 +318,7 @@ impl CachePartitions {
             // so double check before inserting it.
             partitions
                 .entry(key)
-                .or_insert_with(|| ExpiringCache::new(self.buffer_time));
+                .or_insert_with(|| Partition::new(self.buffer_time));
             drop(partitions);
 
             partition = self.partitions.read().unwrap().get(&key).cloned();
@@ -218,11 +329,14 @@ impl CachePartitions {
 
 #[derive(Debug)]
 struct LazyCache {
-    partitions: CachePartitions,
+    partitions: Arc<CachePartitions>,
     load_timeout: Duration,
     buffer_time: Duration,
     buffer_time_jitter_fraction: fn() -> f64,
     default_expiration: Duration,
+    refresh_ahead: Option<Duration>,
+    refresh_jitter_fraction: fn() -> f64,
+    monitor: IdentityCacheMonitor,
 }
 
 impl LazyCache {
@@ -233,11 +347,127 @@ impl LazyCache {
         default_expiration: Duration,
     ) -> Self {
         Self {
-            partitions: CachePartitions::new(buffer_time),
+            partitions: Arc::new(CachePartitions::new(buffer_time)),
             load_timeout,
             buffer_time,
             buffer_time_jitter_fraction,
             default_expiration,
+            refresh_ahead: None,
+            refresh_jitter_fraction: DEFAULT_REFRESH_JITTER_FRACTION,
+            monitor: IdentityCacheMonitor::new(),
+        }
+    }
+
+    fn with_refresh_ahead(
+        mut self,
+        refresh_ahead: Option<Duration>,
+        refresh_jitter_fraction: fn() -> f64,
+    ) -> Self {
+        self.refresh_ahead = refresh_ahead;
+        self.refresh_jitter_fraction = refresh_jitter_fraction;
+        self
+    }
+
+    fn with_monitor(mut self, monitor: IdentityCacheMonitor) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// Resolves a new identity, giving up after the load timeout.
+    async fn load(
+        &self,
+        resolver: &SharedIdentityResolver,
+        runtime_components: &RuntimeComponents,
+        config_bag: &ConfigBag,
+        timeout_future: Sleep,
+    ) -> Result<Identity, BoxError> {
+        let fut = Timeout::new(
+            resolver.resolve_identity(runtime_components, config_bag),
+            timeout_future,
+        );
+        match fut.await {
+            Ok(result) => result,
+            Err(_err) => resolver
+                .fallback_on_interrupt()
+                .ok_or_else(|| BoxError::from(TimedOutError(self.load_timeout))),
+        }
+    }
+
+    /// Returns the expiration of the given identity, defaulting it if the identity doesn't have one.
+    fn expiration(&self, identity: &Identity, now: SystemTime) -> SystemTime {
+        identity
+            .expiration()
+            .unwrap_or(now + self.default_expiration)
+    }
+
+    /// Returns the jittered expiry to cache the identity with, and schedules its refresh.
+    ///
+    /// The refresh is never scheduled before half of the remaining lifetime of the identity, so
+    /// that identities that live shorter than the refresh window aren't refreshed on every request.
+    fn cache_expiry(
+        &self,
+        partition: &Partition,
+        expiration: SystemTime,
+        now: SystemTime,
+    ) -> SystemTime {
+        let refresh_at = self.refresh_ahead.map(|refresh_ahead| {
+            let refresh_ahead = refresh_ahead.mul_f64(1.0 - (self.refresh_jitter_fraction)());
+            let earliest = now + expiration.duration_since(now).unwrap_or_default() / 2;
+            expiration
+                .checked_sub(refresh_ahead)
+                .map_or(earliest, |refresh_at| refresh_at.max(earliest))
+        });
+        partition.schedule_refresh(refresh_at);
+        expiration
+            + self
+                .buffer_time
+                .mul_f64((self.buffer_time_jitter_fraction)())
+    }
+
+    /// Speculatively replaces a cached identity that is still valid.
+    ///
+    /// If the refresh fails, the cached identity keeps being used, and the refresh is retried after
+    /// [`REFRESH_RETRY_INTERVAL`].
+    #[allow(clippy::too_many_arguments)]
+    async fn refresh(
+        &self,
+        partition: &Partition,
+        _claim: RefreshClaim,
+        resolver: &SharedIdentityResolver,
+        runtime_components: &RuntimeComponents,
+        config_bag: &ConfigBag,
+        timeout_future: Sleep,
+        time_source: &SharedTimeSource,
+    ) -> Option<Identity> {
+        let span = tracing::info_span!("refresh_identity");
+        let result = self
+            .load(resolver, runtime_components, config_bag, timeout_future)
+            .instrument(span)
+            .await;
+        let now = time_source.now();
+        match result {
+            Ok(identity) => {
+                let expiration = self.expiration(&identity, now);
+                let expiry = self.cache_expiry(partition, expiration, now);
+                partition.cache.set(identity.clone(), expiry).await;
+                self.monitor.refreshed();
+                tracing::debug!(
+                    new_expiration=%DateTime::from(expiration),
+                    partition=?resolver.cache_partition(),
+                    "refreshed cached identity ahead of its expiration"
+                );
+                Some(identity)
+            }
+            Err(err) => {
+                partition.schedule_refresh(Some(now + REFRESH_RETRY_INTERVAL));
+                self.monitor.refresh_failed();
+                tracing::warn!(
+                    retry_in=?REFRESH_RETRY_INTERVAL,
+                    "failed to refresh cached identity, so it will continue to be used: {}",
+                    DisplayErrorContext(&*err)
+                );
+                None
+            }
         }
     }
 }
@@ -303,14 +533,31 @@ impl ResolveCachedIdentity for LazyCache {
 
         let now = time_source.now();
         let timeout_future = sleep_impl.sleep(self.load_timeout);
-        let load_timeout = self.load_timeout;
-        let partition = resolver.cache_partition();
-        let cache = self.partitions.partition(partition);
-        let default_expiration = self.default_expiration;
+        let partition_key = resolver.cache_partition();
+        let partition = self.partitions.partition(partition_key);
 
         IdentityFuture::new(async move {
             // Attempt to get cached identity, or clear the cache if they're expired
-            if let Some(identity) = cache.yield_or_clear_if_expired(now).await {
+            if let Some(identity) = partition.cache.yield_or_clear_if_expired(now).await {
+                // The caller that claims the refresh waits for it, while the cached identity is
+                // still valid for everyone else.
+                if let Some(claim) = partition.claim_refresh(now) {
+                    let refreshed = self
+                        .refresh(
+                            &partition,
+                            claim,
+                            &resolver,
+                            runtime_components,
+                            config_bag,
+                            timeout_future,
+                            &time_source,
+                        )
+                        .await;
+                    if let Some(refreshed) = refreshed {
+                        return Ok(refreshed);
+                    }
+                }
+                self.monitor.hit();
                 tracing::debug!(
                     buffer_time=?self.buffer_time,
                     cached_expiration=?identity.expiration(),
@@ -319,35 +566,23 @@ impl ResolveCachedIdentity for LazyCache {
                 );
                 Ok(identity)
             } else {
+                self.monitor.miss();
                 // If we didn't get identity from the cache, then we need to try and load.
                 // There may be other threads also loading simultaneously, but this is OK
                 // since the futures are not eagerly executed, and the cache will only run one
                 // of them.
                 let start_time = time_source.now();
-                let result = cache
+                let result = partition
+                    .cache
                     .get_or_load(|| {
                         let span = tracing::info_span!("lazy_load_identity");
-                        async move {
-                            let fut = Timeout::new(
-                                resolver.resolve_identity(runtime_components, config_bag),
-                                timeout_future,
-                            );
-                            let identity = match fut.await {
-                                Ok(result) => result?,
-                                Err(_err) => match resolver.fallback_on_interrupt() {
-                                    Some(identity) => identity,
-                                    None => {
-                                        return Err(BoxError::from(TimedOutError(load_timeout)))
-                                    }
-                                },
-                            };
+                        async {
+                            let identity = self
+                                .load(&resolver, runtime_components, config_bag, timeout_future)
+                                .await?;
                             // If the identity don't have an expiration time, then create a default one
-                            let expiration =
-                                identity.expiration().unwrap_or(now + default_expiration);
-
-                            let jitter = self
-                                .buffer_time
-                                .mul_f64((self.buffer_time_jitter_fraction)());
+                            let expiration = self.expiration(&identity, now);
+                            let expiry = self.cache_expiry(&partition, expiration, now);
 
                             // Logging for cache miss should be emitted here as opposed to after the call to
                             // `cache.get_or_load` above. In the case of multiple threads concurrently executing
@@ -357,12 +592,12 @@ impl ResolveCachedIdentity for LazyCache {
                             tracing::debug!(
                                 new_expiration=%printable,
                                 valid_for=?expiration.duration_since(time_source.now()).unwrap_or_default(),
-                                partition=?partition,
+                                partition=?partition_key,
                                 "identity cache miss occurred; added new identity (took {:?})",
                                 time_source.now().duration_since(start_time).unwrap_or_default()
                             );
 
-                            Ok((identity, expiration + jitter))
+                            Ok((identity, expiry))
                         }
                         // Only instrument the the actual load future so that no span
                         // is opened if the cache decides not to execute it.
@@ -768,4 +1003,167 @@ mod tests {
         assert_eq!(1, resolver_a_calls.load(Ordering::Relaxed));
         assert_eq!(1, resolver_b_calls.load(Ordering::Relaxed));
     }
+
+    const REFRESH_NO_JITTER: fn() -> f64 = || 0_f64;
+
+    fn test_components(time: ManualTimeSource) -> RuntimeComponents {
+        RuntimeComponentsBuilder::for_tests()
+            .with_time_source(Some(time))
+            .with_sleep_impl(Some(TokioSleep::new()))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn speculative_refresh() {
+        let time = ManualTimeSource::new(epoch_secs(100));
+        let components = test_components(time.clone());
+        let monitor = IdentityCacheMonitor::new();
+        let (cache, resolver) = test_cache(
+            BUFFER_TIME_NO_JITTER,
+            vec![Ok(test_identity(1000)), Ok(test_identity(2000))],
+        );
+        let cache = cache
+            .with_refresh_ahead(Some(Duration::from_secs(100)), REFRESH_NO_JITTER)
+            .with_monitor(monitor.clone());
+
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(899));
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        // Entering the refresh window replaces the identity before it expires
+        time.set_time(epoch_secs(900));
+        expect_identity(2000, &cache, &components, resolver.clone()).await;
+        expect_identity(2000, &cache, &components, resolver.clone()).await;
+
+        let stats = monitor.stats();
+        assert_eq!(1, stats.misses());
+        assert_eq!(2, stats.hits());
+        assert_eq!(1, stats.refreshes());
+        assert_eq!(0, stats.refresh_failures());
+    }
+
+    #[tokio::test]
+    async fn failed_speculative_refresh_keeps_cached_identity() {
+        let time = ManualTimeSource::new(epoch_secs(100));
+        let components = test_components(time.clone());
+        let monitor = IdentityCacheMonitor::new();
+        let (cache, resolver) = test_cache(
+            BUFFER_TIME_NO_JITTER,
+            vec![
+                Ok(test_identity(1000)),
+                Err("failed".into()),
+                Ok(test_identity(2000)),
+            ],
+        );
+        let cache = cache
+            .with_refresh_ahead(Some(Duration::from_secs(100)), REFRESH_NO_JITTER)
+            .with_monitor(monitor.clone());
+
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(900));
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        // The refresh isn't retried until the retry interval has passed
+        time.set_time(epoch_secs(909));
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(910));
+        expect_identity(2000, &cache, &components, resolver.clone()).await;
+
+        let stats = monitor.stats();
+        assert_eq!(1, stats.misses());
+        assert_eq!(2, stats.hits());
+        assert_eq!(1, stats.refreshes());
+        assert_eq!(1, stats.refresh_failures());
+    }
+
+    #[tokio::test]
+    async fn refresh_jitter() {
+        let time = ManualTimeSource::new(epoch_secs(100));
+        let components = test_components(time.clone());
+        let (cache, resolver) = test_cache(
+            BUFFER_TIME_NO_JITTER,
+            vec![Ok(test_identity(1000)), Ok(test_identity(2000))],
+        );
+        // Half of the 100 second refresh window is removed by the jitter
+        let cache = cache.with_refresh_ahead(Some(Duration::from_secs(100)), || 0.5_f64);
+
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(949));
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(950));
+        expect_identity(2000, &cache, &components, resolver.clone()).await;
+    }
+
+    #[tokio::test]
+    async fn short_lived_identity_is_not_refreshed_on_every_request() {
+        let time = ManualTimeSource::new(epoch_secs(100));
+        let components = test_components(time.clone());
+        let monitor = IdentityCacheMonitor::new();
+        let (cache, resolver) = test_cache(
+            BUFFER_TIME_NO_JITTER,
+            vec![Ok(test_identity(160)), Ok(test_identity(1000))],
+        );
+        // The refresh window is longer than the 60 second lifetime of the identity
+        let cache = cache
+            .with_refresh_ahead(Some(Duration::from_secs(100)), REFRESH_NO_JITTER)
+            .with_monitor(monitor.clone());
+
+        expect_identity(160, &cache, &components, resolver.clone()).await;
+        // The refresh waits for half of the lifetime of the identity
+        for secs in [110, 129] {
+            time.set_time(epoch_secs(secs));
+            expect_identity(160, &cache, &components, resolver.clone()).await;
+        }
+        assert_eq!(0, monitor.stats().refreshes());
+
+        time.set_time(epoch_secs(130));
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        assert_eq!(1, monitor.stats().refreshes());
+    }
+
+    #[tokio::test]
+    async fn concurrent_callers_use_cached_identity_during_refresh() {
+        let time = ManualTimeSource::new(epoch_secs(100));
+        let components = test_components(time.clone());
+        let config_bag = ConfigBag::base();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let refresh_gate = Arc::new(tokio::sync::Notify::new());
+        let resolver = resolver_fn({
+            let (calls, refresh_gate) = (calls.clone(), refresh_gate.clone());
+            move || {
+                if calls.fetch_add(1, Ordering::Relaxed) == 0 {
+                    IdentityFuture::ready(Ok(test_identity(1000)))
+                } else {
+                    let refresh_gate = refresh_gate.clone();
+                    IdentityFuture::new(async move {
+                        refresh_gate.notified().await;
+                        Ok(test_identity(2000))
+                    })
+                }
+            }
+        });
+        let cache = LazyCache::new(
+            DEFAULT_LOAD_TIMEOUT,
+            DEFAULT_BUFFER_TIME,
+            BUFFER_TIME_NO_JITTER,
+            DEFAULT_EXPIRATION,
+        )
+        .with_refresh_ahead(Some(Duration::from_secs(100)), REFRESH_NO_JITTER);
+
+        expect_identity(1000, &cache, &components, resolver.clone()).await;
+        time.set_time(epoch_secs(900));
+
+        // The first caller in the refresh window waits for the refresh...
+        let refreshing = cache.resolve_cached_identity(resolver.clone(), &components, &config_bag);
+        // ...while other callers get the cached identity
+        let others = async {
+            expect_identity(1000, &cache, &components, resolver.clone()).await;
+            assert_eq!(2, calls.load(Ordering::Relaxed));
+            refresh_gate.notify_one();
+        };
+        let (identity, ()) = tokio::join!(refreshing, others);
+        assert_eq!(Some(epoch_secs(2000)), identity.unwrap().expiration());
+
+        expect_identity(2000, &cache, &components, resolver.clone()).await;
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+    }
 }
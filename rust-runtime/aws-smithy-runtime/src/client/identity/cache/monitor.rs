@@ -0,0 +1,107 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Counts how a lazy identity cache serves identities.
+///
+/// Clones share the same counters, so a clone can be given to a
+/// [`LazyCacheBuilder`](super::LazyCacheBuilder) and the original used to read [`stats`](Self::stats).
+///
+/// # Examples
+///
+/// ```no_run
+/// use aws_smithy_runtime::client::identity::{IdentityCache, IdentityCacheMonitor};
+///
+/// let monitor = IdentityCacheMonitor::new();
+/// let identity_cache = IdentityCache::lazy().monitor(monitor.clone()).build();
+///
+/// // ... make requests with a client configured with `identity_cache` ...
+///
+/// let stats = monitor.stats();
+/// println!("{} hits, {} misses", stats.hits(), stats.misses());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IdentityCacheMonitor {
+    counters: Arc<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+impl IdentityCacheMonitor {
+    /// Creates a new monitor with all counters at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of the cache's current statistics.
+    pub fn stats(&self) -> IdentityCacheStats {
+        IdentityCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            refreshes: self.counters.refreshes.load(Ordering::Relaxed),
+            refresh_failures: self.counters.refresh_failures.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(super) fn hit(&self) {
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn miss(&self) {
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn refreshed(&self) {
+        self.counters.refreshes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn refresh_failed(&self) {
+        self.counters
+            .refresh_failures
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A snapshot of an identity cache's statistics, returned by [`IdentityCacheMonitor::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IdentityCacheStats {
+    hits: u64,
+    misses: u64,
+    refreshes: u64,
+    refresh_failures: u64,
+}
+
+impl IdentityCacheStats {
+    /// The number of times a cached identity was returned without calling the identity resolver.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of times there was no valid cached identity, so the caller had to wait
+    /// for the identity resolver.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The number of times a cached identity was replaced by a speculative refresh before it expired.
+    pub fn refreshes(&self) -> u64 {
+        self.refreshes
+    }
+
+    /// The number of speculative refreshes that failed. The still valid cached identity
+    /// continues to be used after a failed refresh.
+    pub fn refresh_failures(&self) -> u64 {
+        self.refresh_failures
+    }
+}
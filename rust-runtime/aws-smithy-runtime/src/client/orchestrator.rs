@@ -4,8 +4,10 @@
  */
 
 use self::auth::orchestrate_auth;
+use self::metrics::{OperationMetrics, Timer};
 use crate::client::interceptors::Interceptors;
 use crate::client::orchestrator::http::{log_response_body, read_body};
+use crate::client::retries::PreviousRetryDelay;
 use crate::client::timeout::{MaybeTimeout, MaybeTimeoutConfig, TimeoutKind};
 use crate::client::{
     http::body::minimum_throughput::MaybeUploadThroughputCheckFuture,
@@ -13,10 +15,13 @@ use crate::client::{
 };
 use aws_smithy_async::rt::sleep::AsyncSleep;
 use aws_smithy_runtime_api::box_error::BoxError;
-use aws_smithy_runtime_api::client::http::{HttpClient, HttpConnector, HttpConnectorSettings};
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorSettings, HttpVersion,
+};
 use aws_smithy_runtime_api::client::interceptors::context::{
     Error, Input, InterceptorContext, Output, RewindResult,
 };
+use aws_smithy_runtime_api::client::metrics::instruments;
 use aws_smithy_runtime_api::client::orchestrator::{
     HttpResponse, LoadedRequestBody, OrchestratorError,
 };
@@ -39,9 +44,15 @@ mod auth;
 /// Defines types that implement a trait for endpoint resolution
 pub mod endpoints;
 
+/// Hedged requests for idempotent operations
+pub mod hedging;
+
 /// Defines types that work with HTTP types
 mod http;
 
+/// Records client metrics for an operation
+pub(crate) mod metrics;
+
 /// Utility for making one-off unmodeled requests with the orchestrator.
 pub mod operation;
 
@@ -149,10 +160,16 @@ pub async fn invoke_with_stop_point(
             .map_err(SdkError::construction_failure)?;
         trace!(runtime_components = ?runtime_components);
 
+        let metrics = OperationMetrics::new(&runtime_components, service_name, operation_name);
+        if let Some(metrics) = &metrics {
+            cfg.interceptor_state().store_put(metrics.clone());
+        }
+        let call_timer = Timer::start(cfg, instruments::CALL_DURATION);
+
         let operation_timeout_config =
             MaybeTimeoutConfig::new(&runtime_components, cfg, TimeoutKind::Operation);
         trace!(operation_timeout_config = ?operation_timeout_config);
-        async {
+        let result = async {
             // If running the pre-execution interceptors failed, then we skip running the op and run the
             // final interceptors instead.
             if !ctx.is_failed() {
@@ -166,7 +183,13 @@ pub async fn invoke_with_stop_point(
             }
         }
         .maybe_timeout(operation_timeout_config)
-        .await
+        .await;
+
+        call_timer.stop();
+        if let (Some(metrics), Err(_)) = (&metrics, &result) {
+            metrics.add(instruments::CALL_ERRORS, 1);
+        }
+        result
     }
     // Include a random, internal-only, seven-digit ID for the operation invocation so that it can be correlated in the logs.
     .instrument(debug_span!("invoke", service = %service_name, operation = %operation_name, sdk_invocation_id = fastrand::u32(1_000_000..10_000_000)))
@@ -230,7 +253,9 @@ async fn try_op(
             .expect("request serializer must be in the config bag")
             .clone();
         let input = ctx.take_input().expect("input set at this point");
+        let timer = Timer::start(cfg, instruments::SERIALIZATION_DURATION);
         let request = halt_on_err!([ctx] => request_serializer.serialize_input(input, cfg).map_err(OrchestratorError::other));
+        timer.stop();
         ctx.set_request(request);
     }
 
@@ -284,7 +309,9 @@ async fn try_op(
     // the request in the case of retry attempts.
     ctx.save_checkpoint();
     let mut retry_delay = None;
-    for i in 1u32.. {
+    let mut i = 0u32;
+    loop {
+        i += 1;
         // Break from the loop if we can't rewind the request's state. This will always succeed the
         // first time, but will fail on subsequent iterations if the request body wasn't retryable.
         trace!("checking if context can be rewound for attempt #{i}");
@@ -300,21 +327,8 @@ async fn try_op(
             debug!("delaying for {delay:?}");
             sleep.await;
         }
-        let attempt_timeout_config =
-            MaybeTimeoutConfig::new(runtime_components, cfg, TimeoutKind::OperationAttempt);
-        trace!(attempt_timeout_config = ?attempt_timeout_config);
-        let maybe_timeout = async {
-            debug!("beginning attempt #{i}");
-            try_attempt(ctx, cfg, runtime_components, stop_point).await;
-            finally_attempt(ctx, cfg, runtime_components).await;
-            Result::<_, SdkError<Error, HttpResponse>>::Ok(())
-        }
-        .maybe_timeout(attempt_timeout_config)
-        .await
-        .map_err(|err| OrchestratorError::timeout(err.into_source().unwrap()));
-
-        // We continue when encountering a timeout error. The retry classifier will decide what to do with it.
-        continue_on_err!([ctx] => maybe_timeout);
+        // A hedged attempt counts as an attempt of its own.
+        i = hedging::run_maybe_hedged_attempt(i, ctx, cfg, runtime_components, stop_point).await;
 
         // If we got a retry strategy from the bag, ask it what to do.
         // If no strategy was set, we won't retry.
@@ -334,6 +348,7 @@ async fn try_op(
                 let sleep_impl = halt_on_err!([ctx] => runtime_components.sleep_impl().ok_or_else(|| OrchestratorError::other(
                     "the retry strategy requested a delay before sending the retry request, but no 'async sleep' implementation was set"
                 )));
+                cfg.interceptor_state().store_put(PreviousRetryDelay(delay));
                 retry_delay = Some((delay, sleep_impl.sleep(delay)));
                 continue;
             }
@@ -341,6 +356,33 @@ async fn try_op(
     }
 }
 
+async fn run_attempt(
+    i: u32,
+    ctx: &mut InterceptorContext,
+    cfg: &mut ConfigBag,
+    runtime_components: &RuntimeComponents,
+    stop_point: StopPoint,
+) {
+    let attempt_timeout_config =
+        MaybeTimeoutConfig::new(runtime_components, cfg, TimeoutKind::OperationAttempt);
+    trace!(attempt_timeout_config = ?attempt_timeout_config);
+    OperationMetrics::add_if_enabled(cfg, instruments::CALL_ATTEMPTS, 1);
+    let attempt_timer = Timer::start(cfg, instruments::CALL_ATTEMPT_DURATION);
+    let maybe_timeout = async {
+        debug!("beginning attempt #{i}");
+        try_attempt(ctx, cfg, runtime_components, stop_point).await;
+        finally_attempt(ctx, cfg, runtime_components).await;
+        Result::<_, SdkError<Error, HttpResponse>>::Ok(())
+    }
+    .maybe_timeout(attempt_timeout_config)
+    .await
+    .map_err(|err| OrchestratorError::timeout(err.into_source().unwrap()));
+    attempt_timer.stop();
+
+    // We continue when encountering a timeout error. The retry classifier will decide what to do with it.
+    continue_on_err!([ctx] => maybe_timeout);
+}
+
 #[instrument(skip_all, level = "debug")]
 async fn try_attempt(
     ctx: &mut InterceptorContext,
@@ -386,9 +428,13 @@ async fn try_attempt(
             let mut builder = HttpConnectorSettings::builder();
             builder.set_connect_timeout(timeout_config.connect_timeout());
             builder.set_read_timeout(timeout_config.read_timeout());
+            builder.set_http_version(cfg.load::<HttpVersion>().copied());
             builder.build()
         };
         let connector = http_client.http_connector(&settings, runtime_components);
+        if let Some(content_length) = request.body().content_length() {
+            OperationMetrics::add_if_enabled(cfg, instruments::BYTES_SENT, content_length);
+        }
         let response_future = MaybeUploadThroughputCheckFuture::new(
             cfg,
             runtime_components,
@@ -397,6 +443,10 @@ async fn try_attempt(
         response_future.await.map_err(OrchestratorError::connector)
     });
     trace!(response = ?response, "received response from service");
+    let response_length = response.body().content_length();
+    if let Some(content_length) = response_length {
+        OperationMetrics::add_if_enabled(cfg, instruments::BYTES_RECEIVED, content_length);
+    }
     ctx.set_response(response);
     ctx.enter_before_deserialization_phase();
 
@@ -412,20 +462,36 @@ async fn try_attempt(
         let response_deserializer = cfg
             .load::<SharedResponseDeserializer>()
             .expect("a request deserializer must be in the config bag");
+        let timer = Timer::start(cfg, instruments::DESERIALIZATION_DURATION);
         let maybe_deserialized = {
             let _span = debug_span!("deserialize_streaming").entered();
             response_deserializer.deserialize_streaming(response)
         };
         match maybe_deserialized {
-            Some(output_or_error) => output_or_error,
+            Some(output_or_error) => {
+                timer.stop();
+                output_or_error
+            }
             None => read_body(response)
                 .instrument(debug_span!("read_body"))
                 .await
                 .map_err(OrchestratorError::response)
                 .and_then(|_| {
                     let _span = debug_span!("deserialize_nonstreaming").entered();
+                    // The length of bodies without a `Content-Length` is only known once they're read
+                    if let (None, Some(body)) = (response_length, response.body().bytes()) {
+                        OperationMetrics::add_if_enabled(
+                            cfg,
+                            instruments::BYTES_RECEIVED,
+                            body.len() as u64,
+                        );
+                    }
                     log_response_body(response, cfg);
-                    response_deserializer.deserialize_nonstreaming(response)
+                    // Time spent reading the body from the network isn't counted
+                    let timer = Timer::start(cfg, instruments::DESERIALIZATION_DURATION);
+                    let result = response_deserializer.deserialize_nonstreaming(response);
+                    timer.stop();
+                    result
                 }),
         }
     }
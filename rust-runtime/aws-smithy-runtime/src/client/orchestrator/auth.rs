@@ -5,6 +5,7 @@
 
 use crate::client::auth::no_auth::NO_AUTH_SCHEME_ID;
 use crate::client::identity::IdentityCache;
+use crate::client::orchestrator::metrics::Timer;
 use aws_smithy_runtime_api::box_error::BoxError;
 use aws_smithy_runtime_api::client::auth::{
     AuthScheme, AuthSchemeEndpointConfig, AuthSchemeId, AuthSchemeOptionResolverParams,
@@ -13,6 +14,7 @@ use aws_smithy_runtime_api::client::auth::{
 use aws_smithy_runtime_api::client::identity::ResolveIdentity;
 use aws_smithy_runtime_api::client::identity::{IdentityCacheLocation, ResolveCachedIdentity};
 use aws_smithy_runtime_api::client::interceptors::context::InterceptorContext;
+use aws_smithy_runtime_api::client::metrics::{attributes, instruments};
 use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
 use aws_smithy_types::config_bag::ConfigBag;
 use aws_smithy_types::endpoint::Endpoint;
@@ -157,9 +159,11 @@ pub(super) async fn orchestrate_auth(
                     Ok(auth_scheme_endpoint_config) => {
                         trace!(auth_scheme_endpoint_config = ?auth_scheme_endpoint_config, "extracted auth scheme endpoint config");
 
+                        let timer = Timer::start(cfg, instruments::RESOLVE_IDENTITY_DURATION);
                         let identity = identity_cache
                             .resolve_cached_identity(identity_resolver, runtime_components, cfg)
                             .await?;
+                        timer.stop_with(attributes::AUTH_SCHEME_ID, scheme_id.as_str());
                         trace!(identity = ?identity, "resolved identity");
 
                         trace!("signing request");
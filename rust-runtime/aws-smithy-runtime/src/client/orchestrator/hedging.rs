@@ -0,0 +1,578 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Hedged requests: when an attempt is slow to respond, a second attempt is sent in parallel, and
+//! whichever attempt succeeds first is used.
+//!
+//! Hedging sends the same request more than once, so it only applies to operations that are safe
+//! to send twice: those modeled as `@readonly` or `@idempotent`, which generated clients mark with
+//! [`IdempotentOperation`]. It is enabled by storing a [`HedgingConfig`] in the config bag.
+
+use super::{finally_attempt, run_attempt, StopPoint};
+use crate::client::http::connection_poisoning::CaptureSmithyConnection;
+use crate::client::retries::TokenBucket;
+use aws_smithy_async::rt::sleep::AsyncSleep;
+use aws_smithy_runtime_api::client::interceptors::context::InterceptorContext;
+use aws_smithy_runtime_api::client::orchestrator::OrchestratorError;
+use aws_smithy_runtime_api::client::retries::RequestAttempts;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::config_bag::{ConfigBag, Layer, Storable, StoreReplace};
+use aws_smithy_types::retry::{ErrorKind, RetryConfig};
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::mem;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+use tracing::debug;
+
+/// The number of recent attempt latencies that percentiles are calculated from.
+const LATENCY_SAMPLES: usize = 100;
+/// The number of latencies that must be recorded before percentiles are used.
+const MIN_LATENCY_SAMPLES: usize = 20;
+
+/// Configuration for hedged requests.
+///
+/// When an attempt hasn't completed within the hedging delay, a second attempt of the same request
+/// is sent in parallel. The first attempt to succeed is used, and the other one is cancelled. If
+/// the first attempt to complete fails, the other one is awaited.
+///
+/// Only operations marked with [`IdempotentOperation`] are hedged.
+///
+/// A hedged attempt goes through the whole attempt lifecycle, so interceptors see it like any
+/// other attempt; they can tell it apart by loading [`HedgedAttempt`] from the config bag. The
+/// attempt that is cancelled fails, still runs the attempt completion hooks, and its connection
+/// is closed rather than reused.
+///
+/// Hedged attempts are accounted for like retries: they count towards the max attempts of the
+/// retry config, so no hedged attempt is sent by the last allowed attempt, and they cost retry
+/// quota, so no hedged attempt is sent when the retry token bucket is empty. The quota is only
+/// given back if the request succeeds.
+///
+/// Requests whose body can't be cloned are never hedged.
+#[derive(Clone, Debug)]
+pub struct HedgingConfig {
+    delay: HedgingDelay,
+}
+
+#[derive(Clone, Debug)]
+enum HedgingDelay {
+    Fixed(Duration),
+    Percentile {
+        percentile: f64,
+        fallback: Duration,
+        latencies: Arc<Mutex<VecDeque<Duration>>>,
+    },
+}
+
+impl Storable for HedgingConfig {
+    type Storer = StoreReplace<Self>;
+}
+
+impl HedgingConfig {
+    /// Sends a hedged attempt when an attempt hasn't completed after `delay`.
+    pub fn after(delay: Duration) -> Self {
+        Self {
+            delay: HedgingDelay::Fixed(delay),
+        }
+    }
+
+    /// Sends a hedged attempt when an attempt is slower than the given percentile, between `0.0`
+    /// and `100.0`, of the latencies of recent successful attempts.
+    ///
+    /// Until enough attempts were made to calculate the percentile, `fallback` is used as the
+    /// delay. Latencies are tracked separately for each `HedgingConfig`, and shared by its clones.
+    pub fn at_percentile(percentile: f64, fallback: Duration) -> Self {
+        Self {
+            delay: HedgingDelay::Percentile {
+                percentile: percentile.clamp(0.0, 100.0),
+                fallback,
+                latencies: Default::default(),
+            },
+        }
+    }
+
+    /// Returns how long to wait for an attempt before sending a hedged attempt.
+    pub fn delay(&self) -> Duration {
+        match &self.delay {
+            HedgingDelay::Fixed(delay) => *delay,
+            HedgingDelay::Percentile {
+                percentile,
+                fallback,
+                latencies,
+            } => {
+                let latencies = latencies.lock().unwrap();
+                if latencies.len() < MIN_LATENCY_SAMPLES {
+                    return *fallback;
+                }
+                let mut sorted: Vec<_> = latencies.iter().copied().collect();
+                sorted.sort_unstable();
+                let rank = (percentile / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+                sorted[rank]
+            }
+        }
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        if let HedgingDelay::Percentile { latencies, .. } = &self.delay {
+            let mut latencies = latencies.lock().unwrap();
+            if latencies.len() == LATENCY_SAMPLES {
+                latencies.pop_front();
+            }
+            latencies.push_back(latency);
+        }
+    }
+}
+
+/// Stored in the config bag of operations that are safe to send more than once, because they are
+/// modeled as `@readonly` or `@idempotent`. Other operations are never hedged.
+#[non_exhaustive]
+#[derive(Clone, Debug, Default)]
+pub struct IdempotentOperation;
+
+impl IdempotentOperation {
+    /// Creates a new `IdempotentOperation`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Storable for IdempotentOperation {
+    type Storer = StoreReplace<Self>;
+}
+
+/// Stored in the config bag of a hedged attempt, so that interceptors can tell it apart from the
+/// attempt it hedges.
+#[non_exhaustive]
+#[derive(Clone, Debug)]
+pub struct HedgedAttempt;
+
+impl Storable for HedgedAttempt {
+    type Storer = StoreReplace<Self>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Winner {
+    Primary,
+    Hedge,
+}
+
+/// Returns a config bag for an attempt that runs alongside the attempt that uses `cfg`.
+///
+/// The interceptor state of `cfg` is frozen into a layer that both bags share, so that values
+/// stored in the interceptor state of either attempt afterwards are only visible to that attempt.
+fn fork(cfg: &mut ConfigBag) -> ConfigBag {
+    let interceptor_state =
+        mem::replace(cfg.interceptor_state(), Layer::new("interceptor_state")).freeze();
+    cfg.push_shared_layer(interceptor_state);
+    cfg.without_interceptor_state()
+}
+
+/// Makes attempt number `i`, hedging it if a [`HedgingConfig`] is configured.
+///
+/// Returns the number of the last attempt that was made: `i + 1` if a hedged attempt was sent,
+/// and `i` otherwise.
+pub(super) async fn run_maybe_hedged_attempt(
+    i: u32,
+    ctx: &mut InterceptorContext,
+    cfg: &mut ConfigBag,
+    runtime_components: &RuntimeComponents,
+    stop_point: StopPoint,
+) -> u32 {
+    let hedging_config = match (cfg.load::<HedgingConfig>(), stop_point) {
+        (Some(hedging_config), StopPoint::None) => hedging_config.clone(),
+        _ => {
+            run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+            return i;
+        }
+    };
+    if cfg.load::<IdempotentOperation>().is_none() {
+        debug!("the operation isn't known to be idempotent; not hedging");
+        run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+        return i;
+    }
+    if let Some(retry_config) = cfg.load::<RetryConfig>() {
+        if i >= retry_config.max_attempts() {
+            debug!("attempt #{i} is the last attempt allowed by the retry config; not hedging");
+            run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+            return i;
+        }
+    }
+    let (Some(sleep_impl), Some(time_source)) = (
+        runtime_components.sleep_impl(),
+        runtime_components.time_source(),
+    ) else {
+        debug!("hedging requires a sleep implementation and a time source; not hedging");
+        run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+        return i;
+    };
+    let Some(mut hedge_ctx) = ctx.fork_from_checkpoint() else {
+        debug!("the request body cannot be cloned; not hedging");
+        run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+        return i;
+    };
+    let mut hedge_cfg = fork(cfg);
+    let hedge_i = i + 1;
+    hedge_cfg.interceptor_state().store_put(HedgedAttempt);
+    hedge_cfg
+        .interceptor_state()
+        .store_put(RequestAttempts::new(hedge_i));
+    let token_bucket = cfg.load::<TokenBucket>().cloned();
+
+    let delay = hedging_config.delay();
+    let start = time_source.now();
+    let hedge_started = AtomicBool::new(false);
+    let mut hedge_permit = None;
+    let (winner, cancelled) = {
+        let primary = async {
+            run_attempt(i, ctx, cfg, runtime_components, stop_point).await;
+            !ctx.is_failed()
+        };
+        let hedge = async {
+            sleep_impl.sleep(delay).await;
+            if let Some(token_bucket) = &token_bucket {
+                match token_bucket.acquire(&ErrorKind::ServerError) {
+                    Some(permit) => hedge_permit = Some(permit),
+                    None => {
+                        debug!("not enough retry quota is available to hedge attempt #{i}");
+                        return std::future::pending().await;
+                    }
+                }
+            }
+            debug!("attempt #{i} hasn't completed after {delay:?}; sending attempt #{hedge_i}");
+            hedge_started.store(true, Ordering::Relaxed);
+            run_attempt(
+                hedge_i,
+                &mut hedge_ctx,
+                &mut hedge_cfg,
+                runtime_components,
+                stop_point,
+            )
+            .await;
+            !hedge_ctx.is_failed()
+        };
+        race(primary, hedge, &hedge_started).await
+    };
+
+    if cancelled {
+        let (loser_ctx, loser_cfg) = match winner {
+            Winner::Primary => (&mut hedge_ctx, &mut hedge_cfg),
+            Winner::Hedge => (&mut *ctx, &mut *cfg),
+        };
+        // The response of the cancelled attempt was never read, so its connection can't be reused.
+        if let Some(connection) = loser_cfg
+            .load::<CaptureSmithyConnection>()
+            .and_then(CaptureSmithyConnection::get)
+        {
+            connection.poison();
+        }
+        loser_ctx.fail(OrchestratorError::other(
+            "the attempt was cancelled because a concurrent attempt succeeded first",
+        ));
+        finally_attempt(loser_ctx, loser_cfg, runtime_components).await;
+    }
+    if winner == Winner::Hedge {
+        debug!("the hedged attempt completed first");
+        mem::swap(ctx, &mut hedge_ctx);
+        mem::swap(cfg, &mut hedge_cfg);
+        cfg.interceptor_state().unset::<HedgedAttempt>();
+    }
+    if !ctx.is_failed() {
+        let latency = time_source.now().duration_since(start).unwrap_or_default();
+        hedging_config.record_latency(latency);
+    } else if let Some(permit) = hedge_permit {
+        // Like the quota of a failed retry, the quota of a failed hedge is not given back.
+        permit.forget();
+    }
+
+    if hedge_started.load(Ordering::Relaxed) {
+        // Let the retry strategy know that both attempts were made.
+        cfg.interceptor_state()
+            .store_put(RequestAttempts::new(hedge_i));
+        hedge_i
+    } else {
+        i
+    }
+}
+
+/// Polls both attempts until one of them succeeds, or until both of them failed. If the primary
+/// attempt fails before the hedged attempt started, the hedged attempt is never sent.
+///
+/// Returns the attempt to use, and whether the other attempt was cancelled before it completed.
+async fn race(
+    primary: impl Future<Output = bool>,
+    hedge: impl Future<Output = bool>,
+    hedge_started: &AtomicBool,
+) -> (Winner, bool) {
+    let mut primary = pin!(primary);
+    let mut hedge = pin!(hedge);
+    let (mut primary_succeeded, mut hedge_succeeded) = (None, None);
+    poll_fn(|cx| {
+        if primary_succeeded.is_none() {
+            if let Poll::Ready(succeeded) = primary.as_mut().poll(cx) {
+                primary_succeeded = Some(succeeded);
+            }
+        }
+        if hedge_succeeded.is_none() {
+            if let Poll::Ready(succeeded) = hedge.as_mut().poll(cx) {
+                hedge_succeeded = Some(succeeded);
+                // If both attempts failed, the one that failed last is used.
+                if primary_succeeded == Some(false) {
+                    return Poll::Ready((Winner::Hedge, false));
+                }
+            }
+        }
+        let hedge_running = hedge_succeeded.is_none() && hedge_started.load(Ordering::Relaxed);
+        match (primary_succeeded, hedge_succeeded) {
+            (Some(true), _) => Poll::Ready((Winner::Primary, hedge_running)),
+            (_, Some(true)) => Poll::Ready((Winner::Hedge, primary_succeeded.is_none())),
+            (Some(false), Some(false)) => Poll::Ready((Winner::Primary, false)),
+            (Some(false), None) if !hedge_running => Poll::Ready((Winner::Primary, false)),
+            _ => Poll::Pending,
+        }
+    })
+    .await
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use super::*;
+    use crate::client::orchestrator::operation::Operation;
+    use aws_smithy_async::rt::sleep::TokioSleep;
+    use aws_smithy_runtime_api::box_error::BoxError;
+    use aws_smithy_runtime_api::client::connection::ConnectionMetadata;
+    use aws_smithy_runtime_api::client::http::{
+        HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+    };
+    use aws_smithy_runtime_api::client::interceptors::context::{
+        BeforeTransmitInterceptorContextRef, FinalizerInterceptorContextRef,
+    };
+    use aws_smithy_runtime_api::client::interceptors::Intercept;
+    use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+    use aws_smithy_runtime_api::shared::IntoShared;
+    use aws_smithy_types::body::SdkBody;
+    use aws_smithy_types::retry::RetryConfig;
+    use aws_smithy_types::timeout::TimeoutConfig;
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Responds to each request after the corresponding delay, with the index of the request.
+    #[derive(Clone, Debug)]
+    struct DelayedClient {
+        delays: Arc<Vec<Duration>>,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl DelayedClient {
+        fn new(delays: Vec<Duration>) -> Self {
+            Self {
+                delays: Arc::new(delays),
+                requests: Default::default(),
+            }
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    impl HttpConnector for DelayedClient {
+        fn call(&self, _request: HttpRequest) -> HttpConnectorFuture {
+            let index = self.requests.fetch_add(1, Ordering::SeqCst);
+            let delay = self.delays[index];
+            HttpConnectorFuture::new(async move {
+                tokio::time::sleep(delay).await;
+                Ok(HttpResponse::new(
+                    200.try_into().unwrap(),
+                    SdkBody::from(index.to_string()),
+                ))
+            })
+        }
+    }
+
+    impl HttpClient for DelayedClient {
+        fn http_connector(
+            &self,
+            _: &HttpConnectorSettings,
+            _: &RuntimeComponents,
+        ) -> SharedHttpConnector {
+            self.clone().into_shared()
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct CountAttempts {
+        hedged: AtomicUsize,
+        completed: AtomicUsize,
+        poisoned: AtomicUsize,
+        attempt_numbers: Mutex<Vec<u32>>,
+    }
+
+    #[derive(Debug)]
+    struct CountAttemptsInterceptor(Arc<CountAttempts>);
+
+    impl Intercept for CountAttemptsInterceptor {
+        fn name(&self) -> &'static str {
+            "CountAttempts"
+        }
+
+        fn read_before_attempt(
+            &self,
+            _: &BeforeTransmitInterceptorContextRef<'_>,
+            _: &RuntimeComponents,
+            cfg: &mut ConfigBag,
+        ) -> Result<(), BoxError> {
+            if cfg.load::<HedgedAttempt>().is_some() {
+                self.0.hedged.fetch_add(1, Ordering::SeqCst);
+            }
+            let attempt = cfg.load::<RequestAttempts>().unwrap().attempts();
+            self.0.attempt_numbers.lock().unwrap().push(attempt);
+            Ok(())
+        }
+
+        fn read_before_transmit(
+            &self,
+            _: &BeforeTransmitInterceptorContextRef<'_>,
+            _: &RuntimeComponents,
+            cfg: &mut ConfigBag,
+        ) -> Result<(), BoxError> {
+            let attempts = self.0.clone();
+            let connection = CaptureSmithyConnection::new();
+            connection.set_connection_retriever(move || {
+                let attempts = attempts.clone();
+                Some(
+                    ConnectionMetadata::builder()
+                        .proxied(false)
+                        .poison_fn(move || {
+                            attempts.poisoned.fetch_add(1, Ordering::SeqCst);
+                        })
+                        .build(),
+                )
+            });
+            cfg.interceptor_state().store_put(connection);
+            Ok(())
+        }
+
+        fn read_after_attempt(
+            &self,
+            _: &FinalizerInterceptorContextRef<'_>,
+            _: &RuntimeComponents,
+            _: &mut ConfigBag,
+        ) -> Result<(), BoxError> {
+            self.0.completed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    async fn invoke(client: &DelayedClient, attempts: Arc<CountAttempts>) -> String {
+        invoke_operation(client, attempts, true, RetryConfig::standard()).await
+    }
+
+    async fn invoke_operation(
+        client: &DelayedClient,
+        attempts: Arc<CountAttempts>,
+        idempotent: bool,
+        retry_config: RetryConfig,
+    ) -> String {
+        let builder = Operation::builder();
+        let builder = if idempotent {
+            builder.idempotent()
+        } else {
+            builder
+        };
+        builder
+            .service_name("test")
+            .operation_name("test")
+            .http_client(client.clone())
+            .endpoint_url("http://localhost:1234")
+            .no_auth()
+            .standard_retry(&retry_config)
+            .timeout_config(TimeoutConfig::disabled())
+            .sleep_impl(TokioSleep::new())
+            .hedging(HedgingConfig::after(Duration::from_millis(50)))
+            .interceptor(CountAttemptsInterceptor(attempts))
+            .serializer(|_: ()| Ok(HttpRequest::new(SdkBody::from("request"))))
+            .deserializer::<_, Infallible>(|response| {
+                Ok(std::str::from_utf8(response.body().bytes().unwrap())
+                    .unwrap()
+                    .to_string())
+            })
+            .build()
+            .invoke(())
+            .await
+            .expect("success")
+    }
+
+    #[tokio::test]
+    async fn slow_attempt_is_hedged() {
+        let client = DelayedClient::new(vec![Duration::from_secs(10), Duration::ZERO]);
+        let attempts = Arc::new(CountAttempts::default());
+
+        let output = invoke(&client, attempts.clone()).await;
+
+        assert_eq!("1", output);
+        assert_eq!(2, client.requests());
+        assert_eq!(1, attempts.hedged.load(Ordering::SeqCst));
+        // The cancelled attempt completes too, and its connection isn't reused
+        assert_eq!(2, attempts.completed.load(Ordering::SeqCst));
+        assert_eq!(1, attempts.poisoned.load(Ordering::SeqCst));
+        // The hedged attempt counts as the second attempt
+        assert_eq!(vec![1, 2], *attempts.attempt_numbers.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn last_attempt_is_not_hedged() {
+        let client = DelayedClient::new(vec![Duration::from_millis(100)]);
+        let attempts = Arc::new(CountAttempts::default());
+
+        let retry_config = RetryConfig::standard().with_max_attempts(1);
+        let output = invoke_operation(&client, attempts.clone(), true, retry_config).await;
+
+        assert_eq!("0", output);
+        assert_eq!(1, client.requests());
+        assert_eq!(0, attempts.hedged.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn fast_attempt_is_not_hedged() {
+        let client = DelayedClient::new(vec![Duration::ZERO]);
+        let attempts = Arc::new(CountAttempts::default());
+
+        let output = invoke(&client, attempts.clone()).await;
+
+        assert_eq!("0", output);
+        assert_eq!(1, client.requests());
+        assert_eq!(0, attempts.hedged.load(Ordering::SeqCst));
+        assert_eq!(1, attempts.completed.load(Ordering::SeqCst));
+        assert_eq!(0, attempts.poisoned.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn operation_that_isnt_idempotent_is_not_hedged() {
+        let client = DelayedClient::new(vec![Duration::from_millis(100)]);
+        let attempts = Arc::new(CountAttempts::default());
+
+        let output =
+            invoke_operation(&client, attempts.clone(), false, RetryConfig::standard()).await;
+
+        assert_eq!("0", output);
+        assert_eq!(1, client.requests());
+        assert_eq!(0, attempts.hedged.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn percentile_delay() {
+        let config = HedgingConfig::at_percentile(90.0, Duration::from_secs(1));
+        for millis in 1..MIN_LATENCY_SAMPLES as u64 {
+            config.record_latency(Duration::from_millis(millis * 10));
+        }
+        assert_eq!(Duration::from_secs(1), config.clone().delay());
+
+        config.record_latency(Duration::from_millis(200));
+        assert_eq!(Duration::from_millis(180), config.delay());
+    }
+}
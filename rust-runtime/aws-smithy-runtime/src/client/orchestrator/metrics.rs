@@ -0,0 +1,127 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use aws_smithy_async::time::SharedTimeSource;
+use aws_smithy_runtime_api::client::metrics::{
+    attributes, instruments, Attributes, Meter, MetricsProvider, SharedMeter,
+};
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::config_bag::{ConfigBag, Storable, StoreReplace};
+use aws_smithy_types::retry::ErrorKind;
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+/// The instrumentation scope of the meter used by the orchestrator.
+const SCOPE: &str = "aws-smithy-runtime";
+
+/// Records the measurements of a single operation invocation.
+///
+/// This is stored in the config bag when a metrics provider is configured, so that
+/// the retry strategy and auth orchestration can record measurements too.
+#[derive(Clone, Debug)]
+pub(crate) struct OperationMetrics {
+    meter: SharedMeter,
+    time_source: SharedTimeSource,
+    attributes: Attributes,
+}
+
+impl Storable for OperationMetrics {
+    type Storer = StoreReplace<Self>;
+}
+
+impl OperationMetrics {
+    /// Returns `None` if no metrics provider is configured.
+    pub(crate) fn new(
+        runtime_components: &RuntimeComponents,
+        service_name: &str,
+        operation_name: &str,
+    ) -> Option<Self> {
+        let meter = runtime_components.metrics_provider()?.meter(SCOPE);
+        Some(Self {
+            meter,
+            time_source: runtime_components.time_source().unwrap_or_default(),
+            attributes: Attributes::new()
+                .with(attributes::RPC_SERVICE, service_name.to_owned())
+                .with(attributes::RPC_METHOD, operation_name.to_owned()),
+        })
+    }
+
+    /// Adds `value` to the counter `name` if an [`OperationMetrics`] is stored in the config bag.
+    pub(crate) fn add_if_enabled(cfg: &ConfigBag, name: &'static str, value: u64) {
+        if let Some(metrics) = cfg.load::<Self>() {
+            metrics.add(name, value);
+        }
+    }
+
+    pub(crate) fn add(&self, name: &'static str, value: u64) {
+        self.meter.add(name, value, &self.attributes);
+    }
+
+    /// Counts a retry caused by an error of the given kind.
+    pub(crate) fn add_retry(&self, kind: ErrorKind) {
+        let attributes = self
+            .attributes
+            .clone()
+            .with(attributes::RETRY_REASON, retry_reason(kind));
+        self.meter.add(instruments::CALL_RETRIES, 1, &attributes);
+    }
+}
+
+/// Measures the duration of a phase of the orchestration, if metrics are enabled.
+#[derive(Debug)]
+pub(crate) struct Timer {
+    name: &'static str,
+    started: Option<(OperationMetrics, SystemTime)>,
+}
+
+impl Timer {
+    /// Starts timing `name` if an [`OperationMetrics`] is stored in the config bag.
+    pub(crate) fn start(cfg: &ConfigBag, name: &'static str) -> Self {
+        let started = cfg
+            .load::<OperationMetrics>()
+            .map(|metrics| (metrics.clone(), metrics.time_source.now()));
+        Self { name, started }
+    }
+
+    /// Records the time elapsed since the timer started, in seconds.
+    pub(crate) fn stop(self) {
+        if let Some((metrics, start)) = &self.started {
+            metrics.meter.record(
+                self.name,
+                self.elapsed(metrics, *start),
+                &metrics.attributes,
+            );
+        }
+    }
+
+    /// Records the time elapsed since the timer started, in seconds, with an extra attribute.
+    pub(crate) fn stop_with(self, key: &'static str, value: impl Into<Cow<'static, str>>) {
+        if let Some((metrics, start)) = &self.started {
+            let attributes = metrics.attributes.clone().with(key, value);
+            metrics
+                .meter
+                .record(self.name, self.elapsed(metrics, *start), &attributes);
+        }
+    }
+
+    fn elapsed(&self, metrics: &OperationMetrics, start: SystemTime) -> f64 {
+        metrics
+            .time_source
+            .now()
+            .duration_since(start)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+}
+
+fn retry_reason(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::TransientError => "transient_error",
+        ErrorKind::ThrottlingError => "throttling_error",
+        ErrorKind::ServerError => "server_error",
+        ErrorKind::ClientError => "client_error",
+        _ => "other",
+    }
+}
@@ -9,6 +9,7 @@ use crate::client::http::connection_poisoning::ConnectionPoisoningInterceptor;
 use crate::client::identity::no_auth::NoAuthIdentityResolver;
 use crate::client::identity::IdentityCache;
 use crate::client::orchestrator::endpoints::StaticUriEndpointResolver;
+use crate::client::orchestrator::hedging::{HedgingConfig, IdempotentOperation};
 use crate::client::retries::strategy::{NeverRetryStrategy, StandardRetryStrategy};
 use aws_smithy_async::rt::sleep::AsyncSleep;
 use aws_smithy_async::time::TimeSource;
@@ -17,10 +18,11 @@ use aws_smithy_runtime_api::client::auth::{
     AuthSchemeOptionResolverParams, SharedAuthScheme, SharedAuthSchemeOptionResolver,
 };
 use aws_smithy_runtime_api::client::endpoint::{EndpointResolverParams, SharedEndpointResolver};
-use aws_smithy_runtime_api::client::http::HttpClient;
+use aws_smithy_runtime_api::client::http::{HttpClient, HttpVersion};
 use aws_smithy_runtime_api::client::identity::SharedIdentityResolver;
 use aws_smithy_runtime_api::client::interceptors::context::{Error, Input, Output};
 use aws_smithy_runtime_api::client::interceptors::Intercept;
+use aws_smithy_runtime_api::client::metrics::MetricsProvider;
 use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
 use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, OrchestratorError};
 use aws_smithy_runtime_api::client::result::SdkError;
@@ -239,12 +241,31 @@ impl<I, O, E> OperationBuilder<I, O, E> {
         self
     }
 
+    /// Enables hedged requests for the operation. Only operations marked as
+    /// [`idempotent`](Self::idempotent) are hedged.
+    pub fn hedging(mut self, hedging_config: HedgingConfig) -> Self {
+        self.config.store_put(hedging_config);
+        self
+    }
+
+    /// Marks the operation as safe to send more than once, which allows it to be hedged.
+    pub fn idempotent(mut self) -> Self {
+        self.config.store_put(IdempotentOperation::new());
+        self
+    }
+
     /// Configures the timeout configuration for the builder.
     pub fn timeout_config(mut self, timeout_config: TimeoutConfig) -> Self {
         self.config.store_put(timeout_config);
         self
     }
 
+    /// Configures the HTTP protocol version used to send requests.
+    pub fn http_version(mut self, http_version: HttpVersion) -> Self {
+        self.config.store_put(http_version);
+        self
+    }
+
     /// Disables auth for the operation.
     pub fn no_auth(mut self) -> Self {
         self.config
@@ -278,6 +299,13 @@ impl<I, O, E> OperationBuilder<I, O, E> {
         self
     }
 
+    /// Configures the metrics provider for the builder.
+    pub fn metrics_provider(mut self, metrics_provider: impl MetricsProvider + 'static) -> Self {
+        self.runtime_components
+            .set_metrics_provider(Some(metrics_provider.into_shared()));
+        self
+    }
+
     /// Configures the interceptor for the builder.
     pub fn interceptor(mut self, interceptor: impl Intercept + 'static) -> Self {
         self.runtime_components.push_interceptor(interceptor);
@@ -443,6 +471,8 @@ mod tests {
     use crate::client::http::test_util::{capture_request, ReplayEvent, StaticReplayClient};
     use crate::client::retries::classifiers::HttpStatusCodeClassifier;
     use aws_smithy_async::rt::sleep::{SharedAsyncSleep, TokioSleep};
+    use aws_smithy_runtime_api::client::metrics::test_util::InMemoryMetricsProvider;
+    use aws_smithy_runtime_api::client::metrics::{attributes, instruments};
     use aws_smithy_runtime_api::client::result::ConnectorError;
     use aws_smithy_types::body::SdkBody;
     use std::convert::Infallible;
@@ -540,4 +570,132 @@ mod tests {
 
         connector.assert_requests_match(&[]);
     }
+
+    #[tokio::test]
+    async fn operation_metrics() {
+        let connector = StaticReplayClient::new(vec![
+            ReplayEvent::new(
+                http_02x::Request::builder()
+                    .uri("http://localhost:1234/")
+                    .body(SdkBody::from(&b"what are you?"[..]))
+                    .unwrap(),
+                http_02x::Response::builder()
+                    .status(503)
+                    .body(SdkBody::from(&b""[..]))
+                    .unwrap(),
+            ),
+            ReplayEvent::new(
+                http_02x::Request::builder()
+                    .uri("http://localhost:1234/")
+                    .body(SdkBody::from(&b"what are you?"[..]))
+                    .unwrap(),
+                http_02x::Response::builder()
+                    .status(418)
+                    .body(SdkBody::from(&b"I'm a teapot!"[..]))
+                    .unwrap(),
+            ),
+        ]);
+        let metrics = InMemoryMetricsProvider::new();
+        let operation = Operation::builder()
+            .service_name("test-service")
+            .operation_name("TestOperation")
+            .http_client(connector.clone())
+            .endpoint_url("http://localhost:1234")
+            .no_auth()
+            .standard_retry(&RetryConfig::standard())
+            .retry_classifier(HttpStatusCodeClassifier::default())
+            .timeout_config(TimeoutConfig::disabled())
+            .sleep_impl(SharedAsyncSleep::new(TokioSleep::new()))
+            .metrics_provider(metrics.clone())
+            .serializer(|input: String| Ok(HttpRequest::new(SdkBody::from(input.as_bytes()))))
+            .deserializer::<_, Infallible>(|response| {
+                if u16::from(response.status()) == 503 {
+                    Err(OrchestratorError::connector(ConnectorError::io(
+                        "test".into(),
+                    )))
+                } else {
+                    Ok(std::str::from_utf8(response.body().bytes().unwrap())
+                        .unwrap()
+                        .to_string())
+                }
+            })
+            .build();
+
+        operation
+            .invoke("what are you?".to_string())
+            .await
+            .expect("success");
+
+        assert_eq!(2, metrics.counter_total(instruments::CALL_ATTEMPTS));
+        assert_eq!(1, metrics.counter_total(instruments::CALL_RETRIES));
+        assert_eq!(0, metrics.counter_total(instruments::CALL_ERRORS));
+        assert_eq!(26, metrics.counter_total(instruments::BYTES_SENT));
+        assert_eq!(13, metrics.counter_total(instruments::BYTES_RECEIVED));
+        for (name, count) in [
+            (instruments::CALL_DURATION, 1),
+            (instruments::CALL_ATTEMPT_DURATION, 2),
+            (instruments::SERIALIZATION_DURATION, 1),
+            (instruments::DESERIALIZATION_DURATION, 2),
+            (instruments::RESOLVE_IDENTITY_DURATION, 2),
+        ] {
+            assert_eq!(count, metrics.measurements_named(name).len(), "{name}");
+        }
+
+        let identity = metrics.measurements_named(instruments::RESOLVE_IDENTITY_DURATION);
+        assert_eq!(
+            Some(NO_AUTH_SCHEME_ID.as_str()),
+            identity[0].attributes().get(attributes::AUTH_SCHEME_ID)
+        );
+
+        let retries = metrics.measurements_named(instruments::CALL_RETRIES);
+        let retry_attributes = retries[0].attributes();
+        assert_eq!(
+            Some("test-service"),
+            retry_attributes.get(attributes::RPC_SERVICE)
+        );
+        assert_eq!(
+            Some("TestOperation"),
+            retry_attributes.get(attributes::RPC_METHOD)
+        );
+        assert_eq!(
+            Some("transient_error"),
+            retry_attributes.get(attributes::RETRY_REASON)
+        );
+    }
+
+    #[tokio::test]
+    async fn operation_metrics_count_errors() {
+        let (connector, _request_rx) = capture_request(Some(
+            http_02x::Response::builder()
+                .status(500)
+                .body(SdkBody::empty())
+                .unwrap(),
+        ));
+        let metrics = InMemoryMetricsProvider::new();
+        let operation = Operation::builder()
+            .service_name("test")
+            .operation_name("test")
+            .http_client(connector)
+            .endpoint_url("http://localhost:1234")
+            .no_auth()
+            .no_retry()
+            .timeout_config(TimeoutConfig::disabled())
+            .metrics_provider(metrics.clone())
+            .serializer(|input: String| Ok(HttpRequest::new(SdkBody::from(input.as_bytes()))))
+            .deserializer::<String, Infallible>(|_response| {
+                Err(OrchestratorError::connector(ConnectorError::io(
+                    "test".into(),
+                )))
+            })
+            .build();
+
+        operation
+            .invoke("what are you?".to_string())
+            .await
+            .expect_err("failure");
+
+        assert_eq!(1, metrics.counter_total(instruments::CALL_ATTEMPTS));
+        assert_eq!(1, metrics.counter_total(instruments::CALL_ERRORS));
+        assert_eq!(0, metrics.counter_total(instruments::CALL_RETRIES));
+    }
 }
@@ -6,15 +6,23 @@
 /// Smithy retry classifiers.
 pub mod classifiers;
 
+/// Sources of randomness for retry backoff jitter.
+pub mod jitter;
+
 /// Smithy retry strategies.
 pub mod strategy;
 
+mod circuit_breaker;
 mod client_rate_limiter;
 mod token_bucket;
 
 use aws_smithy_types::config_bag::{Storable, StoreReplace};
 use std::fmt;
+use std::time::Duration;
 
+pub use circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerOpenError, CircuitState,
+};
 pub use client_rate_limiter::ClientRateLimiter;
 pub use token_bucket::TokenBucket;
 
@@ -44,3 +52,13 @@ impl fmt::Display for RetryPartition {
 impl Storable for RetryPartition {
     type Storer = StoreReplace<RetryPartition>;
 }
+
+/// The delay before the current retry attempt.
+///
+/// The orchestrator stores it in the config bag, so that a retry strategy can base the next delay on it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PreviousRetryDelay(pub(crate) Duration);
+
+impl Storable for PreviousRetryDelay {
+    type Storer = StoreReplace<Self>;
+}
@@ -0,0 +1,409 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! A circuit breaker that fails requests fast when a service is struggling. Circuit breakers are
+//! shared by every client in the process that uses the same [`RetryPartition`].
+
+use crate::client::retries::RetryPartition;
+use crate::static_partition_map::StaticPartitionMap;
+use aws_smithy_types::config_bag::{Storable, StoreReplace};
+use aws_smithy_types::retry::ErrorKind;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+static CIRCUIT_BREAKER: StaticPartitionMap<RetryPartition, CircuitBreaker> =
+    StaticPartitionMap::new();
+
+const DEFAULT_FAILURE_RATIO: f64 = 0.5;
+const DEFAULT_MINIMUM_REQUESTS: u32 = 20;
+const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+const DEFAULT_OPEN_DURATION: Duration = Duration::from_secs(30);
+const DEFAULT_HALF_OPEN_PROBES: u32 = 1;
+
+/// Configuration for the circuit breaker of the
+/// [`StandardRetryStrategy`](crate::client::retries::strategy::StandardRetryStrategy).
+///
+/// The circuit breaker is disabled unless this config is stored in the config bag.
+///
+/// The circuit breaker counts the attempts made in a window of time. When at least
+/// [`minimum_requests`](Self::with_minimum_requests) attempts were made and the ratio of them that
+/// failed with a transient, throttling, or server error reaches the
+/// [`failure_ratio`](Self::with_failure_ratio), the circuit opens: requests fail immediately
+/// with a [`CircuitBreakerOpenError`] for the [`open_duration`](Self::with_open_duration). After
+/// that, the circuit is half-open: a limited number of probe requests are sent, and the circuit
+/// closes if they succeed or opens again if they fail.
+///
+/// The circuit breaker for a [`RetryPartition`] is created with the config of the first request
+/// made in that partition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitBreakerConfig {
+    failure_ratio: f64,
+    minimum_requests: u32,
+    window: Duration,
+    open_duration: Duration,
+    half_open_probes: u32,
+}
+
+impl Storable for CircuitBreakerConfig {
+    type Storer = StoreReplace<Self>;
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_ratio: DEFAULT_FAILURE_RATIO,
+            minimum_requests: DEFAULT_MINIMUM_REQUESTS,
+            window: DEFAULT_WINDOW,
+            open_duration: DEFAULT_OPEN_DURATION,
+            half_open_probes: DEFAULT_HALF_OPEN_PROBES,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Creates a new `CircuitBreakerConfig` with the default settings: the circuit opens for 30
+    /// seconds when half of at least 20 attempts made within 10 seconds fail.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ratio of failed attempts, between `0.0` and `1.0`, at which the circuit opens.
+    pub fn with_failure_ratio(mut self, failure_ratio: f64) -> Self {
+        self.failure_ratio = failure_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the minimum number of attempts made in a window before the circuit can open.
+    pub fn with_minimum_requests(mut self, minimum_requests: u32) -> Self {
+        self.minimum_requests = minimum_requests.max(1);
+        self
+    }
+
+    /// Sets the length of the window in which attempts are counted.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets how long the circuit stays open before probe requests are allowed through.
+    pub fn with_open_duration(mut self, open_duration: Duration) -> Self {
+        self.open_duration = open_duration;
+        self
+    }
+
+    /// Sets how many probe requests can be in flight while the circuit is half-open.
+    pub fn with_half_open_probes(mut self, half_open_probes: u32) -> Self {
+        self.half_open_probes = half_open_probes.max(1);
+        self
+    }
+}
+
+/// The state of a [`CircuitBreaker`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail immediately.
+    Open,
+    /// A limited number of probe requests are sent to find out whether the service recovered.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "closed"),
+            Self::Open => write!(f, "open"),
+            Self::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// The error returned for requests that are not sent because the circuit is open.
+#[derive(Debug)]
+pub struct CircuitBreakerOpenError {
+    retry_partition: RetryPartition,
+}
+
+impl CircuitBreakerOpenError {
+    /// Returns the retry partition of the circuit breaker that rejected the request.
+    pub fn retry_partition(&self) -> &RetryPartition {
+        &self.retry_partition
+    }
+
+    /// Returns [`ErrorKind::CircuitBreakerOpen`].
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::CircuitBreakerOpen
+    }
+}
+
+impl fmt::Display for CircuitBreakerOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the circuit breaker for retry partition `{}` is open, so the request was not sent",
+            self.retry_partition
+        )
+    }
+}
+
+impl std::error::Error for CircuitBreakerOpenError {}
+
+/// A circuit breaker shared by every client that uses the same [`RetryPartition`].
+///
+/// See [`CircuitBreakerConfig`] for how it behaves.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    retry_partition: RetryPartition,
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed {
+        window_start: Option<SystemTime>,
+        attempts: u32,
+        failures: u32,
+    },
+    Open {
+        until: SystemTime,
+    },
+    HalfOpen {
+        since: SystemTime,
+        probes: u32,
+    },
+}
+
+impl State {
+    fn closed() -> Self {
+        Self::Closed {
+            window_start: None,
+            attempts: 0,
+            failures: 0,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    fn new(retry_partition: RetryPartition, config: CircuitBreakerConfig) -> Self {
+        Self {
+            retry_partition,
+            config,
+            state: Arc::new(Mutex::new(State::closed())),
+        }
+    }
+
+    /// Returns the circuit breaker for the given retry partition, if a request was made in that
+    /// partition with a [`CircuitBreakerConfig`].
+    pub fn for_partition(retry_partition: &RetryPartition) -> Option<Self> {
+        CIRCUIT_BREAKER.get(retry_partition.clone())
+    }
+
+    pub(crate) fn get_or_init(
+        retry_partition: &RetryPartition,
+        config: &CircuitBreakerConfig,
+    ) -> Self {
+        CIRCUIT_BREAKER.get_or_init(retry_partition.clone(), || {
+            Self::new(retry_partition.clone(), config.clone())
+        })
+    }
+
+    /// Returns the current state of the circuit.
+    ///
+    /// An open circuit only becomes half-open when a request is made after the open duration
+    /// elapsed.
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Returns an error if a request made at `now` must fail fast.
+    pub(crate) fn acquire(&self, now: SystemTime) -> Result<(), CircuitBreakerOpenError> {
+        let mut state = self.state.lock().unwrap();
+        let rejected = match *state {
+            State::Closed { .. } => false,
+            State::Open { until } if now < until => true,
+            State::Open { .. } => {
+                debug!(partition = %self.retry_partition, "circuit breaker is half-open");
+                *state = State::HalfOpen {
+                    since: now,
+                    probes: 1,
+                };
+                false
+            }
+            State::HalfOpen {
+                ref mut since,
+                ref mut probes,
+            } => {
+                // Probes that never report back, for example because their request was dropped,
+                // would otherwise keep the circuit half-open forever.
+                if elapsed(*since, now) >= self.config.open_duration {
+                    *since = now;
+                    *probes = 0;
+                }
+                if *probes < self.config.half_open_probes {
+                    *probes += 1;
+                    false
+                } else {
+                    true
+                }
+            }
+        };
+        if rejected {
+            Err(CircuitBreakerOpenError {
+                retry_partition: self.retry_partition.clone(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Records the outcome of an attempt that finished at `now`.
+    pub(crate) fn record(&self, failed: bool, now: SystemTime) {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed {
+                ref mut window_start,
+                ref mut attempts,
+                ref mut failures,
+            } => {
+                let start = *window_start.get_or_insert(now);
+                if elapsed(start, now) >= self.config.window {
+                    *window_start = Some(now);
+                    *attempts = 0;
+                    *failures = 0;
+                }
+                *attempts += 1;
+                *failures += failed as u32;
+
+                let ratio = f64::from(*failures) / f64::from(*attempts);
+                if *attempts >= self.config.minimum_requests && ratio >= self.config.failure_ratio {
+                    warn!(
+                        partition = %self.retry_partition,
+                        attempts = *attempts,
+                        failures = *failures,
+                        "circuit breaker is open"
+                    );
+                    *state = State::Open {
+                        until: now + self.config.open_duration,
+                    };
+                }
+            }
+            // Attempts that were already in flight when the circuit opened don't change anything.
+            State::Open { .. } => {}
+            State::HalfOpen { .. } if failed => {
+                warn!(partition = %self.retry_partition, "probe request failed, circuit breaker is open");
+                *state = State::Open {
+                    until: now + self.config.open_duration,
+                };
+            }
+            State::HalfOpen { .. } => {
+                debug!(partition = %self.retry_partition, "probe request succeeded, circuit breaker is closed");
+                *state = State::closed();
+            }
+        }
+    }
+}
+
+fn elapsed(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            RetryPartition::new("test"),
+            CircuitBreakerConfig::new()
+                .with_minimum_requests(4)
+                .with_failure_ratio(0.5)
+                .with_window(Duration::from_secs(10))
+                .with_open_duration(Duration::from_secs(30)),
+        )
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn opens_after_failure_ratio_is_reached() {
+        let breaker = breaker();
+        for failed in [true, false, true] {
+            breaker.acquire(at(0)).unwrap();
+            breaker.record(failed, at(0));
+        }
+        assert_eq!(CircuitState::Closed, breaker.state());
+
+        breaker.record(false, at(1));
+        assert_eq!(CircuitState::Open, breaker.state());
+        let err = breaker.acquire(at(2)).unwrap_err();
+        assert_eq!(ErrorKind::CircuitBreakerOpen, err.kind());
+        assert_eq!(&RetryPartition::new("test"), err.retry_partition());
+    }
+
+    #[test]
+    fn failures_outside_the_window_are_forgotten() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            breaker.record(true, at(0));
+        }
+        breaker.record(true, at(10));
+        breaker.record(false, at(11));
+        breaker.record(false, at(12));
+        assert_eq!(CircuitState::Closed, breaker.state());
+    }
+
+    #[test]
+    fn half_open_probe_success_closes_the_circuit() {
+        let breaker = breaker();
+        for _ in 0..4 {
+            breaker.record(true, at(0));
+        }
+        assert!(breaker.acquire(at(29)).is_err());
+
+        breaker.acquire(at(30)).unwrap();
+        assert_eq!(CircuitState::HalfOpen, breaker.state());
+        // Only one probe is allowed at a time
+        assert!(breaker.acquire(at(31)).is_err());
+
+        breaker.record(false, at(32));
+        assert_eq!(CircuitState::Closed, breaker.state());
+        breaker.acquire(at(33)).unwrap();
+    }
+
+    #[test]
+    fn half_open_probe_failure_opens_the_circuit() {
+        let breaker = breaker();
+        for _ in 0..4 {
+            breaker.record(true, at(0));
+        }
+        breaker.acquire(at(30)).unwrap();
+        breaker.record(true, at(31));
+        assert_eq!(CircuitState::Open, breaker.state());
+        assert!(breaker.acquire(at(60)).is_err());
+        breaker.acquire(at(61)).unwrap();
+    }
+
+    #[test]
+    fn lost_probes_are_replaced() {
+        let breaker = breaker();
+        for _ in 0..4 {
+            breaker.record(true, at(0));
+        }
+        breaker.acquire(at(30)).unwrap();
+        assert!(breaker.acquire(at(59)).is_err());
+        breaker.acquire(at(60)).unwrap();
+    }
+}
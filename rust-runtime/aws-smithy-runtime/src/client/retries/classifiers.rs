@@ -3,14 +3,18 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use aws_smithy_async::time::{SharedTimeSource, TimeSource};
 use aws_smithy_runtime_api::client::interceptors::context::InterceptorContext;
 use aws_smithy_runtime_api::client::retries::classifiers::{
     ClassifyRetry, RetryAction, RetryClassifierPriority, SharedRetryClassifier,
 };
-use aws_smithy_types::retry::ProvideErrorKind;
+use aws_smithy_types::date_time::Format;
+use aws_smithy_types::retry::{ErrorKind, ProvideErrorKind};
+use aws_smithy_types::DateTime;
 use std::borrow::Cow;
 use std::error::Error as StdError;
 use std::marker::PhantomData;
+use std::time::Duration;
 
 /// A retry classifier for checking if an error is modeled as retryable.
 #[derive(Debug, Default)]
@@ -176,6 +180,118 @@ impl ClassifyRetry for HttpStatusCodeClassifier {
     }
 }
 
+const RETRY_AFTER_STATUS_CODES: &[u16] = &[429, 503];
+
+/// A retry classifier that honours the delay a server requests with the `x-amz-retry-after` or
+/// `Retry-After` response headers.
+///
+/// `x-amz-retry-after` is a number of milliseconds and takes precedence when both are present.
+/// `Retry-After` may be either a number of seconds or an HTTP-date. An HTTP-date is measured
+/// relative to the response's `Date` header when there is one, so that clock skew between client
+/// and server doesn't affect the delay. Otherwise, it is measured relative to the classifier's
+/// time source, which defaults to the system clock.
+///
+/// The `Default` version only considers 429 and 503 responses, which are classified as throttling
+/// and server errors respectively. Since this classifier runs after the built-in classifiers, the
+/// delay it returns replaces any computed backoff. The [`StandardRetryStrategy`](crate::client::retries::strategy::StandardRetryStrategy)
+/// still caps the delay at the configured max backoff.
+#[derive(Debug)]
+pub struct RetryAfterClassifier {
+    retryable_status_codes: Cow<'static, [u16]>,
+    time_source: SharedTimeSource,
+}
+
+impl Default for RetryAfterClassifier {
+    fn default() -> Self {
+        Self::new_from_codes(RETRY_AFTER_STATUS_CODES)
+    }
+}
+
+impl RetryAfterClassifier {
+    /// Given a `Vec<u16>` where the `u16`s represent status codes, create a `RetryAfterClassifier`
+    /// that will read retry-after headers from HTTP responses with those status codes. The
+    /// `Default` version will read them from 429 and 503 responses.
+    pub fn new_from_codes(retryable_status_codes: impl Into<Cow<'static, [u16]>>) -> Self {
+        Self {
+            retryable_status_codes: retryable_status_codes.into(),
+            time_source: SharedTimeSource::default(),
+        }
+    }
+
+    /// Set the time source used to measure a `Retry-After` HTTP-date when the response has no
+    /// `Date` header.
+    pub fn with_time_source(mut self, time_source: impl TimeSource + 'static) -> Self {
+        self.time_source = SharedTimeSource::new(time_source);
+        self
+    }
+
+    /// Return the priority of this retry classifier.
+    pub fn priority() -> RetryClassifierPriority {
+        RetryClassifierPriority::run_after(RetryClassifierPriority::transient_error_classifier())
+    }
+
+    fn parse_retry_after(&self, value: &str, date: Option<&str>) -> Option<Duration> {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let retry_at = DateTime::from_str(value, Format::HttpDate).ok()?;
+        let now = date
+            .and_then(|date| DateTime::from_str(date.trim(), Format::HttpDate).ok())
+            .unwrap_or_else(|| DateTime::from(self.time_source.now()));
+        // A date in the past means the request can be retried immediately
+        let millis = (retry_at.as_nanos() - now.as_nanos()).max(0) / 1_000_000;
+        Some(Duration::from_millis(millis.try_into().unwrap_or(u64::MAX)))
+    }
+}
+
+impl ClassifyRetry for RetryAfterClassifier {
+    fn classify_retry(&self, ctx: &InterceptorContext) -> RetryAction {
+        let Some(response) = ctx.response() else {
+            return RetryAction::NoActionIndicated;
+        };
+        let status = u16::from(response.status());
+        if !self.retryable_status_codes.contains(&status) {
+            return RetryAction::NoActionIndicated;
+        }
+
+        let headers = response.headers();
+        let retry_after = headers
+            .get("x-amz-retry-after")
+            .and_then(parse_amz_retry_after)
+            .or_else(|| {
+                headers
+                    .get("retry-after")
+                    .and_then(|value| self.parse_retry_after(value, headers.get("date")))
+            });
+
+        match retry_after {
+            Some(retry_after) => {
+                let kind = if status == 429 {
+                    ErrorKind::ThrottlingError
+                } else {
+                    ErrorKind::ServerError
+                };
+                RetryAction::retryable_error_with_explicit_delay(kind, retry_after)
+            }
+            None => RetryAction::NoActionIndicated,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "Retry-After Header"
+    }
+
+    fn priority(&self) -> RetryClassifierPriority {
+        Self::priority()
+    }
+}
+
+fn parse_amz_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_millis)
+}
+
 /// Given an iterator of retry classifiers and an interceptor context, run retry classifiers on the
 /// context. Each classifier is passed the classification result from the previous classifier (the
 /// 'root' classifier is passed `None`.)
@@ -216,14 +332,16 @@ pub fn run_classifiers_on_ctx(
 #[cfg(test)]
 mod test {
     use crate::client::retries::classifiers::{
-        HttpStatusCodeClassifier, ModeledAsRetryableClassifier,
+        HttpStatusCodeClassifier, ModeledAsRetryableClassifier, RetryAfterClassifier,
     };
+    use aws_smithy_async::time::StaticTimeSource;
     use aws_smithy_runtime_api::client::interceptors::context::{Error, Input, InterceptorContext};
     use aws_smithy_runtime_api::client::orchestrator::OrchestratorError;
     use aws_smithy_runtime_api::client::retries::classifiers::{ClassifyRetry, RetryAction};
     use aws_smithy_types::body::SdkBody;
     use aws_smithy_types::retry::{ErrorKind, ProvideErrorKind};
     use std::fmt;
+    use std::time::{Duration, UNIX_EPOCH};
 
     use super::TransientErrorClassifier;
 
@@ -316,4 +434,94 @@ mod test {
         )));
         assert_eq!(policy.classify_retry(&ctx), RetryAction::transient_error(),);
     }
+
+    fn response_ctx(status: u16, headers: &[(&str, &str)]) -> InterceptorContext {
+        let mut res = http_02x::Response::builder().status(status);
+        for (name, value) in headers {
+            res = res.header(*name, *value);
+        }
+        let res = res.body("error!").unwrap().map(SdkBody::from);
+        let mut ctx = InterceptorContext::new(Input::doesnt_matter());
+        ctx.set_response(res.try_into().unwrap());
+        ctx
+    }
+
+    #[test]
+    fn classify_retry_after_seconds() {
+        let policy = RetryAfterClassifier::default();
+        let ctx = response_ctx(429, &[("retry-after", "3")]);
+        assert_eq!(
+            policy.classify_retry(&ctx),
+            RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ThrottlingError,
+                Duration::from_secs(3)
+            )
+        );
+    }
+
+    #[test]
+    fn classify_retry_after_http_date() {
+        let policy = RetryAfterClassifier::default();
+        let ctx = response_ctx(
+            503,
+            &[
+                ("retry-after", "Wed, 21 Oct 2015 07:28:30 GMT"),
+                ("date", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            ],
+        );
+        assert_eq!(
+            policy.classify_retry(&ctx),
+            RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ServerError,
+                Duration::from_secs(30)
+            )
+        );
+
+        // Without a `Date` header, the time source is used. A date in the past allows an
+        // immediate retry.
+        let policy = RetryAfterClassifier::default().with_time_source(StaticTimeSource::new(
+            UNIX_EPOCH + Duration::from_secs(1445412420),
+        ));
+        let ctx = response_ctx(503, &[("retry-after", "Wed, 21 Oct 2015 07:28:30 GMT")]);
+        assert_eq!(
+            policy.classify_retry(&ctx),
+            RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ServerError,
+                Duration::from_secs(90)
+            )
+        );
+        let ctx = response_ctx(503, &[("retry-after", "Wed, 21 Oct 2015 07:26:00 GMT")]);
+        assert_eq!(
+            policy.classify_retry(&ctx),
+            RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ServerError,
+                Duration::ZERO
+            )
+        );
+    }
+
+    #[test]
+    fn amz_retry_after_takes_precedence() {
+        let policy = RetryAfterClassifier::default();
+        let ctx = response_ctx(429, &[("retry-after", "3"), ("x-amz-retry-after", "1500")]);
+        assert_eq!(
+            policy.classify_retry(&ctx),
+            RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ThrottlingError,
+                Duration::from_millis(1500)
+            )
+        );
+    }
+
+    #[test]
+    fn retry_after_is_ignored_when_not_applicable() {
+        let policy = RetryAfterClassifier::default();
+        for ctx in [
+            response_ctx(500, &[("retry-after", "3")]),
+            response_ctx(429, &[]),
+            response_ctx(429, &[("retry-after", "soon")]),
+        ] {
+            assert_eq!(policy.classify_retry(&ctx), RetryAction::NoActionIndicated);
+        }
+    }
 }
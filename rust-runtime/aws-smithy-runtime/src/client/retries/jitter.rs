@@ -0,0 +1,132 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A source of randomness for the jitter applied to retry backoff.
+///
+/// The [`StandardRetryStrategy`](crate::client::retries::strategy::StandardRetryStrategy) uses
+/// [`RandomJitterSource`] by default. Tests that assert on retry delays can use a
+/// `DeterministicJitterSource` instead, which is available with the `test-util` feature.
+pub trait JitterSource: fmt::Debug + Send + Sync {
+    /// Returns a number in the range `0.0..=1.0`.
+    fn next_f64(&self) -> f64;
+}
+
+/// A [`JitterSource`] backed by a thread-local random number generator.
+#[non_exhaustive]
+#[derive(Debug, Default, Clone)]
+pub struct RandomJitterSource;
+
+impl RandomJitterSource {
+    /// Creates a new `RandomJitterSource`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl JitterSource for RandomJitterSource {
+    fn next_f64(&self) -> f64 {
+        fastrand::f64()
+    }
+}
+
+/// A [`JitterSource`] that can be shared between retry strategies.
+#[derive(Debug, Clone)]
+pub struct SharedJitterSource(Arc<dyn JitterSource>);
+
+impl SharedJitterSource {
+    /// Creates a new `SharedJitterSource` from the given jitter source.
+    pub fn new(source: impl JitterSource + 'static) -> Self {
+        Self(Arc::new(source))
+    }
+}
+
+impl Default for SharedJitterSource {
+    fn default() -> Self {
+        Self::new(RandomJitterSource::new())
+    }
+}
+
+impl JitterSource for SharedJitterSource {
+    fn next_f64(&self) -> f64 {
+        self.0.next_f64()
+    }
+}
+
+#[cfg(feature = "test-util")]
+pub use test_util::DeterministicJitterSource;
+
+#[cfg(feature = "test-util")]
+mod test_util {
+    use super::JitterSource;
+    use std::sync::Mutex;
+
+    /// A [`JitterSource`] that produces the same sequence of numbers every time it is created
+    /// with the same arguments.
+    ///
+    /// This is intended for tests that assert on retry delays.
+    #[derive(Debug)]
+    pub struct DeterministicJitterSource {
+        inner: Inner,
+    }
+
+    #[derive(Debug)]
+    enum Inner {
+        Constant(f64),
+        Seeded(Mutex<fastrand::Rng>),
+    }
+
+    impl DeterministicJitterSource {
+        /// Creates a jitter source that always returns `value`, clamped to `0.0..=1.0`.
+        ///
+        /// A value of `1.0` disables jitter entirely.
+        pub fn constant(value: f64) -> Self {
+            Self {
+                inner: Inner::Constant(value.clamp(0.0, 1.0)),
+            }
+        }
+
+        /// Creates a jitter source that returns a pseudo-random sequence determined by `seed`.
+        pub fn with_seed(seed: u64) -> Self {
+            Self {
+                inner: Inner::Seeded(Mutex::new(fastrand::Rng::with_seed(seed))),
+            }
+        }
+    }
+
+    impl JitterSource for DeterministicJitterSource {
+        fn next_f64(&self) -> f64 {
+            match &self.inner {
+                Inner::Constant(value) => *value,
+                Inner::Seeded(rng) => rng.lock().unwrap().f64(),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn seeded_sources_repeat_their_sequence() {
+            let a = DeterministicJitterSource::with_seed(42);
+            let b = DeterministicJitterSource::with_seed(42);
+            for _ in 0..10 {
+                let value = a.next_f64();
+                assert!((0.0..1.0).contains(&value));
+                assert_eq!(value, b.next_f64());
+            }
+        }
+
+        #[test]
+        fn constant_sources_are_clamped() {
+            assert_eq!(0.25, DeterministicJitterSource::constant(0.25).next_f64());
+            assert_eq!(1.0, DeterministicJitterSource::constant(2.0).next_f64());
+            assert_eq!(0.0, DeterministicJitterSource::constant(-1.0).next_f64());
+        }
+    }
+}
@@ -18,15 +18,18 @@ use aws_smithy_runtime_api::client::retries::classifiers::{RetryAction, RetryRea
 use aws_smithy_runtime_api::client::retries::{RequestAttempts, RetryStrategy, ShouldAttempt};
 use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
 use aws_smithy_types::config_bag::{ConfigBag, Layer, Storable, StoreReplace};
-use aws_smithy_types::retry::{ErrorKind, RetryConfig, RetryMode};
+use aws_smithy_types::retry::{BackoffStrategy, ErrorKind, RetryConfig, RetryMode};
 
+use crate::client::orchestrator::metrics::OperationMetrics;
+use crate::client::retries::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::client::retries::classifiers::run_classifiers_on_ctx;
 use crate::client::retries::client_rate_limiter::{ClientRateLimiter, RequestReason};
+use crate::client::retries::jitter::{JitterSource, SharedJitterSource};
 use crate::client::retries::strategy::standard::ReleaseResult::{
     APermitWasReleased, NoPermitWasReleased,
 };
 use crate::client::retries::token_bucket::TokenBucket;
-use crate::client::retries::{ClientRateLimiterPartition, RetryPartition};
+use crate::client::retries::{ClientRateLimiterPartition, PreviousRetryDelay, RetryPartition};
 use crate::static_partition_map::StaticPartitionMap;
 
 static CLIENT_RATE_LIMITER: StaticPartitionMap<ClientRateLimiterPartition, ClientRateLimiter> =
@@ -36,9 +39,12 @@ static CLIENT_RATE_LIMITER: StaticPartitionMap<ClientRateLimiterPartition, Clien
 static TOKEN_BUCKET: StaticPartitionMap<RetryPartition, TokenBucket> = StaticPartitionMap::new();
 
 /// Retry strategy with exponential backoff, max attempts, and a token bucket.
+///
+/// The shape of the backoff is chosen with [`RetryConfig::with_backoff_strategy`].
 #[derive(Debug, Default)]
 pub struct StandardRetryStrategy {
     retry_permit: Mutex<Option<OwnedSemaphorePermit>>,
+    jitter_source: SharedJitterSource,
 }
 
 impl Storable for StandardRetryStrategy {
@@ -51,6 +57,14 @@ impl StandardRetryStrategy {
         Default::default()
     }
 
+    /// Sets the source of randomness used to jitter the backoff between attempts.
+    ///
+    /// Defaults to a [`RandomJitterSource`](crate::client::retries::jitter::RandomJitterSource).
+    pub fn with_jitter_source(mut self, jitter_source: impl JitterSource + 'static) -> Self {
+        self.jitter_source = SharedJitterSource::new(jitter_source);
+        self
+    }
+
     fn release_retry_permit(&self) -> ReleaseResult {
         let mut retry_permit = self.retry_permit.lock().unwrap();
         match retry_permit.take() {
@@ -97,6 +111,24 @@ impl StandardRetryStrategy {
         None
     }
 
+    /// Returns the [`CircuitBreaker`] for the retry partition, and the current time, if a circuit
+    /// breaker is configured.
+    fn circuit_breaker(
+        runtime_components: &RuntimeComponents,
+        cfg: &ConfigBag,
+    ) -> Option<(CircuitBreaker, SystemTime)> {
+        let config = cfg.load::<CircuitBreakerConfig>()?;
+        let Some(time_source) = runtime_components.time_source() else {
+            debug!("a circuit breaker is configured, but it can't be used without a time source");
+            return None;
+        };
+        let retry_partition = cfg.load::<RetryPartition>().expect("set in default config");
+        Some((
+            CircuitBreaker::get_or_init(retry_partition, config),
+            time_source.now(),
+        ))
+    }
+
     fn calculate_backoff(
         &self,
         runtime_components: &RuntimeComponents,
@@ -122,22 +154,10 @@ impl StandardRetryStrategy {
                     debug!("rate limiter has requested a {delay:?} delay before retrying");
                     Ok(delay)
                 } else {
-                    let base = if retry_cfg.use_static_exponential_base() {
-                        1.0
-                    } else {
-                        fastrand::f64()
-                    };
-                    Ok(calculate_exponential_backoff(
-                        // Generate a random base multiplier to create jitter
-                        base,
-                        // Get the backoff time multiplier in seconds (with fractional seconds)
-                        retry_cfg.initial_backoff().as_secs_f64(),
-                        // `self.local.attempts` tracks number of requests made including the initial request
-                        // The initial attempt shouldn't count towards backoff calculations, so we subtract it
-                        request_attempts - 1,
-                        // Maximum backoff duration as a fallback to prevent overflow when calculating a power
-                        retry_cfg.max_backoff(),
-                    ))
+                    // `request_attempts` includes the initial request, which shouldn't count
+                    // towards backoff calculations, so we subtract it
+                    let previous_delay = cfg.load::<PreviousRetryDelay>().map(|delay| delay.0);
+                    Ok(self.jittered_backoff(retry_cfg, request_attempts - 1, previous_delay))
                 }
             }
             RetryAction::RetryForbidden | RetryAction::NoActionIndicated => {
@@ -151,6 +171,60 @@ impl StandardRetryStrategy {
             _ => unreachable!("RetryAction is non-exhaustive"),
         }
     }
+
+    /// Calculates the backoff before the next attempt according to the configured
+    /// [`BackoffStrategy`].
+    ///
+    /// `previous_delay` is the delay before the current attempt, if it was a retry.
+    fn jittered_backoff(
+        &self,
+        retry_cfg: &RetryConfig,
+        retry_attempts: u32,
+        previous_delay: Option<Duration>,
+    ) -> Duration {
+        let jitter = || {
+            if retry_cfg.use_static_exponential_base() {
+                1.0
+            } else {
+                self.jitter_source.next_f64()
+            }
+        };
+        // Get the backoff time multiplier in seconds (with fractional seconds)
+        let initial_backoff = retry_cfg.initial_backoff().as_secs_f64();
+        // Maximum backoff duration as a fallback to prevent overflow when calculating a power
+        let max_backoff = retry_cfg.max_backoff();
+
+        match retry_cfg.backoff_strategy() {
+            BackoffStrategy::EqualJitter => {
+                let half = calculate_exponential_backoff(
+                    1.0,
+                    initial_backoff,
+                    retry_attempts,
+                    max_backoff,
+                ) / 2;
+                half + half.mul_f64(jitter())
+            }
+            BackoffStrategy::DecorrelatedJitter => calculate_decorrelated_backoff(
+                jitter(),
+                retry_cfg.initial_backoff(),
+                previous_delay,
+                max_backoff,
+            ),
+            BackoffStrategy::Fixed(schedule) => schedule
+                .get(retry_attempts as usize)
+                .or(schedule.last())
+                .copied()
+                .unwrap_or_default()
+                .min(max_backoff),
+            // Full jitter is the default, and the fallback for strategies added in the future
+            _ => calculate_exponential_backoff(
+                jitter(),
+                initial_backoff,
+                retry_attempts,
+                max_backoff,
+            ),
+        }
+    }
 }
 
 enum ReleaseResult {
@@ -164,6 +238,10 @@ impl RetryStrategy for StandardRetryStrategy {
         runtime_components: &RuntimeComponents,
         cfg: &ConfigBag,
     ) -> Result<ShouldAttempt, BoxError> {
+        if let Some((circuit_breaker, now)) = Self::circuit_breaker(runtime_components, cfg) {
+            circuit_breaker.acquire(now)?;
+        }
+
         if let Some(crl) = Self::adaptive_retry_rate_limiter(runtime_components, cfg) {
             let seconds_since_unix_epoch = get_seconds_since_unix_epoch(runtime_components);
             if let Err(delay) = crl.acquire_permission_to_send_a_request(
@@ -203,6 +281,14 @@ impl RetryStrategy for StandardRetryStrategy {
             .unwrap_or(false);
         update_rate_limiter_if_exists(runtime_components, cfg, is_throttling_error);
 
+        // Client errors say nothing about the health of the service, so they don't count as
+        // failures for the circuit breaker
+        let circuit_breaker = Self::circuit_breaker(runtime_components, cfg);
+        if let Some((circuit_breaker, now)) = &circuit_breaker {
+            let failed = error_kind.is_some_and(|kind| kind != ErrorKind::ClientError);
+            circuit_breaker.record(failed, *now);
+        }
+
         // on success release any retry quota held by previous attempts
         if !ctx.is_failed() {
             if let NoPermitWasReleased = self.release_retry_permit() {
@@ -239,6 +325,13 @@ impl RetryStrategy for StandardRetryStrategy {
             return Ok(ShouldAttempt::No);
         }
 
+        if let Some((circuit_breaker, now)) = circuit_breaker {
+            if let Err(err) = circuit_breaker.acquire(now) {
+                debug!("attempt #{request_attempts} failed with {classifier_result:?}; however, {err}, so no retry will be attempted.");
+                return Ok(ShouldAttempt::No);
+            }
+        }
+
         //  acquire permit for retry
         let error_kind = error_kind.expect("result was classified retryable");
         match token_bucket.acquire(&error_kind) {
@@ -261,6 +354,9 @@ impl RetryStrategy for StandardRetryStrategy {
             "attempt #{request_attempts} failed with {:?}; retrying after {:?}",
             classifier_result, backoff
         );
+        if let Some(metrics) = cfg.load::<OperationMetrics>() {
+            metrics.add_retry(error_kind);
+        }
         Ok(ShouldAttempt::YesAfterDelay(backoff))
     }
 }
@@ -331,6 +427,22 @@ fn calculate_exponential_backoff(
     result.mul_f64(base)
 }
 
+/// Each decorrelated backoff is a random duration between the initial backoff and three times the
+/// previous delay, capped at the maximum backoff. The first retry is based on the initial backoff.
+fn calculate_decorrelated_backoff(
+    jitter: f64,
+    initial_backoff: Duration,
+    previous_delay: Option<Duration>,
+    max_backoff: Duration,
+) -> Duration {
+    let initial_backoff = initial_backoff.min(max_backoff);
+    let upper = previous_delay
+        .unwrap_or(initial_backoff)
+        .saturating_mul(3)
+        .clamp(initial_backoff, max_backoff);
+    initial_backoff + (upper - initial_backoff).mul_f64(jitter)
+}
+
 fn get_seconds_since_unix_epoch(runtime_components: &RuntimeComponents) -> f64 {
     let request_time = runtime_components
         .time_source()
@@ -403,6 +515,7 @@ mod tests {
     use std::sync::Mutex;
     use std::time::Duration;
 
+    use aws_smithy_async::time::StaticTimeSource;
     use aws_smithy_runtime_api::client::interceptors::context::{
         Input, InterceptorContext, Output,
     };
@@ -417,10 +530,21 @@ mod tests {
         RuntimeComponents, RuntimeComponentsBuilder,
     };
     use aws_smithy_types::config_bag::{ConfigBag, Layer};
+    #[cfg(feature = "test-util")]
+    use aws_smithy_types::retry::BackoffStrategy;
     use aws_smithy_types::retry::{ErrorKind, RetryConfig};
 
-    use super::{calculate_exponential_backoff, StandardRetryStrategy};
+    use super::{
+        calculate_decorrelated_backoff, calculate_exponential_backoff, StandardRetryStrategy,
+    };
+    #[cfg(feature = "test-util")]
+    use crate::client::retries::jitter::DeterministicJitterSource;
+    #[cfg(feature = "test-util")]
+    use crate::client::retries::PreviousRetryDelay;
     use crate::client::retries::TokenBucket;
+    use crate::client::retries::{
+        CircuitBreaker, CircuitBreakerConfig, CircuitBreakerOpenError, CircuitState, RetryPartition,
+    };
 
     #[test]
     fn no_retry_necessary_for_ok_result() {
@@ -958,6 +1082,274 @@ mod tests {
         assert_eq!(token_bucket.available_permits(), 480);
     }
 
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn explicit_delay_is_capped_by_max_backoff() {
+        let (mut cfg, rc, ctx) = setup_test(
+            vec![RetryAction::retryable_error_with_explicit_delay(
+                ErrorKind::ThrottlingError,
+                Duration::from_secs(60),
+            )],
+            RetryConfig::standard()
+                .with_max_attempts(3)
+                .with_max_backoff(Duration::from_secs(20)),
+        );
+        let strategy = StandardRetryStrategy::new();
+        cfg.interceptor_state().store_put(TokenBucket::default());
+
+        cfg.interceptor_state().store_put(RequestAttempts::new(1));
+        let should_retry = strategy.should_attempt_retry(&ctx, &rc, &cfg).unwrap();
+        assert_eq!(should_retry.expect_delay(), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn circuit_breaker_fails_fast() {
+        let retry_partition = RetryPartition::new("circuit_breaker_fails_fast");
+        let mut ctx = InterceptorContext::new(Input::doesnt_matter());
+        ctx.set_output_or_error(Err(OrchestratorError::other("doesn't matter")));
+        let rc = RuntimeComponentsBuilder::for_tests()
+            .with_retry_classifier(SharedRetryClassifier::new(AlwaysRetry(
+                ErrorKind::ServerError,
+            )))
+            .with_time_source(Some(StaticTimeSource::from_secs(0)))
+            .build()
+            .unwrap();
+        let mut layer = Layer::new("test");
+        layer.store_put(RetryConfig::standard());
+        layer.store_put(TokenBucket::default());
+        layer.store_put(retry_partition.clone());
+        layer.store_put(
+            CircuitBreakerConfig::new()
+                .with_minimum_requests(2)
+                .with_failure_ratio(1.0),
+        );
+        let mut cfg = ConfigBag::of_layers(vec![layer]);
+        let strategy = StandardRetryStrategy::new();
+
+        assert_eq!(
+            ShouldAttempt::Yes,
+            strategy.should_attempt_initial_request(&rc, &cfg).unwrap()
+        );
+        cfg.interceptor_state().store_put(RequestAttempts::new(1));
+        strategy
+            .should_attempt_retry(&ctx, &rc, &cfg)
+            .unwrap()
+            .expect_delay();
+
+        // The second failure opens the circuit, so the request isn't retried
+        cfg.interceptor_state().store_put(RequestAttempts::new(2));
+        assert_eq!(
+            ShouldAttempt::No,
+            strategy.should_attempt_retry(&ctx, &rc, &cfg).unwrap()
+        );
+        let circuit_breaker = CircuitBreaker::for_partition(&retry_partition).unwrap();
+        assert_eq!(CircuitState::Open, circuit_breaker.state());
+
+        let err = strategy
+            .should_attempt_initial_request(&rc, &cfg)
+            .unwrap_err();
+        let err = err.downcast_ref::<CircuitBreakerOpenError>().unwrap();
+        assert_eq!(ErrorKind::CircuitBreakerOpen, err.kind());
+    }
+
+    #[cfg(feature = "test-util")]
+    fn delays(
+        strategy: StandardRetryStrategy,
+        retry_config: RetryConfig,
+        attempts: std::ops::RangeInclusive<u32>,
+    ) -> Vec<Duration> {
+        let (mut cfg, rc, ctx) = setup_test(vec![RetryAction::server_error()], retry_config);
+        cfg.interceptor_state().store_put(TokenBucket::default());
+        attempts
+            .map(|attempt| {
+                cfg.interceptor_state()
+                    .store_put(RequestAttempts::new(attempt));
+                let delay = strategy
+                    .should_attempt_retry(&ctx, &rc, &cfg)
+                    .unwrap()
+                    .expect_delay();
+                // Like the orchestrator, record the delay before the next attempt
+                cfg.interceptor_state().store_put(PreviousRetryDelay(delay));
+                delay
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn full_jitter_backoff() {
+        let strategy = StandardRetryStrategy::new()
+            .with_jitter_source(DeterministicJitterSource::constant(0.5));
+        let retry_config = RetryConfig::standard().with_max_attempts(5);
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_secs(2)
+            ],
+            delays(strategy, retry_config, 1..=3)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn equal_jitter_backoff() {
+        let strategy = StandardRetryStrategy::new()
+            .with_jitter_source(DeterministicJitterSource::constant(0.5));
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(5)
+            .with_max_backoff(Duration::from_secs(3))
+            .with_backoff_strategy(BackoffStrategy::EqualJitter);
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(750),
+                Duration::from_millis(1500),
+                Duration::from_millis(2250),
+                Duration::from_millis(2250)
+            ],
+            delays(strategy, retry_config, 1..=4)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn decorrelated_jitter_backoff() {
+        let strategy = StandardRetryStrategy::new()
+            .with_jitter_source(DeterministicJitterSource::constant(0.5));
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(5)
+            .with_max_backoff(Duration::from_secs(10))
+            .with_backoff_strategy(BackoffStrategy::DecorrelatedJitter);
+
+        assert_eq!(
+            vec![
+                Duration::from_secs(2),
+                Duration::from_millis(3500),
+                Duration::from_millis(5500),
+                Duration::from_millis(5500)
+            ],
+            delays(strategy, retry_config, 1..=4)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn decorrelated_jitter_with_static_exponential_base() {
+        let retry_config = RetryConfig::standard()
+            .with_use_static_exponential_base(true)
+            .with_max_attempts(5)
+            .with_backoff_strategy(BackoffStrategy::DecorrelatedJitter);
+
+        assert_eq!(
+            vec![
+                Duration::from_secs(3),
+                Duration::from_secs(9),
+                Duration::from_secs(20)
+            ],
+            delays(StandardRetryStrategy::new(), retry_config, 1..=3)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn fixed_backoff_schedule() {
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(5)
+            .with_max_backoff(Duration::from_secs(3))
+            .with_backoff_strategy(BackoffStrategy::Fixed(vec![
+                Duration::from_millis(100),
+                Duration::from_secs(5),
+            ]));
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(100),
+                Duration::from_secs(3),
+                Duration::from_secs(3)
+            ],
+            delays(StandardRetryStrategy::new(), retry_config, 1..=3)
+        );
+
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(5)
+            .with_backoff_strategy(BackoffStrategy::Fixed(Vec::new()));
+        assert_eq!(
+            vec![Duration::ZERO, Duration::ZERO],
+            delays(StandardRetryStrategy::new(), retry_config, 1..=2)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn seeded_jitter_source_is_reproducible() {
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(5)
+            .with_backoff_strategy(BackoffStrategy::DecorrelatedJitter);
+        let strategy = || {
+            StandardRetryStrategy::new().with_jitter_source(DeterministicJitterSource::with_seed(7))
+        };
+
+        let first = delays(strategy(), retry_config.clone(), 1..=4);
+        let second = delays(strategy(), retry_config, 1..=4);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|delay| *delay <= Duration::from_secs(20)));
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn decorrelated_jitter_stays_within_bounds() {
+        let initial_backoff = Duration::from_millis(100);
+        let max_backoff = Duration::from_secs(5);
+        let retry_config = RetryConfig::standard()
+            .with_max_attempts(50)
+            .with_initial_backoff(initial_backoff)
+            .with_max_backoff(max_backoff)
+            .with_backoff_strategy(BackoffStrategy::DecorrelatedJitter);
+
+        for seed in 0..20 {
+            let strategy = StandardRetryStrategy::new()
+                .with_jitter_source(DeterministicJitterSource::with_seed(seed));
+            let delays = delays(strategy, retry_config.clone(), 1..=49);
+            let mut previous = initial_backoff;
+            for delay in delays {
+                let upper = (previous * 3).clamp(initial_backoff, max_backoff);
+                assert!(
+                    initial_backoff <= delay && delay <= upper,
+                    "seed {seed}: {delay:?} isn't between {initial_backoff:?} and {upper:?}"
+                );
+                previous = delay;
+            }
+        }
+    }
+
+    #[test]
+    fn decorrelated_backoff_is_based_on_the_previous_delay() {
+        let initial_backoff = Duration::from_secs(1);
+        let max_backoff = Duration::from_secs(20);
+        let backoff = |jitter, previous| {
+            calculate_decorrelated_backoff(jitter, initial_backoff, previous, max_backoff)
+        };
+
+        assert_eq!(Duration::from_secs(1), backoff(0.0, None));
+        assert_eq!(Duration::from_secs(3), backoff(1.0, None));
+        assert_eq!(
+            Duration::from_secs(1),
+            backoff(0.0, Some(Duration::from_secs(4)))
+        );
+        assert_eq!(
+            Duration::from_secs(12),
+            backoff(1.0, Some(Duration::from_secs(4)))
+        );
+        assert_eq!(max_backoff, backoff(1.0, Some(Duration::from_secs(10))));
+        // A delay shorter than the initial backoff, e.g. requested by the server, doesn't lower the bounds
+        assert_eq!(
+            initial_backoff,
+            backoff(1.0, Some(Duration::from_millis(100)))
+        );
+    }
+
     const MAX_BACKOFF: Duration = Duration::from_secs(20);
 
     #[test]
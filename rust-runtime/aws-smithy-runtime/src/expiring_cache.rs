@@ -74,6 +74,11 @@ where
         future.await.map(|(value, _expiry)| value.clone())
     }
 
+    /// Replaces the cached value, whether or not the current value has expired.
+    pub async fn set(&self, value: T, expiry: SystemTime) {
+        *self.value.write().await = OnceCell::new_with(Some((value, expiry)));
+    }
+
     /// If the value is expired, clears the cache. Otherwise, yields the current value.
     pub async fn yield_or_clear_if_expired(&self, now: SystemTime) -> Option<T> {
         // Short-circuit if the value is not expired
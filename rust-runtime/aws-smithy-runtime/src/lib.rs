@@ -12,6 +12,9 @@
 //!
 //! - `http-auth`: Enables auth scheme and identity resolver implementations for HTTP API Key,
 //!   Basic Auth, Bearer Token, and Digest Auth.
+//! - `connector-hyper-1-x`: Enables the [hyper 1.x HTTP client](crate::client::http::hyper_1).
+//! - `tls-rustls-ring`, `tls-rustls-aws-lc`, `tls-rustls-aws-lc-fips`: Enable the default rustls
+//!   HTTPS connector of the hyper 1.x HTTP client, using the given cryptography implementation.
 //! - `test-util`: Enables utilities for unit tests. DO NOT ENABLE IN PRODUCTION.
 
 #![warn(
@@ -0,0 +1,172 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![cfg(all(feature = "client", feature = "test-util", feature = "tls-rustls"))]
+
+use aws_smithy_async::time::SystemTimeSource;
+use aws_smithy_runtime::client::http::tls::{ClientIdentity, TlsContext, TrustStore};
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorSettings, SharedHttpClient,
+};
+use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponentsBuilder;
+use aws_smithy_types::byte_stream::ByteStream;
+use hyper_0_14::service::service_fn;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const CA: &[u8] = include_bytes!("../test-data/tls/ca.crt");
+const SERVER_CERT: &[u8] = include_bytes!("../test-data/tls/server.crt");
+const SERVER_KEY: &[u8] = include_bytes!("../test-data/tls/server.key");
+const CLIENT_A_CERT: &[u8] = include_bytes!("../test-data/tls/client-a.crt");
+const CLIENT_A_KEY: &[u8] = include_bytes!("../test-data/tls/client-a.key");
+const CLIENT_B_CERT: &[u8] = include_bytes!("../test-data/tls/client-b.crt");
+const CLIENT_B_KEY: &[u8] = include_bytes!("../test-data/tls/client-b.key");
+
+fn pem_items(pem: &[u8]) -> Vec<Vec<u8>> {
+    rustls_pemfile::read_all(&mut std::io::Cursor::new(pem))
+        .unwrap()
+        .into_iter()
+        .map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) | rustls_pemfile::Item::PKCS8Key(der) => der,
+            _ => panic!("unexpected PEM item"),
+        })
+        .collect()
+}
+
+/// Serves HTTPS for `localhost`, requiring a client certificate signed by the test CA.
+/// Each response contains the name of the client certificate that was presented.
+async fn server() -> u16 {
+    let mut client_roots = rustls::RootCertStore::empty();
+    client_roots
+        .add(&rustls::Certificate(pem_items(CA).remove(0)))
+        .unwrap();
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(
+            rustls::server::AllowAnyAuthenticatedClient::new(client_roots).boxed(),
+        )
+        .with_single_cert(
+            vec![rustls::Certificate(pem_items(SERVER_CERT).remove(0))],
+            rustls::PrivateKey(pem_items(SERVER_KEY).remove(0)),
+        )
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                // Handshakes without a client certificate are expected to fail
+                let Ok(stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let peer = stream.get_ref().1.peer_certificates().unwrap()[0].0.clone();
+                let name = if peer == pem_items(CLIENT_A_CERT)[0] {
+                    "client-a"
+                } else {
+                    "client-b"
+                };
+                let service = service_fn(move |_| async move {
+                    Ok::<_, Infallible>(http_02x::Response::new(hyper_0_14::Body::from(name)))
+                });
+                let _ = hyper_0_14::server::conn::Http::new()
+                    .serve_connection(stream, service)
+                    .await;
+            });
+        }
+    });
+    port
+}
+
+fn tls_context() -> TlsContext {
+    TlsContext::builder()
+        .trust_store(TrustStore::empty().with_pem_certificates(CA).unwrap())
+        .client_identity(ClientIdentity::from_pem(CLIENT_A_CERT, CLIENT_A_KEY).unwrap())
+        .build()
+}
+
+fn connector(client: SharedHttpClient) -> impl HttpConnector {
+    let components = RuntimeComponentsBuilder::for_tests()
+        .with_time_source(Some(SystemTimeSource::new()))
+        .build()
+        .unwrap();
+    client.http_connector(&HttpConnectorSettings::builder().build(), &components)
+}
+
+async fn presented_identity(connector: &impl HttpConnector, port: u16) -> Option<String> {
+    let request = HttpRequest::get(format!("https://localhost:{port}/")).unwrap();
+    let response = connector.call(request).await.ok()?;
+    let body = ByteStream::new(response.into_body()).collect().await;
+    Some(String::from_utf8(body.unwrap().to_vec()).unwrap())
+}
+
+/// Presents client-a, then client-b after the identity is updated, and then no certificate.
+async fn identity_is_updated_without_rebuilding(client: SharedHttpClient, tls_context: TlsContext) {
+    let port = server().await;
+    let connector = connector(client);
+    assert_eq!(
+        Some("client-a"),
+        presented_identity(&connector, port).await.as_deref()
+    );
+
+    tls_context.update_client_identity(Some(
+        ClientIdentity::from_pem(CLIENT_B_CERT, CLIENT_B_KEY).unwrap(),
+    ));
+    assert_eq!(
+        Some("client-b"),
+        presented_identity(&connector, port).await.as_deref()
+    );
+
+    tls_context.update_client_identity(None);
+    assert_eq!(None, presented_identity(&connector, port).await);
+}
+
+#[tokio::test]
+async fn hyper_014_mutual_tls() {
+    use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
+
+    let tls_context = tls_context();
+    // Don't keep idle connections, so that each request makes a new TLS handshake
+    let client = HyperClientBuilder::new()
+        .pool_max_idle_per_host(0)
+        .tls_context(tls_context.clone())
+        .build_https();
+    identity_is_updated_without_rebuilding(client, tls_context).await;
+}
+
+#[cfg(feature = "tls-rustls-ring")]
+#[tokio::test]
+async fn hyper_1_mutual_tls() {
+    use aws_smithy_runtime::client::http::hyper_1::{CryptoMode, HyperClientBuilder};
+
+    let tls_context = tls_context();
+    let client = HyperClientBuilder::new()
+        .pool_max_idle_per_host(0)
+        .crypto_mode(CryptoMode::Ring)
+        .tls_context(tls_context.clone())
+        .build_https();
+    identity_is_updated_without_rebuilding(client, tls_context).await;
+}
+
+#[tokio::test]
+async fn servers_signed_by_untrusted_authorities_are_rejected() {
+    use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
+
+    let port = server().await;
+    let tls_context = TlsContext::builder()
+        .trust_store(TrustStore::empty())
+        .client_identity(ClientIdentity::from_pem(CLIENT_A_CERT, CLIENT_A_KEY).unwrap())
+        .build();
+    let connector = connector(
+        HyperClientBuilder::new()
+            .tls_context(tls_context)
+            .build_https(),
+    );
+    assert_eq!(None, presented_identity(&connector, port).await);
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#![cfg(all(
+    feature = "client",
+    feature = "test-util",
+    feature = "connector-hyper-0-14-x"
+))]
+
+use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
+use aws_smithy_runtime::client::http::pool::ConnectionPoolMonitor;
+use aws_smithy_runtime_api::client::http::{HttpVersion, SharedHttpClient};
+use aws_smithy_types::byte_stream::ByteStream;
+use hyper_0_14::service::{make_service_fn, service_fn};
+use std::net::SocketAddr;
+use tokio::sync::oneshot;
+
+#[macro_use]
+mod stalled_stream_common;
+use stalled_stream_common::*;
+
+/// Scenario: Two downloads share an HTTP/2 connection, and the server stalls one of them.
+/// Expected: The stalled download MUST timeout, and the other download MUST NOT.
+#[tokio::test]
+async fn multiplexed_download_stalls() {
+    let _logs = show_test_logs();
+
+    let (time, sleep) = tick_advance_time_and_sleep();
+    let addr = h2_server().await;
+    let monitor = ConnectionPoolMonitor::new();
+    let client = HyperClientBuilder::new()
+        .pool_monitor(monitor.clone())
+        .build(hyper_0_14::client::HttpConnector::new());
+
+    let stalled = operation(&client, &format!("http://{addr}/stall"), &time, &sleep)
+        .invoke(())
+        .await
+        .expect("initial success");
+    let (first_chunk_sender, first_chunk) = oneshot::channel();
+    let stalled = tokio::spawn(async move {
+        let mut body = stalled;
+        let chunk = body.data().await.expect("first chunk")?;
+        first_chunk_sender.send(chunk).unwrap();
+        ByteStream::new(body)
+            .collect()
+            .await
+            .map_err(BoxError::from)
+    });
+    assert_eq!(NEAT_DATA, first_chunk.await.unwrap());
+
+    // Downloads on the same connection keep working while the other stream is stalled
+    for _ in 0..3 {
+        let body = operation(&client, &format!("http://{addr}/ok"), &time, &sleep)
+            .invoke(())
+            .await
+            .expect("initial success");
+        let data = ByteStream::new(body)
+            .collect()
+            .await
+            .expect("response MUST NOT timeout");
+        assert_eq!(NEAT_DATA, data.into_bytes());
+    }
+
+    for _ in 0..10 {
+        tick!(time, Duration::from_secs(1));
+    }
+    let err = stalled.await.unwrap().expect_err("should have timed out");
+    assert_str_contains!(
+        DisplayErrorContext(err.as_ref()).to_string(),
+        "minimum throughput was specified at 1 B/s, but throughput of 0 B/s was observed"
+    );
+    assert_eq!(1, monitor.stats().connections_opened());
+}
+
+/// Serves HTTP/2 prior knowledge. Requests to `/stall` receive one chunk of data, and then
+/// the response stalls forever. Requests to any other path receive a complete response.
+async fn h2_server() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(
+            |request: http_02x::Request<hyper_0_14::Body>| async move {
+                let body = if request.uri().path().starts_with("/stall") {
+                    let (mut sender, body) = hyper_0_14::Body::channel();
+                    tokio::spawn(async move {
+                        sender.send_data(NEAT_DATA).await.unwrap();
+                        // Keep the stream open without sending anything else
+                        std::future::pending::<()>().await;
+                        drop(sender);
+                    });
+                    body
+                } else {
+                    hyper_0_14::Body::from(NEAT_DATA)
+                };
+                Ok::<_, Infallible>(http_02x::Response::new(body))
+            },
+        ))
+    });
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = hyper_0_14::Server::from_tcp(listener)
+        .unwrap()
+        .http2_only(true)
+        .serve(make_service);
+    tokio::spawn(server);
+    addr
+}
+
+fn operation(
+    client: &SharedHttpClient,
+    uri: &str,
+    time: &TickAdvanceTime,
+    sleep: &TickAdvanceSleep,
+) -> Operation<(), SdkBody, Infallible> {
+    #[derive(Debug)]
+    struct Deserializer;
+    impl DeserializeResponse for Deserializer {
+        fn deserialize_streaming(
+            &self,
+            response: &mut HttpResponse,
+        ) -> Option<Result<Output, OrchestratorError<Error>>> {
+            let body = mem::replace(response.body_mut(), SdkBody::taken());
+            Some(Ok(Output::erase(body)))
+        }
+
+        fn deserialize_nonstreaming(
+            &self,
+            _: &HttpResponse,
+        ) -> Result<Output, OrchestratorError<Error>> {
+            unreachable!()
+        }
+    }
+
+    Operation::builder()
+        .service_name("test")
+        .operation_name("test")
+        .http_client(client.clone())
+        .http_version(HttpVersion::Http2PriorKnowledge)
+        .endpoint_url(uri)
+        .no_auth()
+        .no_retry()
+        .timeout_config(TimeoutConfig::disabled())
+        .serializer(|_body: ()| Ok(HttpRequest::new(SdkBody::empty())))
+        .deserializer_impl(Deserializer)
+        .stalled_stream_protection(
+            StalledStreamProtectionConfig::enabled()
+                .grace_period(Duration::from_secs(5))
+                .build(),
+        )
+        .interceptor(StalledStreamProtectionInterceptor::default())
+        .sleep_impl(sleep.clone())
+        .time_source(time.clone())
+        .build()
+}
@@ -703,6 +703,17 @@ impl ConfigBag {
         self.with_fn(name, |_| {})
     }
 
+    /// Returns a bag that shares the frozen layers of this bag, with an empty interceptor state.
+    ///
+    /// This allows two attempts of the same operation to run at the same time, each with interceptor
+    /// state of its own.
+    pub fn without_interceptor_state(&self) -> ConfigBag {
+        ConfigBag {
+            interceptor_state: ConfigBag::base().interceptor_state,
+            tail: self.tail.clone(),
+        }
+    }
+
     /// Return a value (or values) of type `T` depending on how it has been stored in a `ConfigBag`
     ///
     /// It flexibly chooses to return a single value vs. an iterator of values depending on how
@@ -861,6 +872,29 @@ mod test {
         assert_eq!(open_bag.layers().count(), 4);
     }
 
+    #[test]
+    fn without_interceptor_state() {
+        #[derive(Debug)]
+        struct Config;
+        impl Storable for Config {
+            type Storer = StoreReplace<Self>;
+        }
+        #[derive(Debug)]
+        struct Attempt;
+        impl Storable for Attempt {
+            type Storer = StoreReplace<Self>;
+        }
+
+        let mut layer = Layer::new("config");
+        layer.store_put(Config);
+        let mut bag = ConfigBag::of_layers(vec![layer]);
+        bag.interceptor_state().store_put(Attempt);
+
+        let detached = bag.without_interceptor_state();
+        assert!(detached.load::<Config>().is_some());
+        assert!(detached.load::<Attempt>().is_none());
+    }
+
     #[test]
     fn store_append() {
         let mut layer = Layer::new("test");
@@ -30,6 +30,7 @@ pub mod event_stream;
 pub mod primitive;
 pub mod retry;
 pub mod timeout;
+pub mod trace_context;
 
 /// Utilities for type erasure.
 pub mod type_erasure;
@@ -39,6 +39,12 @@ pub enum ErrorKind {
 
     /// Doesn't count against any budgets. This could be something like a 401 challenge in Http.
     ClientError,
+
+    /// The request was not sent because a circuit breaker observed too many failures from the
+    /// service and is failing fast until it recovers.
+    ///
+    /// Requests that fail with this kind of error should not be retried.
+    CircuitBreakerOpen,
 }
 
 impl fmt::Display for ErrorKind {
@@ -48,6 +54,7 @@ impl fmt::Display for ErrorKind {
             Self::ThrottlingError => write!(f, "throttling error"),
             Self::ServerError => write!(f, "server error"),
             Self::ClientError => write!(f, "client error"),
+            Self::CircuitBreakerOpen => write!(f, "circuit breaker open"),
         }
     }
 }
@@ -148,6 +155,36 @@ impl fmt::Display for RetryModeParseError {
 
 impl std::error::Error for RetryModeParseError {}
 
+/// The shape of the delay between retry attempts.
+///
+/// Except for [`BackoffStrategy::Fixed`], the delay grows exponentially from the
+/// [initial backoff](RetryConfig::initial_backoff) with each attempt, up to the
+/// [max backoff](RetryConfig::max_backoff). See
+/// [Exponential Backoff And Jitter](https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/)
+/// for a comparison of the jitter strategies.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum BackoffStrategy {
+    /// Waits for a random duration between zero and the exponential backoff.
+    ///
+    /// This is the default.
+    #[default]
+    FullJitter,
+
+    /// Waits for half of the exponential backoff, plus a random duration of up to the other half.
+    EqualJitter,
+
+    /// Waits for a random duration between the initial backoff and three times the previous
+    /// delay.
+    DecorrelatedJitter,
+
+    /// Waits for the durations in the given schedule, in order, without any jitter.
+    ///
+    /// Once the schedule is exhausted, its last duration is reused for the remaining attempts.
+    /// An empty schedule retries immediately. Delays are still capped at the max backoff.
+    Fixed(Vec<Duration>),
+}
+
 /// Builder for [`RetryConfig`].
 #[non_exhaustive]
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -157,6 +194,7 @@ pub struct RetryConfigBuilder {
     initial_backoff: Option<Duration>,
     max_backoff: Option<Duration>,
     reconnect_mode: Option<ReconnectMode>,
+    backoff_strategy: Option<BackoffStrategy>,
 }
 
 impl RetryConfigBuilder {
@@ -237,6 +275,18 @@ impl RetryConfigBuilder {
         self
     }
 
+    /// Sets the [`BackoffStrategy`] used to calculate the delay between attempts.
+    pub fn set_backoff_strategy(&mut self, backoff_strategy: Option<BackoffStrategy>) -> &mut Self {
+        self.backoff_strategy = backoff_strategy;
+        self
+    }
+
+    /// Sets the [`BackoffStrategy`] used to calculate the delay between attempts.
+    pub fn backoff_strategy(mut self, backoff_strategy: BackoffStrategy) -> Self {
+        self.set_backoff_strategy(Some(backoff_strategy));
+        self
+    }
+
     /// Merge two builders together. Values from `other` will only be used as a fallback for values
     /// from `self` Useful for merging configs from different sources together when you want to
     /// handle "precedence" per value instead of at the config level
@@ -260,6 +310,7 @@ impl RetryConfigBuilder {
             initial_backoff: self.initial_backoff.or(other.initial_backoff),
             max_backoff: self.max_backoff.or(other.max_backoff),
             reconnect_mode: self.reconnect_mode.or(other.reconnect_mode),
+            backoff_strategy: self.backoff_strategy.or(other.backoff_strategy),
         }
     }
 
@@ -276,6 +327,7 @@ impl RetryConfigBuilder {
                 .unwrap_or(ReconnectMode::ReconnectOnTransientError),
             max_backoff: self.max_backoff.unwrap_or_else(|| Duration::from_secs(20)),
             use_static_exponential_base: false,
+            backoff_strategy: self.backoff_strategy.unwrap_or_default(),
         }
     }
 }
@@ -290,6 +342,7 @@ pub struct RetryConfig {
     max_backoff: Duration,
     reconnect_mode: ReconnectMode,
     use_static_exponential_base: bool,
+    backoff_strategy: BackoffStrategy,
 }
 
 impl Storable for RetryConfig {
@@ -326,6 +379,7 @@ impl RetryConfig {
             reconnect_mode: ReconnectMode::ReconnectOnTransientError,
             max_backoff: Duration::from_secs(20),
             use_static_exponential_base: false,
+            backoff_strategy: BackoffStrategy::FullJitter,
         }
     }
 
@@ -338,6 +392,7 @@ impl RetryConfig {
             reconnect_mode: ReconnectMode::ReconnectOnTransientError,
             max_backoff: Duration::from_secs(20),
             use_static_exponential_base: false,
+            backoff_strategy: BackoffStrategy::FullJitter,
         }
     }
 
@@ -399,6 +454,14 @@ impl RetryConfig {
         self
     }
 
+    /// Set the [`BackoffStrategy`] used to calculate the delay between attempts.
+    ///
+    /// Defaults to [`BackoffStrategy::FullJitter`].
+    pub fn with_backoff_strategy(mut self, backoff_strategy: BackoffStrategy) -> Self {
+        self.backoff_strategy = backoff_strategy;
+        self
+    }
+
     /// Hint to the retry strategy whether to use a static exponential base.
     ///
     /// When a retry strategy uses exponential backoff, it calculates a random base. This causes the
@@ -438,6 +501,11 @@ impl RetryConfig {
         self.max_backoff
     }
 
+    /// Returns the [`BackoffStrategy`].
+    pub fn backoff_strategy(&self) -> &BackoffStrategy {
+        &self.backoff_strategy
+    }
+
     /// Returns true if retry is enabled with this config
     pub fn has_retry(&self) -> bool {
         self.max_attempts > 1
@@ -454,7 +522,7 @@ impl RetryConfig {
 
 #[cfg(test)]
 mod tests {
-    use crate::retry::{RetryConfigBuilder, RetryMode};
+    use crate::retry::{BackoffStrategy, RetryConfig, RetryConfigBuilder, RetryMode};
     use std::str::FromStr;
 
     #[test]
@@ -471,6 +539,26 @@ mod tests {
         assert_eq!(retry_config.mode, RetryMode::Adaptive);
     }
 
+    #[test]
+    fn retry_config_builder_merges_backoff_strategy() {
+        let retry_config = RetryConfigBuilder::new()
+            .take_unset_from(
+                RetryConfigBuilder::new().backoff_strategy(BackoffStrategy::EqualJitter),
+            )
+            .build();
+        assert_eq!(
+            BackoffStrategy::EqualJitter,
+            *retry_config.backoff_strategy()
+        );
+
+        let retry_config = RetryConfigBuilder::new().build();
+        assert_eq!(RetryConfig::standard(), retry_config);
+        assert_eq!(
+            BackoffStrategy::FullJitter,
+            *retry_config.backoff_strategy()
+        );
+    }
+
     #[test]
     fn retry_mode_from_str_parses_valid_strings_regardless_of_casing() {
         assert_eq!(
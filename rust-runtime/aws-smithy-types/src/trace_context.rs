@@ -0,0 +1,559 @@
+/*
+ * Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Distributed trace context, and its propagation in W3C Trace Context and B3 headers.
+//!
+//! A [`TraceContext`] identifies a span of a distributed trace. Clients propagate it to the
+//! services they call in request headers, and services continue the trace from the context they
+//! receive, so that a chain of calls across services shows up as a single trace.
+//!
+//! The context of the work currently being done is set with [`TraceContext::scope`] or
+//! [`TraceContext::in_scope`], and read with [`TraceContext::current`]. The current context is
+//! thread-local, so it doesn't follow work moved to another task, for example with `tokio::spawn`.
+
+use pin_project_lite::pin_project;
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const TRACEPARENT: &str = "traceparent";
+const TRACESTATE: &str = "tracestate";
+const B3: &str = "b3";
+const B3_TRACE_ID: &str = "x-b3-traceid";
+const B3_SPAN_ID: &str = "x-b3-spanid";
+const B3_SAMPLED: &str = "x-b3-sampled";
+const B3_FLAGS: &str = "x-b3-flags";
+/// The maximum number of list members of a W3C `tracestate`.
+const MAX_TRACE_STATE_MEMBERS: usize = 32;
+
+thread_local! {
+    static CURRENT: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+/// Identifies a span of a distributed trace.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: u128,
+    span_id: u64,
+    sampled: bool,
+    trace_state: Option<String>,
+}
+
+impl fmt::Debug for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceContext")
+            .field("trace_id", &format_args!("{:032x}", self.trace_id))
+            .field("span_id", &format_args!("{:016x}", self.span_id))
+            .field("sampled", &self.sampled)
+            .field("trace_state", &self.trace_state)
+            .finish()
+    }
+}
+
+impl TraceContext {
+    /// Creates a new sampled trace context for the given trace and span.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `trace_id` or `span_id` is zero, since those are invalid IDs.
+    pub fn new(trace_id: u128, span_id: u64) -> Self {
+        assert!(trace_id != 0, "trace IDs cannot be zero");
+        assert!(span_id != 0, "span IDs cannot be zero");
+        Self {
+            trace_id,
+            span_id,
+            sampled: true,
+            trace_state: None,
+        }
+    }
+
+    /// Sets whether the trace is sampled, that is, whether the caller may have recorded it.
+    pub fn sampled(mut self, sampled: bool) -> Self {
+        self.sampled = sampled;
+        self
+    }
+
+    /// Sets the vendor-specific W3C `tracestate` that is propagated along with the trace.
+    ///
+    /// The value isn't validated here. A `tracestate` that isn't a valid header value fails the
+    /// request that propagates it.
+    pub fn trace_state(mut self, trace_state: impl Into<String>) -> Self {
+        self.trace_state = Some(trace_state.into());
+        self
+    }
+
+    /// Returns the trace ID.
+    pub fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// Returns the span ID.
+    pub fn span_id(&self) -> u64 {
+        self.span_id
+    }
+
+    /// Returns `true` if the trace is sampled.
+    pub fn is_sampled(&self) -> bool {
+        self.sampled
+    }
+
+    /// Returns the vendor-specific W3C `tracestate`, if any.
+    pub fn get_trace_state(&self) -> Option<&str> {
+        self.trace_state.as_deref()
+    }
+
+    /// Returns the context of a new span of the same trace, a child of this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `span_id` is zero.
+    pub fn child(&self, span_id: u64) -> Self {
+        assert!(span_id != 0, "span IDs cannot be zero");
+        Self {
+            span_id,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the trace context of the work currently being done, if any.
+    ///
+    /// The current context is thread-local. It is set while a future wrapped with
+    /// [`scope`](Self::scope) is polled, so it isn't set in tasks the future spawns, for example
+    /// with `tokio::spawn`, unless their futures are wrapped with `scope` as well.
+    pub fn current() -> Option<TraceContext> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Calls `f` with this context as the [current](Self::current) one.
+    pub fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<TraceContext>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(self.clone()))));
+        f()
+    }
+
+    /// Polls `future` with this context as the [current](Self::current) one.
+    pub fn scope<F: Future>(self, future: F) -> WithTraceContext<F> {
+        WithTraceContext {
+            future,
+            context: self,
+        }
+    }
+}
+
+pin_project! {
+    /// A future polled with a [`TraceContext`] as the current one. Returned by [`TraceContext::scope`].
+    #[derive(Debug)]
+    pub struct WithTraceContext<F> {
+        #[pin]
+        future: F,
+        context: TraceContext,
+    }
+}
+
+impl<F: Future> Future for WithTraceContext<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let future = this.future;
+        this.context.in_scope(|| future.poll(cx))
+    }
+}
+
+/// A format in which trace context is propagated in HTTP headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Propagation {
+    /// The [W3C Trace Context](https://www.w3.org/TR/trace-context/) `traceparent` and `tracestate` headers.
+    W3c,
+    /// The [B3](https://github.com/openzipkin/b3-propagation) single `b3` header.
+    B3Single,
+    /// The [B3](https://github.com/openzipkin/b3-propagation) multiple `X-B3-*` headers.
+    B3Multi,
+}
+
+impl Propagation {
+    /// Returns the names of the headers of this format, in lowercase.
+    pub fn header_names(&self) -> &'static [&'static str] {
+        match self {
+            Self::W3c => &[TRACEPARENT, TRACESTATE],
+            Self::B3Single => &[B3],
+            Self::B3Multi => &[B3_TRACE_ID, B3_SPAN_ID, B3_SAMPLED, B3_FLAGS],
+        }
+    }
+
+    /// Calls `set_header` with the name and value of each header propagating `context`.
+    pub fn inject(&self, context: &TraceContext, mut set_header: impl FnMut(&'static str, String)) {
+        let TraceContext {
+            trace_id,
+            span_id,
+            sampled,
+            trace_state,
+        } = context;
+        let sampled = if *sampled { "1" } else { "0" };
+        match self {
+            Self::W3c => {
+                let flags = if context.sampled { "01" } else { "00" };
+                set_header(
+                    TRACEPARENT,
+                    format!("00-{trace_id:032x}-{span_id:016x}-{flags}"),
+                );
+                if let Some(trace_state) = trace_state {
+                    set_header(TRACESTATE, trace_state.clone());
+                }
+            }
+            Self::B3Single => {
+                set_header(B3, format!("{trace_id:032x}-{span_id:016x}-{sampled}"));
+            }
+            Self::B3Multi => {
+                set_header(B3_TRACE_ID, format!("{trace_id:032x}"));
+                set_header(B3_SPAN_ID, format!("{span_id:016x}"));
+                set_header(B3_SAMPLED, sampled.to_owned());
+            }
+        }
+    }
+
+    /// Extracts the trace context propagated in the headers returned by `get_header`, which is
+    /// called with lowercase header names.
+    ///
+    /// Returns `None` if the headers are missing or invalid. An invalid W3C `tracestate` is
+    /// discarded, and the rest of the context is still extracted.
+    pub fn extract<'a>(
+        &self,
+        get_header: impl Fn(&str) -> Option<&'a str>,
+    ) -> Option<TraceContext> {
+        match self {
+            Self::W3c => {
+                let mut context = parse_traceparent(get_header(TRACEPARENT)?.trim())?;
+                context.trace_state = get_header(TRACESTATE)
+                    .map(str::trim)
+                    .filter(|state| !state.is_empty() && is_valid_trace_state(state))
+                    .map(ToOwned::to_owned);
+                Some(context)
+            }
+            Self::B3Single => parse_b3(get_header(B3)?.trim()),
+            Self::B3Multi => {
+                let trace_id = parse_b3_trace_id(get_header(B3_TRACE_ID)?.trim())?;
+                let span_id = parse_id(get_header(B3_SPAN_ID)?.trim(), 16)?;
+                let debug = get_header(B3_FLAGS).map(str::trim) == Some("1");
+                let sampled = match get_header(B3_SAMPLED).map(str::trim) {
+                    Some("0") | Some("false") => debug,
+                    Some("1") | Some("true") | None => true,
+                    Some(_) => return None,
+                };
+                Some(TraceContext::new(trace_id, span_id).sampled(sampled))
+            }
+        }
+    }
+}
+
+/// Parses `00-<trace ID>-<span ID>-<flags>`, allowing the additional fields of later versions.
+fn parse_traceparent(traceparent: &str) -> Option<TraceContext> {
+    let mut fields = traceparent.split('-');
+    let version = fields.next()?;
+    let trace_id = fields.next()?;
+    let span_id = fields.next()?;
+    let flags = fields.next()?;
+    if version.len() != 2 || !version.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    match version {
+        "ff" => return None,
+        "00" if fields.next().is_some() => return None,
+        _ => {}
+    }
+    let trace_id = parse_id(trace_id, 32)?;
+    let span_id = parse_id(span_id, 16)?;
+    let flags = u8::from_str_radix(flags, 16)
+        .ok()
+        .filter(|_| flags.len() == 2)?;
+    Some(TraceContext::new(trace_id, span_id).sampled(flags & 1 == 1))
+}
+
+/// Parses `<trace ID>-<span ID>[-<sampling state>[-<parent span ID>]]`.
+///
+/// A header with only a sampling state doesn't carry a trace context, so returns `None`.
+fn parse_b3(b3: &str) -> Option<TraceContext> {
+    let mut fields = b3.split('-');
+    let trace_id = parse_b3_trace_id(fields.next()?)?;
+    let span_id = parse_id(fields.next()?, 16)?;
+    let sampled = match fields.next() {
+        Some("0") => false,
+        Some("1") | Some("d") | None => true,
+        Some(_) => return None,
+    };
+    if let Some(parent_span_id) = fields.next() {
+        parse_id::<u64>(parent_span_id, 16)?;
+    }
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(TraceContext::new(trace_id, span_id).sampled(sampled))
+}
+
+/// Checks that `trace_state` is a list of up to 32 `key=value` members, as the W3C Trace Context
+/// specification requires, so that an invalid `tracestate` is never propagated further.
+fn is_valid_trace_state(trace_state: &str) -> bool {
+    let mut members = 0;
+    for member in trace_state.split(',') {
+        let member = member.trim_matches(|c| c == ' ' || c == '\t');
+        if member.is_empty() {
+            continue;
+        }
+        members += 1;
+        let Some((key, value)) = member.split_once('=') else {
+            return false;
+        };
+        let valid_key = !key.is_empty()
+            && key.len() <= 256
+            && key
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'*' | b'/' | b'@'));
+        let valid_value = !value.is_empty()
+            && value.len() <= 256
+            && !value.ends_with(' ')
+            && value
+                .bytes()
+                .all(|b| matches!(b, b' '..=b'~') && b != b',' && b != b'=');
+        if !valid_key || !valid_value {
+            return false;
+        }
+    }
+    members <= MAX_TRACE_STATE_MEMBERS
+}
+
+/// B3 trace IDs are either 64 or 128 bits long.
+fn parse_b3_trace_id(trace_id: &str) -> Option<u128> {
+    match trace_id.len() {
+        16 => parse_id(trace_id, 16),
+        _ => parse_id(trace_id, 32),
+    }
+}
+
+/// Parses a non-zero ID of exactly `len` lowercase hex digits.
+fn parse_id<T: TryFrom<u128>>(id: &str, len: usize) -> Option<T> {
+    if id.len() != len || !id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return None;
+    }
+    u128::from_str_radix(id, 16)
+        .ok()
+        .filter(|id| *id != 0)
+        .and_then(|id| T::try_from(id).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    const TRACE_ID: u128 = 0x4bf92f3577b34da6a3ce929d0e0e4736;
+    const SPAN_ID: u64 = 0x00f067aa0ba902b7;
+
+    fn inject(propagation: Propagation, context: &TraceContext) -> HashMap<&'static str, String> {
+        let mut headers = HashMap::new();
+        propagation.inject(context, |name, value| {
+            headers.insert(name, value);
+        });
+        headers
+    }
+
+    fn extract(propagation: Propagation, headers: &[(&str, &str)]) -> Option<TraceContext> {
+        propagation.extract(|name| {
+            headers
+                .iter()
+                .find(|(header, _)| *header == name)
+                .map(|(_, value)| *value)
+        })
+    }
+
+    #[test]
+    fn w3c_round_trip() {
+        let context = TraceContext::new(TRACE_ID, SPAN_ID).trace_state("vendor=value");
+        let headers = inject(Propagation::W3c, &context);
+        assert_eq!(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            headers[TRACEPARENT]
+        );
+        assert_eq!("vendor=value", headers[TRACESTATE]);
+
+        let extracted = extract(
+            Propagation::W3c,
+            &[
+                (TRACEPARENT, &headers[TRACEPARENT]),
+                (TRACESTATE, &headers[TRACESTATE]),
+            ],
+        );
+        assert_eq!(Some(context), extracted);
+    }
+
+    #[test]
+    fn w3c_invalid_trace_state_is_discarded() {
+        const TRACEPARENT_VALUE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let trace_state = |trace_state: &str| {
+            extract(
+                Propagation::W3c,
+                &[(TRACEPARENT, TRACEPARENT_VALUE), (TRACESTATE, trace_state)],
+            )
+            .unwrap()
+            .get_trace_state()
+            .map(ToOwned::to_owned)
+        };
+        assert_eq!(
+            Some("a=1, b@vendor=x y,,c=3"),
+            trace_state("a=1, b@vendor=x y,,c=3").as_deref()
+        );
+        assert_eq!(None, trace_state("a=1\r\nx-injected: value"));
+        assert_eq!(None, trace_state("A=1"));
+        assert_eq!(None, trace_state("novalue"));
+        assert_eq!(None, trace_state("a=b=c"));
+        let too_many = (0..33).map(|i| format!("k{i}=v")).collect::<Vec<_>>();
+        assert_eq!(None, trace_state(&too_many.join(",")));
+    }
+
+    #[test]
+    fn w3c_unsampled_and_future_versions() {
+        let context = extract(
+            Propagation::W3c,
+            &[(
+                TRACEPARENT,
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+            )],
+        )
+        .unwrap();
+        assert!(!context.is_sampled());
+        assert_eq!(None, context.get_trace_state());
+
+        let context = extract(
+            Propagation::W3c,
+            &[(
+                TRACEPARENT,
+                "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            )],
+        )
+        .unwrap();
+        assert_eq!(TRACE_ID, context.trace_id());
+    }
+
+    #[test]
+    fn invalid_w3c_headers_are_ignored() {
+        for traceparent in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(
+                None,
+                extract(Propagation::W3c, &[(TRACEPARENT, traceparent)]),
+                "{traceparent}"
+            );
+        }
+    }
+
+    #[test]
+    fn b3_single_round_trip() {
+        let context = TraceContext::new(TRACE_ID, SPAN_ID).sampled(false);
+        let headers = inject(Propagation::B3Single, &context);
+        assert_eq!(
+            "4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-0",
+            headers[B3]
+        );
+        assert_eq!(
+            Some(context),
+            extract(Propagation::B3Single, &[(B3, &headers[B3])])
+        );
+    }
+
+    #[test]
+    fn b3_single_variants() {
+        let context = extract(
+            Propagation::B3Single,
+            &[(B3, "a3ce929d0e0e4736-00f067aa0ba902b7-d-05e3ac9a4f6e3b90")],
+        )
+        .unwrap();
+        assert_eq!(0xa3ce929d0e0e4736, context.trace_id());
+        assert!(context.is_sampled());
+
+        assert_eq!(None, extract(Propagation::B3Single, &[(B3, "0")]));
+        assert_eq!(
+            None,
+            extract(
+                Propagation::B3Single,
+                &[(B3, "a3ce929d0e0e4736-00f067aa0ba902b7-x")]
+            )
+        );
+    }
+
+    #[test]
+    fn b3_multi_round_trip() {
+        let context = TraceContext::new(TRACE_ID, SPAN_ID);
+        let headers = inject(Propagation::B3Multi, &context);
+        assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", headers[B3_TRACE_ID]);
+        assert_eq!("00f067aa0ba902b7", headers[B3_SPAN_ID]);
+        assert_eq!("1", headers[B3_SAMPLED]);
+
+        let extracted = extract(
+            Propagation::B3Multi,
+            &[
+                (B3_TRACE_ID, &headers[B3_TRACE_ID]),
+                (B3_SPAN_ID, &headers[B3_SPAN_ID]),
+                (B3_SAMPLED, "0"),
+                (B3_FLAGS, "1"),
+            ],
+        );
+        assert_eq!(Some(context), extracted);
+    }
+
+    #[test]
+    fn child_keeps_the_trace() {
+        let context = TraceContext::new(TRACE_ID, SPAN_ID)
+            .sampled(false)
+            .trace_state("a=b");
+        let child = context.child(7);
+        assert_eq!(TRACE_ID, child.trace_id());
+        assert_eq!(7, child.span_id());
+        assert!(!child.is_sampled());
+        assert_eq!(Some("a=b"), child.get_trace_state());
+    }
+
+    #[test]
+    fn in_scope_sets_and_restores_the_current_context() {
+        let outer = TraceContext::new(TRACE_ID, 1);
+        let inner = TraceContext::new(TRACE_ID, 2);
+        assert_eq!(None, TraceContext::current());
+        outer.in_scope(|| {
+            assert_eq!(Some(&outer), TraceContext::current().as_ref());
+            inner.in_scope(|| assert_eq!(Some(&inner), TraceContext::current().as_ref()));
+            assert_eq!(Some(&outer), TraceContext::current().as_ref());
+        });
+        assert_eq!(None, TraceContext::current());
+    }
+
+    #[tokio::test]
+    async fn scope_sets_the_current_context_while_polling() {
+        let context = TraceContext::new(TRACE_ID, SPAN_ID);
+        let current = context
+            .clone()
+            .scope(async {
+                tokio::task::yield_now().await;
+                TraceContext::current()
+            })
+            .await;
+        assert_eq!(Some(context), current);
+        assert_eq!(None, TraceContext::current());
+    }
+}
@@ -5,6 +5,7 @@
 
 use aws_smithy_compression::body::compress::CompressedBody;
 use aws_smithy_compression::http::http_body_0_4_x::CompressRequest;
+use aws_smithy_compression::http::PendingRequestCompression;
 use aws_smithy_compression::{CompressionAlgorithm, CompressionOptions};
 use aws_smithy_runtime::client::sdk_feature::SmithySdkFeature;
 use aws_smithy_runtime_api::box_error::BoxError;
@@ -46,15 +47,6 @@ impl RuntimePlugin for RequestCompressionRuntimePlugin {
     }
 }
 
-#[derive(Debug)]
-struct RequestCompressionInterceptorState {
-    options: Option<CompressionOptions>,
-}
-
-impl Storable for RequestCompressionInterceptorState {
-    type Storer = StoreReplace<Self>;
-}
-
 /// Interceptor for Smithy [`@requestCompression`][spec].
 ///
 /// [spec]: https://smithy.io/2.0/spec/behavior-traits.html#requestcompression-trait
@@ -94,10 +86,16 @@ impl Intercept for RequestCompressionInterceptor {
             .with_min_compression_size_bytes(request_min_compression_size_bytes.0)?
             .with_enabled(!disable_request_compression.0);
 
+        // Don't wrap a body if compression is disabled.
+        if !options.is_enabled() {
+            tracing::trace!("request compression is disabled and will not be applied");
+            return Ok(());
+        }
+
         let mut layer = Layer::new("RequestCompressionInterceptor");
-        layer.store_put(RequestCompressionInterceptorState {
-            options: Some(options),
-        });
+        layer.store_put(PendingRequestCompression::new(move |request, cfg| {
+            compress_request(request, &options, cfg)
+        }));
 
         cfg.push_layer(layer);
 
@@ -110,47 +108,65 @@ impl Intercept for RequestCompressionInterceptor {
         _runtime_components: &RuntimeComponents,
         cfg: &mut ConfigBag,
     ) -> Result<(), BoxError> {
-        let state = cfg
-            .load::<RequestCompressionInterceptorState>()
-            .expect("set in `read_before_execution`");
+        // An interceptor that needs the compressed body, such as a request checksum interceptor,
+        // may have already taken the pending compression and applied or declined it.
+        if let Some(compression) = PendingRequestCompression::take(cfg) {
+            compression.apply(context.request_mut(), cfg)?;
+        }
+
+        Ok(())
+    }
+}
 
-        let options = state.options.clone().unwrap();
-        let request = context.request_mut();
+fn compress_request(
+    request: &mut HttpRequest,
+    options: &CompressionOptions,
+    cfg: &mut ConfigBag,
+) -> Result<(), BoxError> {
+    // Don't wrap a body that has already been aws-chunked encoded, since its chunk lengths
+    // would no longer match once the body is compressed.
+    if is_aws_chunked_encoded(request) {
+        tracing::trace!("request body is aws-chunked encoded and will not be compressed");
+        return Ok(());
+    }
 
-        // Don't wrap a body if compression is disabled.
-        if !options.is_enabled() {
-            tracing::trace!("request compression is disabled and will not be applied");
+    // Don't wrap a body if it's below the minimum size
+    //
+    // Because compressing small amounts of data can actually increase its size,
+    // we check to see if the data is big enough to make compression worthwhile.
+    let size_hint = http_body::Body::size_hint(request.body()).exact();
+    if let Some(known_size) = size_hint {
+        if known_size < options.min_compression_size_bytes() as u64 {
+            tracing::trace!(
+                min_compression_size_bytes = options.min_compression_size_bytes(),
+                known_size,
+                "request body is below minimum size and will not be compressed"
+            );
             return Ok(());
         }
+        tracing::trace!("compressing sized request body...");
+    } else {
+        tracing::trace!("compressing unsized request body...");
+    }
 
-        // Don't wrap a body if it's below the minimum size
-        //
-        // Because compressing small amounts of data can actually increase its size,
-        // we check to see if the data is big enough to make compression worthwhile.
-        let size_hint = http_body::Body::size_hint(request.body()).exact();
-        if let Some(known_size) = size_hint {
-            if known_size < options.min_compression_size_bytes() as u64 {
-                tracing::trace!(
-                    min_compression_size_bytes = options.min_compression_size_bytes(),
-                    known_size,
-                    "request body is below minimum size and will not be compressed"
-                );
-                return Ok(());
-            }
-            tracing::trace!("compressing sized request body...");
-        } else {
-            tracing::trace!("compressing unsized request body...");
-        }
+    wrap_request_body_in_compressed_body(
+        request,
+        CompressionAlgorithm::Gzip.into_impl_http_body_0_4_x(options),
+    )?;
+    cfg.interceptor_state()
+        .store_append::<SmithySdkFeature>(SmithySdkFeature::GzipRequestCompression);
 
-        wrap_request_body_in_compressed_body(
-            request,
-            CompressionAlgorithm::Gzip.into_impl_http_body_0_4_x(&options),
-        )?;
-        cfg.interceptor_state()
-            .store_append::<SmithySdkFeature>(SmithySdkFeature::GzipRequestCompression);
+    Ok(())
+}
 
-        Ok(())
-    }
+const AWS_CHUNKED: &str = "aws-chunked";
+
+fn is_aws_chunked_encoded(request: &HttpRequest) -> bool {
+    request
+        .headers()
+        .get_all(http::header::CONTENT_ENCODING)
+        .flat_map(|value| value.split(','))
+        .any(|encoding| encoding.trim().eq_ignore_ascii_case(AWS_CHUNKED))
 }
 
 fn wrap_request_body_in_compressed_body(
@@ -165,6 +181,8 @@ fn wrap_request_body_in_compressed_body(
         let body = mem::replace(request.body_mut(), SdkBody::taken());
 
         if body.is_streaming() {
+            // Streaming bodies are compressed frame by frame as they're sent, so the compressed length
+            // isn't known up front. Without a `Content-Length`, the body is sent with chunked transfer encoding.
             request.headers_mut().remove(http::header::CONTENT_LENGTH);
             body.map(move |body| {
                 let body = CompressedBody::new(body, request_compress_impl.clone());
@@ -225,6 +243,8 @@ mod tests {
     use crate::client_request_compression::{
         RequestCompressionInterceptor, RequestMinCompressionSizeBytes,
     };
+    use aws_smithy_compression::body::decompress::DecompressedBody;
+    use aws_smithy_compression::http::PendingRequestCompression;
     use aws_smithy_compression::{CompressionAlgorithm, CompressionOptions};
     use aws_smithy_runtime::client::sdk_feature::SmithySdkFeature;
     use aws_smithy_runtime_api::client::interceptors::context::{Input, InterceptorContext};
@@ -284,20 +304,117 @@ mod tests {
     }
 
     fn context() -> InterceptorContext {
-        let mut context = InterceptorContext::new(Input::doesnt_matter());
-        context.enter_serialization_phase();
-        context.set_request(
+        context_with_request(
             http::Request::builder()
                 .body(SdkBody::from(UNCOMPRESSED_INPUT))
                 .unwrap()
                 .try_into()
                 .unwrap(),
-        );
+        )
+    }
+
+    fn context_with_request(request: HttpRequest) -> InterceptorContext {
+        let mut context = InterceptorContext::new(Input::doesnt_matter());
+        context.enter_serialization_phase();
+        context.set_request(request);
         let _ = context.take_input();
         context.enter_before_transmit_phase();
         context
     }
 
+    fn streaming_body() -> SdkBody {
+        SdkBody::retryable(|| SdkBody::from_body_0_4(SdkBody::from(UNCOMPRESSED_INPUT)))
+    }
+
+    fn run_interceptor(context: &mut InterceptorContext) -> ConfigBag {
+        let mut cfg = ConfigBag::base();
+        let mut layer = Layer::new("test");
+        layer.store_put(RequestMinCompressionSizeBytes::from(0));
+        cfg.push_layer(layer);
+
+        let sut = RequestCompressionInterceptor::new();
+        sut.read_before_execution(&Into::into(&*context), &mut cfg)
+            .unwrap();
+        let rc = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        sut.modify_before_retry_loop(&mut Into::into(context), &rc, &mut cfg)
+            .unwrap();
+        cfg
+    }
+
+    async fn read_body(mut body: impl Body<Data = bytes::Bytes> + Unpin) -> Vec<u8> {
+        let mut body_data = Vec::new();
+        while let Some(data) = body.data().await {
+            body_data.extend_from_slice(&data.ok().unwrap())
+        }
+        body_data
+    }
+
+    #[tokio::test]
+    async fn test_streaming_body_is_compressed() {
+        let mut context = context_with_request(
+            http::Request::builder()
+                .header(http::header::CONTENT_LENGTH, UNCOMPRESSED_INPUT.len())
+                .body(streaming_body())
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        let cfg = run_interceptor(&mut context);
+
+        let request = context.request().unwrap();
+        // The compressed length isn't known up front, so the original `Content-Length` must be removed.
+        assert_eq!(None, request.headers().get(http::header::CONTENT_LENGTH));
+        assert_eq!(
+            Some("gzip"),
+            request.headers().get(http::header::CONTENT_ENCODING)
+        );
+        assert_eq!(
+            &SmithySdkFeature::GzipRequestCompression,
+            cfg.load::<SmithySdkFeature>().next().unwrap()
+        );
+
+        // The compressed body is still retryable, and decompresses to the original data.
+        let body = request.body().try_clone().expect("body is retryable");
+        let decompressed = read_body(DecompressedBody::new(
+            body,
+            CompressionAlgorithm::Gzip.into_impl_decompress(),
+        ))
+        .await;
+        assert_eq!(UNCOMPRESSED_INPUT, decompressed.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_aws_chunked_body_is_not_compressed() {
+        let mut context = context_with_request(
+            http::Request::builder()
+                .header(http::header::CONTENT_LENGTH, "95")
+                .header(http::header::CONTENT_ENCODING, "aws-chunked")
+                .header("x-amz-trailer", "x-amz-checksum-crc32")
+                .body(streaming_body())
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        let cfg = run_interceptor(&mut context);
+
+        let request = context.request().unwrap();
+        assert_eq!(
+            Some("95"),
+            request.headers().get(http::header::CONTENT_LENGTH)
+        );
+        assert_eq!(
+            vec!["aws-chunked"],
+            request
+                .headers()
+                .get_all(http::header::CONTENT_ENCODING)
+                .collect::<Vec<_>>()
+        );
+        assert!(cfg.load::<SmithySdkFeature>().next().is_none());
+
+        let body = request.body().try_clone().expect("body is retryable");
+        assert_eq!(UNCOMPRESSED_INPUT, read_body(body).await.as_slice());
+    }
+
     #[tokio::test]
     async fn test_sdk_feature_gzip_request_compression_should_be_tracked() {
         let mut cfg = ConfigBag::base();
@@ -320,4 +437,65 @@ mod tests {
             cfg.load::<SmithySdkFeature>().next().unwrap()
         );
     }
+
+    #[tokio::test]
+    async fn test_compression_applied_by_another_interceptor_is_not_applied_again() {
+        let mut context = context();
+        let mut cfg = ConfigBag::base();
+        let mut layer = Layer::new("test");
+        layer.store_put(RequestMinCompressionSizeBytes::from(0));
+        cfg.push_layer(layer);
+
+        let sut = RequestCompressionInterceptor::new();
+        sut.read_before_execution(&Into::into(&context), &mut cfg)
+            .unwrap();
+        // Stands in for an interceptor that needs the compressed body, such as the request
+        // checksum interceptor, running before the request compression interceptor.
+        let compression =
+            PendingRequestCompression::take(&mut cfg).expect("compression is enabled");
+        compression
+            .apply(context.request_mut().unwrap(), &mut cfg)
+            .unwrap();
+        let rc = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        sut.modify_before_retry_loop(&mut Into::into(&mut context), &rc, &mut cfg)
+            .unwrap();
+
+        let request = context.request().unwrap();
+        assert_eq!(
+            vec!["gzip"],
+            request
+                .headers()
+                .get_all(http::header::CONTENT_ENCODING)
+                .collect::<Vec<_>>()
+        );
+        let body = request.body().try_clone().expect("body is retryable");
+        let decompressed = read_body(DecompressedBody::new(
+            body,
+            CompressionAlgorithm::Gzip.into_impl_decompress(),
+        ))
+        .await;
+        assert_eq!(UNCOMPRESSED_INPUT, decompressed.as_slice());
+    }
+
+    #[test]
+    fn test_compression_declined_by_another_interceptor_is_not_applied() {
+        let mut context = context();
+        let mut cfg = ConfigBag::base();
+        let mut layer = Layer::new("test");
+        layer.store_put(RequestMinCompressionSizeBytes::from(0));
+        cfg.push_layer(layer);
+
+        let sut = RequestCompressionInterceptor::new();
+        sut.read_before_execution(&Into::into(&context), &mut cfg)
+            .unwrap();
+        let _ = PendingRequestCompression::take(&mut cfg).expect("compression is enabled");
+        let rc = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        sut.modify_before_retry_loop(&mut Into::into(&mut context), &rc, &mut cfg)
+            .unwrap();
+
+        let request = context.request().unwrap();
+        assert_eq!(None, request.headers().get(http::header::CONTENT_ENCODING));
+        assert_eq!(Some(UNCOMPRESSED_INPUT), request.body().bytes());
+        assert!(cfg.load::<SmithySdkFeature>().next().is_none());
+    }
 }